@@ -0,0 +1,150 @@
+//! Derive macro for typed Outlit events.
+//!
+//! Use it through the `outlit` crate with the `derive` feature enabled:
+//!
+//! ```rust,ignore
+//! use outlit::OutlitEvent;
+//!
+//! #[derive(OutlitEvent)]
+//! struct SignupEvent {
+//!     plan: String,
+//!     seats: u32,
+//! }
+//! ```
+//!
+//! The event name defaults to the struct name in snake_case, without an
+//! `Event` suffix (`"signup"` above). Each field becomes a property; fields
+//! that serialize to `null`, such as `None`, are left out.
+//!
+//! # Attributes
+//!
+//! - `#[outlit(name = "...")]` on the struct sets the event name.
+//! - `#[outlit(rename = "...")]` on a field sets its property key.
+//! - `#[outlit(skip)]` on a field leaves it out.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, LitStr};
+
+/// Derive `outlit::OutlitEvent` for a struct with named fields.
+#[proc_macro_derive(OutlitEvent, attributes(outlit))]
+pub fn derive_outlit_event(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let mut event_name = None;
+    for attr in &input.attrs {
+        if !attr.path().is_ident("outlit") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                event_name = Some(meta.value()?.parse::<LitStr>()?.value());
+                Ok(())
+            } else {
+                Err(meta.error("expected `name = \"...\"`"))
+            }
+        })?;
+    }
+    let event_name = event_name.unwrap_or_else(|| default_event_name(&input.ident.to_string()));
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "OutlitEvent can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "OutlitEvent can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut inserts = Vec::new();
+    for field in fields {
+        let ident = field.ident.as_ref().expect("named field");
+        let mut key = ident.to_string();
+        let mut skip = false;
+        for attr in &field.attrs {
+            if !attr.path().is_ident("outlit") {
+                continue;
+            }
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    key = meta.value()?.parse::<LitStr>()?.value();
+                    Ok(())
+                } else if meta.path.is_ident("skip") {
+                    skip = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `rename = \"...\"` or `skip`"))
+                }
+            })?;
+        }
+        if !skip {
+            inserts.push(quote! {
+                ::outlit::__private::insert_property(&mut properties, #key, &self.#ident)?;
+            });
+        }
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::outlit::OutlitEvent for #ident #ty_generics #where_clause {
+            fn event_name(&self) -> &str {
+                #event_name
+            }
+
+            fn properties(&self) -> ::std::result::Result<::outlit::__private::Properties, ::outlit::Error> {
+                let mut properties = ::outlit::__private::Properties::new();
+                #(#inserts)*
+                ::std::result::Result::Ok(properties)
+            }
+        }
+    })
+}
+
+/// `SignupEvent` -> `signup`, `PlanUpgraded` -> `plan_upgraded`.
+fn default_event_name(struct_name: &str) -> String {
+    let name = match struct_name.strip_suffix("Event") {
+        Some(stripped) if !stripped.is_empty() => stripped,
+        _ => struct_name,
+    };
+
+    let mut snake = String::with_capacity(name.len() + 4);
+    for (i, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i > 0 {
+                snake.push('_');
+            }
+            snake.push(c.to_ascii_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_event_name() {
+        assert_eq!(default_event_name("SignupEvent"), "signup");
+        assert_eq!(default_event_name("PlanUpgraded"), "plan_upgraded");
+        assert_eq!(default_event_name("Event"), "event");
+    }
+}
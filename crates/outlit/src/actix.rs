@@ -0,0 +1,221 @@
+//! actix-web integration.
+//!
+//! Enabled with the `actix-web` feature. [`configure`] registers the client as
+//! `web::Data<Outlit>` for handlers, [`OutlitMiddleware`] tracks requests
+//! automatically, and [`run`] shuts the client down once the server stops so
+//! queued events are not lost:
+//!
+//! ```rust,ignore
+//! use actix_web::{web, App, HttpServer};
+//! use outlit::{actix, email, Outlit};
+//!
+//! async fn signup(client: web::Data<Outlit>) -> &'static str {
+//!     let _ = client.track("signed_up", email("user@example.com")).send().await;
+//!     "ok"
+//! }
+//!
+//! let client = web::Data::new(Outlit::builder("pk_xxx").build()?);
+//! let server = HttpServer::new({
+//!     let client = client.clone();
+//!     move || {
+//!         App::new()
+//!             .configure(actix::configure(client.clone()))
+//!             .wrap(actix::OutlitMiddleware::new(client.clone()).user_id_header("x-user-id"))
+//!             .route("/signup", web::post().to(signup))
+//!     }
+//! })
+//! .bind(("127.0.0.1", 8080))?
+//! .run();
+//!
+//! actix::run(server, client).await?;
+//! ```
+
+use crate::{email, user_id, Identity, Outlit};
+use actix_web::body::MessageBody;
+use actix_web::dev::{forward_ready, Server, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::{web, HttpMessage};
+use std::fmt;
+use std::future::{ready, Future, Ready};
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::{error, warn};
+
+/// Default name of tracked request events.
+pub const DEFAULT_EVENT_NAME: &str = "http_request";
+
+type IdentifyFn = dyn Fn(&ServiceRequest) -> Option<Identity>;
+
+/// Register `client` as app data, so handlers can take `web::Data<Outlit>`.
+///
+/// Pass to [`App::configure`](actix_web::App::configure).
+pub fn configure(client: web::Data<Outlit>) -> impl FnOnce(&mut web::ServiceConfig) {
+    move |config| {
+        config.app_data(client);
+    }
+}
+
+/// Run `server` to completion, then shut down `client`, flushing any queued
+/// events.
+///
+/// The server stops on Ctrl-C, `SIGTERM`, or
+/// [`ServerHandle::stop`](actix_web::dev::ServerHandle::stop). A failed final
+/// flush is logged rather than returned, so the server's own result is kept.
+pub async fn run(server: Server, client: web::Data<Outlit>) -> std::io::Result<()> {
+    let result = server.await;
+    if let Err(e) = client.shutdown().await {
+        error!(error = %e, "failed to flush events on server stop");
+    }
+    result
+}
+
+/// Middleware that tracks requests through an [`Outlit`] client.
+///
+/// Each request with an identity is tracked once its response is ready, as an
+/// `http_request` event with `method`, `path`, `status`, and `duration_ms`
+/// properties. Events are queued on a spawned task, so responses never wait
+/// on the client. Requests without an identity are not tracked.
+///
+/// By default the identity is read from an [`Identity`] request extension,
+/// which authentication middleware can insert. Use
+/// [`user_id_header`](Self::user_id_header),
+/// [`email_header`](Self::email_header), or
+/// [`identify_with`](Self::identify_with) to read it from elsewhere.
+#[derive(Clone)]
+pub struct OutlitMiddleware {
+    client: web::Data<Outlit>,
+    event_name: Arc<str>,
+    identify: Rc<IdentifyFn>,
+}
+
+impl OutlitMiddleware {
+    /// Create middleware that tracks requests through `client`.
+    pub fn new(client: web::Data<Outlit>) -> Self {
+        Self {
+            client,
+            event_name: DEFAULT_EVENT_NAME.into(),
+            identify: Rc::new(|request| request.extensions().get::<Identity>().cloned()),
+        }
+    }
+
+    /// Set the name requests are tracked under. Defaults to `http_request`.
+    pub fn event_name(mut self, name: impl Into<String>) -> Self {
+        self.event_name = name.into().into();
+        self
+    }
+
+    /// Identify requests by the user ID in the `name` header.
+    pub fn user_id_header(self, name: &'static str) -> Self {
+        self.identify_with(move |request| header_value(request, name).map(|v| user_id(v).into()))
+    }
+
+    /// Identify requests by the email address in the `name` header.
+    pub fn email_header(self, name: &'static str) -> Self {
+        self.identify_with(move |request| header_value(request, name).map(|v| email(v).into()))
+    }
+
+    /// Identify requests with a custom function. Return `None` to leave a
+    /// request untracked.
+    pub fn identify_with<F>(mut self, identify: F) -> Self
+    where
+        F: Fn(&ServiceRequest) -> Option<Identity> + 'static,
+    {
+        self.identify = Rc::new(identify);
+        self
+    }
+}
+
+impl fmt::Debug for OutlitMiddleware {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OutlitMiddleware")
+            .field("event_name", &self.event_name)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for OutlitMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    type Transform = OutlitMiddlewareService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(OutlitMiddlewareService {
+            service: Rc::new(service),
+            middleware: self.clone(),
+        }))
+    }
+}
+
+/// Service created by [`OutlitMiddleware`].
+pub struct OutlitMiddlewareService<S> {
+    service: Rc<S>,
+    middleware: OutlitMiddleware,
+}
+
+impl<S, B> Service<ServiceRequest> for OutlitMiddlewareService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, request: ServiceRequest) -> Self::Future {
+        let identity = (self.middleware.identify)(&request);
+        let method = request.method().to_string();
+        let path = request.path().to_string();
+        let started = Instant::now();
+
+        let service = Rc::clone(&self.service);
+        let client = self.middleware.client.clone();
+        let event_name = Arc::clone(&self.middleware.event_name);
+
+        Box::pin(async move {
+            let response = service.call(request).await?;
+
+            if let Some(identity) = identity {
+                let status = response.status().as_u16();
+                let duration_ms = started.elapsed().as_millis() as u64;
+                actix_web::rt::spawn(async move {
+                    let name = &*event_name;
+                    let track = match identity {
+                        Identity::Email(email) => client.track(name, email),
+                        Identity::UserId(id) => client.track_by_user_id(name, id),
+                        Identity::Fingerprint(fp) => client.track_by_fingerprint(name, fp),
+                    };
+                    let result = track
+                        .property("method", method)
+                        .property("path", path)
+                        .property("status", status)
+                        .property("duration_ms", duration_ms)
+                        .send()
+                        .await;
+                    if let Err(e) = result {
+                        warn!(error = %e, "failed to track request");
+                    }
+                });
+            }
+
+            Ok(response)
+        })
+    }
+}
+
+fn header_value(request: &ServiceRequest, name: &str) -> Option<String> {
+    request
+        .headers()
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| !value.is_empty())
+        .map(String::from)
+}
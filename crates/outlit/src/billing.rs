@@ -0,0 +1,176 @@
+//! Billing changes decoded from payment provider webhooks.
+
+use crate::builders::BillingBuilder;
+use crate::types::{BillingActivity, BillingInterval, BillingStatus};
+use crate::{Error, Outlit, SendableBilling};
+use serde_json::Value;
+
+/// A billing change read from a payment provider's webhook, ready to send
+/// as a billing event.
+///
+/// Produced by the provider adapters: `stripe::translate`,
+/// `paddle::translate`, and `lemonsqueezy::translate`. Providers don't know
+/// the customer's domain, so it is passed when sending; look it up from
+/// [`provider_customer_id`](Self::provider_customer_id) or
+/// [`customer_email`](Self::customer_email).
+///
+/// Invoice and refund webhooks don't say what state the customer's
+/// subscription is in, so their [`status`](Self::status) is `None`; set it
+/// to the customer's current status before sending.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BillingUpdate {
+    /// Status the customer is in after the change, or `None` if the
+    /// webhook only reports an activity such as an invoice or refund.
+    pub status: Option<BillingStatus>,
+    /// What happened, if more specific than a status update.
+    pub activity: Option<BillingActivity>,
+    /// The provider's ID for the webhook event, used as the idempotency
+    /// key so redelivered webhooks are deduplicated.
+    pub event_id: String,
+    /// The provider's ID for the customer. Sent as `stripe_customer_id`
+    /// for Stripe and as a `<provider>_customer_id` property otherwise.
+    pub provider_customer_id: Option<String>,
+    /// The customer's email, if the webhook includes it.
+    pub customer_email: Option<String>,
+    pub invoice_id: Option<String>,
+    /// Amount charged or refunded, in the currency's minor unit.
+    pub amount: Option<i64>,
+    /// Lowercase ISO 4217 currency code.
+    pub currency: Option<String>,
+    /// Monthly recurring revenue, in the currency's minor unit.
+    pub mrr: Option<i64>,
+    pub interval: Option<BillingInterval>,
+    pub plan: Option<String>,
+    pub previous_plan: Option<String>,
+    pub seats: Option<u32>,
+    pub previous_seats: Option<u32>,
+    provider: Provider,
+}
+
+/// Payment provider a [`BillingUpdate`] came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Provider {
+    #[cfg(feature = "stripe")]
+    Stripe,
+    #[cfg(feature = "paddle")]
+    Paddle,
+    #[cfg(feature = "lemonsqueezy")]
+    LemonSqueezy,
+}
+
+impl BillingUpdate {
+    pub(crate) fn new(provider: Provider, status: Option<BillingStatus>, event_id: String) -> Self {
+        Self {
+            status,
+            activity: None,
+            event_id,
+            provider_customer_id: None,
+            customer_email: None,
+            invoice_id: None,
+            amount: None,
+            currency: None,
+            mrr: None,
+            interval: None,
+            plan: None,
+            previous_plan: None,
+            seats: None,
+            previous_seats: None,
+            provider,
+        }
+    }
+
+    /// Build the billing event for the customer at `domain`.
+    ///
+    /// Further setters, such as `.customer_id()`, can be chained before
+    /// sending. Returns [`Error::Validation`] if [`status`](Self::status)
+    /// is `None`.
+    pub fn to_billing<'a>(
+        &self,
+        client: &'a Outlit,
+        domain: impl Into<String>,
+    ) -> Result<SendableBilling<'a>, Error> {
+        let Some(status) = self.status.clone() else {
+            return Err(Error::Validation(format!(
+                "billing update {} has no status; set it to the customer's current status",
+                self.event_id
+            )));
+        };
+        let mut builder = BillingBuilder::new(status, domain).idempotency_key(&self.event_id);
+        if let Some(activity) = self.activity {
+            builder = builder.activity(activity);
+        }
+        if let Some(id) = &self.provider_customer_id {
+            builder = match self.provider {
+                #[cfg(feature = "stripe")]
+                Provider::Stripe => builder.stripe_customer_id(id),
+                #[cfg(feature = "paddle")]
+                Provider::Paddle => builder.property("paddle_customer_id", id.as_str()),
+                #[cfg(feature = "lemonsqueezy")]
+                Provider::LemonSqueezy => builder.property("lemonsqueezy_customer_id", id.as_str()),
+            };
+        }
+        if let Some(id) = &self.invoice_id {
+            builder = builder.invoice_id(id);
+        }
+        if let Some(amount) = self.amount {
+            builder = builder.amount_cents(amount);
+        }
+        if let Some(currency) = &self.currency {
+            builder = builder.currency(currency);
+        }
+        if let Some(mrr) = self.mrr {
+            builder = builder.mrr_cents(mrr);
+        }
+        if let Some(interval) = self.interval {
+            builder = builder.interval(interval);
+        }
+        if let Some(plan) = &self.plan {
+            builder = builder.plan(plan);
+        }
+        if let Some(plan) = &self.previous_plan {
+            builder = builder.previous_plan(plan);
+        }
+        if let Some(seats) = self.seats {
+            builder = builder.seats(seats);
+        }
+        if let Some(seats) = self.previous_seats {
+            builder = builder.previous_seats(seats);
+        }
+        Ok(client.customer().billing(builder))
+    }
+}
+
+/// Monthly equivalent of `amount` billed every `count` `interval`s.
+#[cfg(any(feature = "stripe", feature = "paddle"))]
+pub(crate) fn monthly(amount: i64, interval: BillingInterval, count: i64) -> i64 {
+    let count = count.max(1);
+    match interval {
+        BillingInterval::Day => amount * 365 / (12 * count),
+        BillingInterval::Week => amount * 52 / (12 * count),
+        BillingInterval::Month => amount / count,
+        BillingInterval::Year => amount / (12 * count),
+    }
+}
+
+#[cfg(any(feature = "stripe", feature = "paddle"))]
+pub(crate) fn interval(interval: &str) -> Option<BillingInterval> {
+    Some(match interval {
+        "day" => BillingInterval::Day,
+        "week" => BillingInterval::Week,
+        "month" => BillingInterval::Month,
+        "year" => BillingInterval::Year,
+        _ => return None,
+    })
+}
+
+pub(crate) fn str_at<'v>(value: &'v Value, pointer: &str) -> Option<&'v str> {
+    value.pointer(pointer).and_then(Value::as_str)
+}
+
+/// An integer, or a string holding one; Paddle sends amounts as strings.
+pub(crate) fn int_at(value: &Value, pointer: &str) -> Option<i64> {
+    match value.pointer(pointer)? {
+        Value::String(s) => s.parse().ok(),
+        value => value.as_i64(),
+    }
+}
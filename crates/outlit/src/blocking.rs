@@ -0,0 +1,766 @@
+//! Blocking client for applications without an async runtime.
+//!
+//! Enabled with the `blocking` feature. [`Outlit`] owns a background thread
+//! that runs batching and periodic flushes, so CLIs and synchronous web
+//! frameworks don't need to set up Tokio themselves.
+//!
+//! Don't use the blocking client from async code: its methods block the
+//! calling thread and panic if called from within a Tokio runtime.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use outlit::email;
+//!
+//! fn main() -> Result<(), outlit::Error> {
+//!     let client = outlit::Outlit::builder("pk_xxx").build_blocking()?;
+//!
+//!     client.track("signup", email("user@example.com"))
+//!         .property("plan", "pro")
+//!         .send()?;
+//!
+//!     client.shutdown()?;
+//!     Ok(())
+//! }
+//! ```
+
+use crate::builders::Identity;
+use crate::types::{BillingStatus, FlushReport, TrackerEvent};
+use crate::{Config, Email, Error, Fingerprint, OutlitBuilder, UserId};
+use serde_json::Value;
+use std::thread::JoinHandle;
+use std::time::Duration;
+use tokio::runtime::Handle;
+use tokio::sync::oneshot;
+
+/// Blocking Outlit analytics client.
+///
+/// Mirrors [`crate::Outlit`], with `send()`, `flush()`, and `shutdown()`
+/// blocking until they complete. Build one with
+/// [`OutlitBuilder::build_blocking`].
+///
+/// Dropping the client stops its background thread without flushing; call
+/// [`shutdown`](Self::shutdown) first to send pending events.
+pub struct Outlit {
+    inner: crate::Outlit,
+    handle: Handle,
+    stop: Option<oneshot::Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Outlit {
+    fn from_config(config: Config) -> Result<Self, Error> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        let handle = runtime.handle().clone();
+
+        // The flush timer is spawned onto the runtime, so build the client
+        // inside its context.
+        let inner = {
+            let _guard = handle.enter();
+            crate::Outlit::from_config(config)?
+        };
+
+        // Timers, I/O, and the flush task only make progress while a thread
+        // is inside `Runtime::block_on`.
+        let (stop, stopped) = oneshot::channel();
+        let thread = std::thread::Builder::new()
+            .name("outlit-runtime".into())
+            .spawn(move || {
+                runtime.block_on(async {
+                    let _ = stopped.await;
+                });
+            })?;
+
+        Ok(Self {
+            inner,
+            handle,
+            stop: Some(stop),
+            thread: Some(thread),
+        })
+    }
+
+    /// Get the client configuration.
+    pub fn config(&self) -> &Config {
+        self.inner.config()
+    }
+
+    /// Get the number of pending events.
+    pub fn pending_event_count(&self) -> usize {
+        self.handle.block_on(self.inner.pending_event_count())
+    }
+
+    /// Get the number of events dropped without being sent.
+    pub fn dropped_event_count(&self) -> usize {
+        self.inner.dropped_event_count()
+    }
+
+    /// Get a snapshot of delivery counters and the last flush's outcome.
+    pub fn stats(&self) -> crate::ClientStats {
+        self.inner.stats()
+    }
+
+    /// Set a context property merged into every subsequent track and stage
+    /// event. See [`crate::Outlit::set_context`].
+    pub fn set_context(&self, key: impl Into<String>, value: impl Into<Value>) {
+        self.inner.set_context(key, value);
+    }
+
+    /// Turn tracking on or off at runtime. See
+    /// [`crate::Outlit::set_enabled`].
+    pub fn set_enabled(&self, enabled: bool) {
+        self.inner.set_enabled(enabled);
+    }
+
+    /// Whether tracking is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.inner.is_enabled()
+    }
+
+    /// Forget all context set with [`set_context`](Self::set_context).
+    pub fn reset_context(&self) {
+        self.inner.reset_context();
+    }
+
+    /// Track a custom event with email identity.
+    pub fn track(
+        &self,
+        event_name: impl Into<String>,
+        identity: impl Into<Email>,
+    ) -> SendableTrack<'_> {
+        self.wrap(self.inner.track(event_name, identity))
+    }
+
+    /// Track a custom event with user_id.
+    pub fn track_by_user_id(
+        &self,
+        event_name: impl Into<String>,
+        identity: impl Into<UserId>,
+    ) -> SendableTrack<'_> {
+        self.wrap(self.inner.track_by_user_id(event_name, identity))
+    }
+
+    /// Track a custom event with fingerprint (device identifier).
+    pub fn track_by_fingerprint(
+        &self,
+        event_name: impl Into<String>,
+        identity: impl Into<Fingerprint>,
+    ) -> SendableTrack<'_> {
+        self.wrap(self.inner.track_by_fingerprint(event_name, identity))
+    }
+
+    /// Import historical events, sending them directly instead of queueing.
+    /// See [`crate::Outlit::import`].
+    pub fn import(
+        &self,
+        events: impl IntoIterator<Item = TrackerEvent>,
+    ) -> Result<FlushReport, Error> {
+        self.handle.block_on(self.inner.import(events))
+    }
+
+    /// Record a web page view.
+    pub fn page(&self, identity: impl Into<Identity>, name: impl Into<String>) -> SendablePage<'_> {
+        self.wrap(self.inner.page(identity, name))
+    }
+
+    /// Record an app screen view.
+    pub fn screen(
+        &self,
+        identity: impl Into<Identity>,
+        name: impl Into<String>,
+    ) -> SendablePage<'_> {
+        self.wrap(self.inner.screen(identity, name))
+    }
+
+    /// Track a typed custom event, sending its fields as properties.
+    pub fn track_event(
+        &self,
+        event: impl crate::OutlitEvent,
+        identity: impl Into<Identity>,
+    ) -> Result<SendableTrack<'_>, Error> {
+        Ok(self.wrap(self.inner.track_event(event, identity)?))
+    }
+
+    /// Queue a batch of pre-built events in one step.
+    pub fn track_batch(&self, events: impl IntoIterator<Item = TrackerEvent>) -> Result<(), Error> {
+        self.handle.block_on(self.inner.track_batch(events))
+    }
+
+    /// Identify or update a user.
+    pub fn identify(&self, identity: impl Into<Email>) -> SendableIdentify<'_> {
+        self.wrap(self.inner.identify(identity))
+    }
+
+    /// Identify by user_id.
+    pub fn identify_by_user_id(&self, identity: impl Into<UserId>) -> SendableIdentify<'_> {
+        self.wrap(self.inner.identify_by_user_id(identity))
+    }
+
+    /// Link a previous identity to the current one.
+    pub fn alias(
+        &self,
+        previous: impl Into<Identity>,
+        current: impl Into<Identity>,
+    ) -> SendableAlias<'_> {
+        self.wrap(self.inner.alias(previous, current))
+    }
+
+    /// Identify or update a group (account), optionally associating a user.
+    pub fn group(&self, group_id: impl Into<String>) -> SendableGroup<'_> {
+        self.wrap(self.inner.group(group_id))
+    }
+
+    /// User journey stage methods.
+    pub fn user(&self) -> UserMethods<'_> {
+        self.wrap(self.inner.user())
+    }
+
+    /// Customer billing methods.
+    pub fn customer(&self) -> CustomerMethods<'_> {
+        self.wrap(self.inner.customer())
+    }
+
+    /// Record what a user agreed to. See [`crate::Outlit::set_consent`].
+    pub fn set_consent(
+        &self,
+        identity: impl Into<Identity>,
+        consent: impl IntoIterator<Item = crate::Consent>,
+    ) {
+        self.inner.set_consent(identity, consent);
+    }
+
+    /// Forget a user's consent decision.
+    pub fn clear_consent(&self, identity: impl Into<Identity>) {
+        self.inner.clear_consent(identity);
+    }
+
+    /// Privacy request methods.
+    pub fn privacy(&self) -> PrivacyMethods<'_> {
+        self.wrap(self.inner.privacy())
+    }
+
+    /// Check that the ingest endpoint is reachable and accepts the public key.
+    pub fn ping(&self) -> Result<(), Error> {
+        self.handle.block_on(self.inner.ping())
+    }
+
+    /// Flush all pending events, blocking until the batch is sent.
+    pub fn flush(&self) -> Result<(), Error> {
+        self.handle.block_on(self.inner.flush())
+    }
+
+    /// Flush pending events only if they are due. See
+    /// [`Outlit::flush_if_needed`](crate::Outlit::flush_if_needed).
+    pub fn flush_if_needed(&self) -> Result<(), Error> {
+        self.handle.block_on(self.inner.flush_if_needed())
+    }
+
+    /// Flush pending events, giving up after `timeout`. See
+    /// [`Outlit::flush_with_timeout`](crate::Outlit::flush_with_timeout).
+    pub fn flush_with_timeout(&self, timeout: Duration) -> usize {
+        self.handle.block_on(self.inner.flush_with_timeout(timeout))
+    }
+
+    /// Flush all pending events and report what the API accepted.
+    pub fn flush_detailed(&self) -> Result<FlushReport, Error> {
+        self.handle.block_on(self.inner.flush_detailed())
+    }
+
+    /// Drop all pending events without sending them.
+    pub fn discard_pending(&self) -> usize {
+        self.handle.block_on(self.inner.discard_pending())
+    }
+
+    /// Shutdown the client gracefully.
+    ///
+    /// Flushes remaining events and stops the background flush timer.
+    pub fn shutdown(&self) -> Result<(), Error> {
+        self.handle.block_on(self.inner.shutdown())
+    }
+
+    /// Shutdown the client, giving up on the final flush after `timeout`. See
+    /// [`Outlit::shutdown_with_timeout`](crate::Outlit::shutdown_with_timeout).
+    pub fn shutdown_with_timeout(&self, timeout: Duration) -> Result<(), Error> {
+        self.handle
+            .block_on(self.inner.shutdown_with_timeout(timeout))
+    }
+
+    fn wrap<T>(&self, inner: T) -> Blocking<'_, T> {
+        Blocking {
+            inner,
+            handle: &self.handle,
+        }
+    }
+}
+
+impl Drop for Outlit {
+    fn drop(&mut self) {
+        if let Some(stop) = self.stop.take() {
+            let _ = stop.send(());
+        }
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl OutlitBuilder {
+    /// Build a [blocking client](crate::blocking::Outlit) that batches and
+    /// flushes on its own background thread.
+    pub fn build_blocking(self) -> Result<Outlit, Error> {
+        let config = self.build_config()?;
+        Outlit::from_config(config)
+    }
+}
+
+// ============================================
+// SENDABLE WRAPPERS
+// ============================================
+
+/// Blocking wrapper around one of the async builders.
+///
+/// Exposed through the `Sendable*` and `*Methods` aliases in this module.
+pub struct Blocking<'a, T> {
+    inner: T,
+    handle: &'a Handle,
+}
+
+/// Forward builder methods to the wrapped async builder.
+macro_rules! forward {
+    ($($(#[$meta:meta])* fn $name:ident($($arg:ident: $ty:ty),*);)*) => {
+        $(
+            $(#[$meta])*
+            pub fn $name(self, $($arg: $ty),*) -> Self {
+                Self {
+                    inner: self.inner.$name($($arg),*),
+                    handle: self.handle,
+                }
+            }
+        )*
+    };
+}
+
+/// Blocking track event builder.
+pub type SendableTrack<'a> = Blocking<'a, crate::SendableTrack<'a>>;
+
+impl<'a> SendableTrack<'a> {
+    forward! {
+        /// Add email (if identity was user_id or fingerprint).
+        fn email(email: impl Into<String>);
+        /// Add user_id (if identity was email or fingerprint).
+        fn user_id(user_id: impl Into<String>);
+        /// Add fingerprint (device identifier) to link this event to a device.
+        fn fingerprint(fingerprint: impl Into<String>);
+        /// Add a property.
+        fn property(key: impl Into<String>, value: impl Into<Value>);
+        /// Set custom timestamp from any [`IntoTimestamp`](crate::IntoTimestamp).
+        fn timestamp(ts: impl crate::IntoTimestamp);
+        /// Mark the consent the event needs.
+        fn consent(consent: crate::Consent);
+        /// Set an idempotency key so repeated sends of this event are deduplicated.
+        fn idempotency_key(key: impl Into<String>);
+        /// Send to another [project](crate::OutlitBuilder::project).
+        fn project(name: impl Into<String>);
+    }
+
+    /// Add each top-level field of a serializable struct or map as a
+    /// property.
+    ///
+    /// Returns [`Error::Serialization`] if `value` does not serialize to a
+    /// JSON object.
+    pub fn properties(self, value: impl serde::Serialize) -> Result<Self, Error> {
+        Ok(Self {
+            inner: self.inner.properties(value)?,
+            handle: self.handle,
+        })
+    }
+
+    /// Send the event, blocking if the queue is full and must be flushed.
+    pub fn send(self) -> Result<(), Error> {
+        self.handle.block_on(self.inner.send())
+    }
+}
+
+/// Blocking page view event builder.
+pub type SendablePage<'a> = Blocking<'a, crate::SendablePage<'a>>;
+
+impl<'a> SendablePage<'a> {
+    forward! {
+        /// Add email (if identity was user_id or fingerprint).
+        fn email(email: impl Into<String>);
+        /// Add user_id (if identity was email or fingerprint).
+        fn user_id(user_id: impl Into<String>);
+        /// Add fingerprint (device identifier) to link this event to a device.
+        fn fingerprint(fingerprint: impl Into<String>);
+        /// Set the full URL that was visited; the path is taken from it.
+        fn url(url: impl Into<String>);
+        /// Set the path that was visited, overriding the one parsed from the URL.
+        fn path(path: impl Into<String>);
+        /// Set the referring URL.
+        fn referrer(referrer: impl Into<String>);
+        /// Add a property.
+        fn property(key: impl Into<String>, value: impl Into<Value>);
+        /// Set custom timestamp from any [`IntoTimestamp`](crate::IntoTimestamp).
+        fn timestamp(ts: impl crate::IntoTimestamp);
+        /// Mark the consent the event needs.
+        fn consent(consent: crate::Consent);
+        /// Set an idempotency key so repeated sends of this event are deduplicated.
+        fn idempotency_key(key: impl Into<String>);
+        /// Send to another [project](crate::OutlitBuilder::project).
+        fn project(name: impl Into<String>);
+    }
+
+    /// Add each top-level field of a serializable struct or map as a
+    /// property.
+    pub fn properties(self, value: impl serde::Serialize) -> Result<Self, Error> {
+        Ok(Self {
+            inner: self.inner.properties(value)?,
+            handle: self.handle,
+        })
+    }
+
+    /// Send the event, blocking if the queue is full and must be flushed.
+    pub fn send(self) -> Result<(), Error> {
+        self.handle.block_on(self.inner.send())
+    }
+}
+
+/// Blocking identify event builder.
+pub type SendableIdentify<'a> = Blocking<'a, crate::SendableIdentify<'a>>;
+
+impl<'a> SendableIdentify<'a> {
+    forward! {
+        /// Add email.
+        fn email(email: impl Into<String>);
+        /// Add user_id.
+        fn user_id(user_id: impl Into<String>);
+        /// Add fingerprint (device identifier) to link this device to the user.
+        fn fingerprint(fingerprint: impl Into<String>);
+        /// Add a trait.
+        fn trait_(key: impl Into<String>, value: impl Into<Value>);
+        /// Mark the consent the event needs.
+        fn consent(consent: crate::Consent);
+        /// Set an idempotency key so repeated sends of this event are deduplicated.
+        fn idempotency_key(key: impl Into<String>);
+        /// Send to another [project](crate::OutlitBuilder::project).
+        fn project(name: impl Into<String>);
+    }
+
+    /// Send the event, blocking if the queue is full and must be flushed.
+    pub fn send(self) -> Result<(), Error> {
+        self.handle.block_on(self.inner.send())
+    }
+}
+
+/// Blocking stage event builder.
+pub type SendableStage<'a> = Blocking<'a, crate::SendableStage<'a>>;
+
+impl<'a> SendableStage<'a> {
+    forward! {
+        /// Add email.
+        fn email(email: impl Into<String>);
+        /// Add user_id.
+        fn user_id(user_id: impl Into<String>);
+        /// Add fingerprint (device identifier).
+        fn fingerprint(fingerprint: impl Into<String>);
+        /// Set why the user moved to this stage.
+        fn reason(reason: impl Into<String>);
+        /// Set the stage the user moved from.
+        fn previous_stage(stage: impl Into<crate::JourneyStage>);
+        /// Set when the transition took effect.
+        fn effective_at(at: impl crate::IntoTimestamp);
+        /// Add a property.
+        fn property(key: impl Into<String>, value: impl Into<Value>);
+        /// Mark the consent the event needs.
+        fn consent(consent: crate::Consent);
+        /// Set an idempotency key so repeated sends of this event are deduplicated.
+        fn idempotency_key(key: impl Into<String>);
+        /// Send to another [project](crate::OutlitBuilder::project).
+        fn project(name: impl Into<String>);
+    }
+
+    /// Add each top-level field of a serializable struct or map as a
+    /// property.
+    pub fn properties(self, value: impl serde::Serialize) -> Result<Self, Error> {
+        Ok(Self {
+            inner: self.inner.properties(value)?,
+            handle: self.handle,
+        })
+    }
+
+    /// Send the event, blocking if the queue is full and must be flushed.
+    pub fn send(self) -> Result<(), Error> {
+        self.handle.block_on(self.inner.send())
+    }
+}
+
+/// Blocking billing event builder.
+pub type SendableBilling<'a> = Blocking<'a, crate::SendableBilling<'a>>;
+
+impl<'a> SendableBilling<'a> {
+    forward! {
+        /// Set customer ID.
+        fn customer_id(id: impl Into<String>);
+        /// Set Stripe customer ID.
+        fn stripe_customer_id(id: impl Into<String>);
+        /// Set the amount charged, in the currency's minor unit (e.g. cents).
+        fn amount_cents(amount: i64);
+        /// Set the ISO 4217 currency code, such as `"usd"`. Sent lowercase.
+        fn currency(currency: impl Into<String>);
+        /// Set monthly recurring revenue, in the currency's minor unit.
+        fn mrr_cents(mrr: i64);
+        /// Set how often the customer is billed.
+        fn interval(interval: crate::BillingInterval);
+        /// Override the billing status.
+        fn status(status: BillingStatus);
+        /// Set the invoice this event is about.
+        fn invoice_id(id: impl Into<String>);
+        /// Set the customer's current plan.
+        fn plan(plan: impl Into<String>);
+        /// Set the customer's plan before a plan change.
+        fn previous_plan(plan: impl Into<String>);
+        /// Set the number of seats.
+        fn seats(seats: u32);
+        /// Set the seat count before a seat change.
+        fn previous_seats(seats: u32);
+        /// Add a property.
+        fn property(key: impl Into<String>, value: impl Into<Value>);
+        /// Set an idempotency key so repeated sends of this event are deduplicated.
+        fn idempotency_key(key: impl Into<String>);
+        /// Send to another [project](crate::OutlitBuilder::project).
+        fn project(name: impl Into<String>);
+    }
+
+    /// Add each top-level field of a serializable struct or map as a
+    /// property.
+    pub fn properties(self, value: impl serde::Serialize) -> Result<Self, Error> {
+        Ok(Self {
+            inner: self.inner.properties(value)?,
+            handle: self.handle,
+        })
+    }
+
+    /// Send the event, blocking if the queue is full and must be flushed.
+    pub fn send(self) -> Result<(), Error> {
+        self.handle.block_on(self.inner.send())
+    }
+}
+
+/// Blocking alias event builder.
+pub type SendableAlias<'a> = Blocking<'a, crate::SendableAlias<'a>>;
+
+impl<'a> SendableAlias<'a> {
+    forward! {
+        /// Add email for the current identity.
+        fn email(email: impl Into<String>);
+        /// Add user_id for the current identity.
+        fn user_id(user_id: impl Into<String>);
+        /// Add fingerprint for the current identity.
+        fn fingerprint(fingerprint: impl Into<String>);
+        /// Set an idempotency key so repeated sends of this event are deduplicated.
+        fn idempotency_key(key: impl Into<String>);
+        /// Send to another [project](crate::OutlitBuilder::project).
+        fn project(name: impl Into<String>);
+    }
+
+    /// Send the event, blocking if the queue is full and must be flushed.
+    pub fn send(self) -> Result<(), Error> {
+        self.handle.block_on(self.inner.send())
+    }
+}
+
+/// Blocking group event builder.
+pub type SendableGroup<'a> = Blocking<'a, crate::SendableGroup<'a>>;
+
+impl<'a> SendableGroup<'a> {
+    forward! {
+        /// Associate a user with the group.
+        fn user(identity: impl Into<Identity>);
+        /// Add a group trait.
+        fn trait_(key: impl Into<String>, value: impl Into<Value>);
+        /// Set an idempotency key so repeated sends of this event are deduplicated.
+        fn idempotency_key(key: impl Into<String>);
+        /// Send to another [project](crate::OutlitBuilder::project).
+        fn project(name: impl Into<String>);
+    }
+
+    /// Send the event, blocking if the queue is full and must be flushed.
+    pub fn send(self) -> Result<(), Error> {
+        self.handle.block_on(self.inner.send())
+    }
+}
+
+// ============================================
+// NAMESPACE METHODS
+// ============================================
+
+/// Blocking user journey stage methods.
+pub type UserMethods<'a> = Blocking<'a, crate::UserMethods<'a>>;
+
+impl<'a> UserMethods<'a> {
+    /// Move the user to a named journey stage.
+    pub fn stage(
+        &self,
+        stage: impl Into<String>,
+        identity: impl Into<Identity>,
+    ) -> SendableStage<'a> {
+        self.stage_builder(self.inner.stage(stage, identity))
+    }
+
+    /// Mark user as activated.
+    pub fn activate(&self, identity: impl Into<Email>) -> SendableStage<'a> {
+        self.stage_builder(self.inner.activate(identity))
+    }
+
+    /// Mark user as activated by user_id.
+    pub fn activate_by_user_id(&self, identity: impl Into<UserId>) -> SendableStage<'a> {
+        self.stage_builder(self.inner.activate_by_user_id(identity))
+    }
+
+    /// Mark user as activated by fingerprint.
+    pub fn activate_by_fingerprint(&self, identity: impl Into<Fingerprint>) -> SendableStage<'a> {
+        self.stage_builder(self.inner.activate_by_fingerprint(identity))
+    }
+
+    fn stage_builder(&self, inner: crate::SendableStage<'a>) -> SendableStage<'a> {
+        Blocking {
+            inner,
+            handle: self.handle,
+        }
+    }
+}
+
+/// Blocking customer billing methods.
+pub type CustomerMethods<'a> = Blocking<'a, crate::CustomerMethods<'a>>;
+
+impl<'a> CustomerMethods<'a> {
+    /// Mark customer as trialing.
+    pub fn trialing(&self, domain: impl Into<String>) -> SendableBilling<'a> {
+        self.billing_builder(self.inner.trialing(domain))
+    }
+
+    /// Mark customer as paid.
+    pub fn paid(&self, domain: impl Into<String>) -> SendableBilling<'a> {
+        self.billing_builder(self.inner.paid(domain))
+    }
+
+    /// Mark customer as churned.
+    pub fn churned(&self, domain: impl Into<String>) -> SendableBilling<'a> {
+        self.billing_builder(self.inner.churned(domain))
+    }
+
+    /// Mark customer as past due, after a failed payment.
+    pub fn past_due(&self, domain: impl Into<String>) -> SendableBilling<'a> {
+        self.billing_builder(self.inner.past_due(domain))
+    }
+
+    /// Mark customer's subscription as canceled.
+    pub fn canceled(&self, domain: impl Into<String>) -> SendableBilling<'a> {
+        self.billing_builder(self.inner.canceled(domain))
+    }
+
+    /// Mark customer as unpaid, after payment retries ran out.
+    pub fn unpaid(&self, domain: impl Into<String>) -> SendableBilling<'a> {
+        self.billing_builder(self.inner.unpaid(domain))
+    }
+
+    /// Mark customer's billing as paused.
+    pub fn paused(&self, domain: impl Into<String>) -> SendableBilling<'a> {
+        self.billing_builder(self.inner.paused(domain))
+    }
+
+    /// Mark customer as incomplete, before the first payment succeeds.
+    pub fn incomplete(&self, domain: impl Into<String>) -> SendableBilling<'a> {
+        self.billing_builder(self.inner.incomplete(domain))
+    }
+
+    /// Record a paid invoice. Sent with status paid.
+    pub fn invoice_paid(
+        &self,
+        domain: impl Into<String>,
+        invoice_id: impl Into<String>,
+        amount_cents: i64,
+    ) -> SendableBilling<'a> {
+        self.billing_builder(self.inner.invoice_paid(domain, invoice_id, amount_cents))
+    }
+
+    /// Record a failed invoice payment. Sent with status past due.
+    pub fn invoice_failed(
+        &self,
+        domain: impl Into<String>,
+        invoice_id: impl Into<String>,
+        amount_cents: i64,
+    ) -> SendableBilling<'a> {
+        self.billing_builder(self.inner.invoice_failed(domain, invoice_id, amount_cents))
+    }
+
+    /// Record a refund of `amount_cents`. Sent with status paid.
+    pub fn refund(&self, domain: impl Into<String>, amount_cents: i64) -> SendableBilling<'a> {
+        self.billing_builder(self.inner.refund(domain, amount_cents))
+    }
+
+    /// Record a move from `previous_plan` to `plan`. Sent with status paid.
+    pub fn plan_changed(
+        &self,
+        domain: impl Into<String>,
+        previous_plan: impl Into<String>,
+        plan: impl Into<String>,
+    ) -> SendableBilling<'a> {
+        self.billing_builder(self.inner.plan_changed(domain, previous_plan, plan))
+    }
+
+    /// Record a change from `previous_seats` to `seats`. Sent with status
+    /// paid.
+    pub fn seats_changed(
+        &self,
+        domain: impl Into<String>,
+        previous_seats: u32,
+        seats: u32,
+    ) -> SendableBilling<'a> {
+        self.billing_builder(self.inner.seats_changed(domain, previous_seats, seats))
+    }
+
+    /// Set the customer's billing status.
+    pub fn set_status(
+        &self,
+        status: BillingStatus,
+        domain: impl Into<String>,
+    ) -> SendableBilling<'a> {
+        self.billing_builder(self.inner.set_status(status, domain))
+    }
+
+    /// Fetch the customer's current billing status and MRR, blocking until
+    /// the API responds. See [`crate::CustomerMethods::status`].
+    pub fn status(&self, domain: impl AsRef<str>) -> Result<Option<crate::CustomerStatus>, Error> {
+        self.handle.block_on(self.inner.status(domain))
+    }
+
+    fn billing_builder(&self, inner: crate::SendableBilling<'a>) -> SendableBilling<'a> {
+        Blocking {
+            inner,
+            handle: self.handle,
+        }
+    }
+}
+
+/// Blocking privacy request methods.
+pub type PrivacyMethods<'a> = Blocking<'a, crate::PrivacyMethods<'a>>;
+
+impl<'a> PrivacyMethods<'a> {
+    /// Delete everything Outlit stores about a user, and suppress them.
+    /// See [`crate::PrivacyMethods::delete_user`].
+    pub fn delete_user(&self, identity: impl Into<Identity>) -> Result<(), Error> {
+        self.handle.block_on(self.inner.delete_user(identity))
+    }
+
+    /// Stop tracking a user. See [`crate::PrivacyMethods::suppress_user`].
+    pub fn suppress_user(&self, identity: impl Into<Identity>) -> Result<(), Error> {
+        self.handle.block_on(self.inner.suppress_user(identity))
+    }
+
+    /// Check whether a user was suppressed by this client.
+    pub fn is_suppressed(&self, identity: impl Into<Identity>) -> bool {
+        self.inner.is_suppressed(identity)
+    }
+}
@@ -1,20 +1,75 @@
 //! Event builders for fluent API.
 
+use crate::sampling::Rng;
 use crate::types::{
-    BillingEventData, BillingStatus, CustomEventData, IdentifyEventData, JourneyStage,
-    StageEventData, TrackerEvent,
+    AliasEventData, BillingActivity, BillingEventData, BillingInterval, BillingStatus, Consent,
+    CustomEventData, GroupEventData, Identifiers, IdentifyEventData, JourneyStage,
+    PageviewEventData, StageEventData, TrackerEvent, CONSENT_PROPERTY, EMAIL_PROPERTY,
+    FINGERPRINT_PROPERTY, RESERVED_NAMESPACE, RESERVED_PREFIX, USER_ID_PROPERTY,
 };
-use crate::{Email, Fingerprint, UserId};
+use crate::{Email, Error, Fingerprint, IntoTimestamp, UserId};
+use serde::Serialize;
 use serde_json::{json, Value};
 use std::collections::HashMap;
+use std::sync::OnceLock;
 use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::warn;
+
+/// Generate a random (version 4) UUID for an event.
+pub(crate) fn new_event_id() -> String {
+    static RNG: OnceLock<Rng> = OnceLock::new();
+    let rng = RNG.get_or_init(|| {
+        // Mix in the process ID so processes started together don't collide
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos() as u64;
+        Rng::with_seed(nanos ^ (u64::from(std::process::id()) << 32))
+    });
+
+    let high = (rng.next_u64() & !0xF000) | 0x4000;
+    let low = (rng.next_u64() & !(0b11 << 62)) | (0b10 << 62);
+    format!(
+        "{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+        high >> 32,
+        (high >> 16) & 0xFFFF,
+        high & 0xFFFF,
+        low >> 48,
+        low & 0xFFFF_FFFF_FFFF
+    )
+}
 
-/// Get current timestamp in milliseconds.
-fn now_ms() -> i64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_millis() as i64
+/// Insert a user-supplied property, moving keys with the reserved prefix
+/// under [`RESERVED_NAMESPACE`] so they can't overwrite SDK-set values.
+pub(crate) fn insert_property(
+    properties: &mut HashMap<String, Value>,
+    mut key: String,
+    value: Value,
+) {
+    if key.starts_with(RESERVED_PREFIX) {
+        warn!(key = %key, "property uses reserved `__` prefix, sending as `{RESERVED_NAMESPACE}{key}`");
+        key.insert_str(0, RESERVED_NAMESPACE);
+    }
+    properties.insert(key, value);
+}
+
+/// Insert each top-level field of `value` as a property, via
+/// [`insert_property`].
+fn extend_properties(
+    properties: &mut HashMap<String, Value>,
+    value: impl Serialize,
+) -> Result<(), Error> {
+    match serde_json::to_value(value)? {
+        Value::Object(fields) => {
+            for (key, value) in fields {
+                insert_property(properties, key, value);
+            }
+            Ok(())
+        }
+        other => Err(Error::Serialization(serde::ser::Error::custom(format!(
+            "expected a struct or map of properties, got {other}"
+        )))),
+    }
 }
 
 /// Build a server URL from identity.
@@ -24,10 +79,16 @@ fn server_url(email: Option<&str>, user_id: Option<&str>, fingerprint: Option<&s
 }
 
 /// Identity for events.
+///
+/// Built from the [`email`](crate::email), [`user_id`](crate::user_id), and
+/// [`fingerprint`](crate::fingerprint) helpers.
 #[derive(Debug, Clone)]
 pub enum Identity {
+    /// Email identity.
     Email(Email),
+    /// User ID identity.
     UserId(UserId),
+    /// Fingerprint (device identifier) identity.
     Fingerprint(Fingerprint),
 }
 
@@ -54,6 +115,16 @@ impl Identity {
     }
 }
 
+impl From<Identity> for Identifiers {
+    fn from(identity: Identity) -> Self {
+        Identifiers {
+            email: identity.email().map(String::from),
+            user_id: identity.user_id().map(String::from),
+            fingerprint: identity.fingerprint().map(String::from),
+        }
+    }
+}
+
 impl From<Email> for Identity {
     fn from(e: Email) -> Self {
         Identity::Email(e)
@@ -79,13 +150,14 @@ impl From<Fingerprint> for Identity {
 /// Builder for track events.
 #[derive(Debug)]
 pub struct TrackBuilder {
-    event_name: String,
+    pub(crate) event_name: String,
     identity: Identity,
     additional_email: Option<String>,
     additional_user_id: Option<String>,
     additional_fingerprint: Option<String>,
     properties: HashMap<String, Value>,
     timestamp: Option<i64>,
+    message_id: Option<String>,
 }
 
 impl TrackBuilder {
@@ -98,12 +170,13 @@ impl TrackBuilder {
             additional_fingerprint: None,
             properties: HashMap::new(),
             timestamp: None,
+            message_id: None,
         }
     }
 
     /// Add email (if identity was user_id or fingerprint).
     pub fn email(mut self, email: impl Into<String>) -> Self {
-        self.additional_email = Some(email.into());
+        self.additional_email = Some(crate::normalize_email(email.into()));
         self
     }
 
@@ -120,19 +193,50 @@ impl TrackBuilder {
     }
 
     /// Add a property.
+    ///
+    /// Keys starting with `__` are reserved for the SDK; such properties
+    /// are sent under a `user.` namespace, e.g. `user.__email`, with a
+    /// warning.
     pub fn property(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
-        self.properties.insert(key.into(), value.into());
+        insert_property(&mut self.properties, key.into(), value.into());
         self
     }
 
-    /// Set custom timestamp (milliseconds since epoch).
-    pub fn timestamp(mut self, ts: i64) -> Self {
-        self.timestamp = Some(ts);
+    /// Add each top-level field of a serializable struct or map as a
+    /// property.
+    ///
+    /// Nested fields are kept as nested JSON. Returns
+    /// [`Error::Serialization`] if `value` does not serialize to a JSON
+    /// object. Reserved `__` keys are namespaced as in
+    /// [`property`](Self::property).
+    pub fn properties(mut self, value: impl Serialize) -> Result<Self, Error> {
+        extend_properties(&mut self.properties, value)?;
+        Ok(self)
+    }
+
+    /// Set custom timestamp: milliseconds since epoch, or any other
+    /// [`IntoTimestamp`] such as a `SystemTime`.
+    pub fn timestamp(mut self, ts: impl IntoTimestamp) -> Self {
+        self.timestamp = Some(ts.into_timestamp_millis());
         self
     }
 
-    /// Build the event.
-    pub(crate) fn build(self) -> TrackerEvent {
+    /// Mark the consent the event needs, checked against the client's
+    /// [`ConsentPolicy`](crate::ConsentPolicy). Sent as `__consent`.
+    pub fn consent(mut self, consent: Consent) -> Self {
+        self.properties
+            .insert(CONSENT_PROPERTY.into(), json!(consent));
+        self
+    }
+
+    /// Set an idempotency key so repeated sends of this event are deduplicated.
+    pub fn idempotency_key(mut self, key: impl Into<String>) -> Self {
+        self.message_id = Some(key.into());
+        self
+    }
+
+    /// Build the event, timestamped `now` unless a timestamp was set.
+    pub(crate) fn build_at(self, now: i64) -> TrackerEvent {
         let email = self
             .identity
             .email()
@@ -151,16 +255,196 @@ impl TrackBuilder {
 
         let mut properties = self.properties;
         // Include identity in properties for server-side resolution
-        properties.insert("__email".into(), json!(email));
-        properties.insert("__userId".into(), json!(user_id));
-        properties.insert("__fingerprint".into(), json!(fingerprint));
+        properties.insert(EMAIL_PROPERTY.into(), json!(email));
+        properties.insert(USER_ID_PROPERTY.into(), json!(user_id));
+        properties.insert(FINGERPRINT_PROPERTY.into(), json!(fingerprint));
 
         TrackerEvent::Custom(CustomEventData {
-            timestamp: self.timestamp.unwrap_or_else(now_ms),
+            timestamp: self.timestamp.unwrap_or(now),
             url: server_url(email.as_deref(), user_id.as_deref(), fingerprint.as_deref()),
             path: "/".into(),
             event_name: self.event_name,
             properties: Some(properties),
+            message_id: self.message_id,
+            event_id: Some(new_event_id()),
+        })
+    }
+}
+
+// ============================================
+// PAGE BUILDER
+// ============================================
+
+/// Builder for page view events, covering both web pages and app screens.
+#[derive(Debug)]
+pub struct PageBuilder {
+    name: String,
+    screen: bool,
+    identity: Identity,
+    additional_email: Option<String>,
+    additional_user_id: Option<String>,
+    additional_fingerprint: Option<String>,
+    url: Option<String>,
+    path: Option<String>,
+    referrer: Option<String>,
+    properties: HashMap<String, Value>,
+    timestamp: Option<i64>,
+    message_id: Option<String>,
+}
+
+impl PageBuilder {
+    /// Start a web page view; `name` is sent as the page title.
+    pub(crate) fn page(identity: impl Into<Identity>, name: impl Into<String>) -> Self {
+        Self::new(identity.into(), name.into(), false)
+    }
+
+    /// Start an app screen view; `name` is sent as the title and, unless a
+    /// URL is set, as a `screen://` URL.
+    pub(crate) fn screen(identity: impl Into<Identity>, name: impl Into<String>) -> Self {
+        Self::new(identity.into(), name.into(), true)
+    }
+
+    fn new(identity: Identity, name: String, screen: bool) -> Self {
+        Self {
+            name,
+            screen,
+            identity,
+            additional_email: None,
+            additional_user_id: None,
+            additional_fingerprint: None,
+            url: None,
+            path: None,
+            referrer: None,
+            properties: HashMap::new(),
+            timestamp: None,
+            message_id: None,
+        }
+    }
+
+    /// Add email (if identity was user_id or fingerprint).
+    pub fn email(mut self, email: impl Into<String>) -> Self {
+        self.additional_email = Some(crate::normalize_email(email.into()));
+        self
+    }
+
+    /// Add user_id (if identity was email or fingerprint).
+    pub fn user_id(mut self, user_id: impl Into<String>) -> Self {
+        self.additional_user_id = Some(user_id.into());
+        self
+    }
+
+    /// Add fingerprint (device identifier) to link this event to a device.
+    pub fn fingerprint(mut self, fingerprint: impl Into<String>) -> Self {
+        self.additional_fingerprint = Some(fingerprint.into());
+        self
+    }
+
+    /// Set the full URL that was visited.
+    ///
+    /// The path is taken from the URL unless set with [`path`](Self::path).
+    pub fn url(mut self, url: impl Into<String>) -> Self {
+        self.url = Some(url.into());
+        self
+    }
+
+    /// Set the path that was visited, overriding the one parsed from the URL.
+    pub fn path(mut self, path: impl Into<String>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// Set the referring URL.
+    pub fn referrer(mut self, referrer: impl Into<String>) -> Self {
+        self.referrer = Some(referrer.into());
+        self
+    }
+
+    /// Add a property.
+    ///
+    /// Keys starting with `__` are reserved for the SDK; such properties
+    /// are sent under a `user.` namespace, e.g. `user.__email`, with a
+    /// warning.
+    pub fn property(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+        insert_property(&mut self.properties, key.into(), value.into());
+        self
+    }
+
+    /// Add each top-level field of a serializable struct or map as a
+    /// property. See [`TrackBuilder::properties`].
+    pub fn properties(mut self, value: impl Serialize) -> Result<Self, Error> {
+        extend_properties(&mut self.properties, value)?;
+        Ok(self)
+    }
+
+    /// Set custom timestamp: milliseconds since epoch, or any other
+    /// [`IntoTimestamp`] such as a `SystemTime`.
+    pub fn timestamp(mut self, ts: impl IntoTimestamp) -> Self {
+        self.timestamp = Some(ts.into_timestamp_millis());
+        self
+    }
+
+    /// Mark the consent the event needs, checked against the client's
+    /// [`ConsentPolicy`](crate::ConsentPolicy). Sent as `__consent`.
+    pub fn consent(mut self, consent: Consent) -> Self {
+        self.properties
+            .insert(CONSENT_PROPERTY.into(), json!(consent));
+        self
+    }
+
+    /// Set an idempotency key so repeated sends of this event are deduplicated.
+    pub fn idempotency_key(mut self, key: impl Into<String>) -> Self {
+        self.message_id = Some(key.into());
+        self
+    }
+
+    /// Build the event, timestamped `now` unless a timestamp was set.
+    pub(crate) fn build_at(self, now: i64) -> TrackerEvent {
+        let email = self
+            .identity
+            .email()
+            .map(String::from)
+            .or(self.additional_email);
+        let user_id = self
+            .identity
+            .user_id()
+            .map(String::from)
+            .or(self.additional_user_id);
+        let fingerprint = self
+            .identity
+            .fingerprint()
+            .map(String::from)
+            .or(self.additional_fingerprint);
+
+        let url = match self.url {
+            Some(url) => url,
+            None if self.screen => format!("screen://{}", self.name),
+            None => server_url(email.as_deref(), user_id.as_deref(), fingerprint.as_deref()),
+        };
+        let path = self
+            .path
+            .or_else(|| {
+                reqwest::Url::parse(&url)
+                    .ok()
+                    .map(|parsed| parsed.path().to_string())
+                    .filter(|path| path.starts_with('/'))
+            })
+            .unwrap_or_else(|| "/".into());
+
+        let mut properties = self.properties;
+        // Include identity in properties for server-side resolution
+        properties.insert(EMAIL_PROPERTY.into(), json!(email));
+        properties.insert(USER_ID_PROPERTY.into(), json!(user_id));
+        properties.insert(FINGERPRINT_PROPERTY.into(), json!(fingerprint));
+
+        TrackerEvent::Pageview(PageviewEventData {
+            timestamp: self.timestamp.unwrap_or(now),
+            url,
+            path,
+            title: Some(self.name),
+            referrer: self.referrer,
+            properties: Some(properties),
+            message_id: self.message_id,
+            event_id: Some(new_event_id()),
         })
     }
 }
@@ -177,6 +461,7 @@ pub struct IdentifyBuilder {
     additional_user_id: Option<String>,
     additional_fingerprint: Option<String>,
     traits: HashMap<String, Value>,
+    message_id: Option<String>,
 }
 
 impl IdentifyBuilder {
@@ -187,12 +472,13 @@ impl IdentifyBuilder {
             additional_user_id: None,
             additional_fingerprint: None,
             traits: HashMap::new(),
+            message_id: None,
         }
     }
 
     /// Add email (if identity was user_id or fingerprint).
     pub fn email(mut self, email: impl Into<String>) -> Self {
-        self.additional_email = Some(email.into());
+        self.additional_email = Some(crate::normalize_email(email.into()));
         self
     }
 
@@ -214,8 +500,21 @@ impl IdentifyBuilder {
         self
     }
 
-    /// Build the event.
-    pub(crate) fn build(self) -> TrackerEvent {
+    /// Mark the consent the event needs, checked against the client's
+    /// [`ConsentPolicy`](crate::ConsentPolicy). Sent as `__consent`.
+    pub fn consent(mut self, consent: Consent) -> Self {
+        self.traits.insert(CONSENT_PROPERTY.into(), json!(consent));
+        self
+    }
+
+    /// Set an idempotency key so repeated sends of this event are deduplicated.
+    pub fn idempotency_key(mut self, key: impl Into<String>) -> Self {
+        self.message_id = Some(key.into());
+        self
+    }
+
+    /// Build the event, timestamped `now`.
+    pub(crate) fn build_at(self, now: i64) -> TrackerEvent {
         let email = self
             .identity
             .email()
@@ -233,7 +532,7 @@ impl IdentifyBuilder {
             .or(self.additional_fingerprint);
 
         TrackerEvent::Identify(IdentifyEventData {
-            timestamp: now_ms(),
+            timestamp: now,
             url: server_url(email.as_deref(), user_id.as_deref(), fingerprint.as_deref()),
             path: "/".into(),
             email,
@@ -244,6 +543,8 @@ impl IdentifyBuilder {
             } else {
                 Some(self.traits)
             },
+            message_id: self.message_id,
+            event_id: Some(new_event_id()),
         })
     }
 }
@@ -256,28 +557,36 @@ impl IdentifyBuilder {
 #[derive(Debug)]
 pub struct StageBuilder {
     stage: JourneyStage,
+    reason: Option<String>,
+    previous_stage: Option<JourneyStage>,
+    effective_at: Option<i64>,
     identity: Identity,
     additional_email: Option<String>,
     additional_user_id: Option<String>,
     additional_fingerprint: Option<String>,
     properties: HashMap<String, Value>,
+    message_id: Option<String>,
 }
 
 impl StageBuilder {
     pub(crate) fn new(stage: JourneyStage, identity: impl Into<Identity>) -> Self {
         Self {
             stage,
+            reason: None,
+            previous_stage: None,
+            effective_at: None,
             identity: identity.into(),
             additional_email: None,
             additional_user_id: None,
             additional_fingerprint: None,
             properties: HashMap::new(),
+            message_id: None,
         }
     }
 
     /// Add email (if identity was user_id or fingerprint).
     pub fn email(mut self, email: impl Into<String>) -> Self {
-        self.additional_email = Some(email.into());
+        self.additional_email = Some(crate::normalize_email(email.into()));
         self
     }
 
@@ -293,14 +602,58 @@ impl StageBuilder {
         self
     }
 
+    /// Set why the user moved to this stage.
+    pub fn reason(mut self, reason: impl Into<String>) -> Self {
+        self.reason = Some(reason.into());
+        self
+    }
+
+    /// Set the stage the user moved from.
+    pub fn previous_stage(mut self, stage: impl Into<JourneyStage>) -> Self {
+        self.previous_stage = Some(stage.into());
+        self
+    }
+
+    /// Set when the transition took effect, if earlier than the event is
+    /// sent: milliseconds since epoch, or any other [`IntoTimestamp`].
+    pub fn effective_at(mut self, at: impl IntoTimestamp) -> Self {
+        self.effective_at = Some(at.into_timestamp_millis());
+        self
+    }
+
     /// Add a property.
+    ///
+    /// Keys starting with `__` are reserved for the SDK; such properties
+    /// are sent under a `user.` namespace, e.g. `user.__email`, with a
+    /// warning.
     pub fn property(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
-        self.properties.insert(key.into(), value.into());
+        insert_property(&mut self.properties, key.into(), value.into());
+        self
+    }
+
+    /// Add each top-level field of a serializable struct or map as a
+    /// property. See [`TrackBuilder::properties`].
+    pub fn properties(mut self, value: impl Serialize) -> Result<Self, Error> {
+        extend_properties(&mut self.properties, value)?;
+        Ok(self)
+    }
+
+    /// Mark the consent the event needs, checked against the client's
+    /// [`ConsentPolicy`](crate::ConsentPolicy). Sent as `__consent`.
+    pub fn consent(mut self, consent: Consent) -> Self {
+        self.properties
+            .insert(CONSENT_PROPERTY.into(), json!(consent));
+        self
+    }
+
+    /// Set an idempotency key so repeated sends of this event are deduplicated.
+    pub fn idempotency_key(mut self, key: impl Into<String>) -> Self {
+        self.message_id = Some(key.into());
         self
     }
 
-    /// Build the event.
-    pub(crate) fn build(self) -> TrackerEvent {
+    /// Build the event, timestamped `now`.
+    pub(crate) fn build_at(self, now: i64) -> TrackerEvent {
         let email = self
             .identity
             .email()
@@ -319,20 +672,25 @@ impl StageBuilder {
 
         let mut properties = self.properties;
         // Include identity in properties for server-side resolution
-        properties.insert("__email".into(), json!(email));
-        properties.insert("__userId".into(), json!(user_id));
-        properties.insert("__fingerprint".into(), json!(fingerprint));
+        properties.insert(EMAIL_PROPERTY.into(), json!(email));
+        properties.insert(USER_ID_PROPERTY.into(), json!(user_id));
+        properties.insert(FINGERPRINT_PROPERTY.into(), json!(fingerprint));
 
         TrackerEvent::Stage(StageEventData {
-            timestamp: now_ms(),
+            timestamp: now,
             url: server_url(email.as_deref(), user_id.as_deref(), fingerprint.as_deref()),
             path: "/".into(),
             stage: self.stage,
+            reason: self.reason,
+            previous_stage: self.previous_stage,
+            effective_at: self.effective_at,
             properties: if properties.is_empty() {
                 None
             } else {
                 Some(properties)
             },
+            message_id: self.message_id,
+            event_id: Some(new_event_id()),
         })
     }
 }
@@ -348,7 +706,18 @@ pub struct BillingBuilder {
     domain: String,
     customer_id: Option<String>,
     stripe_customer_id: Option<String>,
+    amount: Option<i64>,
+    currency: Option<String>,
+    mrr: Option<i64>,
+    interval: Option<BillingInterval>,
+    activity: Option<BillingActivity>,
+    invoice_id: Option<String>,
+    plan: Option<String>,
+    previous_plan: Option<String>,
+    seats: Option<u32>,
+    previous_seats: Option<u32>,
     properties: HashMap<String, Value>,
+    message_id: Option<String>,
 }
 
 impl BillingBuilder {
@@ -358,7 +727,18 @@ impl BillingBuilder {
             domain: domain.into(),
             customer_id: None,
             stripe_customer_id: None,
+            amount: None,
+            currency: None,
+            mrr: None,
+            interval: None,
+            activity: None,
+            invoice_id: None,
+            plan: None,
+            previous_plan: None,
+            seats: None,
+            previous_seats: None,
             properties: HashMap::new(),
+            message_id: None,
         }
     }
 
@@ -374,36 +754,370 @@ impl BillingBuilder {
         self
     }
 
+    /// Set the amount charged, in the currency's minor unit (e.g. cents).
+    pub fn amount_cents(mut self, amount: i64) -> Self {
+        self.amount = Some(amount);
+        self
+    }
+
+    /// Set the ISO 4217 currency code, such as `"usd"`. Sent lowercase.
+    pub fn currency(mut self, currency: impl Into<String>) -> Self {
+        self.currency = Some(currency.into().to_ascii_lowercase());
+        self
+    }
+
+    /// Set monthly recurring revenue, in the currency's minor unit.
+    pub fn mrr_cents(mut self, mrr: i64) -> Self {
+        self.mrr = Some(mrr);
+        self
+    }
+
+    /// Set how often the customer is billed.
+    pub fn interval(mut self, interval: BillingInterval) -> Self {
+        self.interval = Some(interval);
+        self
+    }
+
+    /// Override the billing status.
+    pub fn status(mut self, status: BillingStatus) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Set the invoice this event is about.
+    pub fn invoice_id(mut self, id: impl Into<String>) -> Self {
+        self.invoice_id = Some(id.into());
+        self
+    }
+
+    /// Set the customer's current plan.
+    pub fn plan(mut self, plan: impl Into<String>) -> Self {
+        self.plan = Some(plan.into());
+        self
+    }
+
+    /// Set the customer's plan before a plan change.
+    pub fn previous_plan(mut self, plan: impl Into<String>) -> Self {
+        self.previous_plan = Some(plan.into());
+        self
+    }
+
+    /// Set the number of seats.
+    pub fn seats(mut self, seats: u32) -> Self {
+        self.seats = Some(seats);
+        self
+    }
+
+    /// Set the seat count before a seat change.
+    pub fn previous_seats(mut self, seats: u32) -> Self {
+        self.previous_seats = Some(seats);
+        self
+    }
+
+    pub(crate) fn activity(mut self, activity: BillingActivity) -> Self {
+        self.activity = Some(activity);
+        self
+    }
+
     /// Add a property.
+    ///
+    /// Keys starting with `__` are reserved for the SDK; such properties
+    /// are sent under a `user.` namespace, e.g. `user.__email`, with a
+    /// warning.
     pub fn property(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
-        self.properties.insert(key.into(), value.into());
+        insert_property(&mut self.properties, key.into(), value.into());
+        self
+    }
+
+    /// Add each top-level field of a serializable struct or map as a
+    /// property. See [`TrackBuilder::properties`].
+    pub fn properties(mut self, value: impl Serialize) -> Result<Self, Error> {
+        extend_properties(&mut self.properties, value)?;
+        Ok(self)
+    }
+
+    /// Set an idempotency key so repeated sends of this event are deduplicated.
+    pub fn idempotency_key(mut self, key: impl Into<String>) -> Self {
+        self.message_id = Some(key.into());
         self
     }
 
-    /// Build the event.
-    pub(crate) fn build(self) -> TrackerEvent {
+    /// Build the event, timestamped `now`.
+    pub(crate) fn build_at(self, now: i64) -> TrackerEvent {
         TrackerEvent::Billing(BillingEventData {
-            timestamp: now_ms(),
+            timestamp: now,
             url: format!("server://{}", self.domain),
             path: "/".into(),
             status: self.status,
             customer_id: self.customer_id,
             stripe_customer_id: self.stripe_customer_id,
             domain: Some(self.domain),
+            amount: self.amount,
+            currency: self.currency,
+            mrr: self.mrr,
+            interval: self.interval,
+            activity: self.activity,
+            invoice_id: self.invoice_id,
+            plan: self.plan,
+            previous_plan: self.previous_plan,
+            seats: self.seats,
+            previous_seats: self.previous_seats,
             properties: if self.properties.is_empty() {
                 None
             } else {
                 Some(self.properties)
             },
+            message_id: self.message_id,
+            event_id: Some(new_event_id()),
+        })
+    }
+}
+
+// ============================================
+// ALIAS BUILDER
+// ============================================
+
+/// Builder for alias events.
+#[derive(Debug)]
+pub struct AliasBuilder {
+    previous: Identifiers,
+    current: Identifiers,
+    message_id: Option<String>,
+}
+
+impl AliasBuilder {
+    pub(crate) fn new(previous: impl Into<Identity>, current: impl Into<Identity>) -> Self {
+        Self {
+            previous: previous.into().into(),
+            current: current.into().into(),
+            message_id: None,
+        }
+    }
+
+    /// Add email to the current identity.
+    pub fn email(mut self, email: impl Into<String>) -> Self {
+        self.current.email = Some(crate::normalize_email(email.into()));
+        self
+    }
+
+    /// Add user_id to the current identity.
+    pub fn user_id(mut self, user_id: impl Into<String>) -> Self {
+        self.current.user_id = Some(user_id.into());
+        self
+    }
+
+    /// Add fingerprint (device identifier) to the current identity.
+    pub fn fingerprint(mut self, fingerprint: impl Into<String>) -> Self {
+        self.current.fingerprint = Some(fingerprint.into());
+        self
+    }
+
+    /// Set an idempotency key so repeated sends of this event are deduplicated.
+    pub fn idempotency_key(mut self, key: impl Into<String>) -> Self {
+        self.message_id = Some(key.into());
+        self
+    }
+
+    /// Build the event, timestamped `now`.
+    pub(crate) fn build_at(self, now: i64) -> TrackerEvent {
+        TrackerEvent::Alias(AliasEventData {
+            timestamp: now,
+            url: server_url(
+                self.current.email.as_deref(),
+                self.current.user_id.as_deref(),
+                self.current.fingerprint.as_deref(),
+            ),
+            path: "/".into(),
+            previous: self.previous,
+            current: self.current,
+            message_id: self.message_id,
+            event_id: Some(new_event_id()),
         })
     }
 }
 
+// ============================================
+// GROUP BUILDER
+// ============================================
+
+/// Builder for group (account) events.
+#[derive(Debug)]
+pub struct GroupBuilder {
+    group_id: String,
+    user: Option<Identifiers>,
+    traits: HashMap<String, Value>,
+    message_id: Option<String>,
+}
+
+impl GroupBuilder {
+    pub(crate) fn new(group_id: impl Into<String>) -> Self {
+        Self {
+            group_id: group_id.into(),
+            user: None,
+            traits: HashMap::new(),
+            message_id: None,
+        }
+    }
+
+    /// Associate a user with the group.
+    pub fn user(mut self, identity: impl Into<Identity>) -> Self {
+        self.user = Some(identity.into().into());
+        self
+    }
+
+    /// Add a group trait.
+    pub fn trait_(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.traits.insert(key.into(), value.into());
+        self
+    }
+
+    /// Set an idempotency key so repeated sends of this event are deduplicated.
+    pub fn idempotency_key(mut self, key: impl Into<String>) -> Self {
+        self.message_id = Some(key.into());
+        self
+    }
+
+    /// Build the event, timestamped `now`.
+    pub(crate) fn build_at(self, now: i64) -> TrackerEvent {
+        TrackerEvent::Group(GroupEventData {
+            timestamp: now,
+            url: format!("server://{}", self.group_id),
+            path: "/".into(),
+            group_id: self.group_id,
+            user: self.user,
+            traits: if self.traits.is_empty() {
+                None
+            } else {
+                Some(self.traits)
+            },
+            message_id: self.message_id,
+            event_id: Some(new_event_id()),
+        })
+    }
+}
+
+/// An event builder the client can build and queue.
+pub(crate) trait BuildEvent {
+    /// Build the event, timestamped `now` unless a track or page event has
+    /// its own timestamp.
+    fn build_at(self, now: i64) -> TrackerEvent;
+
+    /// Build the event, timestamped with the system clock.
+    #[cfg(test)]
+    fn build(self) -> TrackerEvent
+    where
+        Self: Sized,
+    {
+        self.build_at(crate::Clock::now_ms(&crate::SystemClock))
+    }
+}
+
+impl BuildEvent for TrackBuilder {
+    fn build_at(self, now: i64) -> TrackerEvent {
+        self.build_at(now)
+    }
+}
+
+impl BuildEvent for PageBuilder {
+    fn build_at(self, now: i64) -> TrackerEvent {
+        self.build_at(now)
+    }
+}
+
+impl BuildEvent for IdentifyBuilder {
+    fn build_at(self, now: i64) -> TrackerEvent {
+        self.build_at(now)
+    }
+}
+
+impl BuildEvent for StageBuilder {
+    fn build_at(self, now: i64) -> TrackerEvent {
+        self.build_at(now)
+    }
+}
+
+impl BuildEvent for BillingBuilder {
+    fn build_at(self, now: i64) -> TrackerEvent {
+        self.build_at(now)
+    }
+}
+
+impl BuildEvent for AliasBuilder {
+    fn build_at(self, now: i64) -> TrackerEvent {
+        self.build_at(now)
+    }
+}
+
+impl BuildEvent for GroupBuilder {
+    fn build_at(self, now: i64) -> TrackerEvent {
+        self.build_at(now)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{email, fingerprint, user_id};
 
+    #[test]
+    fn test_new_event_id_is_uuid_v4() {
+        let id = new_event_id();
+        let groups: Vec<_> = id.split('-').map(str::len).collect();
+        assert_eq!(groups, [8, 4, 4, 4, 12]);
+        assert_eq!(&id[14..15], "4");
+        assert!(matches!(&id[19..20], "8" | "9" | "a" | "b"));
+        assert_ne!(id, new_event_id());
+    }
+
+    #[test]
+    fn test_builders_assign_event_ids() {
+        let first = TrackBuilder::new("signup", email("user@example.com")).build();
+        let second = TrackBuilder::new("signup", email("user@example.com")).build();
+        assert!(first.event_id().is_some());
+        assert_ne!(first.event_id(), second.event_id());
+    }
+
+    #[test]
+    fn test_emails_are_normalized() {
+        let event = TrackBuilder::new("signup", email("  User@Example.COM "))
+            .build()
+            .identity()
+            .unwrap();
+        assert_eq!(event.email.as_deref(), Some("user@example.com"));
+
+        let event = IdentifyBuilder::new(user_id("usr_123"))
+            .email(" Jane@Example.com\n")
+            .build();
+        assert_eq!(
+            event.identity().unwrap().email.as_deref(),
+            Some("jane@example.com")
+        );
+    }
+
+    #[test]
+    fn test_email_validation() {
+        for valid in ["user@example.com", "first.last+tag@mail.example.co.uk"] {
+            assert!(email(valid).is_valid(), "{valid}");
+        }
+        for invalid in [
+            "N/A",
+            "",
+            "user@",
+            "@example.com",
+            "user@localhost",
+            "user@example..com",
+            "first last@example.com",
+            "user@exa_mple.com",
+            "a@b@example.com",
+        ] {
+            assert!(!email(invalid).is_valid(), "{invalid}");
+            assert!(matches!(
+                crate::Email::parse(invalid),
+                Err(crate::Error::InvalidIdentity(_))
+            ));
+        }
+    }
+
     #[test]
     fn test_track_builder_with_email() {
         let event = TrackBuilder::new("signup", email("user@example.com"))
@@ -485,6 +1199,143 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_track_builder_reserved_property_is_namespaced() {
+        let event = TrackBuilder::new("signup", email("user@example.com"))
+            .property("__email", "attacker@example.com")
+            .property("__custom", "value")
+            .property("plan", "pro")
+            .build();
+
+        if let TrackerEvent::Custom(data) = event {
+            let props = data.properties.unwrap();
+            assert_eq!(props.get("__email").unwrap(), "user@example.com");
+            assert_eq!(props.get("user.__email").unwrap(), "attacker@example.com");
+            assert!(!props.contains_key("__custom"));
+            assert_eq!(props.get("user.__custom").unwrap(), "value");
+            assert_eq!(props.get("plan").unwrap(), "pro");
+        } else {
+            panic!("Expected custom event");
+        }
+    }
+
+    #[test]
+    fn test_properties_accepts_maps_on_stage_builder() {
+        let extra = HashMap::from([("source", "onboarding"), ("__email", "spoofed")]);
+
+        let event = StageBuilder::new(JourneyStage::Activated, email("user@example.com"))
+            .properties(extra)
+            .unwrap()
+            .build();
+
+        if let TrackerEvent::Stage(data) = event {
+            let props = data.properties.unwrap();
+            assert_eq!(props.get("source").unwrap(), "onboarding");
+            assert_eq!(props.get("__email").unwrap(), "user@example.com");
+            assert_eq!(props.get("user.__email").unwrap(), "spoofed");
+        } else {
+            panic!("Expected stage event");
+        }
+    }
+
+    #[test]
+    fn test_track_builder_properties() {
+        #[derive(Serialize)]
+        struct Checkout {
+            plan: &'static str,
+            seats: u32,
+            billing: Billing,
+        }
+
+        #[derive(Serialize)]
+        struct Billing {
+            interval: &'static str,
+        }
+
+        let event = TrackBuilder::new("checkout", email("user@example.com"))
+            .properties(Checkout {
+                plan: "pro",
+                seats: 5,
+                billing: Billing { interval: "yearly" },
+            })
+            .unwrap()
+            .build();
+
+        if let TrackerEvent::Custom(data) = event {
+            let props = data.properties.unwrap();
+            assert_eq!(props.get("plan").unwrap(), "pro");
+            assert_eq!(props.get("seats").unwrap(), 5);
+            assert_eq!(
+                props.get("billing").unwrap(),
+                &json!({ "interval": "yearly" })
+            );
+            assert_eq!(props.get("__email").unwrap(), "user@example.com");
+        } else {
+            panic!("Expected custom event");
+        }
+    }
+
+    #[test]
+    fn test_track_builder_properties_rejects_non_object() {
+        let result = TrackBuilder::new("checkout", email("user@example.com")).properties("pro");
+
+        assert!(matches!(result, Err(Error::Serialization(_))));
+    }
+
+    #[test]
+    fn test_billing_builder_namespaces_reserved_property() {
+        let event = BillingBuilder::new(BillingStatus::Paid, "acme.com")
+            .property("__userId", "usr_123")
+            .build();
+
+        if let TrackerEvent::Billing(data) = event {
+            let props = data.properties.unwrap();
+            assert!(!props.contains_key("__userId"));
+            assert_eq!(props.get("user.__userId").unwrap(), "usr_123");
+        } else {
+            panic!("Expected billing event");
+        }
+    }
+
+    #[test]
+    fn test_timestamp_accepts_system_time() {
+        let time = UNIX_EPOCH + std::time::Duration::from_millis(1_706_400_000_123);
+        let event = PageBuilder::page(email("user@example.com"), "Pricing")
+            .timestamp(time)
+            .build();
+
+        assert_eq!(event.timestamp(), 1_706_400_000_123);
+    }
+
+    #[test]
+    fn test_track_builder_timestamp_before_epoch() {
+        let time = UNIX_EPOCH - std::time::Duration::from_millis(1_500);
+        let event = TrackBuilder::new("signup", email("user@example.com"))
+            .timestamp(time)
+            .build();
+
+        if let TrackerEvent::Custom(data) = event {
+            assert_eq!(data.timestamp, -1_500);
+        } else {
+            panic!("Expected custom event");
+        }
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_track_builder_timestamp_accepts_datetime() {
+        let time = chrono::DateTime::from_timestamp_millis(1_706_400_000_123).unwrap();
+        let event = TrackBuilder::new("signup", email("user@example.com"))
+            .timestamp(time)
+            .build();
+
+        if let TrackerEvent::Custom(data) = event {
+            assert_eq!(data.timestamp, 1_706_400_000_123);
+        } else {
+            panic!("Expected custom event");
+        }
+    }
+
     #[test]
     fn test_identify_builder() {
         let event = IdentifyBuilder::new(email("user@example.com"))
@@ -532,6 +1383,27 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_stage_builder_transition_metadata() {
+        let event = StageBuilder::new(JourneyStage::Inactive, email("user@example.com"))
+            .reason("no_logins_30d")
+            .previous_stage("engaged")
+            .effective_at(1706400000000)
+            .build();
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["stage"], "inactive");
+        assert_eq!(json["reason"], "no_logins_30d");
+        assert_eq!(json["previousStage"], "engaged");
+        assert_eq!(json["effectiveAt"], 1706400000000_i64);
+
+        let event = StageBuilder::new(JourneyStage::Activated, email("user@example.com")).build();
+        let json = serde_json::to_value(&event).unwrap();
+        assert!(json.get("reason").is_none());
+        assert!(json.get("previousStage").is_none());
+        assert!(json.get("effectiveAt").is_none());
+    }
+
     #[test]
     fn test_stage_builder_with_fingerprint_identity() {
         let event =
@@ -562,4 +1434,128 @@ mod tests {
             panic!("Expected billing event");
         }
     }
+
+    #[test]
+    fn test_billing_builder_monetary_fields() {
+        let event = BillingBuilder::new(BillingStatus::Paid, "acme.com")
+            .amount_cents(58800)
+            .mrr_cents(4900)
+            .currency("USD")
+            .interval(BillingInterval::Year)
+            .build();
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["amount"], 58800);
+        assert_eq!(json["mrr"], 4900);
+        assert_eq!(json["currency"], "usd");
+        assert_eq!(json["interval"], "year");
+        assert!(json.get("properties").is_none());
+    }
+
+    #[test]
+    fn test_alias_builder() {
+        let event = AliasBuilder::new(email("old@example.com"), email("new@example.com"))
+            .user_id("usr_123")
+            .build();
+
+        if let TrackerEvent::Alias(data) = event {
+            assert_eq!(data.previous.email, Some("old@example.com".into()));
+            assert_eq!(data.previous.user_id, None);
+            assert_eq!(data.current.email, Some("new@example.com".into()));
+            assert_eq!(data.current.user_id, Some("usr_123".into()));
+            assert!(data.url.contains("new@example.com"));
+        } else {
+            panic!("Expected alias event");
+        }
+    }
+
+    #[test]
+    fn test_alias_builder_fingerprint_to_user_id() {
+        let event = AliasBuilder::new(fingerprint("device_abc123"), user_id("usr_123")).build();
+
+        if let TrackerEvent::Alias(data) = event {
+            assert_eq!(data.previous.fingerprint, Some("device_abc123".into()));
+            assert_eq!(data.current.user_id, Some("usr_123".into()));
+            assert_eq!(data.current.fingerprint, None);
+        } else {
+            panic!("Expected alias event");
+        }
+    }
+
+    #[test]
+    fn test_group_builder() {
+        let event = GroupBuilder::new("org_123")
+            .user(email("user@example.com"))
+            .trait_("name", "Acme Inc")
+            .build();
+
+        if let TrackerEvent::Group(data) = event {
+            assert_eq!(data.group_id, "org_123");
+            assert_eq!(data.url, "server://org_123");
+            let user = data.user.unwrap();
+            assert_eq!(user.email, Some("user@example.com".into()));
+            assert_eq!(data.traits.unwrap().get("name").unwrap(), "Acme Inc");
+        } else {
+            panic!("Expected group event");
+        }
+    }
+
+    #[test]
+    fn test_group_builder_without_traits() {
+        let event = GroupBuilder::new("org_123").build();
+
+        if let TrackerEvent::Group(data) = event {
+            assert!(data.user.is_none());
+            assert!(data.traits.is_none());
+        } else {
+            panic!("Expected group event");
+        }
+    }
+
+    #[test]
+    fn test_page_builder_takes_path_from_url() {
+        let event = PageBuilder::page(email("user@example.com"), "Pricing")
+            .url("https://example.com/pricing?plan=pro")
+            .referrer("https://google.com/")
+            .build();
+
+        if let TrackerEvent::Pageview(data) = event {
+            assert_eq!(data.url, "https://example.com/pricing?plan=pro");
+            assert_eq!(data.path, "/pricing");
+            assert_eq!(data.title.as_deref(), Some("Pricing"));
+            assert_eq!(data.referrer.as_deref(), Some("https://google.com/"));
+            let props = data.properties.unwrap();
+            assert_eq!(props.get("__email").unwrap(), "user@example.com");
+        } else {
+            panic!("Expected pageview event");
+        }
+    }
+
+    #[test]
+    fn test_page_builder_explicit_path_overrides_url() {
+        let event = PageBuilder::page(user_id("usr_123"), "Docs")
+            .url("https://example.com/docs/intro")
+            .path("/docs")
+            .build();
+
+        if let TrackerEvent::Pageview(data) = event {
+            assert_eq!(data.path, "/docs");
+        } else {
+            panic!("Expected pageview event");
+        }
+    }
+
+    #[test]
+    fn test_screen_builder_defaults_to_screen_url() {
+        let event = PageBuilder::screen(fingerprint("device_abc123"), "Settings").build();
+
+        if let TrackerEvent::Pageview(data) = event {
+            assert_eq!(data.url, "screen://Settings");
+            assert_eq!(data.title.as_deref(), Some("Settings"));
+            let props = data.properties.unwrap();
+            assert_eq!(props.get("__fingerprint").unwrap(), "device_abc123");
+        } else {
+            panic!("Expected pageview event");
+        }
+    }
 }
@@ -0,0 +1,157 @@
+//! Circuit breaker that pauses flushing while the ingest API is down.
+
+use crate::config::CircuitBreaker;
+use crate::types::FlushReport;
+use crate::Error;
+use std::sync::Mutex;
+use tokio::time::Instant;
+use tracing::{info, warn};
+
+/// Tracks consecutive flush failures and decides whether to flush.
+///
+/// Closed: flushes run normally. After `failure_threshold` consecutive
+/// failures the circuit opens and flushes are skipped until `cool_down` has
+/// passed. The next flush is then a probe: success closes the circuit,
+/// failure opens it for another cool-down.
+#[derive(Debug)]
+pub(crate) struct Circuit {
+    settings: Option<CircuitBreaker>,
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    failures: u32,
+    open_until: Option<Instant>,
+    probing: bool,
+}
+
+impl Circuit {
+    pub(crate) fn new(settings: Option<CircuitBreaker>) -> Self {
+        Self {
+            settings,
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    /// Whether a flush may run now. Lets one probe through once the
+    /// cool-down has passed.
+    pub(crate) fn allow(&self) -> bool {
+        if self.settings.is_none() {
+            return true;
+        }
+        let mut state = self.lock();
+        match state.open_until {
+            None => true,
+            Some(until) if Instant::now() >= until && !state.probing => {
+                state.probing = true;
+                true
+            }
+            Some(_) => false,
+        }
+    }
+
+    /// Record a flush result. Flushes of an empty queue are not counted.
+    pub(crate) fn record(&self, result: &Result<FlushReport, Error>) {
+        let Some(settings) = self.settings else {
+            return;
+        };
+        let mut state = self.lock();
+        match result {
+            Ok(report) if report.sent == 0 && report.quarantined.is_empty() => {
+                // Nothing was sent, so a probe proved nothing
+                state.probing = false;
+            }
+            Ok(_) => {
+                if state.open_until.is_some() {
+                    info!("ingest API recovered, closing circuit");
+                }
+                *state = State::default();
+            }
+            Err(_) => {
+                state.failures += 1;
+                if state.probing || state.failures >= settings.failure_threshold {
+                    warn!(
+                        failures = state.failures,
+                        cool_down_ms = settings.cool_down.as_millis() as u64,
+                        "opening circuit, pausing flushes"
+                    );
+                    state.open_until = Some(Instant::now() + settings.cool_down);
+                    state.probing = false;
+                }
+            }
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn circuit() -> Circuit {
+        Circuit::new(Some(CircuitBreaker {
+            failure_threshold: 2,
+            cool_down: Duration::from_secs(30),
+        }))
+    }
+
+    fn sent() -> Result<FlushReport, Error> {
+        Ok(FlushReport {
+            sent: 1,
+            ..FlushReport::default()
+        })
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_opens_after_threshold_and_probes_after_cool_down() {
+        let circuit = circuit();
+
+        circuit.record(&Err(Error::Timeout));
+        assert!(circuit.allow());
+        circuit.record(&Err(Error::Timeout));
+        assert!(!circuit.allow());
+
+        tokio::time::advance(Duration::from_secs(30)).await;
+        assert!(circuit.allow());
+        // Only one probe at a time
+        assert!(!circuit.allow());
+
+        circuit.record(&sent());
+        assert!(circuit.allow());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_failed_probe_reopens() {
+        let circuit = circuit();
+        circuit.record(&Err(Error::Timeout));
+        circuit.record(&Err(Error::Timeout));
+
+        tokio::time::advance(Duration::from_secs(30)).await;
+        assert!(circuit.allow());
+        circuit.record(&Err(Error::Timeout));
+        assert!(!circuit.allow());
+    }
+
+    #[test]
+    fn test_disabled_always_allows() {
+        let circuit = Circuit::new(None);
+        for _ in 0..10 {
+            circuit.record(&Err(Error::Timeout));
+        }
+        assert!(circuit.allow());
+    }
+
+    #[test]
+    fn test_success_resets_failures() {
+        let circuit = circuit();
+        circuit.record(&Err(Error::Timeout));
+        circuit.record(&sent());
+        circuit.record(&Err(Error::Timeout));
+        assert!(circuit.allow());
+    }
+}
@@ -1,16 +1,40 @@
 //! Outlit client implementation.
 
-use crate::builders::{BillingBuilder, IdentifyBuilder, StageBuilder, TrackBuilder};
-use crate::config::{Config, OutlitBuilder};
+use crate::builders::{
+    insert_property, AliasBuilder, BillingBuilder, BuildEvent, GroupBuilder, IdentifyBuilder,
+    Identity, PageBuilder, StageBuilder, TrackBuilder,
+};
+use crate::circuit::Circuit;
+use crate::config::{Config, FlushCallbacks, OutlitBuilder, RateLimitPolicy};
+use crate::consent::Consents;
+use crate::dead_letter::{DeadLetterReason, DeadLetters};
+use crate::dedup::{ContentDeduplicator, Deduplicator};
+use crate::limits::serialized_size;
+#[cfg(feature = "metrics")]
+use crate::metrics;
 use crate::queue::EventQueue;
-use crate::transport::HttpTransport;
-use crate::types::{BillingStatus, IngestPayload, JourneyStage, SourceType};
-use crate::{Email, Error, Fingerprint, UserId};
+use crate::rate_limit::TokenBucket;
+use crate::sampling::Sampler;
+use crate::stats::{ClientStats, Stats};
+use crate::suppression::Suppressions;
+use crate::transport::{HttpTransport, Transport};
+use crate::types::{
+    BillingActivity, BillingInterval, BillingStatus, Consent, CustomerStatus, FlushReport,
+    Identifiers, IngestPayload, JourneyStage, RejectedEvent, SourceType, TrackerEvent,
+    ENVIRONMENT_PROPERTY,
+};
+use crate::{Email, Error, Fingerprint, OutlitEvent, UserId};
+use futures_util::stream::{self, StreamExt};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
-use tokio::sync::Mutex;
-use tokio::time::interval;
-use tracing::{debug, error, info, instrument};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::runtime::{Handle, RuntimeFlavor};
+use tokio::sync::{Mutex, Notify};
+use tokio::time::{interval_at, sleep_until, Instant};
+use tracing::{debug, error, info, instrument, warn};
 
 /// Outlit analytics client.
 ///
@@ -55,9 +79,25 @@ use tracing::{debug, error, info, instrument};
 pub struct Outlit {
     config: Config,
     queue: Arc<EventQueue>,
-    transport: Arc<HttpTransport>,
+    transport: Arc<Transport>,
+    sampler: Sampler,
+    dedup: Deduplicator,
+    content_dedup: ContentDeduplicator,
+    stats: Arc<Stats>,
+    /// Users not to track, shared with the project clients.
+    suppressions: Arc<Suppressions>,
+    /// Users' consent decisions, shared with the project clients.
+    consents: Arc<Consents>,
+    circuit: Arc<Circuit>,
+    rate_limiter: Option<TokenBucket>,
+    context: RwLock<HashMap<String, Value>>,
     is_shutdown: Arc<AtomicBool>,
+    enabled: AtomicBool,
+    flush_signal: Arc<Notify>,
+    enqueue_signal: Arc<Notify>,
     flush_handle: Mutex<Option<tokio::task::JoinHandle<()>>>,
+    /// Clients for the builder's additional projects, by name.
+    projects: HashMap<String, Outlit>,
 }
 
 impl Outlit {
@@ -68,18 +108,106 @@ impl Outlit {
 
     /// Create a new client from config.
     pub(crate) fn from_config(config: Config) -> Result<Self, Error> {
-        let queue = Arc::new(EventQueue::new(config.max_batch_size()));
-        let transport = Arc::new(HttpTransport::new(&config)?);
+        Self::with_transport(config, |config| {
+            Ok(Transport::Http(Box::new(HttpTransport::new(config)?)))
+        })
+    }
+
+    /// Create a new client from config, sending each project's batches to
+    /// the transport `transport` creates for it.
+    pub(crate) fn with_transport(
+        config: Config,
+        transport: impl Fn(&Config) -> Result<Transport, Error>,
+    ) -> Result<Self, Error> {
+        let stats = Arc::new(Stats::default());
+        let suppressions = Arc::new(Suppressions::default());
+        let consents = Arc::new(Consents::default());
+        let projects = config
+            .projects()
+            .iter()
+            .map(|(name, public_key)| {
+                let config = config.for_project(name, public_key);
+                let transport = transport(&config)?;
+                let client = Self::build(
+                    config,
+                    transport,
+                    stats.clone(),
+                    suppressions.clone(),
+                    consents.clone(),
+                    HashMap::new(),
+                )?;
+                Ok((name.clone(), client))
+            })
+            .collect::<Result<_, Error>>()?;
+
+        let transport = transport(&config)?;
+        Self::build(config, transport, stats, suppressions, consents, projects)
+    }
+
+    fn build(
+        config: Config,
+        transport: Transport,
+        stats: Arc<Stats>,
+        suppressions: Arc<Suppressions>,
+        consents: Arc<Consents>,
+        projects: HashMap<String, Outlit>,
+    ) -> Result<Self, Error> {
+        #[cfg(feature = "persist")]
+        let queue = Arc::new(
+            match config.persist_path() {
+                #[cfg(feature = "sqlite")]
+                Some(path) if config.persist_sqlite() => {
+                    EventQueue::sqlite(config.max_batch_size(), path)?
+                }
+                Some(path) => EventQueue::persistent(config.max_batch_size(), path)?,
+                None => EventQueue::new(config.max_batch_size()),
+            }
+            .with_max_attempts(config.max_delivery_attempts()),
+        );
+        #[cfg(not(feature = "persist"))]
+        let queue = Arc::new(
+            EventQueue::new(config.max_batch_size())
+                .with_max_attempts(config.max_delivery_attempts()),
+        );
+        let transport = Arc::new(transport);
+        let sampler = Sampler::new(config.sample_rate())
+            .event_rates(config.event_sample_rates().clone())
+            .deterministic(config.deterministic_sampling());
+        let dedup = Deduplicator::new(config.dedup_window());
+        let content_dedup = ContentDeduplicator::new(config.dedup_identical());
+        let circuit = Arc::new(Circuit::new(config.circuit_breaker()));
+        let rate_limiter = config.rate_limit().map(TokenBucket::new);
+        let enabled = AtomicBool::new(config.enabled());
 
         let client = Self {
             config,
             queue,
             transport,
+            sampler,
+            dedup,
+            content_dedup,
+            stats,
+            suppressions,
+            consents,
+            circuit,
+            rate_limiter,
+            context: RwLock::new(HashMap::new()),
             is_shutdown: Arc::new(AtomicBool::new(false)),
+            enabled,
+            flush_signal: Arc::new(Notify::new()),
+            enqueue_signal: Arc::new(Notify::new()),
             flush_handle: Mutex::new(None),
+            projects,
         };
 
-        client.start_flush_timer();
+        if client.config.auto_flush() {
+            client.start_flush_timer();
+            // Resume sending events recovered from disk without waiting a full interval
+            #[cfg(feature = "persist")]
+            if client.config.persist_path().is_some() {
+                client.flush_signal.notify_one();
+            }
+        }
 
         Ok(client)
     }
@@ -91,7 +219,112 @@ impl Outlit {
 
     /// Get the number of pending events.
     pub async fn pending_event_count(&self) -> usize {
-        self.queue.len().await
+        let mut count = 0;
+        for client in self.clients() {
+            count += client.queue.len().await;
+        }
+        count
+    }
+
+    /// Get the client for a project added with
+    /// [`OutlitBuilder::project`](crate::OutlitBuilder::project).
+    ///
+    /// Returns [`Error::Config`] if no project has that name.
+    pub fn project(&self, name: &str) -> Result<&Outlit, Error> {
+        self.projects
+            .get(name)
+            .ok_or_else(|| Error::Config(format!("unknown project {name:?}")))
+    }
+
+    /// Check whether the background flush task is running.
+    ///
+    /// Returns `false` if auto-flush is disabled, the client has been shut
+    /// down, or the task has exited unexpectedly (e.g. after a panic), in
+    /// which case events are only sent on size-triggered or manual flushes.
+    pub async fn flush_task_alive(&self) -> bool {
+        self.flush_handle
+            .lock()
+            .await
+            .as_ref()
+            .is_some_and(|handle| !handle.is_finished())
+    }
+
+    /// Get the number of events dropped without being sent.
+    pub fn dropped_event_count(&self) -> usize {
+        self.stats.dropped() as usize
+    }
+
+    /// Get a snapshot of delivery counters and the last flush's outcome.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use outlit::Outlit;
+    /// # fn example(client: &Outlit) {
+    /// let stats = client.stats();
+    /// if let Some(error) = stats.last_flush.and_then(|flush| flush.error) {
+    ///     eprintln!("analytics delivery failing: {error}");
+    /// }
+    /// # }
+    /// ```
+    pub fn stats(&self) -> ClientStats {
+        self.stats.snapshot()
+    }
+
+    // ============================================
+    // CONTEXT
+    // ============================================
+
+    /// Set a context property merged into every subsequent track, page view,
+    /// and stage event.
+    ///
+    /// Properties set on the event itself take precedence. Keys starting
+    /// with `__` are reserved and are sent under a `user.` namespace.
+    pub fn set_context(&self, key: impl Into<String>, value: impl Into<Value>) {
+        let (key, value) = (key.into(), value.into());
+        for client in self.clients() {
+            let mut context = client.context.write().unwrap_or_else(|e| e.into_inner());
+            insert_property(&mut context, key.clone(), value.clone());
+        }
+    }
+
+    /// Turn tracking on or off at runtime, e.g. from a feature flag or a
+    /// user's opt-out.
+    ///
+    /// While disabled, `send()`, `send_nowait()`, `track_batch()`, and
+    /// `import()` return `Ok` without doing anything. Events already queued
+    /// are still flushed. Starts enabled unless built with
+    /// [`disabled`](crate::OutlitBuilder::disabled).
+    pub fn set_enabled(&self, enabled: bool) {
+        for client in self.clients() {
+            client.enabled.store(enabled, Ordering::Relaxed);
+        }
+    }
+
+    /// Whether tracking is enabled. See [`set_enabled`](Self::set_enabled).
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Whether events are accepted: tracking is enabled and the
+    /// environment isn't filtered out by
+    /// [`production_only`](crate::OutlitBuilder::production_only).
+    fn is_active(&self) -> bool {
+        self.is_enabled() && !self.config.drops_environment()
+    }
+
+    /// Forget all context set with [`set_context`](Self::set_context).
+    ///
+    /// Call this between requests when a shared client handles several
+    /// users, so one user's context does not leak into another's events.
+    pub fn reset_context(&self) {
+        for client in self.clients() {
+            client
+                .context
+                .write()
+                .unwrap_or_else(|e| e.into_inner())
+                .clear();
+        }
     }
 
     // ============================================
@@ -122,6 +355,7 @@ impl Outlit {
         SendableTrack {
             builder: TrackBuilder::new(event_name, identity.into()),
             client: self,
+            project: None,
         }
     }
 
@@ -134,6 +368,7 @@ impl Outlit {
         SendableTrack {
             builder: TrackBuilder::new(event_name, identity.into()),
             client: self,
+            project: None,
         }
     }
 
@@ -162,6 +397,241 @@ impl Outlit {
         SendableTrack {
             builder: TrackBuilder::new(event_name, identity.into()),
             client: self,
+            project: None,
+        }
+    }
+
+    /// Track a typed custom event, sending its fields as properties.
+    ///
+    /// Returns an error if the event's properties fail to serialize.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use outlit::{email, OutlitEvent};
+    ///
+    /// #[derive(OutlitEvent)]
+    /// struct SignupEvent {
+    ///     plan: String,
+    ///     seats: u32,
+    /// }
+    ///
+    /// let signup = SignupEvent { plan: "pro".into(), seats: 5 };
+    /// client.track_event(signup, email("user@example.com"))?
+    ///     .send()
+    ///     .await?;
+    /// ```
+    pub fn track_event(
+        &self,
+        event: impl OutlitEvent,
+        identity: impl Into<Identity>,
+    ) -> Result<SendableTrack<'_>, Error> {
+        let mut builder = TrackBuilder::new(event.event_name(), identity);
+        for (key, value) in event.properties()? {
+            builder = builder.property(key, value);
+        }
+        Ok(SendableTrack {
+            builder,
+            client: self,
+            project: None,
+        })
+    }
+
+    /// Queue a batch of pre-built events in one step.
+    ///
+    /// Useful for replaying logs or importing history. Sampling, `before_send`
+    /// hooks, and deduplication apply to each event as usual; the queue is
+    /// locked once for the whole batch and flushed at most once afterwards.
+    pub async fn track_batch(
+        &self,
+        events: impl IntoIterator<Item = TrackerEvent>,
+    ) -> Result<(), Error> {
+        let mut routed: Vec<(&Outlit, Vec<TrackerEvent>)> = Vec::new();
+        for event in events {
+            let client = self.route(None, event.event_name())?;
+            match routed.iter_mut().find(|(c, _)| std::ptr::eq(*c, client)) {
+                Some((_, events)) => events.push(event),
+                None => routed.push((client, vec![event])),
+            }
+        }
+        for (client, events) in routed {
+            client.enqueue_batch(events).await?;
+        }
+        Ok(())
+    }
+
+    async fn enqueue_batch(&self, events: Vec<TrackerEvent>) -> Result<(), Error> {
+        self.ensure_not_shutdown()?;
+        if !self.is_active() {
+            return Ok(());
+        }
+
+        let mut events: Vec<_> = events
+            .into_iter()
+            .filter_map(|event| self.prepare_event(event))
+            .collect();
+        for event in &mut events {
+            self.validate_event(event)?;
+        }
+        events.retain(|event| self.record_seen(event));
+        let allowed = self.rate_limit(events.len()).await;
+        for event in events.drain(allowed..) {
+            self.forget_seen(&event);
+        }
+        if events.is_empty() {
+            return Ok(());
+        }
+        self.stats.record_enqueued(events.len());
+        self.queue.enqueue_many(events).await;
+        #[cfg(feature = "metrics")]
+        metrics::queue_depth(self.queue.len().await);
+        self.flush_or_signal().await
+    }
+
+    /// Import historical events, sending them directly instead of queueing.
+    ///
+    /// For backfilling analytics from a warehouse or another tool. Events
+    /// keep their own timestamps, however old, and are sent in requests of
+    /// [`import_batch_size`](crate::OutlitBuilder::import_batch_size) with
+    /// the `integration` source, regardless of `max_batch_size`. Sampling and
+    /// context properties don't apply; `before_send` hooks and deduplication
+    /// do.
+    ///
+    /// Stops at the first failed request and returns its error; events in
+    /// earlier requests have already been sent, and keyed events that weren't
+    /// can be imported again.
+    ///
+    /// Imports always go to the builder's main project; call `import` on
+    /// [`project`](Self::project) to import into another one.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use outlit::{Outlit, TrackerEvent};
+    /// # async fn example(client: &Outlit, rows: Vec<TrackerEvent>) -> Result<(), outlit::Error> {
+    /// let report = client.import(rows).await?;
+    /// println!("imported {} events", report.processed);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(skip(self, events))]
+    pub async fn import(
+        &self,
+        events: impl IntoIterator<Item = TrackerEvent>,
+    ) -> Result<FlushReport, Error> {
+        self.ensure_not_shutdown()?;
+        if !self.is_active() {
+            return Ok(FlushReport::default());
+        }
+
+        let batch_size = self.config.import_batch_size();
+        let mut events = events
+            .into_iter()
+            .filter_map(|event| self.filter_event(event))
+            // Historical rows legitimately repeat, so only idempotency
+            // keys are deduplicated
+            .filter(|event| {
+                event
+                    .message_id()
+                    .map_or(true, |key| self.dedup.insert(key))
+            })
+            .map(|mut event| {
+                self.scrub(&mut event);
+                event
+            })
+            .peekable();
+        let mut report = FlushReport::default();
+        let mut offset = 0;
+
+        while events.peek().is_some() {
+            let batch: Vec<_> = events.by_ref().take(batch_size).collect();
+            let batch_offset = offset;
+            offset += batch.len();
+            let (batch, failed) = partition_serializable(batch);
+            for (index, event, e) in failed {
+                warn!(index = batch_offset + index, error = %e, "skipping imported event that failed to serialize");
+                report.quarantined.push(RejectedEvent {
+                    index: batch_offset + index,
+                    message: e.to_string(),
+                    event: Some(event),
+                });
+            }
+            if batch.is_empty() {
+                continue;
+            }
+
+            let payload = IngestPayload::new(SourceType::Integration, batch);
+            let response = match self.transport.send(&payload).await {
+                Ok(response) => response,
+                Err(e) => {
+                    error!(error = %e, imported = report.sent, "import failed");
+                    // Forget keys recorded for events that were never
+                    // delivered, so the import can be retried
+                    for key in payload
+                        .events
+                        .iter()
+                        .chain(events.peek())
+                        .filter_map(TrackerEvent::message_id)
+                    {
+                        self.dedup.remove(key);
+                    }
+                    return Err(e);
+                }
+            };
+            report.merge(
+                FlushReport::from_response(payload.events, response),
+                batch_offset,
+            );
+        }
+
+        info!(event_count = report.sent, "imported events");
+        Ok(report)
+    }
+
+    // ============================================
+    // PAGE VIEWS
+    // ============================================
+
+    /// Record a web page view.
+    ///
+    /// `name` is sent as the page title. Set the visited URL with
+    /// [`SendablePage::url`]; without one, the event gets the same synthetic
+    /// `server://` URL as track events.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use outlit::{Outlit, email};
+    /// # async fn example(client: &Outlit) -> Result<(), outlit::Error> {
+    /// client.page(email("user@example.com"), "Pricing")
+    ///     .url("https://example.com/pricing?plan=pro")
+    ///     .referrer("https://google.com/")
+    ///     .send()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn page(&self, identity: impl Into<Identity>, name: impl Into<String>) -> SendablePage<'_> {
+        SendablePage {
+            builder: PageBuilder::page(identity, name),
+            client: self,
+            project: None,
+        }
+    }
+
+    /// Record an app screen view.
+    ///
+    /// Sent as a page view titled `name`, with a `screen://{name}` URL unless
+    /// one is set with [`SendablePage::url`].
+    pub fn screen(
+        &self,
+        identity: impl Into<Identity>,
+        name: impl Into<String>,
+    ) -> SendablePage<'_> {
+        SendablePage {
+            builder: PageBuilder::screen(identity, name),
+            client: self,
+            project: None,
         }
     }
 
@@ -192,6 +662,7 @@ impl Outlit {
         SendableIdentify {
             builder: IdentifyBuilder::new(identity.into()),
             client: self,
+            project: None,
         }
     }
 
@@ -200,6 +671,63 @@ impl Outlit {
         SendableIdentify {
             builder: IdentifyBuilder::new(identity.into()),
             client: self,
+            project: None,
+        }
+    }
+
+    /// Link a previous identity to the current one.
+    ///
+    /// Use this when a user changes their primary email or two accounts
+    /// are merged, so the server can merge the profiles.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use outlit::{Outlit, email};
+    /// # async fn example(client: &Outlit) -> Result<(), outlit::Error> {
+    /// client.alias(email("old@example.com"), email("new@example.com"))
+    ///     .user_id("usr_123")
+    ///     .send()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn alias(
+        &self,
+        previous: impl Into<Identity>,
+        current: impl Into<Identity>,
+    ) -> SendableAlias<'_> {
+        SendableAlias {
+            builder: AliasBuilder::new(previous, current),
+            client: self,
+            project: None,
+        }
+    }
+
+    // ============================================
+    // GROUP
+    // ============================================
+
+    /// Identify or update a group (account), optionally associating a user.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use outlit::{Outlit, email};
+    /// # async fn example(client: &Outlit) -> Result<(), outlit::Error> {
+    /// client.group("org_123")
+    ///     .user(email("user@example.com"))
+    ///     .trait_("name", "Acme Inc")
+    ///     .send()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn group(&self, group_id: impl Into<String>) -> SendableGroup<'_> {
+        SendableGroup {
+            builder: GroupBuilder::new(group_id),
+            client: self,
+            project: None,
         }
     }
 
@@ -221,113 +749,440 @@ impl Outlit {
         CustomerMethods { client: self }
     }
 
+    // ============================================
+    // PRIVACY
+    // ============================================
+
+    /// Record what a user agreed to in your consent management platform,
+    /// replacing any earlier decision.
+    ///
+    /// Under a [`ConsentPolicy`](crate::ConsentPolicy), later events about
+    /// the user are dropped unless the consent they need is in `consent`.
+    /// Events already queued are not affected. Decisions are kept in
+    /// memory, so record them again after a restart, e.g. when a user's
+    /// session starts.
+    pub fn set_consent(
+        &self,
+        identity: impl Into<Identity>,
+        consent: impl IntoIterator<Item = Consent>,
+    ) {
+        let identifiers = Identifiers::from(identity.into());
+        self.consents
+            .set(&identifiers, Some(consent.into_iter().collect()));
+    }
+
+    /// Forget a user's consent decision, so the policy's
+    /// [`assumed`](crate::ConsentPolicy::assumed) consent applies again.
+    pub fn clear_consent(&self, identity: impl Into<Identity>) {
+        self.consents.set(&Identifiers::from(identity.into()), None);
+    }
+
+    /// Privacy request methods.
+    pub fn privacy(&self) -> PrivacyMethods<'_> {
+        PrivacyMethods { client: self }
+    }
+
     // ============================================
     // LIFECYCLE
     // ============================================
 
+    /// Check that the ingest endpoint is reachable and accepts the public key.
+    ///
+    /// Sends an empty batch, so nothing is recorded. Call this at startup to
+    /// fail fast on a wrong public key or API host instead of on the first
+    /// flush. Authentication failures (401/403) are returned as
+    /// [`Error::Api`] with a message naming the public key.
+    pub async fn ping(&self) -> Result<(), Error> {
+        let payload = IngestPayload::new(SourceType::Server, Vec::new());
+
+        match self.transport.send(&payload).await {
+            Ok(_) => Ok(()),
+            Err(Error::Api { status, message }) if status == 401 || status == 403 => {
+                Err(Error::Api {
+                    status,
+                    message: format!(
+                        "public key {:?} was rejected: {message}",
+                        self.config.public_key()
+                    ),
+                })
+            }
+            Err(e) => Err(e),
+        }
+    }
+
     /// Flush all pending events immediately.
     ///
+    /// If the background task is already sending a batch, waits for it to
+    /// finish and then sends whatever it left queued.
+    ///
     /// Important: Call this before your application exits!
-    #[instrument(skip(self))]
     pub async fn flush(&self) -> Result<(), Error> {
-        if self.queue.is_empty().await {
-            return Ok(());
-        }
+        self.flush_detailed().await.map(|_| ())
+    }
 
-        let events = self.queue.drain().await;
-        if events.is_empty() {
-            return Ok(());
+    /// Flush pending events only if they are due: the batch is full, or the
+    /// oldest event has waited longer than the flush interval or
+    /// [`max_event_age`](crate::OutlitBuilder::max_event_age).
+    ///
+    /// Cheap when nothing is due, as it only inspects the queue. Meant for
+    /// [`serverless`](crate::OutlitBuilder::serverless) clients, which have
+    /// no background timer, to call between invocations.
+    pub async fn flush_if_needed(&self) -> Result<(), Error> {
+        for client in self.clients() {
+            let Some(oldest) = client.queue.oldest_enqueued_at().await else {
+                continue;
+            };
+            let max_wait = match client.config.max_event_age() {
+                Some(age) => age.min(client.config.flush_interval()),
+                None => client.config.flush_interval(),
+            };
+
+            if client.queue.should_flush().await || oldest.elapsed() >= max_wait {
+                client.flush_project().await?;
+            }
         }
+        Ok(())
+    }
 
-        info!(event_count = events.len(), "flushing events");
-
-        let payload = IngestPayload {
-            source: SourceType::Server,
-            events,
-        };
-
-        if let Err(e) = self.transport.send(&payload).await {
-            // Requeue events on failure to prevent data loss
-            error!(error = %e, "flush failed, requeuing events");
-            self.queue.requeue(payload.events).await;
-            return Err(e);
+    /// Run `future` to completion, then flush all pending events.
+    ///
+    /// Wrap each serverless invocation in this so events tracked during it
+    /// are sent before the runtime freezes the execution environment. A
+    /// failed flush is logged and the events stay queued for the next
+    /// invocation; the future's output is returned either way.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use lambda_runtime::{service_fn, LambdaEvent};
+    ///
+    /// let client = Outlit::builder("pk_xxx").serverless().build()?;
+    /// lambda_runtime::run(service_fn(|event: LambdaEvent<Request>| {
+    ///     client.flush_after(handler(&client, event))
+    /// }))
+    /// .await?;
+    /// ```
+    pub async fn flush_after<T>(&self, future: impl Future<Output = T>) -> T {
+        let output = future.await;
+        if let Err(e) = self.flush().await {
+            error!(error = %e, "failed to flush events after invocation");
         }
+        output
+    }
 
-        Ok(())
+    /// Flush pending events, giving up after `timeout`, and return the number
+    /// of events still queued.
+    ///
+    /// The timeout bounds the whole flush, including retries under the
+    /// [`RetryPolicy`](crate::RetryPolicy), so this is safe to call from a
+    /// request handler during an API outage. Events that weren't sent stay
+    /// queued for a later flush. Failures are logged rather than returned.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use outlit::Outlit;
+    /// # use std::time::Duration;
+    /// # async fn example(client: &Outlit) {
+    /// let remaining = client.flush_with_timeout(Duration::from_millis(500)).await;
+    /// if remaining > 0 {
+    ///     eprintln!("{remaining} events will be sent later");
+    /// }
+    /// # }
+    /// ```
+    pub async fn flush_with_timeout(&self, timeout: Duration) -> usize {
+        match tokio::time::timeout(timeout, self.flush()).await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => warn!(error = %e, "flush failed"),
+            Err(_) => warn!(?timeout, "flush timed out"),
+        }
+        self.pending_event_count().await
     }
 
-    /// Shutdown the client gracefully.
+    /// Flush all pending events and report what the API accepted.
     ///
-    /// Flushes remaining events and stops the background flush timer.
+    /// Events the API rejects are not retried; they are returned in
+    /// [`FlushReport::rejected`] alongside the API's message for each.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use outlit::Outlit;
+    /// # async fn example(client: &Outlit) -> Result<(), outlit::Error> {
+    /// let report = client.flush_detailed().await?;
+    /// for rejected in &report.rejected {
+    ///     eprintln!("event {} rejected: {}", rejected.index, rejected.message);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// With several [projects](crate::OutlitBuilder::project), the report
+    /// covers all of them and rejected indices are within each project's
+    /// batch.
     #[instrument(skip(self))]
-    pub async fn shutdown(&self) -> Result<(), Error> {
-        if self.is_shutdown.swap(true, Ordering::SeqCst) {
-            return Ok(()); // Already shutdown
+    pub async fn flush_detailed(&self) -> Result<FlushReport, Error> {
+        let mut report = FlushReport::default();
+        for client in self.clients() {
+            report.merge(client.flush_project().await?, 0);
         }
+        Ok(report)
+    }
 
-        info!("shutting down client");
-
-        // Stop flush timer
-        if let Some(handle) = self.flush_handle.lock().await.take() {
-            handle.abort();
+    /// Flush this project's pending events, unless its circuit is open.
+    async fn flush_project(&self) -> Result<FlushReport, Error> {
+        if !self.circuit.allow() {
+            return Err(Error::CircuitOpen);
         }
+        self.flush_now().await
+    }
 
-        // Final flush
-        self.flush().await?;
-
-        Ok(())
+    /// Flush pending events regardless of the circuit breaker.
+    async fn flush_now(&self) -> Result<FlushReport, Error> {
+        flush_and_report(
+            &self.queue,
+            &self.transport,
+            FlushOptions::from_config(&self.config),
+            &self.stats,
+            &self.config.flush_callbacks,
+            &self.config.dead_letters,
+            &self.circuit,
+        )
+        .await
     }
 
-    // ============================================
-    // INTERNAL
-    // ============================================
+    /// Flush pending events from synchronous code, blocking the current thread.
+    ///
+    /// An escape hatch for shutdown paths such as signal handlers where
+    /// `.await` isn't available. Reuses the current Tokio runtime when it is
+    /// multi-threaded, and otherwise drives the flush on a temporary runtime.
+    /// Returns [`Error::Timeout`] if the flush doesn't finish within `timeout`.
+    ///
+    /// Avoid calling this from async code; it blocks the calling thread.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use outlit::Outlit;
+    /// # use std::time::Duration;
+    /// # fn on_sigterm(client: &Outlit) {
+    /// if let Err(e) = client.flush_blocking(Duration::from_secs(5)) {
+    ///     eprintln!("failed to flush analytics: {e}");
+    /// }
+    /// # }
+    /// ```
+    pub fn flush_blocking(&self, timeout: Duration) -> Result<(), Error> {
+        block_on_with_timeout(timeout, self.flush())
+    }
 
-    fn ensure_not_shutdown(&self) -> Result<(), Error> {
-        if self.is_shutdown.load(Ordering::SeqCst) {
-            return Err(Error::Shutdown);
+    /// Drop all pending events without sending them.
+    ///
+    /// Events already being sent by an in-flight flush are not affected.
+    /// Returns the number of events dropped.
+    pub async fn discard_pending(&self) -> usize {
+        let mut discarded = 0;
+        for client in self.clients() {
+            discarded += client.queue.clear().await;
         }
-        Ok(())
+        self.stats.record_dropped(discarded);
+        info!(event_count = discarded, "discarded pending events");
+        discarded
     }
 
-    fn start_flush_timer(&self) {
-        let queue = self.queue.clone();
-        let transport = self.transport.clone();
-        let flush_interval = self.config.flush_interval();
-        let is_shutdown = self.is_shutdown.clone();
+    /// Wrap the client in an [`OutlitGuard`](crate::OutlitGuard) that shuts
+    /// it down, flushing pending events, when dropped.
+    pub fn into_guard(self) -> crate::OutlitGuard {
+        crate::OutlitGuard::new(self)
+    }
 
-        let handle = tokio::spawn(async move {
-            let mut timer = interval(flush_interval);
+    /// Shutdown the client gracefully.
+    ///
+    /// Flushes remaining events and stops the background flush timer.
+    #[instrument(skip(self))]
+    pub async fn shutdown(&self) -> Result<(), Error> {
+        let mut result = Ok(());
+        for client in self.clients() {
+            if !client.begin_shutdown().await {
+                continue; // Already shutdown
+            }
 
-            loop {
-                timer.tick().await;
+            // Final flush, even if the circuit breaker is open
+            if let Err(e) = client.flush_now().await {
+                result = result.and(Err(e));
+            }
+            client.queue.sync_store().await;
+        }
+        result
+    }
 
-                // Check if shutdown
-                if is_shutdown.load(Ordering::SeqCst) {
-                    break;
-                }
+    /// Shutdown the client, giving up on the final flush after `timeout`.
+    ///
+    /// Like [`shutdown`](Self::shutdown), but returns [`Error::Timeout`] once
+    /// the deadline passes so process exit isn't blocked by an unreachable
+    /// API. Events that weren't sent stay in the
+    /// [`persist_path`](crate::OutlitBuilder::persist_path) file, if one is
+    /// set, to be sent on the next start. Otherwise they are handed to the
+    /// [`dead_letter`](crate::OutlitBuilder::dead_letter) sink, which can
+    /// spill them to a [`DeadLetterFile`](crate::DeadLetterFile).
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use outlit::Outlit;
+    /// # use std::time::Duration;
+    /// # async fn example(client: &Outlit) {
+    /// if let Err(e) = client.shutdown_with_timeout(Duration::from_secs(5)).await {
+    ///     eprintln!("analytics not fully flushed: {e}");
+    /// }
+    /// # }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn shutdown_with_timeout(&self, timeout: Duration) -> Result<(), Error> {
+        let deadline = Instant::now() + timeout;
+        let mut result = Ok(());
+        for client in self.clients() {
+            if !client.begin_shutdown().await {
+                continue; // Already shutdown
+            }
 
-                if queue.is_empty().await {
-                    continue;
+            match tokio::time::timeout_at(deadline, client.flush_now()).await {
+                Ok(Ok(_)) => {}
+                Ok(Err(e)) => result = result.and(Err(e)),
+                Err(_) => {
+                    warn!(?timeout, "final flush timed out");
+                    client.spill_pending().await;
+                    result = result.and(Err(Error::Timeout));
                 }
+            }
+            client.queue.sync_store().await;
+        }
+        result
+    }
 
-                let events = queue.drain().await;
-                if events.is_empty() {
-                    continue;
-                }
+    // ============================================
+    // INTERNAL
+    // ============================================
 
-                debug!(event_count = events.len(), "periodic flush");
+    /// This client followed by its project clients.
+    fn clients(&self) -> impl Iterator<Item = &Outlit> {
+        std::iter::once(self).chain(self.projects.values())
+    }
 
-                let payload = IngestPayload {
-                    source: SourceType::Server,
-                    events,
-                };
+    /// Pick the client for an event: the explicit `project` if given, else
+    /// the project its `event_name` is routed to, else this one.
+    fn route(&self, project: Option<&str>, event_name: Option<&str>) -> Result<&Outlit, Error> {
+        let routed = event_name.and_then(|name| self.config.event_routes().get(name));
+        match project.or(routed.map(String::as_str)) {
+            Some(project) => self.project(project),
+            None => Ok(self),
+        }
+    }
 
-                if let Err(e) = transport.send(&payload).await {
-                    error!(error = %e, "periodic flush failed, requeuing events");
-                    queue.requeue(payload.events).await;
+    /// Mark the client as shut down and stop the flush timer.
+    ///
+    /// Returns `false` if the client was already shut down.
+    async fn begin_shutdown(&self) -> bool {
+        if self.is_shutdown.swap(true, Ordering::SeqCst) {
+            return false;
+        }
+
+        info!("shutting down client");
+
+        // Stop flush timer, waiting for it to release any events it had
+        // reserved so the final flush sends them
+        if let Some(handle) = self.flush_handle.lock().await.take() {
+            handle.abort();
+            let _ = handle.await;
+        }
+        true
+    }
+
+    /// Hand events left after a timed-out shutdown to the dead-letter sink,
+    /// unless they are already persisted to disk.
+    async fn spill_pending(&self) {
+        #[cfg(feature = "persist")]
+        if self.config.persist_path().is_some() {
+            let event_count = self.queue.len().await;
+            info!(event_count, "unsent events kept in the persisted queue");
+            return;
+        }
+
+        let events = self.queue.take_unreserved().await;
+        warn!(
+            event_count = events.len(),
+            "dropping unsent events at shutdown"
+        );
+        self.stats.record_dropped(events.len());
+        for event in events {
+            self.config.dead_letters.send(
+                DeadLetterReason::Shutdown,
+                "shutdown deadline passed before the event was sent".into(),
+                event,
+            );
+        }
+    }
+
+    fn ensure_not_shutdown(&self) -> Result<(), Error> {
+        if self.is_shutdown.load(Ordering::SeqCst) {
+            return Err(Error::Shutdown);
+        }
+        Ok(())
+    }
+
+    fn start_flush_timer(&self) {
+        let queue = self.queue.clone();
+        let transport = self.transport.clone();
+        let flush_interval = self.config.flush_interval();
+        let is_shutdown = self.is_shutdown.clone();
+        let flush_signal = self.flush_signal.clone();
+        let enqueue_signal = self.enqueue_signal.clone();
+        let max_event_age = self.config.max_event_age();
+        let options = FlushOptions {
+            in_flight: InFlight::Skip,
+            ..FlushOptions::from_config(&self.config)
+        };
+        let callbacks = self.config.flush_callbacks.clone();
+        let stats = self.stats.clone();
+        let circuit = self.circuit.clone();
+        let dead_letters = self.config.dead_letters.clone();
+
+        let handle = tokio::spawn(run_flush_loop(
+            flush_interval,
+            max_event_age,
+            FlushSignals {
+                flush: flush_signal,
+                enqueue: enqueue_signal,
+            },
+            is_shutdown,
+            queue.clone(),
+            move || {
+                let queue = queue.clone();
+                let transport = transport.clone();
+                let callbacks = callbacks.clone();
+                let stats = stats.clone();
+                let circuit = circuit.clone();
+                let dead_letters = dead_letters.clone();
+                async move {
+                    if !circuit.allow() {
+                        debug!("circuit open, skipping flush");
+                        return;
+                    }
+                    let result = flush_and_report(
+                        &queue,
+                        &transport,
+                        options,
+                        &stats,
+                        &callbacks,
+                        &dead_letters,
+                        &circuit,
+                    )
+                    .await;
+                    if let Err(e) = result {
+                        error!(error = %e, "periodic flush failed");
+                    }
                 }
-            }
-        });
+            },
+        ));
 
         // Store handle but don't block on it
         let flush_handle = self.flush_handle.try_lock();
@@ -336,197 +1191,1416 @@ impl Outlit {
         }
     }
 
+    /// Apply sampling, context, and `before_send` hooks, returning `None`
+    /// if the event is dropped.
+    fn prepare_event(&self, mut event: TrackerEvent) -> Option<TrackerEvent> {
+        // Only custom events are sampled; identity and lifecycle events are always kept
+        if !self.sampler.sample_event(&event) {
+            debug!("custom event dropped by sampling");
+            return None;
+        }
+
+        self.apply_context(&mut event);
+        #[cfg(feature = "opentelemetry")]
+        if self.config.trace_context() {
+            apply_trace_context(&mut event);
+        }
+        self.filter_event(event)
+    }
+
+    /// Drop events about suppressed users or without consent, assign a
+    /// missing event ID and tag the environment, then apply `before_send`
+    /// hooks, returning `None` if the event is dropped.
+    fn filter_event(&self, mut event: TrackerEvent) -> Option<TrackerEvent> {
+        if self.suppressions.suppresses(&event) {
+            debug!("event for suppressed user dropped");
+            return None;
+        }
+        if let Some(policy) = self.config.consent_policy() {
+            if !self.consents.allows(policy, &event) {
+                debug!("event dropped without consent");
+                return None;
+            }
+        }
+        event.ensure_event_id();
+        if let (Some(environment), Some(properties)) =
+            (self.config.environment(), event.properties_or_default())
+        {
+            properties.insert(ENVIRONMENT_PROPERTY.into(), environment.into());
+        }
+        if !self.config.before_send.apply(&mut event) {
+            debug!("event dropped by before_send hook");
+            return None;
+        }
+        Some(event)
+    }
+
+    /// Record the event's idempotency key and content, returning `false`
+    /// if either was already seen.
+    ///
+    /// Call once the event has passed validation, and
+    /// [`forget_seen`](Self::forget_seen) if it is dropped afterwards, so a
+    /// rejected event can be retried with the same key or content.
+    fn record_seen(&self, event: &TrackerEvent) -> bool {
+        if let Some(key) = event.message_id() {
+            if !self.dedup.insert(key) {
+                debug!(message_id = key, "duplicate event skipped");
+                return false;
+            }
+        }
+        if !self.content_dedup.insert(event) {
+            debug!("identical event skipped");
+            if let Some(key) = event.message_id() {
+                self.dedup.remove(key);
+            }
+            return false;
+        }
+        true
+    }
+
+    /// Undo [`record_seen`](Self::record_seen) for an event that was not
+    /// queued.
+    fn forget_seen(&self, event: &TrackerEvent) {
+        if let Some(key) = event.message_id() {
+            self.dedup.remove(key);
+        }
+        self.content_dedup.remove(event);
+    }
+
+    /// Drop properties not allowed by the property filter, then scrub PII
+    /// with the configured [`Redactor`](crate::Redactor), if any.
+    fn scrub(&self, event: &mut TrackerEvent) {
+        self.config.property_filter.apply(event);
+        #[cfg(feature = "redact")]
+        if let Some(redactor) = self.config.redactor() {
+            redactor.apply(event);
+        }
+    }
+
+    /// Merge context and default properties into track, page view, and
+    /// stage events without overriding properties set on the event.
+    ///
+    /// Context set at runtime takes precedence over the builder's default
+    /// properties.
+    fn apply_context(&self, event: &mut TrackerEvent) {
+        let properties = match event {
+            TrackerEvent::Custom(data) => &mut data.properties,
+            TrackerEvent::Pageview(data) => &mut data.properties,
+            TrackerEvent::Stage(data) => &mut data.properties,
+            _ => return,
+        };
+
+        let context = self.context.read().unwrap_or_else(|e| e.into_inner());
+        let defaults = self.config.default_properties();
+        if context.is_empty() && defaults.is_empty() {
+            return;
+        }
+
+        let properties = properties.get_or_insert_with(HashMap::new);
+        for (key, value) in context.iter().chain(defaults) {
+            properties
+                .entry(key.clone())
+                .or_insert_with(|| value.clone());
+        }
+    }
+
+    /// Apply the event name policy, property filter, redaction, and size
+    /// limits and, in strict mode, reject malformed email identities.
+    fn validate_event(&self, event: &mut TrackerEvent) -> Result<(), Error> {
+        if let (Some(policy), Some(name)) = (self.config.event_name_policy(), event.event_name()) {
+            policy.check(name)?;
+        }
+        if let Some(policy) = self.config.timestamp_policy() {
+            let timestamp = policy.check(event.timestamp(), self.config.clock().now_ms())?;
+            event.set_timestamp(timestamp);
+        }
+        if let Some(email) = event.identity().and_then(|identity| identity.email) {
+            if !crate::is_valid_email(&email) {
+                if self.config.strict_identity() {
+                    return Err(Error::InvalidIdentity(format!(
+                        "{email:?} is not a valid email address"
+                    )));
+                }
+                debug!(email = %email, "sending event with malformed email identity");
+            }
+        }
+        self.scrub(event);
+        self.config.size_limits().apply(event)
+    }
+
     async fn enqueue_and_maybe_flush(&self, builder: impl BuildEvent) -> Result<(), Error> {
         self.ensure_not_shutdown()?;
+        if !self.is_active() {
+            return Ok(());
+        }
+
+        let Some(mut event) = self.prepare_event(builder.build_at(self.config.clock().now_ms()))
+        else {
+            return Ok(());
+        };
+        self.validate_event(&mut event)?;
+        if !self.record_seen(&event) {
+            return Ok(());
+        }
+        if self.rate_limit(1).await == 0 {
+            self.forget_seen(&event);
+            return Ok(());
+        }
 
-        let event = builder.build();
         self.queue.enqueue(event).await;
+        self.stats.record_enqueued(1);
+        #[cfg(feature = "metrics")]
+        metrics::queue_depth(self.queue.len().await);
+        self.flush_or_signal().await
+    }
+
+    /// Take `count` events' worth of the rate limit, waiting if the policy
+    /// is to throttle. Returns how many of the events may be queued; the
+    /// rest should be dropped.
+    async fn rate_limit(&self, count: usize) -> usize {
+        let Some(limiter) = &self.rate_limiter else {
+            return count;
+        };
+        match limiter.settings().policy {
+            RateLimitPolicy::Throttle => {
+                limiter.acquire(count).await;
+                count
+            }
+            RateLimitPolicy::Drop => self.rate_limit_nowait(count),
+        }
+    }
+
+    /// Take up to `count` events' worth of the rate limit without waiting,
+    /// recording the events over the limit as dropped. Returns how many of
+    /// the events may be queued.
+    fn rate_limit_nowait(&self, count: usize) -> usize {
+        let Some(limiter) = &self.rate_limiter else {
+            return count;
+        };
+        let allowed = limiter.try_acquire(count);
+        if allowed < count {
+            let dropped = count - allowed;
+            debug!(count = dropped, "events dropped by rate limit");
+            self.stats.record_dropped(dropped);
+        }
+        allowed
+    }
+
+    /// Enqueue without awaiting, leaving all flushing to the background
+    /// task.
+    fn enqueue_nowait(&self, builder: impl BuildEvent) -> Result<(), Error> {
+        self.ensure_not_shutdown()?;
+        if !self.is_active() {
+            return Ok(());
+        }
+
+        let Some(mut event) = self.prepare_event(builder.build_at(self.config.clock().now_ms()))
+        else {
+            return Ok(());
+        };
+        self.validate_event(&mut event)?;
+        if !self.record_seen(&event) {
+            return Ok(());
+        }
+        if self.rate_limit_nowait(1) == 0 {
+            self.forget_seen(&event);
+            return Ok(());
+        }
+
+        let queue_len = self.queue.enqueue_nowait(event);
+        self.stats.record_enqueued(1);
+        #[cfg(feature = "metrics")]
+        metrics::queue_depth(queue_len);
+
+        if self.config.max_event_age().is_some() {
+            self.enqueue_signal.notify_one();
+        }
+        if self.config.auto_flush() && queue_len >= self.queue.high_water_mark() {
+            self.flush_signal.notify_one();
+        }
+        Ok(())
+    }
+
+    /// Hand off to the background task at the high-water mark, flushing
+    /// inline only once the queue is full.
+    ///
+    /// Under bursts this keeps `send()` from waiting on the network: the
+    /// background task starts draining at 80% of `max_batch_size`, and
+    /// callers only block if the queue fills up before it catches up.
+    async fn flush_or_signal(&self) -> Result<(), Error> {
+        if self.config.max_event_age().is_some() {
+            // Let the background task pick up the new oldest-event deadline
+            self.enqueue_signal.notify_one();
+        }
 
         if self.queue.should_flush().await {
             self.flush().await?;
+        } else if self.config.auto_flush() && self.queue.above_high_water().await {
+            self.flush_signal.notify_one();
         }
 
         Ok(())
     }
-}
+}
+
+/// Wake-ups for the background flush task.
+struct FlushSignals {
+    /// Flush now, e.g. when the queue reaches its high-water mark or on
+    /// startup with recovered events.
+    flush: Arc<Notify>,
+    /// An event was enqueued; re-check the oldest event's age.
+    enqueue: Arc<Notify>,
+}
+
+/// Call `flush` every `flush_interval`, when `signals.flush` is notified, or
+/// when the oldest queued event is older than `max_event_age`, until
+/// `is_shutdown` is set.
+///
+/// Ticks with an empty queue are skipped. The first tick fires one interval
+/// after starting. If a flush leaves an event past `max_event_age` queued,
+/// because it failed or was skipped while the circuit is open, the age
+/// deadline is ignored until the next tick so the loop doesn't retry
+/// immediately. Uses Tokio's clock, so tests can drive it with
+/// `tokio::time::pause` and `advance`.
+async fn run_flush_loop<F, Fut>(
+    flush_interval: Duration,
+    max_event_age: Option<Duration>,
+    signals: FlushSignals,
+    is_shutdown: Arc<AtomicBool>,
+    queue: Arc<EventQueue>,
+    mut flush: F,
+) where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = ()>,
+{
+    let mut timer = interval_at(Instant::now() + flush_interval, flush_interval);
+    let age_deadline = || async {
+        match max_event_age {
+            Some(age) => queue.oldest_enqueued_at().await.map(|at| at + age),
+            None => None,
+        }
+    };
+    // Set when a flush left an overdue event queued
+    let mut age_paused = false;
+
+    loop {
+        let deadline = age_deadline().await;
+        if deadline.map_or(true, |deadline| deadline > Instant::now()) {
+            age_paused = false;
+        }
+        let deadline = deadline.filter(|_| !age_paused);
+        let aged = async {
+            match deadline {
+                Some(deadline) => sleep_until(deadline).await,
+                None => std::future::pending().await,
+            }
+        };
+
+        tokio::select! {
+            _ = timer.tick() => age_paused = false,
+            _ = signals.flush.notified() => debug!("flush requested"),
+            _ = aged => debug!("max event age reached"),
+            _ = signals.enqueue.notified() => continue,
+        }
+
+        // Check if shutdown
+        if is_shutdown.load(Ordering::SeqCst) {
+            break;
+        }
+
+        if queue.is_empty().await {
+            continue;
+        }
+
+        debug!("periodic flush");
+        flush().await;
+        if age_deadline()
+            .await
+            .is_some_and(|deadline| deadline <= Instant::now())
+        {
+            debug!("events still past max age, waiting for the next interval");
+            age_paused = true;
+        }
+    }
+}
+
+/// Run `future` to completion from synchronous code, giving up with
+/// [`Error::Timeout`] after `timeout`.
+///
+/// Reuses the current Tokio runtime when it is multi-threaded, and otherwise
+/// drives the future on a temporary runtime.
+pub(crate) fn block_on_with_timeout<T: Send>(
+    timeout: Duration,
+    future: impl Future<Output = Result<T, Error>> + Send,
+) -> Result<T, Error> {
+    block_on(async {
+        tokio::time::timeout(timeout, future)
+            .await
+            .map_err(|_| Error::Timeout)?
+    })
+}
+
+/// Run `future` to completion from synchronous code.
+///
+/// Like [`block_on_with_timeout`], for futures that enforce their own
+/// deadline.
+pub(crate) fn block_on<T: Send>(
+    future: impl Future<Output = Result<T, Error>> + Send,
+) -> Result<T, Error> {
+    // A current-thread runtime can't make progress while its only thread
+    // is blocked here, so only reuse multi-threaded runtimes.
+    let handle = Handle::try_current()
+        .ok()
+        .filter(|handle| handle.runtime_flavor() == RuntimeFlavor::MultiThread);
+
+    // Run on a separate thread so this works whether or not the caller
+    // is inside a runtime.
+    std::thread::scope(|scope| {
+        scope
+            .spawn(|| match handle {
+                Some(handle) => handle.block_on(future),
+                None => tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()?
+                    .block_on(future),
+            })
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    })
+}
+
+/// Flush settings taken from the client configuration.
+#[derive(Debug, Clone, Copy)]
+struct FlushOptions {
+    max_bisect_depth: usize,
+    max_payload_bytes: Option<usize>,
+    flush_concurrency: usize,
+    requeue_rejected: bool,
+    in_flight: InFlight,
+}
+
+impl FlushOptions {
+    fn from_config(config: &Config) -> Self {
+        Self {
+            max_bisect_depth: config.max_bisect_depth(),
+            max_payload_bytes: config.max_payload_bytes(),
+            flush_concurrency: config.flush_concurrency(),
+            requeue_rejected: config.requeue_rejected(),
+            in_flight: InFlight::Wait,
+        }
+    }
+}
+
+/// What a flush does when another flush of the same queue is in flight.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InFlight {
+    /// Wait for it to finish, then send what it left queued. Used by
+    /// explicit flushes and shutdown, which must not return early.
+    Wait,
+    /// Send nothing. Used by the background task, whose events the flush
+    /// in flight will send.
+    Skip,
+}
+
+/// Events the API reported as failed that should be queued again.
+fn requeued_events(report: &FlushReport, options: FlushOptions) -> Vec<TrackerEvent> {
+    if !options.requeue_rejected {
+        return Vec::new();
+    }
+    report
+        .rejected
+        .iter()
+        .filter_map(|rejected| rejected.event.clone())
+        .collect()
+}
+
+/// Add the current span's `trace_id` and `span_id` to track, page view, and
+/// stage events, without overriding properties set on the event.
+#[cfg(feature = "opentelemetry")]
+fn apply_trace_context(event: &mut TrackerEvent) {
+    let properties = match event {
+        TrackerEvent::Custom(data) => &mut data.properties,
+        TrackerEvent::Pageview(data) => &mut data.properties,
+        TrackerEvent::Stage(data) => &mut data.properties,
+        _ => return,
+    };
+    let Some(span_context) = crate::trace_context::current() else {
+        return;
+    };
+
+    let properties = properties.get_or_insert_with(HashMap::new);
+    properties
+        .entry("trace_id".to_string())
+        .or_insert_with(|| Value::String(span_context.trace_id().to_string()));
+    properties
+        .entry("span_id".to_string())
+        .or_insert_with(|| Value::String(span_context.span_id().to_string()));
+}
+
+/// Flush pending events and report the outcome to the dead-letter sink,
+/// circuit breaker, stats, metrics, and flush callbacks.
+async fn flush_and_report(
+    queue: &EventQueue,
+    transport: &Transport,
+    options: FlushOptions,
+    stats: &Stats,
+    callbacks: &FlushCallbacks,
+    dead_letters: &DeadLetters,
+    circuit: &Circuit,
+) -> Result<FlushReport, Error> {
+    #[cfg(feature = "metrics")]
+    let started = std::time::Instant::now();
+    let (result, exhausted) = send_reserved(queue, transport, options).await;
+    if let Ok(report) = &result {
+        dead_letters.report(report, options.requeue_rejected);
+    }
+    if !exhausted.is_empty() {
+        warn!(
+            event_count = exhausted.len(),
+            "dropping events that reached the maximum delivery attempts"
+        );
+        stats.record_dropped(exhausted.len());
+        for event in exhausted {
+            dead_letters.send(
+                DeadLetterReason::RetriesExhausted,
+                "reached the maximum delivery attempts".into(),
+                event,
+            );
+        }
+    }
+    circuit.record(&result);
+    stats.record_flush(&result, options.requeue_rejected);
+    #[cfg(feature = "metrics")]
+    metrics::flush(started.elapsed(), &result);
+    #[cfg(feature = "metrics")]
+    metrics::queue_depth(queue.len().await);
+    callbacks.notify(&result);
+    result
+}
+
+/// Send all pending events, removing them from the queue only on success.
+///
+/// Events stay queued while the request is in flight, so a failed send leaves
+/// them ahead of anything enqueued concurrently. Also returns the events
+/// removed because they reached the maximum number of delivery attempts.
+async fn send_reserved(
+    queue: &EventQueue,
+    transport: &Transport,
+    options: FlushOptions,
+) -> (Result<FlushReport, Error>, Vec<TrackerEvent>) {
+    let reservation = match options.in_flight {
+        InFlight::Wait => queue.reserve_when_released(usize::MAX).await,
+        InFlight::Skip => queue.reserve(usize::MAX).await,
+    };
+    let Some(mut reservation) = reservation else {
+        return (Ok(FlushReport::default()), Vec::new());
+    };
+
+    info!(event_count = reservation.events.len(), "flushing events");
+
+    let (events, failed) = partition_serializable(std::mem::take(&mut reservation.events));
+    let mut quarantined: Vec<_> = failed
+        .into_iter()
+        .map(|(index, event, e)| {
+            warn!(index, error = %e, "quarantining event that failed to serialize");
+            RejectedEvent {
+                index,
+                message: e.to_string(),
+                event: Some(event),
+            }
+        })
+        .collect();
+    let mut batches = match options.max_payload_bytes {
+        Some(max_bytes) => {
+            let (batches, oversized) = chunk_by_size(events, max_bytes);
+            quarantined.extend(oversized);
+            batches
+        }
+        None => vec![events],
+    };
+    if options.max_bisect_depth > 0 || batches.len() > 1 {
+        let (mut result, unsent) = send_batches(transport, batches, options).await;
+        match &mut result {
+            Ok(report) => report.quarantined = quarantined,
+            Err(e) => {
+                error!(error = %e, unsent = unsent.len(), "flush failed, keeping unsent events queued");
+            }
+        }
+        let exhausted = queue.commit_partial(reservation, unsent).await;
+        return (result, exhausted);
+    }
+
+    let payload = IngestPayload::new(SourceType::Server, batches.pop().unwrap_or_default());
+
+    match transport.send(&payload).await {
+        Ok(response) => {
+            let mut report = FlushReport::from_response(payload.events, response);
+            let requeue = requeued_events(&report, options);
+            let exhausted = if requeue.is_empty() {
+                queue.commit(reservation).await;
+                Vec::new()
+            } else {
+                queue.commit_partial(reservation, requeue).await
+            };
+            report.quarantined = quarantined;
+            (Ok(report), exhausted)
+        }
+        Err(e) => {
+            // Leave events in place to prevent data loss
+            error!(error = %e, "flush failed, keeping events queued");
+            let exhausted = if quarantined.is_empty() {
+                queue.rollback(reservation).await
+            } else {
+                queue.commit_partial(reservation, payload.events).await
+            };
+            (Err(e), exhausted)
+        }
+    }
+}
+
+/// Split off events that fail to serialize so one bad event can't fail the
+/// whole batch.
+///
+/// Returns the serializable events, in order, and the failed ones with their
+/// position in `events` and the serialization error.
+fn partition_serializable<T: serde::Serialize>(
+    events: Vec<T>,
+) -> (Vec<T>, Vec<(usize, T, serde_json::Error)>) {
+    let mut valid = Vec::with_capacity(events.len());
+    let mut failed = Vec::new();
+
+    for (index, event) in events.into_iter().enumerate() {
+        match serde_json::to_writer(std::io::sink(), &event) {
+            Ok(()) => valid.push(event),
+            Err(e) => failed.push((index, event, e)),
+        }
+    }
+
+    (valid, failed)
+}
+
+/// Split `events` into consecutive batches whose serialized payloads stay
+/// within `max_bytes`.
+///
+/// Returns the batches, in order, and the events too large to fit in a
+/// payload on their own, with their position in `events`.
+fn chunk_by_size(
+    events: Vec<TrackerEvent>,
+    max_bytes: usize,
+) -> (Vec<Vec<TrackerEvent>>, Vec<RejectedEvent>) {
+    let empty_size = serialized_size(&IngestPayload::new(SourceType::Server, Vec::new()));
+    let mut batches = Vec::new();
+    let mut oversized = Vec::new();
+    let mut batch = Vec::new();
+    let mut batch_size = empty_size;
+
+    for (index, event) in events.into_iter().enumerate() {
+        let size = serialized_size(&event);
+        if empty_size + size > max_bytes {
+            warn!(
+                index,
+                size, max_bytes, "quarantining event over max_payload_bytes"
+            );
+            oversized.push(RejectedEvent {
+                index,
+                message: format!("event is {size} bytes, over max_payload_bytes ({max_bytes})"),
+                event: Some(event),
+            });
+            continue;
+        }
+        // Events after the first are preceded by a comma
+        let added = size + usize::from(!batch.is_empty());
+        if batch_size + added > max_bytes {
+            batches.push(std::mem::take(&mut batch));
+            batch_size = empty_size + size;
+        } else {
+            batch_size += added;
+        }
+        batch.push(event);
+    }
+    if !batch.is_empty() || batches.is_empty() {
+        batches.push(batch);
+    }
+
+    (batches, oversized)
+}
+
+/// Send `batches`, up to `flush_concurrency` at a time.
+///
+/// Each batch is sent as by [`send_sequentially`]. Returns the first error,
+/// if any, and the events to keep queued from every batch, in order.
+async fn send_batches(
+    transport: &Transport,
+    batches: Vec<Vec<TrackerEvent>>,
+    options: FlushOptions,
+) -> (Result<FlushReport, Error>, Vec<TrackerEvent>) {
+    if options.flush_concurrency <= 1 || batches.len() <= 1 {
+        return send_sequentially(transport, batches, options).await;
+    }
+
+    debug!(
+        batch_count = batches.len(),
+        concurrency = options.flush_concurrency,
+        "sending batches concurrently"
+    );
+    let results: Vec<_> = stream::iter(batches)
+        .map(|batch| async move {
+            let len = batch.len();
+            (
+                len,
+                send_sequentially(transport, vec![batch], options).await,
+            )
+        })
+        .buffered(options.flush_concurrency)
+        .collect()
+        .await;
+
+    let mut report = FlushReport::default();
+    let mut unsent = Vec::new();
+    let mut failure = None;
+    let mut offset = 0;
+    for (len, (result, batch_unsent)) in results {
+        unsent.extend(batch_unsent);
+        match result {
+            Ok(batch_report) => report.merge(batch_report, offset),
+            Err(e) => {
+                failure.get_or_insert(e);
+            }
+        }
+        offset += len;
+    }
+
+    match failure {
+        Some(e) => (Err(e), unsent),
+        None => (Ok(report), unsent),
+    }
+}
+
+/// Send `batches` in order, halving any batch rejected with
+/// `400 Bad Request` until the offending events are isolated or the maximum
+/// bisect depth is reached.
+///
+/// Events still rejected at the maximum depth are dropped and reported as
+/// rejected, unless bisecting is disabled. On any other error the remaining
+/// events are returned, in order, so they can be kept queued, along with
+/// any events to requeue.
+async fn send_sequentially(
+    transport: &Transport,
+    batches: Vec<Vec<TrackerEvent>>,
+    options: FlushOptions,
+) -> (Result<FlushReport, Error>, Vec<TrackerEvent>) {
+    let mut report = FlushReport::default();
+    let mut unsent = Vec::new();
+    let mut failure = None;
+    // Stack of (events, offset within the flush, depth); batches and halves
+    // are pushed last first so they are sent in queue order.
+    let mut offset = 0;
+    let mut pending: Vec<_> = batches
+        .into_iter()
+        .map(|batch| {
+            let start = offset;
+            offset += batch.len();
+            (batch, start, 0)
+        })
+        .collect();
+    pending.reverse();
+
+    while let Some((events, offset, depth)) = pending.pop() {
+        if failure.is_some() {
+            unsent.extend(events);
+            continue;
+        }
+
+        let payload = IngestPayload::new(SourceType::Server, events);
+
+        match transport.send(&payload).await {
+            Ok(response) => {
+                let sub_report = FlushReport::from_response(payload.events, response);
+                unsent.extend(requeued_events(&sub_report, options));
+                report.merge(sub_report, offset)
+            }
+            Err(Error::Api { status: 400, .. })
+                if depth < options.max_bisect_depth && payload.events.len() > 1 =>
+            {
+                let mut left = payload.events;
+                let right = left.split_off(left.len() / 2);
+                debug!(depth = depth + 1, "batch rejected, splitting");
+                pending.push((right, offset + left.len(), depth + 1));
+                pending.push((left, offset, depth + 1));
+            }
+            Err(Error::Api {
+                status: 400,
+                message,
+            }) if options.max_bisect_depth > 0 => {
+                for (i, event) in payload.events.into_iter().enumerate() {
+                    warn!(index = offset + i, message = %message, "dropping event rejected by the API");
+                    report.rejected.push(RejectedEvent {
+                        index: offset + i,
+                        message: message.clone(),
+                        event: Some(event),
+                    });
+                }
+            }
+            Err(e) => {
+                unsent.extend(payload.events);
+                failure = Some(e);
+            }
+        }
+    }
+
+    match failure {
+        Some(e) => (Err(e), unsent),
+        None => (Ok(report), unsent),
+    }
+}
+
+// ============================================
+// SENDABLE WRAPPERS
+// ============================================
+
+/// Sendable track event builder.
+pub struct SendableTrack<'a> {
+    builder: TrackBuilder,
+    client: &'a Outlit,
+    project: Option<String>,
+}
+
+impl<'a> SendableTrack<'a> {
+    /// Add email (if identity was user_id or fingerprint).
+    pub fn email(mut self, email: impl Into<String>) -> Self {
+        self.builder = self.builder.email(email);
+        self
+    }
+
+    /// Add user_id (if identity was email or fingerprint).
+    pub fn user_id(mut self, user_id: impl Into<String>) -> Self {
+        self.builder = self.builder.user_id(user_id);
+        self
+    }
+
+    /// Add fingerprint (device identifier) to link this event to a device.
+    pub fn fingerprint(mut self, fingerprint: impl Into<String>) -> Self {
+        self.builder = self.builder.fingerprint(fingerprint);
+        self
+    }
+
+    /// Add a property.
+    pub fn property(mut self, key: impl Into<String>, value: impl Into<serde_json::Value>) -> Self {
+        self.builder = self.builder.property(key, value);
+        self
+    }
+
+    /// Add each top-level field of a serializable struct or map as a
+    /// property.
+    ///
+    /// Returns [`Error::Serialization`] if `value` does not serialize to a
+    /// JSON object.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use outlit::{Outlit, email};
+    /// # async fn example(client: &Outlit) -> Result<(), outlit::Error> {
+    /// #[derive(serde::Serialize)]
+    /// struct Checkout {
+    ///     plan: String,
+    ///     seats: u32,
+    /// }
+    ///
+    /// client.track("checkout", email("user@example.com"))
+    ///     .properties(Checkout { plan: "pro".into(), seats: 5 })?
+    ///     .send()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn properties(mut self, value: impl serde::Serialize) -> Result<Self, Error> {
+        self.builder = self.builder.properties(value)?;
+        Ok(self)
+    }
+
+    /// Set custom timestamp: milliseconds since epoch, or any other
+    /// [`IntoTimestamp`](crate::IntoTimestamp) such as a `SystemTime`.
+    pub fn timestamp(mut self, ts: impl crate::IntoTimestamp) -> Self {
+        self.builder = self.builder.timestamp(ts);
+        self
+    }
+
+    /// Mark the consent the event needs, checked against the client's
+    /// [`ConsentPolicy`](crate::ConsentPolicy).
+    pub fn consent(mut self, consent: Consent) -> Self {
+        self.builder = self.builder.consent(consent);
+        self
+    }
+
+    /// Set an idempotency key so repeated sends of this event are deduplicated.
+    pub fn idempotency_key(mut self, key: impl Into<String>) -> Self {
+        self.builder = self.builder.idempotency_key(key);
+        self
+    }
+
+    /// Send to a [project](crate::OutlitBuilder::project) other than the
+    /// one configured for this event.
+    ///
+    /// Sending returns [`Error::Config`] if no project has that name.
+    pub fn project(mut self, name: impl Into<String>) -> Self {
+        self.project = Some(name.into());
+        self
+    }
+
+    /// Send the event.
+    pub async fn send(self) -> Result<(), Error> {
+        self.client
+            .route(
+                self.project.as_deref(),
+                Some(self.builder.event_name.as_str()),
+            )?
+            .enqueue_and_maybe_flush(self.builder)
+            .await
+    }
+
+    /// Queue the event without awaiting, for latency-critical paths.
+    ///
+    /// Unlike [`send`](Self::send), this never flushes inline: it only takes
+    /// the queue's lock briefly and wakes the background flush task once the
+    /// queue nears `max_batch_size`. On a client without auto-flush, events
+    /// wait for an explicit flush.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use outlit::{Outlit, email};
+    /// # fn example(client: &Outlit) -> Result<(), outlit::Error> {
+    /// client.track("checkout_started", email("user@example.com"))
+    ///     .property("cart_size", 3)
+    ///     .send_nowait()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn send_nowait(self) -> Result<(), Error> {
+        self.client
+            .route(
+                self.project.as_deref(),
+                Some(self.builder.event_name.as_str()),
+            )?
+            .enqueue_nowait(self.builder)
+    }
+}
+
+/// Sendable page view event builder.
+pub struct SendablePage<'a> {
+    builder: PageBuilder,
+    client: &'a Outlit,
+    project: Option<String>,
+}
+
+impl<'a> SendablePage<'a> {
+    /// Add email (if identity was user_id or fingerprint).
+    pub fn email(mut self, email: impl Into<String>) -> Self {
+        self.builder = self.builder.email(email);
+        self
+    }
+
+    /// Add user_id (if identity was email or fingerprint).
+    pub fn user_id(mut self, user_id: impl Into<String>) -> Self {
+        self.builder = self.builder.user_id(user_id);
+        self
+    }
+
+    /// Add fingerprint (device identifier) to link this event to a device.
+    pub fn fingerprint(mut self, fingerprint: impl Into<String>) -> Self {
+        self.builder = self.builder.fingerprint(fingerprint);
+        self
+    }
+
+    /// Set the full URL that was visited; the path is taken from it.
+    pub fn url(mut self, url: impl Into<String>) -> Self {
+        self.builder = self.builder.url(url);
+        self
+    }
+
+    /// Set the path that was visited, overriding the one parsed from the URL.
+    pub fn path(mut self, path: impl Into<String>) -> Self {
+        self.builder = self.builder.path(path);
+        self
+    }
+
+    /// Set the referring URL.
+    pub fn referrer(mut self, referrer: impl Into<String>) -> Self {
+        self.builder = self.builder.referrer(referrer);
+        self
+    }
+
+    /// Add a property.
+    pub fn property(mut self, key: impl Into<String>, value: impl Into<serde_json::Value>) -> Self {
+        self.builder = self.builder.property(key, value);
+        self
+    }
+
+    /// Add each top-level field of a serializable struct or map as a
+    /// property. See [`SendableTrack::properties`].
+    pub fn properties(mut self, value: impl serde::Serialize) -> Result<Self, Error> {
+        self.builder = self.builder.properties(value)?;
+        Ok(self)
+    }
+
+    /// Set custom timestamp: milliseconds since epoch, or any other
+    /// [`IntoTimestamp`](crate::IntoTimestamp) such as a `SystemTime`.
+    pub fn timestamp(mut self, ts: impl crate::IntoTimestamp) -> Self {
+        self.builder = self.builder.timestamp(ts);
+        self
+    }
+
+    /// Mark the consent the event needs, checked against the client's
+    /// [`ConsentPolicy`](crate::ConsentPolicy).
+    pub fn consent(mut self, consent: Consent) -> Self {
+        self.builder = self.builder.consent(consent);
+        self
+    }
+
+    /// Set an idempotency key so repeated sends of this event are deduplicated.
+    pub fn idempotency_key(mut self, key: impl Into<String>) -> Self {
+        self.builder = self.builder.idempotency_key(key);
+        self
+    }
+
+    /// Send to a [project](crate::OutlitBuilder::project) other than the
+    /// client's main one.
+    ///
+    /// Sending returns [`Error::Config`] if no project has that name.
+    pub fn project(mut self, name: impl Into<String>) -> Self {
+        self.project = Some(name.into());
+        self
+    }
+
+    /// Send the event.
+    pub async fn send(self) -> Result<(), Error> {
+        self.client
+            .route(self.project.as_deref(), None)?
+            .enqueue_and_maybe_flush(self.builder)
+            .await
+    }
+
+    /// Queue the event without awaiting. See [`SendableTrack::send_nowait`].
+    pub fn send_nowait(self) -> Result<(), Error> {
+        self.client
+            .route(self.project.as_deref(), None)?
+            .enqueue_nowait(self.builder)
+    }
+}
+
+/// Sendable identify event builder.
+pub struct SendableIdentify<'a> {
+    builder: IdentifyBuilder,
+    client: &'a Outlit,
+    project: Option<String>,
+}
+
+impl<'a> SendableIdentify<'a> {
+    /// Add email.
+    pub fn email(mut self, email: impl Into<String>) -> Self {
+        self.builder = self.builder.email(email);
+        self
+    }
+
+    /// Add user_id.
+    pub fn user_id(mut self, user_id: impl Into<String>) -> Self {
+        self.builder = self.builder.user_id(user_id);
+        self
+    }
+
+    /// Add fingerprint (device identifier) to link this device to the user.
+    pub fn fingerprint(mut self, fingerprint: impl Into<String>) -> Self {
+        self.builder = self.builder.fingerprint(fingerprint);
+        self
+    }
+
+    /// Add a trait.
+    pub fn trait_(mut self, key: impl Into<String>, value: impl Into<serde_json::Value>) -> Self {
+        self.builder = self.builder.trait_(key, value);
+        self
+    }
+
+    /// Mark the consent the event needs, checked against the client's
+    /// [`ConsentPolicy`](crate::ConsentPolicy).
+    pub fn consent(mut self, consent: Consent) -> Self {
+        self.builder = self.builder.consent(consent);
+        self
+    }
+
+    /// Set an idempotency key so repeated sends of this event are deduplicated.
+    pub fn idempotency_key(mut self, key: impl Into<String>) -> Self {
+        self.builder = self.builder.idempotency_key(key);
+        self
+    }
+
+    /// Send to a [project](crate::OutlitBuilder::project) other than the
+    /// client's main one.
+    ///
+    /// Sending returns [`Error::Config`] if no project has that name.
+    pub fn project(mut self, name: impl Into<String>) -> Self {
+        self.project = Some(name.into());
+        self
+    }
+
+    /// Send the event.
+    pub async fn send(self) -> Result<(), Error> {
+        self.client
+            .route(self.project.as_deref(), None)?
+            .enqueue_and_maybe_flush(self.builder)
+            .await
+    }
+
+    /// Queue the event without awaiting. See [`SendableTrack::send_nowait`].
+    pub fn send_nowait(self) -> Result<(), Error> {
+        self.client
+            .route(self.project.as_deref(), None)?
+            .enqueue_nowait(self.builder)
+    }
+}
+
+/// Sendable stage event builder.
+pub struct SendableStage<'a> {
+    builder: StageBuilder,
+    client: &'a Outlit,
+    project: Option<String>,
+}
+
+impl<'a> SendableStage<'a> {
+    /// Add email.
+    pub fn email(mut self, email: impl Into<String>) -> Self {
+        self.builder = self.builder.email(email);
+        self
+    }
+
+    /// Add user_id.
+    pub fn user_id(mut self, user_id: impl Into<String>) -> Self {
+        self.builder = self.builder.user_id(user_id);
+        self
+    }
+
+    /// Add fingerprint (device identifier).
+    pub fn fingerprint(mut self, fingerprint: impl Into<String>) -> Self {
+        self.builder = self.builder.fingerprint(fingerprint);
+        self
+    }
+
+    /// Set why the user moved to this stage.
+    pub fn reason(mut self, reason: impl Into<String>) -> Self {
+        self.builder = self.builder.reason(reason);
+        self
+    }
+
+    /// Set the stage the user moved from, such as
+    /// `JourneyStage::Inactive` or a custom stage name.
+    pub fn previous_stage(mut self, stage: impl Into<JourneyStage>) -> Self {
+        self.builder = self.builder.previous_stage(stage);
+        self
+    }
+
+    /// Set when the transition took effect: milliseconds since epoch, or
+    /// any other [`IntoTimestamp`](crate::IntoTimestamp).
+    pub fn effective_at(mut self, at: impl crate::IntoTimestamp) -> Self {
+        self.builder = self.builder.effective_at(at);
+        self
+    }
+
+    /// Add a property.
+    pub fn property(mut self, key: impl Into<String>, value: impl Into<serde_json::Value>) -> Self {
+        self.builder = self.builder.property(key, value);
+        self
+    }
+
+    /// Add each top-level field of a serializable struct or map as a
+    /// property. See [`SendableTrack::properties`].
+    pub fn properties(mut self, value: impl serde::Serialize) -> Result<Self, Error> {
+        self.builder = self.builder.properties(value)?;
+        Ok(self)
+    }
+
+    /// Mark the consent the event needs, checked against the client's
+    /// [`ConsentPolicy`](crate::ConsentPolicy).
+    pub fn consent(mut self, consent: Consent) -> Self {
+        self.builder = self.builder.consent(consent);
+        self
+    }
+
+    /// Set an idempotency key so repeated sends of this event are deduplicated.
+    pub fn idempotency_key(mut self, key: impl Into<String>) -> Self {
+        self.builder = self.builder.idempotency_key(key);
+        self
+    }
+
+    /// Send to a [project](crate::OutlitBuilder::project) other than the
+    /// client's main one.
+    ///
+    /// Sending returns [`Error::Config`] if no project has that name.
+    pub fn project(mut self, name: impl Into<String>) -> Self {
+        self.project = Some(name.into());
+        self
+    }
 
-// ============================================
-// SENDABLE WRAPPERS
-// ============================================
+    /// Send the event.
+    pub async fn send(self) -> Result<(), Error> {
+        self.client
+            .route(self.project.as_deref(), None)?
+            .enqueue_and_maybe_flush(self.builder)
+            .await
+    }
 
-trait BuildEvent {
-    fn build(self) -> crate::types::TrackerEvent;
+    /// Queue the event without awaiting. See [`SendableTrack::send_nowait`].
+    pub fn send_nowait(self) -> Result<(), Error> {
+        self.client
+            .route(self.project.as_deref(), None)?
+            .enqueue_nowait(self.builder)
+    }
 }
 
-impl BuildEvent for TrackBuilder {
-    fn build(self) -> crate::types::TrackerEvent {
-        self.build()
-    }
+/// Sendable billing event builder.
+pub struct SendableBilling<'a> {
+    builder: BillingBuilder,
+    client: &'a Outlit,
+    project: Option<String>,
 }
 
-impl BuildEvent for IdentifyBuilder {
-    fn build(self) -> crate::types::TrackerEvent {
-        self.build()
+impl<'a> SendableBilling<'a> {
+    /// Set customer ID.
+    pub fn customer_id(mut self, id: impl Into<String>) -> Self {
+        self.builder = self.builder.customer_id(id);
+        self
     }
-}
 
-impl BuildEvent for StageBuilder {
-    fn build(self) -> crate::types::TrackerEvent {
-        self.build()
+    /// Set Stripe customer ID.
+    pub fn stripe_customer_id(mut self, id: impl Into<String>) -> Self {
+        self.builder = self.builder.stripe_customer_id(id);
+        self
     }
-}
 
-impl BuildEvent for BillingBuilder {
-    fn build(self) -> crate::types::TrackerEvent {
-        self.build()
+    /// Set the amount charged, in the currency's minor unit (e.g. cents).
+    pub fn amount_cents(mut self, amount: i64) -> Self {
+        self.builder = self.builder.amount_cents(amount);
+        self
     }
-}
 
-/// Sendable track event builder.
-pub struct SendableTrack<'a> {
-    builder: TrackBuilder,
-    client: &'a Outlit,
-}
+    /// Set the ISO 4217 currency code, such as `"usd"`. Sent lowercase.
+    pub fn currency(mut self, currency: impl Into<String>) -> Self {
+        self.builder = self.builder.currency(currency);
+        self
+    }
 
-impl<'a> SendableTrack<'a> {
-    /// Add email (if identity was user_id or fingerprint).
-    pub fn email(mut self, email: impl Into<String>) -> Self {
-        self.builder = self.builder.email(email);
+    /// Set monthly recurring revenue, in the currency's minor unit.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use outlit::{BillingInterval, Outlit};
+    /// # async fn example(client: &Outlit) -> Result<(), outlit::Error> {
+    /// client.customer().paid("acme.com")
+    ///     .mrr_cents(4900)
+    ///     .currency("usd")
+    ///     .interval(BillingInterval::Month)
+    ///     .send()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn mrr_cents(mut self, mrr: i64) -> Self {
+        self.builder = self.builder.mrr_cents(mrr);
         self
     }
 
-    /// Add user_id (if identity was email or fingerprint).
-    pub fn user_id(mut self, user_id: impl Into<String>) -> Self {
-        self.builder = self.builder.user_id(user_id);
+    /// Set how often the customer is billed.
+    pub fn interval(mut self, interval: BillingInterval) -> Self {
+        self.builder = self.builder.interval(interval);
         self
     }
 
-    /// Add fingerprint (device identifier) to link this event to a device.
-    pub fn fingerprint(mut self, fingerprint: impl Into<String>) -> Self {
-        self.builder = self.builder.fingerprint(fingerprint);
+    /// Override the billing status, e.g. for a refund to a trialing
+    /// customer.
+    pub fn status(mut self, status: BillingStatus) -> Self {
+        self.builder = self.builder.status(status);
         self
     }
 
-    /// Add a property.
-    pub fn property(mut self, key: impl Into<String>, value: impl Into<serde_json::Value>) -> Self {
-        self.builder = self.builder.property(key, value);
+    /// Set the invoice this event is about.
+    pub fn invoice_id(mut self, id: impl Into<String>) -> Self {
+        self.builder = self.builder.invoice_id(id);
         self
     }
 
-    /// Set custom timestamp.
-    pub fn timestamp(mut self, ts: i64) -> Self {
-        self.builder = self.builder.timestamp(ts);
+    /// Set the customer's current plan.
+    pub fn plan(mut self, plan: impl Into<String>) -> Self {
+        self.builder = self.builder.plan(plan);
         self
     }
 
-    /// Send the event.
-    pub async fn send(self) -> Result<(), Error> {
-        self.client.enqueue_and_maybe_flush(self.builder).await
+    /// Set the customer's plan before a plan change.
+    pub fn previous_plan(mut self, plan: impl Into<String>) -> Self {
+        self.builder = self.builder.previous_plan(plan);
+        self
     }
-}
 
-/// Sendable identify event builder.
-pub struct SendableIdentify<'a> {
-    builder: IdentifyBuilder,
-    client: &'a Outlit,
-}
+    /// Set the number of seats.
+    pub fn seats(mut self, seats: u32) -> Self {
+        self.builder = self.builder.seats(seats);
+        self
+    }
 
-impl<'a> SendableIdentify<'a> {
-    /// Add email.
-    pub fn email(mut self, email: impl Into<String>) -> Self {
-        self.builder = self.builder.email(email);
+    /// Set the seat count before a seat change.
+    pub fn previous_seats(mut self, seats: u32) -> Self {
+        self.builder = self.builder.previous_seats(seats);
         self
     }
 
-    /// Add user_id.
-    pub fn user_id(mut self, user_id: impl Into<String>) -> Self {
-        self.builder = self.builder.user_id(user_id);
+    /// Add a property.
+    pub fn property(mut self, key: impl Into<String>, value: impl Into<serde_json::Value>) -> Self {
+        self.builder = self.builder.property(key, value);
         self
     }
 
-    /// Add fingerprint (device identifier) to link this device to the user.
-    pub fn fingerprint(mut self, fingerprint: impl Into<String>) -> Self {
-        self.builder = self.builder.fingerprint(fingerprint);
+    /// Add each top-level field of a serializable struct or map as a
+    /// property. See [`SendableTrack::properties`].
+    pub fn properties(mut self, value: impl serde::Serialize) -> Result<Self, Error> {
+        self.builder = self.builder.properties(value)?;
+        Ok(self)
+    }
+
+    /// Set an idempotency key so repeated sends of this event are deduplicated.
+    pub fn idempotency_key(mut self, key: impl Into<String>) -> Self {
+        self.builder = self.builder.idempotency_key(key);
         self
     }
 
-    /// Add a trait.
-    pub fn trait_(mut self, key: impl Into<String>, value: impl Into<serde_json::Value>) -> Self {
-        self.builder = self.builder.trait_(key, value);
+    /// Send to a [project](crate::OutlitBuilder::project) other than the
+    /// client's main one.
+    ///
+    /// Sending returns [`Error::Config`] if no project has that name.
+    pub fn project(mut self, name: impl Into<String>) -> Self {
+        self.project = Some(name.into());
         self
     }
 
     /// Send the event.
     pub async fn send(self) -> Result<(), Error> {
-        self.client.enqueue_and_maybe_flush(self.builder).await
+        self.client
+            .route(self.project.as_deref(), None)?
+            .enqueue_and_maybe_flush(self.builder)
+            .await
+    }
+
+    /// Queue the event without awaiting. See [`SendableTrack::send_nowait`].
+    pub fn send_nowait(self) -> Result<(), Error> {
+        self.client
+            .route(self.project.as_deref(), None)?
+            .enqueue_nowait(self.builder)
     }
 }
 
-/// Sendable stage event builder.
-pub struct SendableStage<'a> {
-    builder: StageBuilder,
+/// Sendable alias event builder.
+pub struct SendableAlias<'a> {
+    builder: AliasBuilder,
     client: &'a Outlit,
+    project: Option<String>,
 }
 
-impl<'a> SendableStage<'a> {
-    /// Add email.
+impl<'a> SendableAlias<'a> {
+    /// Add email to the current identity.
     pub fn email(mut self, email: impl Into<String>) -> Self {
         self.builder = self.builder.email(email);
         self
     }
 
-    /// Add user_id.
+    /// Add user_id to the current identity.
     pub fn user_id(mut self, user_id: impl Into<String>) -> Self {
         self.builder = self.builder.user_id(user_id);
         self
     }
 
-    /// Add fingerprint (device identifier).
+    /// Add fingerprint (device identifier) to the current identity.
     pub fn fingerprint(mut self, fingerprint: impl Into<String>) -> Self {
         self.builder = self.builder.fingerprint(fingerprint);
         self
     }
 
-    /// Add a property.
-    pub fn property(mut self, key: impl Into<String>, value: impl Into<serde_json::Value>) -> Self {
-        self.builder = self.builder.property(key, value);
+    /// Set an idempotency key so repeated sends of this event are deduplicated.
+    pub fn idempotency_key(mut self, key: impl Into<String>) -> Self {
+        self.builder = self.builder.idempotency_key(key);
+        self
+    }
+
+    /// Send to a [project](crate::OutlitBuilder::project) other than the
+    /// client's main one.
+    ///
+    /// Sending returns [`Error::Config`] if no project has that name.
+    pub fn project(mut self, name: impl Into<String>) -> Self {
+        self.project = Some(name.into());
         self
     }
 
     /// Send the event.
     pub async fn send(self) -> Result<(), Error> {
-        self.client.enqueue_and_maybe_flush(self.builder).await
+        self.client
+            .route(self.project.as_deref(), None)?
+            .enqueue_and_maybe_flush(self.builder)
+            .await
+    }
+
+    /// Queue the event without awaiting. See [`SendableTrack::send_nowait`].
+    pub fn send_nowait(self) -> Result<(), Error> {
+        self.client
+            .route(self.project.as_deref(), None)?
+            .enqueue_nowait(self.builder)
     }
 }
 
-/// Sendable billing event builder.
-pub struct SendableBilling<'a> {
-    builder: BillingBuilder,
+/// Sendable group event builder.
+pub struct SendableGroup<'a> {
+    builder: GroupBuilder,
     client: &'a Outlit,
+    project: Option<String>,
 }
 
-impl<'a> SendableBilling<'a> {
-    /// Set customer ID.
-    pub fn customer_id(mut self, id: impl Into<String>) -> Self {
-        self.builder = self.builder.customer_id(id);
+impl<'a> SendableGroup<'a> {
+    /// Associate a user with the group.
+    pub fn user(mut self, identity: impl Into<Identity>) -> Self {
+        self.builder = self.builder.user(identity);
         self
     }
 
-    /// Set Stripe customer ID.
-    pub fn stripe_customer_id(mut self, id: impl Into<String>) -> Self {
-        self.builder = self.builder.stripe_customer_id(id);
+    /// Add a group trait.
+    pub fn trait_(mut self, key: impl Into<String>, value: impl Into<serde_json::Value>) -> Self {
+        self.builder = self.builder.trait_(key, value);
         self
     }
 
-    /// Add a property.
-    pub fn property(mut self, key: impl Into<String>, value: impl Into<serde_json::Value>) -> Self {
-        self.builder = self.builder.property(key, value);
+    /// Set an idempotency key so repeated sends of this event are deduplicated.
+    pub fn idempotency_key(mut self, key: impl Into<String>) -> Self {
+        self.builder = self.builder.idempotency_key(key);
+        self
+    }
+
+    /// Send to a [project](crate::OutlitBuilder::project) other than the
+    /// client's main one.
+    ///
+    /// Sending returns [`Error::Config`] if no project has that name.
+    pub fn project(mut self, name: impl Into<String>) -> Self {
+        self.project = Some(name.into());
         self
     }
 
     /// Send the event.
     pub async fn send(self) -> Result<(), Error> {
-        self.client.enqueue_and_maybe_flush(self.builder).await
+        self.client
+            .route(self.project.as_deref(), None)?
+            .enqueue_and_maybe_flush(self.builder)
+            .await
+    }
+
+    /// Queue the event without awaiting. See [`SendableTrack::send_nowait`].
+    pub fn send_nowait(self) -> Result<(), Error> {
+        self.client
+            .route(self.project.as_deref(), None)?
+            .enqueue_nowait(self.builder)
     }
 }
 
@@ -540,11 +2614,40 @@ pub struct UserMethods<'a> {
 }
 
 impl<'a> UserMethods<'a> {
+    /// Move the user to a named journey stage.
+    ///
+    /// Use this for product-specific stages; built-in stage names
+    /// (`"activated"`, `"engaged"`, `"inactive"`) map to their typed variants.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use outlit::{Outlit, email};
+    /// # async fn example(client: &Outlit) -> Result<(), outlit::Error> {
+    /// client.user().stage("power_user", email("user@example.com"))
+    ///     .send()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn stage(
+        &self,
+        stage: impl Into<String>,
+        identity: impl Into<Identity>,
+    ) -> SendableStage<'a> {
+        SendableStage {
+            builder: StageBuilder::new(JourneyStage::from(stage.into().as_str()), identity),
+            client: self.client,
+            project: None,
+        }
+    }
+
     /// Mark user as activated.
     pub fn activate(&self, identity: impl Into<Email>) -> SendableStage<'a> {
         SendableStage {
             builder: StageBuilder::new(JourneyStage::Activated, identity.into()),
             client: self.client,
+            project: None,
         }
     }
 
@@ -553,6 +2656,7 @@ impl<'a> UserMethods<'a> {
         SendableStage {
             builder: StageBuilder::new(JourneyStage::Activated, identity.into()),
             client: self.client,
+            project: None,
         }
     }
 
@@ -561,6 +2665,7 @@ impl<'a> UserMethods<'a> {
         SendableStage {
             builder: StageBuilder::new(JourneyStage::Activated, identity.into()),
             client: self.client,
+            project: None,
         }
     }
 
@@ -572,6 +2677,7 @@ impl<'a> UserMethods<'a> {
         SendableStage {
             builder: StageBuilder::new(JourneyStage::Engaged, identity.into()),
             client: self.client,
+            project: None,
         }
     }
 
@@ -583,6 +2689,7 @@ impl<'a> UserMethods<'a> {
         SendableStage {
             builder: StageBuilder::new(JourneyStage::Engaged, identity.into()),
             client: self.client,
+            project: None,
         }
     }
 
@@ -594,6 +2701,7 @@ impl<'a> UserMethods<'a> {
         SendableStage {
             builder: StageBuilder::new(JourneyStage::Engaged, identity.into()),
             client: self.client,
+            project: None,
         }
     }
 
@@ -605,6 +2713,7 @@ impl<'a> UserMethods<'a> {
         SendableStage {
             builder: StageBuilder::new(JourneyStage::Inactive, identity.into()),
             client: self.client,
+            project: None,
         }
     }
 
@@ -616,6 +2725,7 @@ impl<'a> UserMethods<'a> {
         SendableStage {
             builder: StageBuilder::new(JourneyStage::Inactive, identity.into()),
             client: self.client,
+            project: None,
         }
     }
 
@@ -627,6 +2737,7 @@ impl<'a> UserMethods<'a> {
         SendableStage {
             builder: StageBuilder::new(JourneyStage::Inactive, identity.into()),
             client: self.client,
+            project: None,
         }
     }
 }
@@ -639,25 +2750,451 @@ pub struct CustomerMethods<'a> {
 impl<'a> CustomerMethods<'a> {
     /// Mark customer as trialing.
     pub fn trialing(&self, domain: impl Into<String>) -> SendableBilling<'a> {
-        SendableBilling {
-            builder: BillingBuilder::new(BillingStatus::Trialing, domain),
-            client: self.client,
-        }
+        self.set_status(BillingStatus::Trialing, domain)
     }
 
     /// Mark customer as paid.
     pub fn paid(&self, domain: impl Into<String>) -> SendableBilling<'a> {
-        SendableBilling {
-            builder: BillingBuilder::new(BillingStatus::Paid, domain),
-            client: self.client,
-        }
+        self.set_status(BillingStatus::Paid, domain)
     }
 
     /// Mark customer as churned.
     pub fn churned(&self, domain: impl Into<String>) -> SendableBilling<'a> {
+        self.set_status(BillingStatus::Churned, domain)
+    }
+
+    /// Mark customer as past due, after a failed payment.
+    pub fn past_due(&self, domain: impl Into<String>) -> SendableBilling<'a> {
+        self.set_status(BillingStatus::PastDue, domain)
+    }
+
+    /// Mark customer's subscription as canceled.
+    pub fn canceled(&self, domain: impl Into<String>) -> SendableBilling<'a> {
+        self.set_status(BillingStatus::Canceled, domain)
+    }
+
+    /// Mark customer as unpaid, after payment retries ran out.
+    pub fn unpaid(&self, domain: impl Into<String>) -> SendableBilling<'a> {
+        self.set_status(BillingStatus::Unpaid, domain)
+    }
+
+    /// Mark customer's billing as paused.
+    pub fn paused(&self, domain: impl Into<String>) -> SendableBilling<'a> {
+        self.set_status(BillingStatus::Paused, domain)
+    }
+
+    /// Mark customer as incomplete, before the first payment succeeds.
+    pub fn incomplete(&self, domain: impl Into<String>) -> SendableBilling<'a> {
+        self.set_status(BillingStatus::Incomplete, domain)
+    }
+
+    /// Record a paid invoice. Sent with status paid.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use outlit::Outlit;
+    /// # async fn example(client: &Outlit) -> Result<(), outlit::Error> {
+    /// client.customer()
+    ///     .invoice_paid("acme.com", "in_123", 4900)
+    ///     .currency("usd")
+    ///     .send()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn invoice_paid(
+        &self,
+        domain: impl Into<String>,
+        invoice_id: impl Into<String>,
+        amount_cents: i64,
+    ) -> SendableBilling<'a> {
+        self.activity(BillingStatus::Paid, BillingActivity::InvoicePaid, domain)
+            .invoice_id(invoice_id)
+            .amount_cents(amount_cents)
+    }
+
+    /// Record a failed invoice payment. Sent with status past due.
+    pub fn invoice_failed(
+        &self,
+        domain: impl Into<String>,
+        invoice_id: impl Into<String>,
+        amount_cents: i64,
+    ) -> SendableBilling<'a> {
+        self.activity(
+            BillingStatus::PastDue,
+            BillingActivity::InvoiceFailed,
+            domain,
+        )
+        .invoice_id(invoice_id)
+        .amount_cents(amount_cents)
+    }
+
+    /// Record a refund of `amount_cents`. Sent with status paid; override
+    /// with [`SendableBilling::status`] if needed.
+    pub fn refund(&self, domain: impl Into<String>, amount_cents: i64) -> SendableBilling<'a> {
+        self.activity(BillingStatus::Paid, BillingActivity::Refund, domain)
+            .amount_cents(amount_cents)
+    }
+
+    /// Record a move from `previous_plan` to `plan`. Sent with status paid;
+    /// override with [`SendableBilling::status`] if needed.
+    pub fn plan_changed(
+        &self,
+        domain: impl Into<String>,
+        previous_plan: impl Into<String>,
+        plan: impl Into<String>,
+    ) -> SendableBilling<'a> {
+        self.activity(BillingStatus::Paid, BillingActivity::PlanChanged, domain)
+            .previous_plan(previous_plan)
+            .plan(plan)
+    }
+
+    /// Record a change from `previous_seats` to `seats`, for seat-based
+    /// expansion and contraction. Sent with status paid; override with
+    /// [`SendableBilling::status`] if needed.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use outlit::Outlit;
+    /// # async fn example(client: &Outlit) -> Result<(), outlit::Error> {
+    /// client.customer().seats_changed("acme.com", 10, 15).send().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn seats_changed(
+        &self,
+        domain: impl Into<String>,
+        previous_seats: u32,
+        seats: u32,
+    ) -> SendableBilling<'a> {
+        self.activity(BillingStatus::Paid, BillingActivity::SeatsChanged, domain)
+            .previous_seats(previous_seats)
+            .seats(seats)
+    }
+
+    fn activity(
+        &self,
+        status: BillingStatus,
+        activity: BillingActivity,
+        domain: impl Into<String>,
+    ) -> SendableBilling<'a> {
+        self.billing(BillingBuilder::new(status, domain).activity(activity))
+    }
+
+    /// Set the customer's billing status.
+    pub fn set_status(
+        &self,
+        status: BillingStatus,
+        domain: impl Into<String>,
+    ) -> SendableBilling<'a> {
+        self.billing(BillingBuilder::new(status, domain))
+    }
+
+    /// Fetch the customer's current billing status and MRR as Outlit
+    /// reports it, e.g. to gate features on it.
+    ///
+    /// Requires a [secret key](crate::OutlitBuilder::secret_key). Returns
+    /// `None` if Outlit has no billing data for `domain`. Events still in
+    /// the queue are not reflected until flushed and processed.
+    pub async fn status(&self, domain: impl AsRef<str>) -> Result<Option<CustomerStatus>, Error> {
+        self.client
+            .transport
+            .get(&["customers", domain.as_ref(), "billing"])
+            .await
+    }
+
+    pub(crate) fn billing(&self, builder: BillingBuilder) -> SendableBilling<'a> {
         SendableBilling {
-            builder: BillingBuilder::new(BillingStatus::Churned, domain),
+            builder,
             client: self.client,
+            project: None,
+        }
+    }
+}
+
+/// Privacy request methods, for erasure and opt-out requests.
+pub struct PrivacyMethods<'a> {
+    client: &'a Outlit,
+}
+
+impl<'a> PrivacyMethods<'a> {
+    /// Delete everything Outlit stores about a user, e.g. for a
+    /// right-to-be-forgotten request.
+    ///
+    /// The user is also suppressed, as by
+    /// [`suppress_user`](Self::suppress_user), so later events don't
+    /// recreate them. Requires a [secret key](crate::OutlitBuilder::secret_key).
+    pub async fn delete_user(&self, identity: impl Into<Identity>) -> Result<(), Error> {
+        self.request("deletions", identity.into()).await
+    }
+
+    /// Stop tracking a user, in this client and in Outlit.
+    ///
+    /// From now on events about the user are dropped before they are
+    /// queued, and events already queued are removed, except those in a
+    /// send already under way. The client's list of suppressed users is
+    /// not persisted; Outlit also drops events for suppressed users it
+    /// receives later, e.g. from another process. Requires a
+    /// [secret key](crate::OutlitBuilder::secret_key).
+    pub async fn suppress_user(&self, identity: impl Into<Identity>) -> Result<(), Error> {
+        self.request("suppressions", identity.into()).await
+    }
+
+    /// Check whether a user was suppressed by this client.
+    pub fn is_suppressed(&self, identity: impl Into<Identity>) -> bool {
+        self.client
+            .suppressions
+            .contains(&Identifiers::from(identity.into()))
+    }
+
+    /// Suppress the user locally, then send the request to each project.
+    async fn request(&self, kind: &str, identity: Identity) -> Result<(), Error> {
+        let identifiers = Identifiers::from(identity);
+        self.client.suppressions.insert(&identifiers);
+        for client in self.client.clients() {
+            let removed = client
+                .queue
+                .remove_unreserved(|event| client.suppressions.suppresses(event))
+                .await;
+            if removed > 0 {
+                debug!(removed, "queued events for suppressed user removed");
+            }
+        }
+
+        let body = serde_json::to_value(&identifiers)?;
+        for client in self.client.clients() {
+            client.transport.post(&["privacy", kind], &body).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::CustomEventData;
+    use std::sync::atomic::AtomicUsize;
+
+    fn make_test_event() -> TrackerEvent {
+        TrackerEvent::Custom(CustomEventData {
+            timestamp: 1706400000000,
+            url: "server://test".into(),
+            path: "/".into(),
+            event_name: "event".into(),
+            properties: None,
+            message_id: None,
+            event_id: None,
+        })
+    }
+
+    /// Fails to serialize when `fail` is set.
+    struct Fallible {
+        id: u32,
+        fail: bool,
+    }
+
+    impl serde::Serialize for Fallible {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            if self.fail {
+                return Err(serde::ser::Error::custom("unsupported value"));
+            }
+            serializer.serialize_u32(self.id)
+        }
+    }
+
+    #[test]
+    fn test_partition_serializable_quarantines_failures() {
+        let events = vec![
+            Fallible { id: 0, fail: false },
+            Fallible { id: 1, fail: true },
+            Fallible { id: 2, fail: false },
+        ];
+
+        let (valid, failed) = partition_serializable(events);
+
+        assert_eq!(valid.iter().map(|e| e.id).collect::<Vec<_>>(), vec![0, 2]);
+        assert_eq!(failed.len(), 1);
+        let (index, event, error) = &failed[0];
+        assert_eq!(*index, 1);
+        assert_eq!(event.id, 1);
+        assert!(error.to_string().contains("unsupported value"));
+    }
+
+    #[test]
+    fn test_partition_serializable_keeps_tracker_events() {
+        let (valid, failed) = partition_serializable(vec![make_test_event(), make_test_event()]);
+
+        assert_eq!(valid.len(), 2);
+        assert!(failed.is_empty());
+    }
+
+    #[test]
+    fn test_chunk_by_size_keeps_payloads_under_limit() {
+        let events = vec![make_test_event(); 5];
+        let event_size = serialized_size(&events[0]);
+        let payload_size = |events: &[TrackerEvent]| {
+            serialized_size(&IngestPayload::new(SourceType::Server, events.to_vec()))
+        };
+        let max_bytes = payload_size(&events[..2]);
+
+        let (batches, oversized) = chunk_by_size(events, max_bytes);
+
+        assert!(oversized.is_empty());
+        assert_eq!(
+            batches.iter().map(Vec::len).collect::<Vec<_>>(),
+            vec![2, 2, 1]
+        );
+        assert!(batches.iter().all(|batch| payload_size(batch) <= max_bytes));
+
+        // A single event that can't fit in any payload is split off
+        let (batches, oversized) = chunk_by_size(vec![make_test_event()], event_size);
+        assert!(batches.iter().all(Vec::is_empty));
+        assert_eq!(oversized[0].index, 0);
+        assert!(oversized[0]
+            .message
+            .starts_with(&format!("event is {event_size} bytes")));
+    }
+
+    fn test_signals() -> FlushSignals {
+        FlushSignals {
+            flush: Arc::new(Notify::new()),
+            enqueue: Arc::new(Notify::new()),
+        }
+    }
+
+    fn counting_flush(
+        queue: &Arc<EventQueue>,
+        flushes: &Arc<AtomicUsize>,
+    ) -> impl FnMut() -> std::pin::Pin<Box<dyn Future<Output = ()> + Send>> {
+        let queue = queue.clone();
+        let flushes = flushes.clone();
+        move || {
+            let queue = queue.clone();
+            let flushes = flushes.clone();
+            Box::pin(async move {
+                flushes.fetch_add(1, Ordering::SeqCst);
+                queue.clear().await;
+            })
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_flush_loop_flushes_when_oldest_event_exceeds_max_age() {
+        let queue = Arc::new(EventQueue::new(100));
+        let flushes = Arc::new(AtomicUsize::new(0));
+        let signals = test_signals();
+        let enqueue_signal = signals.enqueue.clone();
+
+        tokio::spawn(run_flush_loop(
+            Duration::from_secs(60),
+            Some(Duration::from_secs(1)),
+            signals,
+            Arc::new(AtomicBool::new(false)),
+            queue.clone(),
+            counting_flush(&queue, &flushes),
+        ));
+        tokio::task::yield_now().await;
+
+        queue.enqueue(make_test_event()).await;
+        enqueue_signal.notify_one();
+        tokio::task::yield_now().await;
+
+        tokio::time::advance(Duration::from_millis(900)).await;
+        tokio::task::yield_now().await;
+        assert_eq!(flushes.load(Ordering::SeqCst), 0);
+
+        // Flushed after 1s, well before the 60s interval
+        tokio::time::advance(Duration::from_millis(200)).await;
+        tokio::task::yield_now().await;
+        assert_eq!(flushes.load(Ordering::SeqCst), 1);
+        assert!(queue.is_empty().await);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_flush_loop_waits_for_interval_after_failed_age_flush() {
+        let queue = Arc::new(EventQueue::new(100));
+        let flushes = Arc::new(AtomicUsize::new(0));
+        let signals = test_signals();
+        let enqueue_signal = signals.enqueue.clone();
+
+        // A flush that always fails, leaving the event queued
+        let failing_flush = {
+            let flushes = flushes.clone();
+            move || {
+                flushes.fetch_add(1, Ordering::SeqCst);
+                std::future::ready(())
+            }
+        };
+        tokio::spawn(run_flush_loop(
+            Duration::from_secs(60),
+            Some(Duration::from_millis(100)),
+            signals,
+            Arc::new(AtomicBool::new(false)),
+            queue.clone(),
+            failing_flush,
+        ));
+        tokio::task::yield_now().await;
+
+        queue.enqueue(make_test_event()).await;
+        enqueue_signal.notify_one();
+        tokio::task::yield_now().await;
+
+        for _ in 0..10 {
+            tokio::time::advance(Duration::from_millis(100)).await;
+            tokio::task::yield_now().await;
         }
+        assert_eq!(flushes.load(Ordering::SeqCst), 1);
+
+        // New events don't re-arm the stale deadline either
+        queue.enqueue(make_test_event()).await;
+        enqueue_signal.notify_one();
+        tokio::time::advance(Duration::from_millis(100)).await;
+        tokio::task::yield_now().await;
+        assert_eq!(flushes.load(Ordering::SeqCst), 1);
+
+        // Retried on the next interval
+        tokio::time::advance(Duration::from_secs(59)).await;
+        tokio::task::yield_now().await;
+        assert_eq!(flushes.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_flush_loop_fires_once_per_interval() {
+        let queue = Arc::new(EventQueue::new(100));
+        let flushes = Arc::new(AtomicUsize::new(0));
+        let is_shutdown = Arc::new(AtomicBool::new(false));
+
+        let task = tokio::spawn(run_flush_loop(
+            Duration::from_secs(10),
+            None,
+            test_signals(),
+            is_shutdown.clone(),
+            queue.clone(),
+            counting_flush(&queue, &flushes),
+        ));
+
+        queue.enqueue(make_test_event()).await;
+        // Let the loop start its timer
+        tokio::task::yield_now().await;
+
+        tokio::time::advance(Duration::from_secs(9)).await;
+        tokio::task::yield_now().await;
+        assert_eq!(flushes.load(Ordering::SeqCst), 0);
+
+        tokio::time::advance(Duration::from_secs(2)).await;
+        tokio::task::yield_now().await;
+        assert_eq!(flushes.load(Ordering::SeqCst), 1);
+        assert!(queue.is_empty().await);
+
+        // Ticks with an empty queue don't flush
+        tokio::time::advance(Duration::from_secs(30)).await;
+        tokio::task::yield_now().await;
+        assert_eq!(flushes.load(Ordering::SeqCst), 1);
+
+        is_shutdown.store(true, Ordering::SeqCst);
+        tokio::time::advance(Duration::from_secs(10)).await;
+        task.await.unwrap();
     }
 }
@@ -0,0 +1,91 @@
+//! Time source for event timestamps.
+
+use crate::IntoTimestamp;
+use std::fmt;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+/// Source of the current time, used to timestamp events built without an
+/// explicit `.timestamp()`.
+///
+/// Defaults to [`SystemClock`]. Set a [`FixedClock`] with
+/// [`OutlitBuilder::clock`](crate::OutlitBuilder::clock) to pin event
+/// timestamps in tests. Each event still gets a random `eventId`, so leave
+/// it out when comparing serialized payloads.
+pub trait Clock: fmt::Debug + Send + Sync {
+    /// Current time in milliseconds since the Unix epoch.
+    fn now_ms(&self) -> i64;
+}
+
+/// The system's wall clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_ms(&self) -> i64 {
+        SystemTime::now().into_timestamp_millis()
+    }
+}
+
+/// A clock that only moves when told to.
+///
+/// Clones share the same time, so a test can keep one and advance it after
+/// handing another to the builder.
+///
+/// # Example
+///
+/// ```rust
+/// use outlit::{FixedClock, Outlit};
+/// use std::time::Duration;
+///
+/// let clock = FixedClock::new(1706400000000);
+/// let builder = Outlit::builder("pk_test").clock(clock.clone());
+///
+/// clock.advance(Duration::from_secs(60));
+/// # let _ = builder;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FixedClock(Arc<AtomicI64>);
+
+impl FixedClock {
+    /// Create a clock stopped at `now` (milliseconds since epoch, or any
+    /// other [`IntoTimestamp`]).
+    pub fn new(now: impl IntoTimestamp) -> Self {
+        Self(Arc::new(AtomicI64::new(now.into_timestamp_millis())))
+    }
+
+    /// Move the clock to `now`.
+    pub fn set(&self, now: impl IntoTimestamp) {
+        self.0.store(now.into_timestamp_millis(), Ordering::Relaxed);
+    }
+
+    /// Move the clock forward by `duration`.
+    pub fn advance(&self, duration: Duration) {
+        self.0
+            .fetch_add(duration.as_millis() as i64, Ordering::Relaxed);
+    }
+}
+
+impl Clock for FixedClock {
+    fn now_ms(&self) -> i64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixed_clock_clones_share_time() {
+        let clock = FixedClock::new(1_000);
+        let handle = clock.clone();
+
+        handle.advance(Duration::from_millis(500));
+        assert_eq!(clock.now_ms(), 1_500);
+
+        handle.set(42);
+        assert_eq!(clock.now_ms(), 42);
+    }
+}
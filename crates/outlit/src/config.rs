@@ -1,27 +1,529 @@
 //! Client configuration.
 
+use crate::builders::insert_property;
+use crate::clock::{Clock, SystemClock};
+use crate::dead_letter::{DeadLetterSink, DeadLetters};
+use crate::limits::SizeLimits;
+use crate::types::{Consent, FlushReport, TrackerEvent, RESERVED_PREFIX};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::Arc;
 use std::time::Duration;
+use tracing::debug;
 
 /// Default API host.
 pub const DEFAULT_API_HOST: &str = "https://app.outlit.ai";
 
+/// Default ingest endpoint path template.
+pub const DEFAULT_ENDPOINT_PATH: &str = "/api/i/v1/{public_key}/events";
+
+/// Default `User-Agent` header sent with ingest requests.
+pub const DEFAULT_USER_AGENT: &str = concat!("outlit-rust/", env!("CARGO_PKG_VERSION"));
+
+/// Environment that [`OutlitBuilder::production_only`] sends events from.
+pub const PRODUCTION_ENVIRONMENT: &str = "production";
+
 /// Default flush interval.
 pub const DEFAULT_FLUSH_INTERVAL: Duration = Duration::from_secs(10);
 
 /// Default max batch size.
 pub const DEFAULT_MAX_BATCH_SIZE: usize = 100;
 
+/// Default number of events per request for [`Outlit::import`](crate::Outlit::import),
+/// the ingest API's per-request maximum.
+pub const DEFAULT_IMPORT_BATCH_SIZE: usize = 100;
+
 /// Default request timeout.
 pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
 
+/// Default sample rate for custom events (keep everything).
+pub const DEFAULT_SAMPLE_RATE: f64 = 1.0;
+
+/// Default number of idempotency keys remembered for deduplication.
+pub const DEFAULT_DEDUP_WINDOW: usize = 1000;
+
+/// Default number of requests a flush sends at once.
+pub const DEFAULT_FLUSH_CONCURRENCY: usize = 1;
+
+/// JSON field-name casing used for event payloads.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FieldCase {
+    /// `eventName`, as expected by the hosted API (default).
+    #[default]
+    Camel,
+    /// `event_name`, for self-hosted ingest servers expecting snake_case.
+    Snake,
+}
+
+/// Retry policy for transient send failures.
+///
+/// Connection errors, timeouts, and 5xx responses are retried with
+/// exponential backoff: the delay before retry `n` is `base_delay * 2^(n-1)`,
+/// capped at `max_delay`. With `jitter`, each delay is randomized to between
+/// half and all of that value, so many clients don't retry in lockstep.
+///
+/// Rate-limited (429) responses are retried after their `Retry-After` delay
+/// if it is no longer than `max_delay`; otherwise the send fails with
+/// [`Error::RateLimited`](crate::Error::RateLimited) and the events stay
+/// queued.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    /// Total attempts per send, including the first. `1` disables retries.
+    pub max_attempts: u32,
+    /// Delay before the first retry.
+    pub base_delay: Duration,
+    /// Upper bound on any single delay.
+    pub max_delay: Duration,
+    /// Randomize delays.
+    pub jitter: bool,
+}
+
+impl RetryPolicy {
+    /// A policy that never retries.
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Get the delay before retrying after failed attempt `attempt` (1-based).
+    pub(crate) fn delay(&self, attempt: u32, rng: &crate::sampling::Rng) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        let delay = self.base_delay.saturating_mul(factor).min(self.max_delay);
+        if self.jitter {
+            delay.mul_f64(0.5 + rng.next_f64() / 2.0)
+        } else {
+            delay
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    /// Three attempts, starting at 100ms and capped at 5s, with jitter.
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(5),
+            jitter: true,
+        }
+    }
+}
+
+/// Circuit breaker settings for [`OutlitBuilder::circuit_breaker`].
+///
+/// After `failure_threshold` consecutive failed flushes, flushing pauses for
+/// `cool_down` while events keep buffering. The next flush is then a probe:
+/// if it succeeds flushing resumes, otherwise it pauses for another
+/// cool-down.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CircuitBreaker {
+    /// Consecutive failed flushes that open the circuit.
+    pub failure_threshold: u32,
+    /// How long flushing stays paused once the circuit opens.
+    pub cool_down: Duration,
+}
+
+impl Default for CircuitBreaker {
+    /// Open after 5 consecutive failures, for 30s.
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            cool_down: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Client-side rate limit for [`OutlitBuilder::rate_limit`].
+///
+/// A token bucket refilled at `events_per_second` and holding up to `burst`
+/// events: short spikes up to `burst` pass immediately, and sustained
+/// traffic above the rate is handled according to `policy`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    /// Sustained events per second.
+    pub events_per_second: f64,
+    /// Events allowed in a burst above the sustained rate.
+    pub burst: u32,
+    /// What to do with events over the limit.
+    pub policy: RateLimitPolicy,
+}
+
+impl RateLimit {
+    /// Allow `events_per_second` with a burst of the same size, dropping
+    /// excess events.
+    pub fn per_second(events_per_second: u32) -> Self {
+        Self {
+            events_per_second: f64::from(events_per_second),
+            burst: events_per_second,
+            policy: RateLimitPolicy::Drop,
+        }
+    }
+
+    /// Set the burst size.
+    pub fn burst(mut self, burst: u32) -> Self {
+        self.burst = burst;
+        self
+    }
+
+    /// Set what happens to events over the limit.
+    pub fn policy(mut self, policy: RateLimitPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+}
+
+/// What happens to events over a [`RateLimit`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RateLimitPolicy {
+    /// Discard the event and count it in
+    /// [`ClientStats::dropped`](crate::ClientStats::dropped). A
+    /// `track_batch()` keeps the events that fit and drops the rest.
+    #[default]
+    Drop,
+    /// Make `send()` and `track_batch()` wait until the event fits under the
+    /// limit. `send_nowait()` can't wait, so it drops instead.
+    Throttle,
+}
+
+/// Naming rules for custom events, checked by
+/// [`OutlitBuilder::event_name_policy`].
+///
+/// Names must be non-empty, at most `max_length` characters, made of ASCII
+/// letters, digits, and `extra_chars`, and start with `required_prefix` if
+/// set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventNamePolicy {
+    /// Longest allowed name, in characters.
+    pub max_length: usize,
+    /// Characters allowed besides ASCII letters and digits.
+    pub extra_chars: String,
+    /// Prefix every name must start with, such as `"app."`.
+    pub required_prefix: Option<String>,
+}
+
+impl EventNamePolicy {
+    /// Check `name` against the policy, returning
+    /// [`Error::Validation`](crate::Error::Validation) with the broken rule.
+    pub fn check(&self, name: &str) -> Result<(), crate::Error> {
+        let invalid = |reason: String| {
+            Err(crate::Error::Validation(format!(
+                "invalid event name {name:?}: {reason}"
+            )))
+        };
+        if name.is_empty() {
+            return invalid("must not be empty".into());
+        }
+        let length = name.chars().count();
+        if length > self.max_length {
+            return invalid(format!(
+                "{length} characters, over the maximum of {}",
+                self.max_length
+            ));
+        }
+        if let Some(c) = name
+            .chars()
+            .find(|&c| !c.is_ascii_alphanumeric() && !self.extra_chars.contains(c))
+        {
+            return invalid(format!("{c:?} is not an allowed character"));
+        }
+        if let Some(prefix) = &self.required_prefix {
+            if !name.starts_with(prefix.as_str()) {
+                return invalid(format!("must start with {prefix:?}"));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for EventNamePolicy {
+    /// Up to 128 characters of letters, digits, spaces, and `_-.:`, with no
+    /// prefix.
+    fn default() -> Self {
+        Self {
+            max_length: 128,
+            extra_chars: " _-.:".into(),
+            required_prefix: None,
+        }
+    }
+}
+
+/// Bounds on event timestamps, checked by
+/// [`OutlitBuilder::timestamp_policy`].
+///
+/// Catches timestamps in the wrong unit: seconds land before `min`, and
+/// microseconds or nanoseconds land far in the future.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimestampPolicy {
+    /// Earliest allowed timestamp, in milliseconds since epoch.
+    pub min: i64,
+    /// How far ahead of the client's [`Clock`] a timestamp may be.
+    pub max_future: Duration,
+    /// What happens to timestamps outside the bounds.
+    pub action: TimestampAction,
+}
+
+impl TimestampPolicy {
+    /// Check `timestamp` against the policy at time `now`, both in
+    /// milliseconds since epoch.
+    ///
+    /// Returns the timestamp to send, clamped into range with
+    /// [`TimestampAction::Clamp`], or
+    /// [`Error::Validation`](crate::Error::Validation) describing the
+    /// problem with [`TimestampAction::Reject`].
+    pub fn check(&self, timestamp: i64, now: i64) -> Result<i64, crate::Error> {
+        let max = now.saturating_add(self.max_future.as_millis() as i64);
+        let (bound, problem) = if timestamp < self.min {
+            (self.min, format!("before the minimum of {}", self.min))
+        } else if timestamp > max {
+            (
+                max,
+                format!("more than {:?} in the future", self.max_future),
+            )
+        } else {
+            return Ok(timestamp);
+        };
+        match self.action {
+            TimestampAction::Clamp => Ok(bound),
+            TimestampAction::Reject => Err(crate::Error::Validation(format!(
+                "timestamp {timestamp} is {problem}; timestamps are milliseconds since epoch"
+            ))),
+        }
+    }
+}
+
+impl Default for TimestampPolicy {
+    /// Reject timestamps before 2000-01-01 or more than a day ahead.
+    fn default() -> Self {
+        Self {
+            min: 946_684_800_000,
+            max_future: Duration::from_secs(24 * 60 * 60),
+            action: TimestampAction::Reject,
+        }
+    }
+}
+
+/// What happens to timestamps outside a [`TimestampPolicy`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TimestampAction {
+    /// Fail `send()` and `track_batch()` with
+    /// [`Error::Validation`](crate::Error::Validation).
+    #[default]
+    Reject,
+    /// Move the timestamp to the nearest allowed value.
+    Clamp,
+}
+
+/// How consent is enforced, set with [`OutlitBuilder::consent_policy`].
+///
+/// Each event needs the [`Consent`] it was marked with by `.consent()`,
+/// or [`unmarked`](Self::unmarked) if it wasn't; [`Consent::Necessary`] is
+/// always met. What a user agreed to is recorded with
+/// [`Outlit::set_consent`](crate::Outlit::set_consent); users without a
+/// recorded decision are treated as having agreed to
+/// [`assumed`](Self::assumed).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConsentPolicy {
+    /// Consent needed by events not marked with `.consent()`.
+    pub unmarked: Consent,
+    /// Consent assumed for users with no recorded decision.
+    pub assumed: Vec<Consent>,
+}
+
+impl ConsentPolicy {
+    /// Opt-in: until a user consents, only necessary events are sent.
+    pub fn opt_in() -> Self {
+        Self {
+            unmarked: Consent::Analytics,
+            assumed: Vec::new(),
+        }
+    }
+
+    /// Opt-out: users are assumed to agree to everything until a decision
+    /// is recorded.
+    pub fn opt_out() -> Self {
+        Self {
+            unmarked: Consent::Analytics,
+            assumed: Consent::ALL.to_vec(),
+        }
+    }
+
+    /// Set the consent needed by events not marked with `.consent()`.
+    /// Default: [`Consent::Analytics`].
+    pub fn unmarked(mut self, consent: Consent) -> Self {
+        self.unmarked = consent;
+        self
+    }
+}
+
+impl Default for ConsentPolicy {
+    /// [`ConsentPolicy::opt_in`].
+    fn default() -> Self {
+        Self::opt_in()
+    }
+}
+
+/// Hook run on each event before it is queued.
+///
+/// Returning `false` drops the event.
+pub type BeforeSendHook = Arc<dyn Fn(&mut TrackerEvent) -> bool + Send + Sync>;
+
+/// Ordered chain of [`BeforeSendHook`]s.
+#[derive(Clone, Default)]
+pub(crate) struct BeforeSendHooks(Vec<BeforeSendHook>);
+
+impl BeforeSendHooks {
+    /// Run each hook in order, stopping at the first that drops the event.
+    pub(crate) fn apply(&self, event: &mut TrackerEvent) -> bool {
+        self.0.iter().all(|hook| hook(event))
+    }
+}
+
+impl fmt::Debug for BeforeSendHooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "BeforeSendHooks({})", self.0.len())
+    }
+}
+
+/// Allowed and blocked property keys, from
+/// [`OutlitBuilder::allow_properties`] and
+/// [`OutlitBuilder::deny_properties`].
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PropertyFilter {
+    allow: Option<HashSet<String>>,
+    deny: HashSet<String>,
+}
+
+impl PropertyFilter {
+    /// Remove top-level properties or traits that aren't allowed. Reserved
+    /// `__` keys set by the SDK are always kept.
+    pub(crate) fn apply(&self, event: &mut TrackerEvent) {
+        if self.allow.is_none() && self.deny.is_empty() {
+            return;
+        }
+        let Some(properties) = event.properties_mut() else {
+            return;
+        };
+        properties.retain(|key, _| {
+            let keep = key.starts_with(RESERVED_PREFIX)
+                || (self
+                    .allow
+                    .as_ref()
+                    .map_or(true, |allow| allow.contains(key))
+                    && !self.deny.contains(key));
+            if !keep {
+                debug!(key = %key, "dropping property not allowed by property filter");
+            }
+            keep
+        });
+    }
+}
+
+/// Callback run after a flush sends events.
+pub type FlushSuccessHook = Arc<dyn Fn(&FlushReport) + Send + Sync>;
+
+/// Callback run after a flush fails; the events stay queued.
+pub type FlushFailureHook = Arc<dyn Fn(&crate::Error) + Send + Sync>;
+
+/// Callbacks notified of each flush's outcome.
+#[derive(Clone, Default)]
+pub(crate) struct FlushCallbacks {
+    on_success: Vec<FlushSuccessHook>,
+    on_failure: Vec<FlushFailureHook>,
+}
+
+impl FlushCallbacks {
+    /// Report a flush result. Flushes of an empty queue are not reported.
+    pub(crate) fn notify(&self, result: &Result<FlushReport, crate::Error>) {
+        match result {
+            Ok(report) if report.sent > 0 => self.on_success.iter().for_each(|hook| hook(report)),
+            Ok(_) => {}
+            Err(e) => self.on_failure.iter().for_each(|hook| hook(e)),
+        }
+    }
+}
+
+impl fmt::Debug for FlushCallbacks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FlushCallbacks")
+            .field("on_success", &self.on_success.len())
+            .field("on_failure", &self.on_failure.len())
+            .finish()
+    }
+}
+
+/// Secret server key used to sign ingest requests. Redacted in `Debug`
+/// output.
+#[derive(Clone)]
+pub(crate) struct SecretKey(pub(crate) String);
+
+impl fmt::Debug for SecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretKey(..)")
+    }
+}
+
 /// Outlit client configuration.
 #[derive(Debug, Clone)]
 pub struct Config {
     pub(crate) public_key: String,
+    pub(crate) secret_key: Option<SecretKey>,
     pub(crate) api_host: String,
+    pub(crate) fallback_api_host: Option<String>,
+    pub(crate) endpoint_path: String,
+    pub(crate) user_agent: String,
+    pub(crate) headers: reqwest::header::HeaderMap,
     pub(crate) flush_interval: Duration,
+    pub(crate) auto_flush: bool,
+    pub(crate) enabled: bool,
+    pub(crate) clock: Arc<dyn Clock>,
+    pub(crate) environment: Option<String>,
+    pub(crate) projects: HashMap<String, String>,
+    pub(crate) event_routes: HashMap<String, String>,
+    pub(crate) production_only: bool,
+    pub(crate) max_event_age: Option<Duration>,
     pub(crate) max_batch_size: usize,
+    pub(crate) import_batch_size: usize,
     pub(crate) timeout: Duration,
+    pub(crate) connect_timeout: Option<Duration>,
+    pub(crate) http_client: Option<reqwest::Client>,
+    pub(crate) proxy: Option<String>,
+    pub(crate) retry_policy: RetryPolicy,
+    pub(crate) circuit_breaker: Option<CircuitBreaker>,
+    pub(crate) rate_limit: Option<RateLimit>,
+    pub(crate) sample_rate: f64,
+    pub(crate) event_sample_rates: HashMap<String, f64>,
+    pub(crate) deterministic_sampling: bool,
+    pub(crate) dedup_window: usize,
+    pub(crate) dedup_identical: Option<Duration>,
+    pub(crate) max_bisect_depth: usize,
+    pub(crate) max_payload_bytes: Option<usize>,
+    pub(crate) flush_concurrency: usize,
+    pub(crate) max_property_bytes: Option<usize>,
+    pub(crate) max_event_bytes: Option<usize>,
+    pub(crate) strict_identity: bool,
+    pub(crate) event_name_policy: Option<EventNamePolicy>,
+    pub(crate) timestamp_policy: Option<TimestampPolicy>,
+    pub(crate) consent_policy: Option<ConsentPolicy>,
+    pub(crate) property_filter: PropertyFilter,
+    #[cfg(feature = "redact")]
+    pub(crate) redactor: Option<crate::Redactor>,
+    pub(crate) requeue_rejected: bool,
+    pub(crate) max_delivery_attempts: Option<u32>,
+    pub(crate) log_bodies: bool,
+    #[cfg(feature = "opentelemetry")]
+    pub(crate) trace_context: bool,
+    pub(crate) default_properties: HashMap<String, Value>,
+    pub(crate) field_case: FieldCase,
+    #[cfg(feature = "persist")]
+    pub(crate) persist_path: Option<std::path::PathBuf>,
+    #[cfg(feature = "sqlite")]
+    pub(crate) persist_sqlite: bool,
+    pub(crate) before_send: BeforeSendHooks,
+    pub(crate) flush_callbacks: FlushCallbacks,
+    pub(crate) dead_letters: DeadLetters,
 }
 
 impl Config {
@@ -35,30 +537,339 @@ impl Config {
         &self.api_host
     }
 
+    /// Get the fallback API host, if set.
+    pub fn fallback_api_host(&self) -> Option<&str> {
+        self.fallback_api_host.as_deref()
+    }
+
+    /// Get the ingest endpoint path template.
+    pub fn endpoint_path(&self) -> &str {
+        &self.endpoint_path
+    }
+
+    /// Get the `User-Agent` header value.
+    pub fn user_agent(&self) -> &str {
+        &self.user_agent
+    }
+
+    /// Whether ingest requests are signed with a secret key.
+    pub fn signs_requests(&self) -> bool {
+        self.secret_key.is_some()
+    }
+
+    /// Get the extra headers sent with every ingest request.
+    pub fn headers(&self) -> &reqwest::header::HeaderMap {
+        &self.headers
+    }
+
     /// Get the flush interval.
     pub fn flush_interval(&self) -> Duration {
         self.flush_interval
     }
 
+    /// Whether the background flush timer is enabled.
+    pub fn auto_flush(&self) -> bool {
+        self.auto_flush
+    }
+
+    /// Whether the client starts with tracking enabled.
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Get the clock events are timestamped with.
+    pub fn clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
+    }
+
+    /// Get the additional projects events can be routed to, by name, with
+    /// their public keys.
+    pub fn projects(&self) -> &HashMap<String, String> {
+        &self.projects
+    }
+
+    /// Get the projects custom events are routed to, by event name.
+    pub fn event_routes(&self) -> &HashMap<String, String> {
+        &self.event_routes
+    }
+
+    /// Configuration for the project client named `name`: this one with
+    /// its public key, and its own persisted queue file.
+    pub(crate) fn for_project(&self, name: &str, public_key: &str) -> Config {
+        let mut config = self.clone();
+        config.public_key = public_key.to_string();
+        config.projects.clear();
+        config.event_routes.clear();
+        #[cfg(feature = "persist")]
+        if let Some(path) = &mut config.persist_path {
+            let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+            file_name.push(format!(".{name}"));
+            path.set_file_name(file_name);
+        }
+        #[cfg(not(feature = "persist"))]
+        let _ = name;
+        config
+    }
+
+    /// Get the environment attached to every event, if set.
+    pub fn environment(&self) -> Option<&str> {
+        self.environment.as_deref()
+    }
+
+    /// Whether events are only sent from the `"production"` environment.
+    pub fn production_only(&self) -> bool {
+        self.production_only
+    }
+
+    /// Whether every event is dropped because of
+    /// [`production_only`](Self::production_only).
+    pub(crate) fn drops_environment(&self) -> bool {
+        self.production_only && self.environment() != Some(PRODUCTION_ENVIRONMENT)
+    }
+
+    /// Get the maximum time an event may wait in the queue, if set.
+    pub fn max_event_age(&self) -> Option<Duration> {
+        self.max_event_age
+    }
+
     /// Get the max batch size.
     pub fn max_batch_size(&self) -> usize {
         self.max_batch_size
     }
 
+    /// Get the number of events sent per request by
+    /// [`Outlit::import`](crate::Outlit::import).
+    pub fn import_batch_size(&self) -> usize {
+        self.import_batch_size
+    }
+
     /// Get the request timeout.
     pub fn timeout(&self) -> Duration {
         self.timeout
     }
+
+    /// Get the connection timeout, if set.
+    pub fn connect_timeout(&self) -> Option<Duration> {
+        self.connect_timeout
+    }
+
+    /// Get the HTTP client passed to
+    /// [`OutlitBuilder::http_client`], if any.
+    pub fn http_client(&self) -> Option<&reqwest::Client> {
+        self.http_client.as_ref()
+    }
+
+    /// Get the circuit breaker settings, if enabled.
+    pub fn circuit_breaker(&self) -> Option<CircuitBreaker> {
+        self.circuit_breaker
+    }
+
+    /// Get the client-side rate limit, if set.
+    pub fn rate_limit(&self) -> Option<RateLimit> {
+        self.rate_limit
+    }
+
+    /// Get the proxy URL ingest requests are sent through, if set.
+    pub fn proxy(&self) -> Option<&str> {
+        self.proxy.as_deref()
+    }
+
+    /// Get the retry policy for transient send failures.
+    pub fn retry_policy(&self) -> RetryPolicy {
+        self.retry_policy
+    }
+
+    /// Get the sample rate for custom events.
+    pub fn sample_rate(&self) -> f64 {
+        self.sample_rate
+    }
+
+    /// Get the sampling rates overriding the default for specific event
+    /// names.
+    pub fn event_sample_rates(&self) -> &HashMap<String, f64> {
+        &self.event_sample_rates
+    }
+
+    /// Whether sampling is decided by hashing each event's identity.
+    pub fn deterministic_sampling(&self) -> bool {
+        self.deterministic_sampling
+    }
+
+    /// Get the number of idempotency keys remembered for deduplication.
+    pub fn dedup_window(&self) -> usize {
+        self.dedup_window
+    }
+
+    /// Get how long identical events are deduplicated for, if enabled.
+    pub fn dedup_identical(&self) -> Option<Duration> {
+        self.dedup_identical
+    }
+
+    /// Get the maximum depth to which a batch rejected with 400 is split.
+    pub fn max_bisect_depth(&self) -> usize {
+        self.max_bisect_depth
+    }
+
+    /// Get the maximum serialized size of an ingest request body, if limited.
+    pub fn max_payload_bytes(&self) -> Option<usize> {
+        self.max_payload_bytes
+    }
+
+    /// Get the maximum number of requests sent at once by a flush.
+    pub fn flush_concurrency(&self) -> usize {
+        self.flush_concurrency
+    }
+
+    /// Get the length above which string property values are truncated, if
+    /// limited.
+    pub fn max_property_bytes(&self) -> Option<usize> {
+        self.max_property_bytes
+    }
+
+    /// Get the maximum serialized size of an event, if limited.
+    pub fn max_event_bytes(&self) -> Option<usize> {
+        self.max_event_bytes
+    }
+
+    /// Whether events with malformed email identities are rejected.
+    pub fn strict_identity(&self) -> bool {
+        self.strict_identity
+    }
+
+    /// Get the naming rules for custom events, if enforced.
+    pub fn event_name_policy(&self) -> Option<&EventNamePolicy> {
+        self.event_name_policy.as_ref()
+    }
+
+    /// Get the bounds on event timestamps, if enforced.
+    pub fn timestamp_policy(&self) -> Option<TimestampPolicy> {
+        self.timestamp_policy
+    }
+
+    /// Get how consent is enforced, if it is.
+    pub fn consent_policy(&self) -> Option<&ConsentPolicy> {
+        self.consent_policy.as_ref()
+    }
+
+    /// Get the PII redactor, if set.
+    #[cfg(feature = "redact")]
+    pub fn redactor(&self) -> Option<&crate::Redactor> {
+        self.redactor.as_ref()
+    }
+
+    pub(crate) fn size_limits(&self) -> SizeLimits {
+        SizeLimits {
+            max_property_bytes: self.max_property_bytes,
+            max_event_bytes: self.max_event_bytes,
+        }
+    }
+
+    /// Whether events the API reports as failed are queued again.
+    pub fn requeue_rejected(&self) -> bool {
+        self.requeue_rejected
+    }
+
+    /// Get the number of failed delivery attempts after which an event is
+    /// dropped, if limited.
+    pub fn max_delivery_attempts(&self) -> Option<u32> {
+        self.max_delivery_attempts
+    }
+
+    /// Whether request and response bodies are logged at trace level.
+    pub fn log_bodies(&self) -> bool {
+        self.log_bodies
+    }
+
+    /// Whether OpenTelemetry trace context is attached to events and ingest
+    /// requests.
+    #[cfg(feature = "opentelemetry")]
+    pub fn trace_context(&self) -> bool {
+        self.trace_context
+    }
+
+    /// Get the properties merged into every track, page view, and stage
+    /// event.
+    pub fn default_properties(&self) -> &HashMap<String, Value> {
+        &self.default_properties
+    }
+
+    /// Get the JSON field-name casing for event payloads.
+    pub fn field_case(&self) -> FieldCase {
+        self.field_case
+    }
+
+    /// Get the path of the disk-backed queue, if enabled.
+    #[cfg(feature = "persist")]
+    pub fn persist_path(&self) -> Option<&std::path::Path> {
+        self.persist_path.as_deref()
+    }
+
+    /// Check if the disk-backed queue is an SQLite database.
+    #[cfg(feature = "sqlite")]
+    pub fn persist_sqlite(&self) -> bool {
+        self.persist_sqlite
+    }
 }
 
 /// Builder for Outlit client.
 #[derive(Debug)]
 pub struct OutlitBuilder {
     public_key: String,
+    secret_key: Option<SecretKey>,
     api_host: Option<String>,
+    fallback_api_host: Option<String>,
+    endpoint_path: Option<String>,
+    user_agent: Option<String>,
+    headers: Vec<(String, String)>,
     flush_interval: Option<Duration>,
+    auto_flush: bool,
+    enabled: bool,
+    clock: Arc<dyn Clock>,
+    environment: Option<String>,
+    production_only: bool,
+    projects: HashMap<String, String>,
+    event_routes: HashMap<String, String>,
+    max_event_age: Option<Duration>,
     max_batch_size: Option<usize>,
+    import_batch_size: Option<usize>,
     timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    http_client: Option<reqwest::Client>,
+    proxy: Option<String>,
+    retry_policy: Option<RetryPolicy>,
+    circuit_breaker: Option<CircuitBreaker>,
+    rate_limit: Option<RateLimit>,
+    sample_rate: Option<f64>,
+    event_sample_rates: HashMap<String, f64>,
+    deterministic_sampling: bool,
+    dedup_window: Option<usize>,
+    dedup_identical: Option<Duration>,
+    max_bisect_depth: usize,
+    max_payload_bytes: Option<usize>,
+    flush_concurrency: Option<usize>,
+    max_property_bytes: Option<usize>,
+    max_event_bytes: Option<usize>,
+    strict_identity: bool,
+    event_name_policy: Option<EventNamePolicy>,
+    timestamp_policy: Option<TimestampPolicy>,
+    consent_policy: Option<ConsentPolicy>,
+    property_filter: PropertyFilter,
+    #[cfg(feature = "redact")]
+    redactor: Option<crate::Redactor>,
+    requeue_rejected: bool,
+    max_delivery_attempts: Option<u32>,
+    log_bodies: bool,
+    #[cfg(feature = "opentelemetry")]
+    trace_context: bool,
+    default_properties: HashMap<String, Value>,
+    field_case: FieldCase,
+    #[cfg(feature = "persist")]
+    persist_path: Option<std::path::PathBuf>,
+    #[cfg(feature = "sqlite")]
+    persist_sqlite: bool,
+    before_send: BeforeSendHooks,
+    flush_callbacks: FlushCallbacks,
+    dead_letters: DeadLetters,
 }
 
 impl OutlitBuilder {
@@ -66,10 +877,61 @@ impl OutlitBuilder {
     pub fn new(public_key: impl Into<String>) -> Self {
         Self {
             public_key: public_key.into(),
+            secret_key: None,
             api_host: None,
+            fallback_api_host: None,
+            endpoint_path: None,
+            user_agent: None,
+            headers: Vec::new(),
             flush_interval: None,
+            auto_flush: true,
+            enabled: true,
+            clock: Arc::new(SystemClock),
+            environment: None,
+            production_only: false,
+            projects: HashMap::new(),
+            event_routes: HashMap::new(),
+            max_event_age: None,
             max_batch_size: None,
+            import_batch_size: None,
             timeout: None,
+            connect_timeout: None,
+            http_client: None,
+            proxy: None,
+            retry_policy: None,
+            circuit_breaker: None,
+            rate_limit: None,
+            sample_rate: None,
+            event_sample_rates: HashMap::new(),
+            deterministic_sampling: false,
+            dedup_window: None,
+            dedup_identical: None,
+            max_bisect_depth: 0,
+            max_payload_bytes: None,
+            flush_concurrency: None,
+            max_property_bytes: None,
+            max_event_bytes: None,
+            strict_identity: false,
+            event_name_policy: None,
+            timestamp_policy: None,
+            consent_policy: None,
+            property_filter: PropertyFilter::default(),
+            #[cfg(feature = "redact")]
+            redactor: None,
+            requeue_rejected: false,
+            max_delivery_attempts: None,
+            log_bodies: false,
+            #[cfg(feature = "opentelemetry")]
+            trace_context: false,
+            default_properties: HashMap::new(),
+            field_case: FieldCase::default(),
+            #[cfg(feature = "persist")]
+            persist_path: None,
+            #[cfg(feature = "sqlite")]
+            persist_sqlite: false,
+            before_send: BeforeSendHooks::default(),
+            flush_callbacks: FlushCallbacks::default(),
+            dead_letters: DeadLetters::default(),
         }
     }
 
@@ -79,46 +941,953 @@ impl OutlitBuilder {
         self
     }
 
+    /// Set a fallback API host for high availability.
+    ///
+    /// When a send to the primary host fails with a connection error,
+    /// timeout, or 5xx response, the same batch is sent to this host before
+    /// being kept queued. 4xx responses do not fail over.
+    pub fn fallback_api_host(mut self, host: impl Into<String>) -> Self {
+        self.fallback_api_host = Some(host.into());
+        self
+    }
+
+    /// Set the ingest endpoint path template, appended to the API host.
+    ///
+    /// The template must contain a `{public_key}` placeholder. Defaults to
+    /// `/api/i/v1/{public_key}/events`. Useful behind reverse proxies that
+    /// add or strip path prefixes.
+    pub fn endpoint_path(mut self, template: impl Into<String>) -> Self {
+        self.endpoint_path = Some(template.into());
+        self
+    }
+
+    /// Set the `User-Agent` header sent with ingest requests.
+    ///
+    /// Defaults to `outlit-rust/{version}`, with the SDK's version.
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Sign every ingest request with a secret server key (`sk_...`), so the
+    /// API can trust these events more than ones sent from browsers.
+    ///
+    /// Each request carries an `X-Outlit-Timestamp` header with the Unix
+    /// time in seconds and an `X-Outlit-Signature` header of the form
+    /// `v1=<hex>`, where `<hex>` is the HMAC-SHA256 of
+    /// `"{timestamp}.{body}"` keyed with the secret. Keep the secret on the
+    /// server; never ship it to clients.
+    pub fn secret_key(mut self, secret_key: impl Into<String>) -> Self {
+        self.secret_key = Some(SecretKey(secret_key.into()));
+        self
+    }
+
+    /// Add a header sent with every ingest request, such as an
+    /// identification header required by an API gateway.
+    ///
+    /// Adding the same name again replaces the earlier value. Invalid names
+    /// or values make [`build`](Self::build) fail.
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
     /// Set the flush interval.
+    ///
+    /// A zero interval disables the background flush timer.
     pub fn flush_interval(mut self, interval: Duration) -> Self {
         self.flush_interval = Some(interval);
         self
     }
 
+    /// Disable the background flush timer.
+    ///
+    /// Events are then only sent when the batch size is reached or on
+    /// [`flush`](crate::Outlit::flush) / [`shutdown`](crate::Outlit::shutdown).
+    pub fn disable_auto_flush(mut self) -> Self {
+        self.auto_flush = false;
+        self
+    }
+
+    /// Start with tracking disabled, so every send is a cheap no-op until
+    /// [`Outlit::set_enabled`](crate::Outlit::set_enabled) turns it on.
+    pub fn disabled(mut self) -> Self {
+        self.enabled = false;
+        self
+    }
+
+    /// Set the clock used to timestamp events that don't set their own
+    /// timestamp (default: [`SystemClock`]).
+    ///
+    /// Use a [`FixedClock`](crate::FixedClock) to make serialized payloads
+    /// deterministic in tests.
+    pub fn clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// Attach the deployment environment, such as `"production"` or
+    /// `"staging"`, to every event as an `__environment` property, so
+    /// dashboards can filter out non-production traffic.
+    pub fn environment(mut self, environment: impl Into<String>) -> Self {
+        self.environment = Some(environment.into());
+        self
+    }
+
+    /// Only send events when the [`environment`](Self::environment) is
+    /// `"production"`; elsewhere every send is a no-op, as if
+    /// [`disabled`](Self::disabled).
+    ///
+    /// Requires an environment to be set.
+    pub fn production_only(mut self) -> Self {
+        self.production_only = true;
+        self
+    }
+
+    /// Add another Outlit project, by name, that events can be sent to from
+    /// this client.
+    ///
+    /// Events go to the project of the builder's public key unless routed
+    /// with [`route_event`](Self::route_event) or an explicit
+    /// `.project(name)` on the event. Each project has its own queue and
+    /// flush loop using this client's settings, while
+    /// [`flush`](crate::Outlit::flush),
+    /// [`shutdown`](crate::Outlit::shutdown), and
+    /// [`stats`](crate::Outlit::stats) cover all of them.
+    ///
+    /// ```rust
+    /// # use outlit::Outlit;
+    /// let builder = Outlit::builder("pk_product")
+    ///     .project("growth", "pk_growth")
+    ///     .route_event("campaign_clicked", "growth");
+    /// # let _ = builder;
+    /// ```
+    pub fn project(mut self, name: impl Into<String>, public_key: impl Into<String>) -> Self {
+        self.projects.insert(name.into(), public_key.into());
+        self
+    }
+
+    /// Send custom events named `event_name` to the project `project`,
+    /// added with [`project`](Self::project).
+    pub fn route_event(
+        mut self,
+        event_name: impl Into<String>,
+        project: impl Into<String>,
+    ) -> Self {
+        self.event_routes.insert(event_name.into(), project.into());
+        self
+    }
+
+    /// Configure the client for serverless platforms such as AWS Lambda.
+    ///
+    /// No background flush timer is spawned, since it would be frozen
+    /// between invocations. Send events with
+    /// [`Outlit::flush_after`](crate::Outlit::flush_after) around each
+    /// invocation, or call [`Outlit::flush_if_needed`](crate::Outlit::flush_if_needed)
+    /// and [`Outlit::flush`](crate::Outlit::flush) yourself. Events are still
+    /// flushed inline when the batch fills up.
+    pub fn serverless(self) -> Self {
+        self.disable_auto_flush()
+    }
+
+    /// Flush as soon as the oldest queued event has waited this long.
+    ///
+    /// Bounds event latency independently of the flush interval and batch
+    /// size, e.g. a lone event is sent after `age` rather than waiting out a
+    /// long interval. Checked by the background flush task, so it has no
+    /// effect when auto flush is disabled.
+    pub fn max_event_age(mut self, age: Duration) -> Self {
+        self.max_event_age = Some(age);
+        self
+    }
+
     /// Set the max batch size.
+    ///
+    /// Once the queue reaches 80% of this size the background task starts a
+    /// flush; `send()` only flushes inline if the queue fills up completely.
     pub fn max_batch_size(mut self, size: usize) -> Self {
         self.max_batch_size = Some(size);
         self
     }
 
+    /// Set how many events [`Outlit::import`](crate::Outlit::import) sends
+    /// per request.
+    ///
+    /// Imports bypass the queue, so this is independent of
+    /// [`max_batch_size`](Self::max_batch_size). Must be greater than zero.
+    pub fn import_batch_size(mut self, size: usize) -> Self {
+        self.import_batch_size = Some(size);
+        self
+    }
+
     /// Set the request timeout.
+    ///
+    /// This bounds each ingest request end to end, including connecting.
     pub fn timeout(mut self, timeout: Duration) -> Self {
         self.timeout = Some(timeout);
         self
     }
 
+    /// Set the timeout for establishing a connection, including the TLS
+    /// handshake.
+    ///
+    /// Lets an unreachable host fail fast without waiting out the whole
+    /// request [`timeout`](Self::timeout), which still applies overall; a
+    /// connect timeout longer than the request timeout has no effect.
+    /// Unset by default.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Send requests with an existing `reqwest::Client` instead of building
+    /// one, to share its connection pool, proxy, and TLS settings.
+    ///
+    /// The [`timeout`](Self::timeout) and [`user_agent`](Self::user_agent)
+    /// are still applied to each ingest request, but
+    /// [`connect_timeout`](Self::connect_timeout) is ignored; configure it on
+    /// the client instead.
+    pub fn http_client(mut self, client: reqwest::Client) -> Self {
+        self.http_client = Some(client);
+        self
+    }
+
+    /// Send ingest requests through an HTTP or HTTPS proxy, e.g.
+    /// `"http://proxy.internal:3128"`. Credentials may be given in the URL.
+    ///
+    /// Without this, the `HTTPS_PROXY`, `HTTP_PROXY`, `ALL_PROXY`, and
+    /// `NO_PROXY` environment variables are honored. Ignored when an
+    /// [`http_client`](Self::http_client) is set.
+    pub fn proxy(mut self, url: impl Into<String>) -> Self {
+        self.proxy = Some(url.into());
+        self
+    }
+
+    /// Set the retry policy for transient send failures.
+    ///
+    /// Retries happen within a single flush, before events are kept queued
+    /// for the next one. Defaults to [`RetryPolicy::default`]; pass
+    /// [`RetryPolicy::none`] to fail fast instead.
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(policy);
+        self
+    }
+
+    /// Pause flushing after repeated failures instead of retrying a down
+    /// ingest API on every tick. Disabled by default.
+    ///
+    /// While the circuit is open, events keep buffering, background flushes
+    /// are skipped, and [`Outlit::flush`](crate::Outlit::flush) returns
+    /// [`Error::CircuitOpen`](crate::Error::CircuitOpen).
+    /// [`Outlit::shutdown`](crate::Outlit::shutdown) always makes a final
+    /// attempt.
+    pub fn circuit_breaker(mut self, circuit_breaker: CircuitBreaker) -> Self {
+        self.circuit_breaker = Some(circuit_breaker);
+        self
+    }
+
+    /// Limit how fast events are queued, protecting the ingest API from
+    /// runaway loops. Not limited by default.
+    ///
+    /// Applies to every queued event after sampling and `before_send`
+    /// hooks; [`Outlit::import`](crate::Outlit::import) is not limited.
+    ///
+    /// ```rust
+    /// # use outlit::{Outlit, RateLimit, RateLimitPolicy};
+    /// let builder = Outlit::builder("pk_xxx")
+    ///     .rate_limit(RateLimit::per_second(100).burst(500).policy(RateLimitPolicy::Throttle));
+    /// # let _ = builder;
+    /// ```
+    pub fn rate_limit(mut self, rate_limit: RateLimit) -> Self {
+        self.rate_limit = Some(rate_limit);
+        self
+    }
+
+    /// Set the probability (`0.0..=1.0`) of keeping each custom track event.
+    ///
+    /// Identify, stage, and billing events are always kept.
+    pub fn sample_rate(mut self, rate: f64) -> Self {
+        self.sample_rate = Some(rate);
+        self
+    }
+
+    /// Set the probability (`0.0..=1.0`) of keeping custom events named
+    /// `event_name`, overriding [`sample_rate`](Self::sample_rate).
+    ///
+    /// ```rust
+    /// # use outlit::Outlit;
+    /// let builder = Outlit::builder("pk_xxx")
+    ///     .sample_event("heartbeat", 0.1)
+    ///     .sample_event("purchase", 1.0);
+    /// # let _ = builder;
+    /// ```
+    pub fn sample_event(mut self, event_name: impl Into<String>, rate: f64) -> Self {
+        self.event_sample_rates.insert(event_name.into(), rate);
+        self
+    }
+
+    /// Sample by hashing the event name with the user's identity instead of
+    /// at random, so a given user's events of one name are either all kept
+    /// or all dropped (default: false).
+    ///
+    /// The identity is the user ID, else the email, else the fingerprint.
+    /// Events without one are sampled at random.
+    pub fn deterministic_sampling(mut self, deterministic: bool) -> Self {
+        self.deterministic_sampling = deterministic;
+        self
+    }
+
+    /// Set how many recent idempotency keys are remembered.
+    ///
+    /// An event whose idempotency key was seen within this window is
+    /// skipped. Zero disables deduplication.
+    pub fn dedup_window(mut self, size: usize) -> Self {
+        self.dedup_window = Some(size);
+        self
+    }
+
+    /// Skip events identical to one queued within `window`. Disabled by
+    /// default.
+    ///
+    /// Events are identical if they match in type, name, identity, and
+    /// properties, ignoring timestamps and IDs, so retried jobs that emit
+    /// the same business event twice are only counted once. Applies to
+    /// queued events, after `before_send` hooks; imports are not
+    /// deduplicated.
+    pub fn dedup_identical(mut self, window: Duration) -> Self {
+        self.dedup_identical = Some(window);
+        self
+    }
+
+    /// Split batches the API rejects with `400 Bad Request` to isolate the
+    /// offending events.
+    ///
+    /// Without this, a single malformed event blocks the queue: the whole
+    /// batch is kept and fails on every flush. With it, a rejected batch is
+    /// halved and each half retried, up to `depth` times, so a batch of up to
+    /// `2^depth` events can be narrowed down to single events. Events still
+    /// rejected at the maximum depth are dropped, logged, and reported in
+    /// [`FlushReport::rejected`](crate::FlushReport::rejected). Zero (the
+    /// default) disables splitting.
+    pub fn max_bisect_depth(mut self, depth: usize) -> Self {
+        self.max_bisect_depth = depth;
+        self
+    }
+
+    /// Split flushes into requests whose JSON body stays within `bytes`.
+    ///
+    /// Batches are measured by their serialized size rather than event
+    /// count, so events with large property maps don't push a request over
+    /// the server's body limit and fail with `413 Payload Too Large`. An
+    /// event too large to send on its own is dropped and reported in
+    /// [`FlushReport::quarantined`](crate::FlushReport::quarantined). By
+    /// default each flush is sent as a single request.
+    pub fn max_payload_bytes(mut self, bytes: usize) -> Self {
+        self.max_payload_bytes = Some(bytes);
+        self
+    }
+
+    /// Send up to `requests` of a flush's requests in parallel.
+    ///
+    /// Applies when a flush is split into several requests by
+    /// [`max_payload_bytes`](Self::max_payload_bytes), e.g. when draining a
+    /// backlog after downtime. Batches may then arrive out of order. Defaults
+    /// to 1, sending one request at a time.
+    pub fn flush_concurrency(mut self, requests: usize) -> Self {
+        self.flush_concurrency = Some(requests);
+        self
+    }
+
+    /// Truncate string property and trait values longer than `bytes`,
+    /// including strings nested in objects and arrays.
+    ///
+    /// Truncated events get a `__truncated` property listing the affected
+    /// keys. Applies to queued events, after `before_send` hooks; events
+    /// passed to [`Outlit::import`](crate::Outlit::import) are sent as
+    /// given.
+    pub fn max_property_bytes(mut self, bytes: usize) -> Self {
+        self.max_property_bytes = Some(bytes);
+        self
+    }
+
+    /// Reject events whose serialized size is over `bytes`, after any
+    /// truncation by [`max_property_bytes`](Self::max_property_bytes).
+    ///
+    /// `send()` and `track_batch()` then return [`Error::Validation`]
+    /// instead of queueing an event that would fail its whole batch.
+    ///
+    /// [`Error::Validation`]: crate::Error::Validation
+    pub fn max_event_bytes(mut self, bytes: usize) -> Self {
+        self.max_event_bytes = Some(bytes);
+        self
+    }
+
+    /// Reject events whose email identity isn't shaped like an address,
+    /// such as `"N/A"` or `"unknown"` (default: false).
+    ///
+    /// Emails are always trimmed and lowercased. With strict identity,
+    /// `send()` and `track_batch()` also return [`Error::InvalidIdentity`]
+    /// for malformed ones; otherwise they are sent as given.
+    ///
+    /// [`Error::InvalidIdentity`]: crate::Error::InvalidIdentity
+    pub fn strict_identity(mut self, strict: bool) -> Self {
+        self.strict_identity = strict;
+        self
+    }
+
+    /// Check custom event names against `policy` before queueing. Not
+    /// enforced by default.
+    ///
+    /// `send()` and `track_batch()` return [`Error::Validation`] for names
+    /// that break the policy, so typos are caught at the call site instead
+    /// of as per-event ingest errors. The check runs after `before_send`
+    /// hooks, which may rename events.
+    ///
+    /// [`Error::Validation`]: crate::Error::Validation
+    pub fn event_name_policy(mut self, policy: EventNamePolicy) -> Self {
+        self.event_name_policy = Some(policy);
+        self
+    }
+
+    /// Check event timestamps against `policy` before queueing. Not
+    /// enforced by default.
+    ///
+    /// Depending on the policy's [`TimestampAction`], out-of-range
+    /// timestamps are clamped or make `send()` and `track_batch()` return
+    /// [`Error::Validation`]. Imports are not checked.
+    ///
+    /// ```rust
+    /// # use outlit::{Outlit, TimestampPolicy};
+    /// let builder = Outlit::builder("pk_xxx").timestamp_policy(TimestampPolicy::default());
+    /// # let _ = builder;
+    /// ```
+    ///
+    /// [`Error::Validation`]: crate::Error::Validation
+    pub fn timestamp_policy(mut self, policy: TimestampPolicy) -> Self {
+        self.timestamp_policy = Some(policy);
+        self
+    }
+
+    /// Drop events the user hasn't consented to, per `policy`.
+    ///
+    /// Record users' decisions with
+    /// [`Outlit::set_consent`](crate::Outlit::set_consent). Without a
+    /// policy, `.consent()` only tags events. Events not about a user, such
+    /// as billing events, are always sent.
+    ///
+    /// ```rust
+    /// # use outlit::{Outlit, ConsentPolicy};
+    /// let builder = Outlit::builder("pk_xxx").consent_policy(ConsentPolicy::opt_in());
+    /// # let _ = builder;
+    /// ```
+    pub fn consent_policy(mut self, policy: ConsentPolicy) -> Self {
+        self.consent_policy = Some(policy);
+        self
+    }
+
+    /// Only send properties and traits with these keys, dropping all
+    /// others. Can be called repeatedly to extend the list.
+    ///
+    /// Applies to top-level keys of every queued or imported event, after
+    /// `before_send` hooks. Identity properties set by the SDK are always
+    /// sent.
+    pub fn allow_properties<I, K>(mut self, keys: I) -> Self
+    where
+        I: IntoIterator<Item = K>,
+        K: Into<String>,
+    {
+        self.property_filter
+            .allow
+            .get_or_insert_with(HashSet::new)
+            .extend(keys.into_iter().map(Into::into));
+        self
+    }
+
+    /// Never send properties or traits with these keys. Takes precedence
+    /// over [`allow_properties`](Self::allow_properties).
+    pub fn deny_properties<I, K>(mut self, keys: I) -> Self
+    where
+        I: IntoIterator<Item = K>,
+        K: Into<String>,
+    {
+        self.property_filter
+            .deny
+            .extend(keys.into_iter().map(Into::into));
+        self
+    }
+
+    /// Scrub PII from properties and traits, and optionally hash identity
+    /// emails, before events are queued or imported.
+    ///
+    /// Runs after `before_send` hooks and the
+    /// [`strict_identity`](Self::strict_identity) check.
+    #[cfg(feature = "redact")]
+    pub fn redactor(mut self, redactor: crate::Redactor) -> Self {
+        self.redactor = Some(redactor);
+        self
+    }
+
+    /// Queue events again when the API accepts a batch but reports some of
+    /// its events as failed.
+    ///
+    /// By default such events are logged and returned in
+    /// [`FlushReport::rejected`](crate::FlushReport::rejected) but not
+    /// retried. With this enabled only the failed events are requeued, ahead
+    /// of newer events, and retried on the next flush. Events the API rejects
+    /// permanently will then be retried on every flush, unless limited with
+    /// [`max_delivery_attempts`](Self::max_delivery_attempts).
+    pub fn requeue_rejected(mut self, enabled: bool) -> Self {
+        self.requeue_rejected = enabled;
+        self
+    }
+
+    /// Drop an event after `attempts` failed deliveries instead of keeping
+    /// it queued.
+    ///
+    /// Each flush that leaves an event queued counts as an attempt: a failed
+    /// request, or a rejection requeued by
+    /// [`requeue_rejected`](Self::requeue_rejected). Retries within a single
+    /// flush under the [`RetryPolicy`] count once. Dropped events are
+    /// logged and handed to the [`dead_letter`](Self::dead_letter) sink.
+    /// By default events are kept until they are delivered, so a permanently
+    /// rejected event is retried on every flush.
+    pub fn max_delivery_attempts(mut self, attempts: u32) -> Self {
+        self.max_delivery_attempts = Some(attempts);
+        self
+    }
+
+    /// Log the full JSON request and response bodies at `trace` level.
+    ///
+    /// Useful for debugging payload-shape mismatches. Bodies contain user
+    /// identifiers and properties, so this is off by default and should not
+    /// be left on in production.
+    pub fn log_bodies(mut self, enabled: bool) -> Self {
+        self.log_bodies = enabled;
+        self
+    }
+
+    /// Attach the active trace to events and ingest requests, so analytics
+    /// events can be correlated with distributed traces.
+    ///
+    /// When enabled, track, page view, and stage events recorded inside a
+    /// span get `trace_id` and `span_id` properties, and ingest requests sent
+    /// inside a span carry a W3C `traceparent` header. The span is taken from
+    /// the current `tracing` span via `tracing-opentelemetry`, falling back to
+    /// the current OpenTelemetry context. Background flushes run outside any
+    /// span and send no header.
+    #[cfg(feature = "opentelemetry")]
+    pub fn trace_context(mut self, enabled: bool) -> Self {
+        self.trace_context = enabled;
+        self
+    }
+
+    /// Add a property merged into every track, page view, and stage event,
+    /// such as the app version.
+    ///
+    /// Properties set on the event, or with
+    /// [`Outlit::set_context`](crate::Outlit::set_context), take precedence.
+    /// Keys starting with `__` are reserved and are sent under a `user.`
+    /// namespace.
+    pub fn default_property(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+        insert_property(&mut self.default_properties, key.into(), value.into());
+        self
+    }
+
+    /// Set the JSON field-name casing for event payloads.
+    ///
+    /// Defaults to [`FieldCase::Camel`], which the hosted API expects. Only
+    /// event fields are renamed; property and trait keys are sent as given.
+    pub fn field_case(mut self, case: FieldCase) -> Self {
+        self.field_case = case;
+        self
+    }
+
+    /// Persist queued events to a file so they survive a crash or restart.
+    ///
+    /// Events are appended on enqueue and removed once a send is confirmed.
+    /// Unsent events from a previous run are loaded when the client is built.
+    ///
+    /// Writes happen on a background thread, so enqueueing never waits on
+    /// the disk; [`shutdown`](crate::Outlit::shutdown) waits for them to
+    /// finish.
+    #[cfg(feature = "persist")]
+    pub fn persist_path(mut self, path: impl Into<std::path::PathBuf>) -> Self {
+        self.persist_path = Some(path.into());
+        #[cfg(feature = "sqlite")]
+        {
+            self.persist_sqlite = false;
+        }
+        self
+    }
+
+    /// Persist queued events to an SQLite database instead of a file.
+    ///
+    /// Like [`persist_path`](Self::persist_path), but each batch of appends
+    /// and of confirmed sends is a single transaction. A flush cut short by a lost connection or a crash
+    /// resumes from the first undelivered event, which suits long-running
+    /// daemons on devices with flaky connectivity.
+    #[cfg(feature = "sqlite")]
+    pub fn persist_sqlite(mut self, path: impl Into<std::path::PathBuf>) -> Self {
+        self.persist_path = Some(path.into());
+        self.persist_sqlite = true;
+        self
+    }
+
+    /// Add a hook that runs on every event before it is queued.
+    ///
+    /// Hooks may mutate the event (e.g. to redact or enrich properties) and
+    /// return `false` to drop it. Multiple hooks run in the order they were
+    /// added; later hooks don't see events dropped by earlier ones. Context
+    /// and default properties are merged before the hooks run, so hooks can
+    /// filter on them.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use outlit::{Outlit, TrackerEvent};
+    ///
+    /// let builder = Outlit::builder("pk_xxx")
+    ///     // Scrub internal fields
+    ///     .before_send(|event| {
+    ///         if let TrackerEvent::Custom(data) = event {
+    ///             if let Some(props) = data.properties.as_mut() {
+    ///                 props.remove("ssn");
+    ///             }
+    ///         }
+    ///         true
+    ///     })
+    ///     // Suppress events from staging tenants
+    ///     .before_send(|event| match event {
+    ///         TrackerEvent::Custom(data) => data
+    ///             .properties
+    ///             .as_ref()
+    ///             .and_then(|props| props.get("tenant"))
+    ///             .and_then(|tenant| tenant.as_str())
+    ///             .map_or(true, |tenant| !tenant.starts_with("staging-")),
+    ///         _ => true,
+    ///     });
+    /// # let _ = builder;
+    /// ```
+    pub fn before_send(
+        mut self,
+        hook: impl Fn(&mut TrackerEvent) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.before_send.0.push(Arc::new(hook));
+        self
+    }
+
+    /// Add a callback run after each flush that sends events, including
+    /// background and size-triggered flushes.
+    ///
+    /// Callbacks run on the flushing task, so they should be quick, e.g.
+    /// incrementing a metric.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use outlit::Outlit;
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    /// use std::sync::Arc;
+    ///
+    /// let delivered = Arc::new(AtomicUsize::new(0));
+    /// let counter = delivered.clone();
+    /// let builder = Outlit::builder("pk_xxx").on_flush_success(move |report| {
+    ///     counter.fetch_add(report.sent, Ordering::Relaxed);
+    /// });
+    /// # let _ = builder;
+    /// ```
+    pub fn on_flush_success(
+        mut self,
+        callback: impl Fn(&FlushReport) + Send + Sync + 'static,
+    ) -> Self {
+        self.flush_callbacks.on_success.push(Arc::new(callback));
+        self
+    }
+
+    /// Add a callback run after each failed flush, including background and
+    /// size-triggered flushes.
+    ///
+    /// The failed events stay queued and are retried on the next flush, so a
+    /// run of failures means delivery is stuck. Callbacks run on the flushing
+    /// task and should be quick.
+    pub fn on_flush_failure(
+        mut self,
+        callback: impl Fn(&crate::Error) + Send + Sync + 'static,
+    ) -> Self {
+        self.flush_callbacks.on_failure.push(Arc::new(callback));
+        self
+    }
+
+    /// Hand events that can't be delivered to `sink` instead of dropping
+    /// them, so they can be replayed later.
+    ///
+    /// Covers events the API rejects (unless
+    /// [`requeue_rejected`](Self::requeue_rejected) is on) and events that
+    /// fail to serialize. The sink can be a closure, a Tokio `mpsc` sender,
+    /// or a [`DeadLetterFile`](crate::DeadLetterFile).
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use outlit::{DeadLetterFile, Outlit};
+    ///
+    /// # fn example() -> Result<(), outlit::Error> {
+    /// let builder = Outlit::builder("pk_xxx")
+    ///     .dead_letter(DeadLetterFile::open("outlit-dead-letters.jsonl")?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn dead_letter(mut self, sink: impl DeadLetterSink + 'static) -> Self {
+        self.dead_letters = DeadLetters::new(Arc::new(sink));
+        self
+    }
+
     /// Build the configuration.
     pub(crate) fn build_config(self) -> Result<Config, crate::Error> {
         if self.public_key.trim().is_empty() {
             return Err(crate::Error::Config("public_key cannot be empty".into()));
         }
 
-        if let Some(ref host) = self.api_host {
-            if host.trim().is_empty() {
-                return Err(crate::Error::Config("api_host cannot be empty".into()));
+        if let Some(SecretKey(key)) = &self.secret_key {
+            if !key.starts_with("sk_") || key.len() == 3 {
+                return Err(crate::Error::Config(
+                    "secret_key must be a secret server key starting with \"sk_\"".into(),
+                ));
+            }
+        }
+
+        let api_host = match self.api_host {
+            Some(ref host) => normalize_api_host("api_host", host)?,
+            None => DEFAULT_API_HOST.into(),
+        };
+
+        let endpoint_path = self
+            .endpoint_path
+            .unwrap_or_else(|| DEFAULT_ENDPOINT_PATH.into());
+        if !endpoint_path.contains("{public_key}") {
+            return Err(crate::Error::Config(
+                "endpoint_path must contain a {public_key} placeholder".into(),
+            ));
+        }
+
+        let sample_rate = self.sample_rate.unwrap_or(DEFAULT_SAMPLE_RATE);
+        if !(0.0..=1.0).contains(&sample_rate) {
+            return Err(crate::Error::Config(
+                "sample_rate must be between 0.0 and 1.0".into(),
+            ));
+        }
+        if let Some(event_name) = self
+            .event_sample_rates
+            .iter()
+            .find(|(_, rate)| !(0.0..=1.0).contains(*rate))
+            .map(|(name, _)| name)
+        {
+            return Err(crate::Error::Config(format!(
+                "sample rate for {event_name:?} must be between 0.0 and 1.0"
+            )));
+        }
+
+        let import_batch_size = self.import_batch_size.unwrap_or(DEFAULT_IMPORT_BATCH_SIZE);
+        if import_batch_size == 0 {
+            return Err(crate::Error::Config(
+                "import_batch_size must be greater than zero".into(),
+            ));
+        }
+
+        let mut headers = reqwest::header::HeaderMap::new();
+        for (name, value) in &self.headers {
+            let header_name = reqwest::header::HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| crate::Error::Config(format!("invalid header name {name:?}")))?;
+            let header_value = reqwest::header::HeaderValue::from_str(value)
+                .map_err(|_| crate::Error::Config(format!("invalid value for header {name:?}")))?;
+            headers.insert(header_name, header_value);
+        }
+
+        if let Some(proxy) = &self.proxy {
+            reqwest::Proxy::all(proxy.as_str()).map_err(|e| {
+                crate::Error::Config(format!("proxy {proxy:?} is not a valid URL ({e})"))
+            })?;
+        }
+
+        if self
+            .circuit_breaker
+            .is_some_and(|breaker| breaker.failure_threshold == 0)
+        {
+            return Err(crate::Error::Config(
+                "circuit_breaker failure_threshold must be greater than zero".into(),
+            ));
+        }
+
+        if let Some(rate_limit) = self.rate_limit {
+            if !(rate_limit.events_per_second.is_finite() && rate_limit.events_per_second > 0.0) {
+                return Err(crate::Error::Config(
+                    "rate_limit events_per_second must be greater than zero".into(),
+                ));
+            }
+            if rate_limit.burst == 0 {
+                return Err(crate::Error::Config(
+                    "rate_limit burst must be greater than zero".into(),
+                ));
+            }
+        }
+
+        if let Some(policy) = &self.event_name_policy {
+            if policy.max_length == 0 {
+                return Err(crate::Error::Config(
+                    "event_name_policy max_length must be greater than zero".into(),
+                ));
             }
         }
 
+        match self.environment.as_deref() {
+            Some(environment) if environment.trim().is_empty() => {
+                return Err(crate::Error::Config("environment cannot be empty".into()));
+            }
+            None if self.production_only => {
+                return Err(crate::Error::Config(
+                    "production_only requires an environment".into(),
+                ));
+            }
+            _ => {}
+        }
+
+        for (name, public_key) in &self.projects {
+            if name.trim().is_empty() || public_key.trim().is_empty() {
+                return Err(crate::Error::Config(
+                    "project name and public_key cannot be empty".into(),
+                ));
+            }
+        }
+        if let Some((event_name, project)) = self
+            .event_routes
+            .iter()
+            .find(|(_, project)| !self.projects.contains_key(*project))
+        {
+            return Err(crate::Error::Config(format!(
+                "event {event_name:?} is routed to unknown project {project:?}"
+            )));
+        }
+
+        if self.dedup_identical == Some(Duration::ZERO) {
+            return Err(crate::Error::Config(
+                "dedup_identical window must be greater than zero".into(),
+            ));
+        }
+
+        if self.max_payload_bytes == Some(0) {
+            return Err(crate::Error::Config(
+                "max_payload_bytes must be greater than zero".into(),
+            ));
+        }
+
+        if self.flush_concurrency == Some(0) {
+            return Err(crate::Error::Config(
+                "flush_concurrency must be greater than zero".into(),
+            ));
+        }
+
+        if self.max_delivery_attempts == Some(0) {
+            return Err(crate::Error::Config(
+                "max_delivery_attempts must be greater than zero".into(),
+            ));
+        }
+
+        let flush_interval = self.flush_interval.unwrap_or(DEFAULT_FLUSH_INTERVAL);
+
         Ok(Config {
             public_key: self.public_key,
-            api_host: self.api_host.unwrap_or_else(|| DEFAULT_API_HOST.into()),
-            flush_interval: self.flush_interval.unwrap_or(DEFAULT_FLUSH_INTERVAL),
+            secret_key: self.secret_key,
+            api_host,
+            fallback_api_host: self
+                .fallback_api_host
+                .as_deref()
+                .map(|host| normalize_api_host("fallback_api_host", host))
+                .transpose()?,
+            endpoint_path,
+            user_agent: self.user_agent.unwrap_or_else(|| DEFAULT_USER_AGENT.into()),
+            headers,
+            flush_interval,
+            auto_flush: self.auto_flush && !flush_interval.is_zero(),
+            enabled: self.enabled,
+            clock: self.clock,
+            environment: self.environment,
+            production_only: self.production_only,
+            projects: self.projects,
+            event_routes: self.event_routes,
+            max_event_age: self.max_event_age,
             max_batch_size: self.max_batch_size.unwrap_or(DEFAULT_MAX_BATCH_SIZE),
+            import_batch_size,
             timeout: self.timeout.unwrap_or(DEFAULT_TIMEOUT),
+            connect_timeout: self.connect_timeout,
+            http_client: self.http_client,
+            proxy: self.proxy,
+            retry_policy: self.retry_policy.unwrap_or_default(),
+            circuit_breaker: self.circuit_breaker,
+            rate_limit: self.rate_limit,
+            sample_rate,
+            event_sample_rates: self.event_sample_rates,
+            deterministic_sampling: self.deterministic_sampling,
+            dedup_window: self.dedup_window.unwrap_or(DEFAULT_DEDUP_WINDOW),
+            dedup_identical: self.dedup_identical,
+            max_bisect_depth: self.max_bisect_depth,
+            max_payload_bytes: self.max_payload_bytes,
+            flush_concurrency: self.flush_concurrency.unwrap_or(DEFAULT_FLUSH_CONCURRENCY),
+            max_property_bytes: self.max_property_bytes,
+            max_event_bytes: self.max_event_bytes,
+            strict_identity: self.strict_identity,
+            event_name_policy: self.event_name_policy,
+            timestamp_policy: self.timestamp_policy,
+            consent_policy: self.consent_policy,
+            property_filter: self.property_filter,
+            #[cfg(feature = "redact")]
+            redactor: self.redactor,
+            requeue_rejected: self.requeue_rejected,
+            max_delivery_attempts: self.max_delivery_attempts,
+            log_bodies: self.log_bodies,
+            #[cfg(feature = "opentelemetry")]
+            trace_context: self.trace_context,
+            default_properties: self.default_properties,
+            field_case: self.field_case,
+            #[cfg(feature = "persist")]
+            persist_path: self.persist_path,
+            #[cfg(feature = "sqlite")]
+            persist_sqlite: self.persist_sqlite,
+            before_send: self.before_send,
+            flush_callbacks: self.flush_callbacks,
+            dead_letters: self.dead_letters,
         })
     }
 }
 
+/// Validate that `host` is an absolute http(s) URL and strip trailing
+/// slashes so it can be joined with the endpoint path. `field` names the
+/// setting in error messages.
+fn normalize_api_host(field: &str, host: &str) -> Result<String, crate::Error> {
+    let host = host.trim();
+    if host.is_empty() {
+        return Err(crate::Error::Config(format!("{field} cannot be empty")));
+    }
+
+    let url = reqwest::Url::parse(host).map_err(|e| {
+        crate::Error::Config(format!(
+            "{field} {host:?} is not a valid URL ({e}); expected e.g. \"https://app.outlit.ai\""
+        ))
+    })?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(crate::Error::Config(format!(
+            "{field} {host:?} must use http or https, not {:?}",
+            url.scheme()
+        )));
+    }
+    if url.host_str().map_or(true, str::is_empty) {
+        return Err(crate::Error::Config(format!(
+            "{field} {host:?} is missing a host name"
+        )));
+    }
+
+    Ok(host.trim_end_matches('/').to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -129,9 +1898,24 @@ mod tests {
 
         assert_eq!(config.public_key(), "pk_test");
         assert_eq!(config.api_host(), DEFAULT_API_HOST);
+        assert_eq!(config.fallback_api_host(), None);
+        assert_eq!(config.endpoint_path(), DEFAULT_ENDPOINT_PATH);
+        assert_eq!(config.user_agent(), DEFAULT_USER_AGENT);
         assert_eq!(config.flush_interval(), DEFAULT_FLUSH_INTERVAL);
+        assert!(config.auto_flush());
+        assert!(config.enabled());
+        assert_eq!(config.max_event_age(), None);
         assert_eq!(config.max_batch_size(), DEFAULT_MAX_BATCH_SIZE);
+        assert_eq!(config.import_batch_size(), DEFAULT_IMPORT_BATCH_SIZE);
         assert_eq!(config.timeout(), DEFAULT_TIMEOUT);
+        assert_eq!(config.connect_timeout(), None);
+        assert_eq!(config.retry_policy(), RetryPolicy::default());
+        assert_eq!(config.sample_rate(), DEFAULT_SAMPLE_RATE);
+        assert_eq!(config.dedup_window(), DEFAULT_DEDUP_WINDOW);
+        assert_eq!(config.max_bisect_depth(), 0);
+        assert!(!config.requeue_rejected());
+        assert!(!config.log_bodies());
+        assert!(config.default_properties().is_empty());
     }
 
     #[test]
@@ -141,6 +1925,7 @@ mod tests {
             .flush_interval(Duration::from_secs(5))
             .max_batch_size(50)
             .timeout(Duration::from_secs(30))
+            .connect_timeout(Duration::from_secs(2))
             .build_config()
             .unwrap();
 
@@ -148,6 +1933,182 @@ mod tests {
         assert_eq!(config.flush_interval(), Duration::from_secs(5));
         assert_eq!(config.max_batch_size(), 50);
         assert_eq!(config.timeout(), Duration::from_secs(30));
+        assert_eq!(config.connect_timeout(), Some(Duration::from_secs(2)));
+    }
+
+    #[test]
+    fn test_event_name_policy() {
+        let policy = EventNamePolicy::default();
+        assert!(policy.check("checkout_started").is_ok());
+        assert!(policy.check("Button Clicked").is_ok());
+        assert!(policy.check("billing:invoice.paid").is_ok());
+        assert!(policy.check("").is_err());
+        assert!(policy.check("signup!").is_err());
+        assert!(policy.check("é").is_err());
+        assert!(policy.check(&"x".repeat(129)).is_err());
+
+        let policy = EventNamePolicy {
+            max_length: 20,
+            extra_chars: "_.".into(),
+            required_prefix: Some("app.".into()),
+        };
+        assert!(policy.check("app.signup").is_ok());
+        assert!(policy.check("signup").is_err());
+        assert!(policy.check("app.sign up").is_err());
+
+        let err = OutlitBuilder::new("pk_test")
+            .event_name_policy(EventNamePolicy {
+                max_length: 0,
+                ..EventNamePolicy::default()
+            })
+            .build_config()
+            .unwrap_err();
+        assert!(matches!(err, crate::Error::Config(ref m) if m.contains("max_length")));
+    }
+
+    #[test]
+    fn test_timestamp_policy() {
+        let now = 1_706_400_000_000;
+        let policy = TimestampPolicy::default();
+        assert_eq!(policy.check(now, now).unwrap(), now);
+        assert_eq!(policy.check(now + 60_000, now).unwrap(), now + 60_000);
+
+        // Seconds and nanoseconds instead of milliseconds
+        let err = policy.check(now / 1000, now).unwrap_err();
+        assert!(matches!(err, crate::Error::Validation(ref m) if m.contains("before the minimum")));
+        let err = policy.check(now * 1_000_000, now).unwrap_err();
+        assert!(matches!(err, crate::Error::Validation(ref m) if m.contains("in the future")));
+
+        let policy = TimestampPolicy {
+            action: TimestampAction::Clamp,
+            ..policy
+        };
+        assert_eq!(policy.check(now / 1000, now).unwrap(), policy.min);
+        assert_eq!(policy.check(i64::MAX, now).unwrap(), now + 86_400_000);
+    }
+
+    #[test]
+    fn test_builder_sample_rate_out_of_range_fails() {
+        for rate in [-0.1, 1.1, f64::NAN] {
+            let result = OutlitBuilder::new("pk_test")
+                .sample_rate(rate)
+                .build_config();
+            assert!(result.is_err(), "rate {rate} should be rejected");
+        }
+    }
+
+    #[test]
+    fn test_builder_event_sample_rate_out_of_range_fails() {
+        let result = OutlitBuilder::new("pk_test")
+            .sample_event("heartbeat", 1.5)
+            .build_config();
+        assert!(matches!(result, Err(crate::Error::Config(ref m)) if m.contains("heartbeat")));
+    }
+
+    #[test]
+    fn test_builder_zero_import_batch_size_fails() {
+        let result = OutlitBuilder::new("pk_test")
+            .import_batch_size(0)
+            .build_config();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_builder_disable_auto_flush() {
+        let config = OutlitBuilder::new("pk_test")
+            .disable_auto_flush()
+            .build_config()
+            .unwrap();
+        assert!(!config.auto_flush());
+    }
+
+    #[test]
+    fn test_builder_zero_flush_interval_disables_auto_flush() {
+        let config = OutlitBuilder::new("pk_test")
+            .flush_interval(Duration::ZERO)
+            .build_config()
+            .unwrap();
+        assert!(!config.auto_flush());
+    }
+
+    #[test]
+    fn test_before_send_hooks_stop_at_first_drop() {
+        use crate::types::{CustomEventData, TrackerEvent};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counted = calls.clone();
+        let config = OutlitBuilder::new("pk_test")
+            .before_send(|_| false)
+            .before_send(move |_| {
+                counted.fetch_add(1, Ordering::SeqCst);
+                true
+            })
+            .build_config()
+            .unwrap();
+
+        let mut event = TrackerEvent::Custom(CustomEventData {
+            timestamp: 1706400000000,
+            url: "server://user@example.com".into(),
+            path: "/".into(),
+            event_name: "test".into(),
+            properties: None,
+            message_id: None,
+            event_id: None,
+        });
+
+        assert!(!config.before_send.apply(&mut event));
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn test_builder_endpoint_path_without_placeholder_fails() {
+        let result = OutlitBuilder::new("pk_test")
+            .endpoint_path("/ingest/events")
+            .build_config();
+        assert!(matches!(result, Err(crate::Error::Config(_))));
+    }
+
+    #[test]
+    fn test_builder_header_replaces_earlier_value() {
+        let config = OutlitBuilder::new("pk_test")
+            .header("X-Tenant-Id", "acme")
+            .header("x-tenant-id", "globex")
+            .build_config()
+            .unwrap();
+        assert_eq!(config.headers().len(), 1);
+        assert_eq!(config.headers()["x-tenant-id"], "globex");
+    }
+
+    #[test]
+    fn test_builder_invalid_header_fails() {
+        let result = OutlitBuilder::new("pk_test")
+            .header("X-Tenant-Id", "line\nbreak")
+            .build_config();
+        assert!(matches!(result, Err(crate::Error::Config(msg)) if msg.contains("X-Tenant-Id")));
+    }
+
+    #[test]
+    fn test_builder_secret_key_requires_sk_prefix() {
+        let result = OutlitBuilder::new("pk_test")
+            .secret_key("pk_test")
+            .build_config();
+        assert!(matches!(result, Err(crate::Error::Config(msg)) if msg.contains("sk_")));
+
+        let config = OutlitBuilder::new("pk_test")
+            .secret_key("sk_live_abc")
+            .build_config()
+            .unwrap();
+        assert!(config.signs_requests());
+        assert!(!format!("{config:?}").contains("sk_live_abc"));
+    }
+
+    #[test]
+    fn test_builder_invalid_proxy_fails() {
+        let result = OutlitBuilder::new("pk_test")
+            .proxy("not a url")
+            .build_config();
+        assert!(matches!(result, Err(crate::Error::Config(msg)) if msg.contains("proxy")));
     }
 
     #[test]
@@ -178,4 +2139,76 @@ mod tests {
         let _ = OutlitBuilder::new("pk_test").api_host("https://example.com");
         let _ = OutlitBuilder::new("pk_test").api_host(String::from("https://example.com"));
     }
+
+    #[test]
+    fn test_builder_api_host_invalid_scheme_fails() {
+        let result = OutlitBuilder::new("pk_test")
+            .api_host("ftp://example.com")
+            .build_config();
+        assert!(matches!(result, Err(crate::Error::Config(msg)) if msg.contains("http or https")));
+    }
+
+    #[test]
+    fn test_builder_api_host_bare_hostname_fails() {
+        for host in ["example.com", "not a url", "localhost:8080"] {
+            let result = OutlitBuilder::new("pk_test").api_host(host).build_config();
+            assert!(
+                matches!(result, Err(crate::Error::Config(_))),
+                "{host} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_builder_api_host_trailing_slash_normalized() {
+        let config = OutlitBuilder::new("pk_test")
+            .api_host("https://example.com//")
+            .build_config()
+            .unwrap();
+        assert_eq!(config.api_host(), "https://example.com");
+
+        let config = OutlitBuilder::new("pk_test")
+            .api_host("https://proxy.example.com/outlit/")
+            .build_config()
+            .unwrap();
+        assert_eq!(config.api_host(), "https://proxy.example.com/outlit");
+    }
+
+    #[test]
+    fn test_builder_invalid_fallback_api_host_fails() {
+        let result = OutlitBuilder::new("pk_test")
+            .fallback_api_host("backup.example.com")
+            .build_config();
+        assert!(
+            matches!(result, Err(crate::Error::Config(msg)) if msg.starts_with("fallback_api_host"))
+        );
+    }
+
+    #[test]
+    fn test_retry_delay_backs_off_and_caps() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(1),
+            jitter: false,
+        };
+        let rng = crate::sampling::Rng::with_seed(1);
+
+        assert_eq!(policy.delay(1, &rng), Duration::from_millis(100));
+        assert_eq!(policy.delay(2, &rng), Duration::from_millis(200));
+        assert_eq!(policy.delay(4, &rng), Duration::from_millis(800));
+        assert_eq!(policy.delay(5, &rng), Duration::from_secs(1));
+        assert_eq!(policy.delay(40, &rng), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_retry_delay_jitter_stays_in_range() {
+        let policy = RetryPolicy::default();
+        let rng = crate::sampling::Rng::with_seed(7);
+
+        for _ in 0..100 {
+            let delay = policy.delay(2, &rng);
+            assert!(delay >= Duration::from_millis(100) && delay <= Duration::from_millis(200));
+        }
+    }
 }
@@ -0,0 +1,122 @@
+//! Users' consent decisions, checked against the
+//! [`ConsentPolicy`](crate::ConsentPolicy).
+
+use crate::config::ConsentPolicy;
+use crate::types::{Consent, Identifiers, TrackerEvent};
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+/// Consent recorded for each email, user ID, and fingerprint.
+#[derive(Debug, Default)]
+pub struct Consents {
+    granted: RwLock<Granted>,
+}
+
+#[derive(Debug, Default)]
+struct Granted {
+    emails: HashMap<String, Vec<Consent>>,
+    user_ids: HashMap<String, Vec<Consent>>,
+    fingerprints: HashMap<String, Vec<Consent>>,
+}
+
+impl Consents {
+    /// Record what the user with `identifiers` agreed to, replacing any
+    /// earlier decision. `None` forgets the decision.
+    pub fn set(&self, identifiers: &Identifiers, consent: Option<Vec<Consent>>) {
+        let mut granted = self.granted.write().unwrap_or_else(|e| e.into_inner());
+        let granted = &mut *granted;
+        for (map, id) in [
+            (&mut granted.emails, &identifiers.email),
+            (&mut granted.user_ids, &identifiers.user_id),
+            (&mut granted.fingerprints, &identifiers.fingerprint),
+        ] {
+            let Some(id) = id else { continue };
+            match &consent {
+                Some(consent) => map.insert(id.clone(), consent.clone()),
+                None => map.remove(id),
+            };
+        }
+    }
+
+    /// The recorded decision for `identifiers`, checked by user ID, then
+    /// email, then fingerprint.
+    fn get(&self, identifiers: &Identifiers) -> Option<Vec<Consent>> {
+        let granted = self.granted.read().unwrap_or_else(|e| e.into_inner());
+        let lookup = |map: &HashMap<String, Vec<Consent>>, id: &Option<String>| {
+            id.as_ref().and_then(|id| map.get(id)).cloned()
+        };
+        lookup(&granted.user_ids, &identifiers.user_id)
+            .or_else(|| lookup(&granted.emails, &identifiers.email))
+            .or_else(|| lookup(&granted.fingerprints, &identifiers.fingerprint))
+    }
+
+    /// Whether `event` may be sent under `policy`.
+    pub fn allows(&self, policy: &ConsentPolicy, event: &TrackerEvent) -> bool {
+        let required = event.consent().unwrap_or(policy.unmarked);
+        if required == Consent::Necessary {
+            return true;
+        }
+        let Some(identifiers) = event.identity() else {
+            return true;
+        };
+        match self.get(&identifiers) {
+            Some(granted) => granted.contains(&required),
+            None => policy.assumed.contains(&required),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builders::{BuildEvent, TrackBuilder};
+    use crate::{email, user_id};
+
+    fn identifiers(email: &str) -> Identifiers {
+        Identifiers {
+            email: Some(email.into()),
+            user_id: None,
+            fingerprint: None,
+        }
+    }
+
+    #[test]
+    fn test_opt_in_requires_recorded_consent() {
+        let consents = Consents::default();
+        let policy = ConsentPolicy::opt_in();
+        let unmarked = TrackBuilder::new("viewed", email("user@example.com")).build();
+        let necessary = TrackBuilder::new("login", email("user@example.com"))
+            .consent(Consent::Necessary)
+            .build();
+        let marketing = TrackBuilder::new("ad_click", email("user@example.com"))
+            .consent(Consent::Marketing)
+            .build();
+
+        assert!(!consents.allows(&policy, &unmarked));
+        assert!(consents.allows(&policy, &necessary));
+
+        consents.set(
+            &identifiers("user@example.com"),
+            Some(vec![Consent::Analytics]),
+        );
+        assert!(consents.allows(&policy, &unmarked));
+        assert!(!consents.allows(&policy, &marketing));
+
+        consents.set(&identifiers("user@example.com"), None);
+        assert!(!consents.allows(&policy, &unmarked));
+    }
+
+    #[test]
+    fn test_opt_out_until_declined() {
+        let consents = Consents::default();
+        let policy = ConsentPolicy::opt_out();
+        let event = TrackBuilder::new("viewed", user_id("usr_1"))
+            .email("user@example.com")
+            .build();
+        assert!(consents.allows(&policy, &event));
+
+        // A decision under either identifier applies
+        consents.set(&identifiers("user@example.com"), Some(vec![]));
+        assert!(!consents.allows(&policy, &event));
+    }
+}
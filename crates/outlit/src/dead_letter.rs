@@ -0,0 +1,241 @@
+//! Dead-letter handling for events that can't be delivered.
+
+use crate::types::{FlushReport, TrackerEvent};
+use crate::Error;
+use serde::Serialize;
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tracing::{error, warn};
+
+/// An event that could not be delivered, handed to a [`DeadLetterSink`].
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "serde-deserialize", derive(serde::Deserialize))]
+pub struct DeadLetter {
+    /// Why the event was given up on.
+    pub reason: DeadLetterReason,
+    /// Details, such as the API's error message.
+    pub message: String,
+    /// The undelivered event, which can be replayed with
+    /// [`Outlit::track_batch`](crate::Outlit::track_batch).
+    pub event: TrackerEvent,
+}
+
+/// Why an event was dead-lettered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "serde-deserialize", derive(serde::Deserialize))]
+#[serde(rename_all = "snake_case")]
+pub enum DeadLetterReason {
+    /// The API rejected the event.
+    Rejected,
+    /// The event failed to serialize, or was too large to send.
+    Unserializable,
+    /// The event reached the maximum number of delivery attempts set with
+    /// [`OutlitBuilder::max_delivery_attempts`](crate::OutlitBuilder::max_delivery_attempts).
+    RetriesExhausted,
+    /// The event was still queued when
+    /// [`Outlit::shutdown_with_timeout`](crate::Outlit::shutdown_with_timeout)
+    /// gave up.
+    Shutdown,
+}
+
+/// Destination for undeliverable events, set with
+/// [`OutlitBuilder::dead_letter`](crate::OutlitBuilder::dead_letter).
+///
+/// Implemented for closures, Tokio `mpsc` senders, and [`DeadLetterFile`].
+/// Called from the flushing task, so implementations should not block.
+pub trait DeadLetterSink: Send + Sync {
+    /// Receive an undeliverable event.
+    fn dead_letter(&self, letter: DeadLetter);
+}
+
+impl<F> DeadLetterSink for F
+where
+    F: Fn(DeadLetter) + Send + Sync,
+{
+    fn dead_letter(&self, letter: DeadLetter) {
+        self(letter)
+    }
+}
+
+impl DeadLetterSink for tokio::sync::mpsc::UnboundedSender<DeadLetter> {
+    fn dead_letter(&self, letter: DeadLetter) {
+        if self.send(letter).is_err() {
+            warn!("dead-letter receiver closed, dropping event");
+        }
+    }
+}
+
+impl DeadLetterSink for tokio::sync::mpsc::Sender<DeadLetter> {
+    fn dead_letter(&self, letter: DeadLetter) {
+        if let Err(e) = self.try_send(letter) {
+            warn!(error = %e, "dead-letter channel unavailable, dropping event");
+        }
+    }
+}
+
+/// Appends dead letters to a file, one JSON object per line.
+///
+/// With the `serde-deserialize` feature, each line parses back into a
+/// [`DeadLetter`] for replay.
+#[derive(Debug)]
+pub struct DeadLetterFile {
+    path: PathBuf,
+    file: Mutex<File>,
+}
+
+impl DeadLetterFile {
+    /// Open `path` for appending, creating it if needed.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        Ok(Self {
+            path,
+            file: Mutex::new(file),
+        })
+    }
+}
+
+impl DeadLetterSink for DeadLetterFile {
+    fn dead_letter(&self, letter: DeadLetter) {
+        let mut line = match serde_json::to_vec(&letter) {
+            Ok(line) => line,
+            Err(e) => {
+                error!(error = %e, "failed to serialize dead letter");
+                return;
+            }
+        };
+        line.push(b'\n');
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = file.write_all(&line) {
+            error!(error = %e, path = %self.path.display(), "failed to write dead letter");
+        }
+    }
+}
+
+/// The configured dead-letter sink, if any.
+#[derive(Clone, Default)]
+pub(crate) struct DeadLetters(Option<Arc<dyn DeadLetterSink>>);
+
+impl DeadLetters {
+    pub(crate) fn new(sink: Arc<dyn DeadLetterSink>) -> Self {
+        Self(Some(sink))
+    }
+
+    /// Hand an undeliverable event to the sink.
+    pub(crate) fn send(&self, reason: DeadLetterReason, message: String, event: TrackerEvent) {
+        if let Some(sink) = &self.0 {
+            sink.dead_letter(DeadLetter {
+                reason,
+                message,
+                event,
+            });
+        }
+    }
+
+    /// Hand the events a flush gave up on to the sink: quarantined events,
+    /// and rejected ones unless they were queued again.
+    pub(crate) fn report(&self, report: &FlushReport, requeue_rejected: bool) {
+        if self.0.is_none() {
+            return;
+        }
+        let rejected = report
+            .rejected
+            .iter()
+            .filter(|_| !requeue_rejected)
+            .map(|r| (DeadLetterReason::Rejected, r));
+        let quarantined = report
+            .quarantined
+            .iter()
+            .map(|r| (DeadLetterReason::Unserializable, r));
+        for (reason, rejected) in rejected.chain(quarantined) {
+            if let Some(event) = &rejected.event {
+                self.send(reason, rejected.message.clone(), event.clone());
+            }
+        }
+    }
+}
+
+impl fmt::Debug for DeadLetters {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(if self.0.is_some() {
+            "DeadLetters(Some(..))"
+        } else {
+            "DeadLetters(None)"
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{CustomEventData, RejectedEvent};
+
+    fn event() -> TrackerEvent {
+        TrackerEvent::Custom(CustomEventData {
+            timestamp: 1706400000000,
+            url: "server://user@example.com".into(),
+            path: "/".into(),
+            event_name: "test".into(),
+            properties: None,
+            message_id: None,
+            event_id: None,
+        })
+    }
+
+    fn rejected(message: &str) -> RejectedEvent {
+        RejectedEvent {
+            index: 0,
+            message: message.into(),
+            event: Some(event()),
+        }
+    }
+
+    #[test]
+    fn test_report_skips_requeued_rejections() {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let dead_letters = DeadLetters::new(Arc::new(tx));
+        let report = FlushReport {
+            sent: 2,
+            processed: 0,
+            rejected: vec![rejected("invalid")],
+            quarantined: vec![rejected("bad float")],
+        };
+
+        dead_letters.report(&report, false);
+        assert_eq!(rx.try_recv().unwrap().reason, DeadLetterReason::Rejected);
+        assert_eq!(
+            rx.try_recv().unwrap().reason,
+            DeadLetterReason::Unserializable
+        );
+
+        dead_letters.report(&report, true);
+        assert_eq!(
+            rx.try_recv().unwrap().reason,
+            DeadLetterReason::Unserializable
+        );
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_file_sink_writes_json_lines() {
+        let path =
+            std::env::temp_dir().join(format!("outlit-dead-letter-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let sink = DeadLetterFile::open(&path).unwrap();
+        sink.dead_letter(DeadLetter {
+            reason: DeadLetterReason::Rejected,
+            message: "invalid".into(),
+            event: event(),
+        });
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let line: serde_json::Value = serde_json::from_str(contents.trim_end()).unwrap();
+        assert_eq!(line["reason"], "rejected");
+        assert_eq!(line["event"]["eventName"], "test");
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,267 @@
+//! Deduplication of events by idempotency key or content.
+
+use crate::types::TrackerEvent;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Remembers the most recently seen idempotency keys.
+///
+/// Once `capacity` keys have been seen, the oldest key is forgotten.
+#[derive(Debug)]
+pub struct Deduplicator {
+    capacity: usize,
+    seen: Mutex<SeenKeys>,
+}
+
+#[derive(Debug, Default)]
+struct SeenKeys {
+    keys: HashSet<String>,
+    order: VecDeque<String>,
+}
+
+impl Deduplicator {
+    /// Create a deduplicator remembering up to `capacity` keys.
+    ///
+    /// A capacity of zero disables deduplication.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            seen: Mutex::new(SeenKeys::default()),
+        }
+    }
+
+    /// Record `key`, returning `false` if it was already seen within the window.
+    pub fn insert(&self, key: &str) -> bool {
+        if self.capacity == 0 {
+            return true;
+        }
+
+        let mut seen = self.seen.lock().unwrap_or_else(|e| e.into_inner());
+        if seen.keys.contains(key) {
+            return false;
+        }
+
+        if seen.order.len() >= self.capacity {
+            if let Some(oldest) = seen.order.pop_front() {
+                seen.keys.remove(&oldest);
+            }
+        }
+        seen.keys.insert(key.to_string());
+        seen.order.push_back(key.to_string());
+        true
+    }
+
+    /// Forget `key`, so an event with it can be sent again.
+    pub fn remove(&self, key: &str) {
+        let mut seen = self.seen.lock().unwrap_or_else(|e| e.into_inner());
+        if seen.keys.remove(key) {
+            seen.order.retain(|seen| seen != key);
+        }
+    }
+}
+
+/// Remembers the content of events seen within a time window.
+///
+/// Two events are identical if they match in everything but their
+/// timestamp, event ID, and idempotency key: type, name, identity, and
+/// properties.
+#[derive(Debug)]
+pub struct ContentDeduplicator {
+    window: Option<Duration>,
+    seen: Mutex<SeenContent>,
+}
+
+#[derive(Debug, Default)]
+struct SeenContent {
+    first_seen: HashMap<u64, Instant>,
+    order: VecDeque<(Instant, u64)>,
+}
+
+impl ContentDeduplicator {
+    /// Create a deduplicator remembering events for `window`.
+    ///
+    /// `None` disables deduplication.
+    pub fn new(window: Option<Duration>) -> Self {
+        Self {
+            window,
+            seen: Mutex::new(SeenContent::default()),
+        }
+    }
+
+    /// Record `event`, returning `false` if an identical event was seen
+    /// within the window.
+    pub fn insert(&self, event: &TrackerEvent) -> bool {
+        let Some(window) = self.window else {
+            return true;
+        };
+        let Some(hash) = content_hash(event) else {
+            return true;
+        };
+
+        let now = Instant::now();
+        let mut seen = self.seen.lock().unwrap_or_else(|e| e.into_inner());
+        while let Some(&(at, oldest)) = seen.order.front() {
+            if now.duration_since(at) < window {
+                break;
+            }
+            seen.order.pop_front();
+            seen.first_seen.remove(&oldest);
+        }
+
+        if seen.first_seen.contains_key(&hash) {
+            return false;
+        }
+        seen.first_seen.insert(hash, now);
+        seen.order.push_back((now, hash));
+        true
+    }
+
+    /// Forget `event`, so an identical event can be sent again.
+    pub fn remove(&self, event: &TrackerEvent) {
+        if self.window.is_none() {
+            return;
+        }
+        let Some(hash) = content_hash(event) else {
+            return;
+        };
+        let mut seen = self.seen.lock().unwrap_or_else(|e| e.into_inner());
+        if seen.first_seen.remove(&hash).is_some() {
+            seen.order.retain(|&(_, seen)| seen != hash);
+        }
+    }
+}
+
+/// Hash everything about an event except when it happened and its IDs.
+fn content_hash(event: &TrackerEvent) -> Option<u64> {
+    let Ok(Value::Object(mut fields)) = serde_json::to_value(event) else {
+        return None;
+    };
+    for key in ["timestamp", "eventId", "messageId"] {
+        fields.remove(key);
+    }
+
+    let mut hasher = Sha256::new();
+    hash_value(&mut hasher, &Value::Object(fields));
+    let digest = hasher.finalize();
+    Some(u64::from_be_bytes(
+        digest[..8].try_into().expect("digest is 32 bytes"),
+    ))
+}
+
+/// Feed `value` to `hasher` with object keys sorted, so property order
+/// doesn't matter.
+fn hash_value(hasher: &mut Sha256, value: &Value) {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by_key(|(key, _)| *key);
+            hasher.update(b"{");
+            for (key, value) in entries {
+                hasher.update(serde_json::to_string(key).unwrap_or_default());
+                hasher.update(b":");
+                hash_value(hasher, value);
+                hasher.update(b",");
+            }
+            hasher.update(b"}");
+        }
+        Value::Array(values) => {
+            hasher.update(b"[");
+            for value in values {
+                hash_value(hasher, value);
+                hasher.update(b",");
+            }
+            hasher.update(b"]");
+        }
+        other => hasher.update(other.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builders::{BuildEvent, TrackBuilder};
+    use crate::email;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_identical_events_dropped_within_window() {
+        let dedup = ContentDeduplicator::new(Some(Duration::from_secs(60)));
+        let event = |plan: &str| {
+            TrackBuilder::new("upgrade", email("user@example.com"))
+                .property("plan", plan)
+                .property("seats", 5)
+                .build()
+        };
+
+        assert!(dedup.insert(&event("pro")));
+        assert!(!dedup.insert(&event("pro")));
+        assert!(dedup.insert(&event("team")));
+
+        tokio::time::advance(Duration::from_secs(60)).await;
+        assert!(dedup.insert(&event("pro")));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_removed_event_accepted_again() {
+        let dedup = ContentDeduplicator::new(Some(Duration::from_secs(60)));
+        let event = TrackBuilder::new("upgrade", email("user@example.com")).build();
+
+        assert!(dedup.insert(&event));
+        dedup.remove(&event);
+        assert!(dedup.insert(&event));
+        assert!(!dedup.insert(&event));
+    }
+
+    #[test]
+    fn test_content_dedup_disabled_without_window() {
+        let dedup = ContentDeduplicator::new(None);
+        let event = TrackBuilder::new("upgrade", email("user@example.com")).build();
+
+        assert!(dedup.insert(&event));
+        assert!(dedup.insert(&event));
+    }
+
+    #[test]
+    fn test_duplicate_key_rejected() {
+        let dedup = Deduplicator::new(10);
+
+        assert!(dedup.insert("a"));
+        assert!(!dedup.insert("a"));
+        assert!(dedup.insert("b"));
+    }
+
+    #[test]
+    fn test_removed_key_accepted_again() {
+        let dedup = Deduplicator::new(2);
+
+        assert!(dedup.insert("a"));
+        dedup.remove("a");
+        assert!(dedup.insert("a"));
+        // The removed key no longer counts towards the capacity
+        assert!(dedup.insert("b"));
+        assert!(!dedup.insert("a"));
+    }
+
+    #[test]
+    fn test_oldest_key_evicted() {
+        let dedup = Deduplicator::new(2);
+
+        assert!(dedup.insert("a"));
+        assert!(dedup.insert("b"));
+        assert!(dedup.insert("c")); // evicts "a"
+
+        assert!(dedup.insert("a"));
+        assert!(!dedup.insert("c"));
+    }
+
+    #[test]
+    fn test_zero_capacity_disables() {
+        let dedup = Deduplicator::new(0);
+
+        assert!(dedup.insert("a"));
+        assert!(dedup.insert("a"));
+    }
+}
@@ -1,5 +1,7 @@
 //! Error types for the Outlit SDK.
 
+use std::time::Duration;
+
 /// Errors that can occur when using the Outlit SDK.
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
@@ -16,15 +18,54 @@ pub enum Error {
         message: String,
     },
 
+    /// API rejected the request because of rate limiting (HTTP 429).
+    #[error("Rate limited by the API{}", retry_after_suffix(.retry_after))]
+    RateLimited {
+        /// How long the API asked to wait before retrying, from the
+        /// `Retry-After` header.
+        retry_after: Option<Duration>,
+    },
+
+    /// Flushing is paused by the circuit breaker after repeated failures.
+    #[error("Circuit breaker is open; flushing is paused")]
+    CircuitOpen,
+
     /// Invalid configuration.
     #[error("Invalid configuration: {0}")]
     Config(String),
 
+    /// Invalid input value.
+    #[error("Validation error: {0}")]
+    Validation(String),
+
+    /// An identity, such as an email address, is malformed.
+    #[error("Invalid identity: {0}")]
+    InvalidIdentity(String),
+
+    /// Operation did not complete within its deadline.
+    #[error("Operation timed out")]
+    Timeout,
+
+    /// The global client was used before [`init`](crate::init) was called.
+    #[error("Global client is not initialized; call outlit::init first")]
+    NotInitialized,
+
     /// Client has been shutdown.
     #[error("Client has been shutdown")]
     Shutdown,
 
+    /// I/O error.
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
     /// Serialization error.
     #[error("Serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
 }
+
+fn retry_after_suffix(retry_after: &Option<Duration>) -> String {
+    match retry_after {
+        Some(delay) => format!(" (retry after {}s)", delay.as_secs()),
+        None => String::new(),
+    }
+}
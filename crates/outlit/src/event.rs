@@ -0,0 +1,77 @@
+//! Typed custom events.
+
+use crate::Error;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// A custom event with a fixed name and typed properties, sent with
+/// [`Outlit::track_event`](crate::Outlit::track_event).
+///
+/// With the `derive` feature, `#[derive(OutlitEvent)]` implements this for a
+/// struct, turning each field into a property.
+///
+/// # Example
+///
+/// ```rust
+/// use outlit::OutlitEvent;
+/// use serde_json::{json, Value};
+/// use std::collections::HashMap;
+///
+/// struct SignupEvent {
+///     plan: String,
+///     seats: u32,
+/// }
+///
+/// impl OutlitEvent for SignupEvent {
+///     fn event_name(&self) -> &str {
+///         "signup"
+///     }
+///
+///     fn properties(&self) -> Result<HashMap<String, Value>, outlit::Error> {
+///         Ok(HashMap::from([
+///             ("plan".to_string(), json!(self.plan)),
+///             ("seats".to_string(), json!(self.seats)),
+///         ]))
+///     }
+/// }
+/// ```
+pub trait OutlitEvent {
+    /// Name the event is tracked under.
+    fn event_name(&self) -> &str;
+
+    /// Properties sent with the event.
+    fn properties(&self) -> Result<HashMap<String, Value>, Error>;
+}
+
+impl<T: OutlitEvent + ?Sized> OutlitEvent for &T {
+    fn event_name(&self) -> &str {
+        (**self).event_name()
+    }
+
+    fn properties(&self) -> Result<HashMap<String, Value>, Error> {
+        (**self).properties()
+    }
+}
+
+/// Support code for `#[derive(OutlitEvent)]`. Not public API.
+#[doc(hidden)]
+pub mod __private {
+    use crate::Error;
+    use serde::Serialize;
+    use serde_json::Value;
+
+    pub type Properties = std::collections::HashMap<String, Value>;
+
+    /// Serialize `value` into `properties` under `key`, leaving out nulls.
+    pub fn insert_property(
+        properties: &mut Properties,
+        key: &str,
+        value: &impl Serialize,
+    ) -> Result<(), Error> {
+        let value = serde_json::to_value(value)?;
+        if !value.is_null() {
+            properties.insert(key.to_string(), value);
+        }
+        Ok(())
+    }
+}
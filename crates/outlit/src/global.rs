@@ -0,0 +1,106 @@
+//! Process-wide client for fire-and-forget tracking.
+
+use crate::client::{SendableIdentify, SendableTrack};
+use crate::{Email, Error, Fingerprint, Outlit, OutlitBuilder, UserId};
+use std::sync::{Mutex, OnceLock};
+
+static GLOBAL: OnceLock<Outlit> = OnceLock::new();
+/// Serializes [`init`] so a losing caller doesn't start a second client.
+static INIT: Mutex<()> = Mutex::new(());
+
+/// Build the process-wide client used by [`track`], [`identify`], and the
+/// other free functions.
+///
+/// Must be called from within a Tokio runtime, like
+/// [`OutlitBuilder::build`]. Returns [`Error::Config`] if the global client
+/// is already initialized.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use outlit::{email, Outlit};
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), outlit::Error> {
+///     outlit::init(Outlit::builder("pk_xxx"))?;
+///
+///     outlit::track("signup", email("user@example.com"))?
+///         .property("plan", "pro")
+///         .send()
+///         .await?;
+///
+///     outlit::shutdown().await
+/// }
+/// ```
+pub fn init(builder: OutlitBuilder) -> Result<&'static Outlit, Error> {
+    let _init = INIT.lock().unwrap_or_else(|e| e.into_inner());
+    if GLOBAL.get().is_some() {
+        return Err(Error::Config(
+            "the global client is already initialized".into(),
+        ));
+    }
+    let client = builder.build()?;
+    Ok(GLOBAL.get_or_init(|| client))
+}
+
+/// Get the process-wide client, if [`init`] has been called.
+pub fn global() -> Option<&'static Outlit> {
+    GLOBAL.get()
+}
+
+fn client() -> Result<&'static Outlit, Error> {
+    global().ok_or(Error::NotInitialized)
+}
+
+/// Track a custom event with email on the global client. See
+/// [`Outlit::track`].
+pub fn track(
+    event_name: impl Into<String>,
+    identity: impl Into<Email>,
+) -> Result<SendableTrack<'static>, Error> {
+    Ok(client()?.track(event_name, identity))
+}
+
+/// Track a custom event with user_id on the global client. See
+/// [`Outlit::track_by_user_id`].
+pub fn track_by_user_id(
+    event_name: impl Into<String>,
+    identity: impl Into<UserId>,
+) -> Result<SendableTrack<'static>, Error> {
+    Ok(client()?.track_by_user_id(event_name, identity))
+}
+
+/// Track a custom event with fingerprint on the global client. See
+/// [`Outlit::track_by_fingerprint`].
+pub fn track_by_fingerprint(
+    event_name: impl Into<String>,
+    identity: impl Into<Fingerprint>,
+) -> Result<SendableTrack<'static>, Error> {
+    Ok(client()?.track_by_fingerprint(event_name, identity))
+}
+
+/// Identify a user by email on the global client. See [`Outlit::identify`].
+pub fn identify(identity: impl Into<Email>) -> Result<SendableIdentify<'static>, Error> {
+    Ok(client()?.identify(identity))
+}
+
+/// Identify a user by user_id on the global client. See
+/// [`Outlit::identify_by_user_id`].
+pub fn identify_by_user_id(
+    identity: impl Into<UserId>,
+) -> Result<SendableIdentify<'static>, Error> {
+    Ok(client()?.identify_by_user_id(identity))
+}
+
+/// Flush all pending events on the global client. See [`Outlit::flush`].
+pub async fn flush() -> Result<(), Error> {
+    client()?.flush().await
+}
+
+/// Shutdown the global client. See [`Outlit::shutdown`].
+///
+/// The client stays registered but rejects further events with
+/// [`Error::Shutdown`].
+pub async fn shutdown() -> Result<(), Error> {
+    client()?.shutdown().await
+}
@@ -0,0 +1,83 @@
+//! Guard that shuts the client down when dropped.
+
+use crate::client::block_on;
+use crate::Outlit;
+use std::ops::Deref;
+use std::time::Duration;
+use tracing::error;
+
+/// How long a guard's drop waits for the final flush by default.
+const DEFAULT_DROP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Owns an [`Outlit`] client and shuts it down when dropped, flushing
+/// pending events.
+///
+/// Created with [`Outlit::into_guard`]. Dereferences to the client, so it can
+/// be used in its place. The flush on drop is best-effort: it blocks the
+/// dropping thread for at most the [`drop_timeout`](Self::drop_timeout)
+/// (5 seconds by default), hands events it couldn't send to the
+/// [`dead_letter`](crate::OutlitBuilder::dead_letter) sink like
+/// [`shutdown_with_timeout`](Outlit::shutdown_with_timeout), and logs
+/// failures. Calling
+/// [`shutdown`](Outlit::shutdown) explicitly is still preferred where
+/// errors need handling.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use outlit::{email, Outlit};
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), outlit::Error> {
+///     let client = Outlit::builder("pk_xxx").build()?.into_guard();
+///
+///     client.track("signup", email("user@example.com")).send().await?;
+///     Ok(())
+///     // Pending events are flushed here
+/// }
+/// ```
+pub struct OutlitGuard {
+    client: Option<Outlit>,
+    drop_timeout: Duration,
+}
+
+impl OutlitGuard {
+    pub(crate) fn new(client: Outlit) -> Self {
+        Self {
+            client: Some(client),
+            drop_timeout: DEFAULT_DROP_TIMEOUT,
+        }
+    }
+
+    /// Set how long dropping the guard waits for the final flush.
+    pub fn drop_timeout(mut self, timeout: Duration) -> Self {
+        self.drop_timeout = timeout;
+        self
+    }
+
+    /// Take the client back out of the guard without shutting it down.
+    pub fn into_inner(mut self) -> Outlit {
+        self.client.take().expect("client is only taken once")
+    }
+}
+
+impl Deref for OutlitGuard {
+    type Target = Outlit;
+
+    fn deref(&self) -> &Outlit {
+        self.client.as_ref().expect("client is only taken on drop")
+    }
+}
+
+impl Drop for OutlitGuard {
+    fn drop(&mut self) {
+        let Some(client) = self.client.take() else {
+            return;
+        };
+        // Unlike timing out `shutdown`, this spills events still queued at
+        // the deadline to the dead-letter sink instead of losing them
+        if let Err(e) = block_on(client.shutdown_with_timeout(self.drop_timeout)) {
+            error!(error = %e, "failed to flush events when dropping client guard");
+        }
+    }
+}
@@ -0,0 +1,224 @@
+//! Translate Lemon Squeezy webhooks into billing events.
+//!
+//! Enabled with the `lemonsqueezy` feature. Works like
+//! [`stripe`](crate::stripe): [`translate`] reads a webhook body and
+//! returns the matching [`BillingUpdate`], which is sent for a customer's
+//! domain. The Lemon Squeezy customer ID is sent as the
+//! `lemonsqueezy_customer_id` property.
+//!
+//! | Lemon Squeezy event | Billing event |
+//! |---------------------|---------------|
+//! | `subscription_created`, `_updated`, `_cancelled`, `_resumed`, `_paused`, `_unpaused` | Subscription status, with plan and seats |
+//! | `subscription_expired` | [`Churned`](BillingStatus::Churned) |
+//! | `subscription_payment_success`, `_recovered` | [`InvoicePaid`](BillingActivity::InvoicePaid), without a status |
+//! | `subscription_payment_failed` | [`InvoiceFailed`](BillingActivity::InvoiceFailed), without a status |
+//! | `subscription_payment_refunded` | [`Refund`](BillingActivity::Refund), without a status |
+//!
+//! Other events return `None`. Lemon Squeezy webhooks carry no event ID,
+//! so [`BillingUpdate::event_id`] is built from the event name, the
+//! object, and its `updated_at`, which stay the same across redeliveries.
+//! Verify the `X-Signature` header before translating; this module only
+//! reads the payload.
+
+use crate::billing::{int_at, str_at, BillingUpdate, Provider};
+use crate::types::{BillingActivity, BillingStatus};
+use crate::Error;
+use serde_json::Value;
+
+/// Translate a Lemon Squeezy webhook body into a billing update.
+///
+/// Returns `Ok(None)` for event types that don't map to billing events,
+/// [`Error::Serialization`] if the body isn't JSON, and
+/// [`Error::Validation`] if it isn't a Lemon Squeezy event.
+pub fn translate(payload: &[u8]) -> Result<Option<BillingUpdate>, Error> {
+    translate_value(&serde_json::from_slice(payload)?)
+}
+
+/// Same as [`translate`], for a webhook body that is already parsed.
+pub fn translate_value(event: &Value) -> Result<Option<BillingUpdate>, Error> {
+    let (Some(event_name), Some(kind), Some(id)) = (
+        str_at(event, "/meta/event_name"),
+        str_at(event, "/data/type"),
+        event.pointer("/data/id").and_then(id_string),
+    ) else {
+        return Err(Error::Validation(
+            "not a Lemon Squeezy event: missing meta.event_name or data".into(),
+        ));
+    };
+    let attributes = event.pointer("/data/attributes").unwrap_or(&Value::Null);
+    let updated_at = str_at(attributes, "/updated_at").unwrap_or_default();
+    let event_id = format!("{event_name}:{kind}:{id}:{updated_at}");
+    let new = |status| BillingUpdate::new(Provider::LemonSqueezy, status, event_id.clone());
+
+    let mut update = match event_name {
+        "subscription_expired" => {
+            let mut update = subscription(new(Some(BillingStatus::Churned)), attributes);
+            update.mrr = Some(0);
+            update
+        }
+        "subscription_created"
+        | "subscription_updated"
+        | "subscription_cancelled"
+        | "subscription_resumed"
+        | "subscription_paused"
+        | "subscription_unpaused" => {
+            let Some(status) = str_at(attributes, "/status").and_then(subscription_status) else {
+                return Ok(None);
+            };
+            subscription(new(Some(status)), attributes)
+        }
+        "subscription_payment_success" | "subscription_payment_recovered" => {
+            let mut update = new(None);
+            update.activity = Some(BillingActivity::InvoicePaid);
+            update.invoice_id = Some(id.clone());
+            update.amount = int_at(attributes, "/total");
+            update
+        }
+        "subscription_payment_failed" => {
+            let mut update = new(None);
+            update.activity = Some(BillingActivity::InvoiceFailed);
+            update.invoice_id = Some(id.clone());
+            update.amount = int_at(attributes, "/total");
+            update
+        }
+        "subscription_payment_refunded" => {
+            let mut update = new(None);
+            update.activity = Some(BillingActivity::Refund);
+            update.invoice_id = Some(id.clone());
+            update.amount = int_at(attributes, "/refunded_amount");
+            update
+        }
+        _ => return Ok(None),
+    };
+
+    update.provider_customer_id = attributes.get("customer_id").and_then(id_string);
+    update.customer_email = str_at(attributes, "/user_email").map(String::from);
+    update.currency = str_at(attributes, "/currency").map(str::to_ascii_lowercase);
+    Ok(Some(update))
+}
+
+/// Map a Lemon Squeezy subscription status.
+fn subscription_status(status: &str) -> Option<BillingStatus> {
+    Some(match status {
+        "on_trial" => BillingStatus::Trialing,
+        "active" => BillingStatus::Paid,
+        "past_due" => BillingStatus::PastDue,
+        "unpaid" => BillingStatus::Unpaid,
+        "paused" => BillingStatus::Paused,
+        "cancelled" => BillingStatus::Canceled,
+        "expired" => BillingStatus::Churned,
+        _ => return None,
+    })
+}
+
+/// Fill in plan and seats from a subscription's attributes.
+fn subscription(mut update: BillingUpdate, attributes: &Value) -> BillingUpdate {
+    update.plan = str_at(attributes, "/variant_name")
+        .or_else(|| str_at(attributes, "/product_name"))
+        .map(String::from);
+    update.seats =
+        int_at(attributes, "/first_subscription_item/quantity").and_then(|q| u32::try_from(q).ok());
+    update
+}
+
+/// Lemon Squeezy IDs are strings in `data.id` and numbers elsewhere.
+fn id_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn event(event_name: &str, kind: &str, attributes: Value) -> Value {
+        json!({
+            "meta": { "event_name": event_name, "custom_data": {} },
+            "data": { "type": kind, "id": "42", "attributes": attributes }
+        })
+    }
+
+    #[test]
+    fn test_subscription_events() {
+        let attributes = json!({
+            "customer_id": 1001,
+            "user_email": "billing@acme.com",
+            "status": "on_trial",
+            "product_name": "Acme",
+            "variant_name": "Pro",
+            "first_subscription_item": { "id": 7, "quantity": 4 },
+            "updated_at": "2024-01-28T00:00:00.000000Z"
+        });
+        let update = translate_value(&event(
+            "subscription_created",
+            "subscriptions",
+            attributes.clone(),
+        ))
+        .unwrap()
+        .unwrap();
+        assert_eq!(update.status, Some(BillingStatus::Trialing));
+        assert_eq!(update.provider_customer_id.as_deref(), Some("1001"));
+        assert_eq!(update.customer_email.as_deref(), Some("billing@acme.com"));
+        assert_eq!(update.plan.as_deref(), Some("Pro"));
+        assert_eq!(update.seats, Some(4));
+        assert_eq!(
+            update.event_id,
+            "subscription_created:subscriptions:42:2024-01-28T00:00:00.000000Z"
+        );
+
+        let update = translate_value(&event("subscription_expired", "subscriptions", attributes))
+            .unwrap()
+            .unwrap();
+        assert_eq!(update.status, Some(BillingStatus::Churned));
+        assert_eq!(update.mrr, Some(0));
+    }
+
+    #[test]
+    fn test_payment_events() {
+        let attributes = json!({
+            "customer_id": 1001,
+            "total": 4900,
+            "refunded_amount": 900,
+            "currency": "USD"
+        });
+        let update = translate_value(&event(
+            "subscription_payment_failed",
+            "subscription-invoices",
+            attributes.clone(),
+        ))
+        .unwrap()
+        .unwrap();
+        assert_eq!(update.status, None);
+        assert_eq!(update.activity, Some(BillingActivity::InvoiceFailed));
+        assert_eq!(update.invoice_id.as_deref(), Some("42"));
+        assert_eq!(update.amount, Some(4900));
+        assert_eq!(update.currency.as_deref(), Some("usd"));
+
+        let update = translate_value(&event(
+            "subscription_payment_refunded",
+            "subscription-invoices",
+            attributes,
+        ))
+        .unwrap()
+        .unwrap();
+        assert_eq!(update.status, None);
+        assert_eq!(update.activity, Some(BillingActivity::Refund));
+        assert_eq!(update.amount, Some(900));
+    }
+
+    #[test]
+    fn test_unhandled_and_invalid_events() {
+        let unhandled = event("license_key_created", "license-keys", json!({}));
+        assert_eq!(translate_value(&unhandled).unwrap(), None);
+
+        assert!(matches!(
+            translate_value(&json!({ "meta": { "event_name": "order_created" } })),
+            Err(Error::Validation(_))
+        ));
+        assert!(matches!(translate(b"[]"), Err(Error::Validation(_))));
+    }
+}
@@ -22,29 +22,100 @@
 //! }
 //! ```
 
+#[cfg(feature = "actix-web")]
+pub mod actix;
+#[cfg(any(feature = "stripe", feature = "paddle", feature = "lemonsqueezy"))]
+mod billing;
+#[cfg(feature = "blocking")]
+pub mod blocking;
 mod builders;
+mod circuit;
 mod client;
+mod clock;
 mod config;
+mod consent;
+mod dead_letter;
+mod dedup;
 mod error;
+mod event;
+mod global;
+mod guard;
+#[cfg(feature = "lemonsqueezy")]
+pub mod lemonsqueezy;
+mod limits;
+mod metrics;
+#[cfg(feature = "paddle")]
+pub mod paddle;
+#[cfg(feature = "persist")]
+mod persist;
+mod property;
 mod queue;
+mod rate_limit;
+#[cfg(feature = "redact")]
+mod redact;
+mod sampling;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+mod stats;
+#[cfg(feature = "stripe")]
+pub mod stripe;
+mod suppression;
+#[cfg(feature = "test-util")]
+pub mod testing;
+mod timestamp;
+#[cfg(feature = "tower")]
+pub mod tower;
+#[cfg(feature = "opentelemetry")]
+mod trace_context;
 mod transport;
 pub mod types;
+#[cfg(feature = "webhooks")]
+pub mod webhooks;
 
+#[cfg(any(feature = "stripe", feature = "paddle", feature = "lemonsqueezy"))]
+pub use billing::BillingUpdate;
+pub use builders::Identity;
 pub use client::{
-    CustomerMethods, Outlit, SendableBilling, SendableIdentify, SendableStage, SendableTrack,
-    UserMethods,
+    CustomerMethods, Outlit, PrivacyMethods, SendableAlias, SendableBilling, SendableGroup,
+    SendableIdentify, SendablePage, SendableStage, SendableTrack, UserMethods,
 };
-pub use config::{Config, OutlitBuilder};
+pub use clock::{Clock, FixedClock, SystemClock};
+pub use config::{
+    BeforeSendHook, CircuitBreaker, Config, ConsentPolicy, EventNamePolicy, FieldCase,
+    FlushFailureHook, FlushSuccessHook, OutlitBuilder, RateLimit, RateLimitPolicy, RetryPolicy,
+    TimestampAction, TimestampPolicy,
+};
+pub use dead_letter::{DeadLetter, DeadLetterFile, DeadLetterReason, DeadLetterSink};
 pub use error::Error;
+#[doc(hidden)]
+pub use event::__private;
+pub use event::OutlitEvent;
+pub use global::{
+    flush, global, identify, identify_by_user_id, init, shutdown, track, track_by_fingerprint,
+    track_by_user_id,
+};
+pub use guard::OutlitGuard;
+#[cfg(feature = "derive")]
+pub use outlit_derive::OutlitEvent;
+pub use property::PropertyValue;
+#[cfg(feature = "redact")]
+pub use redact::Redactor;
+pub use stats::{ClientStats, LastFlush};
+pub use timestamp::IntoTimestamp;
 pub use types::{
-    BillingStatus, IngestPayload, IngestResponse, JourneyStage, SourceType, TrackerEvent,
+    BillingActivity, BillingInterval, BillingStatus, Consent, CustomerStatus, FlushReport,
+    IngestPayload, IngestResponse, JourneyStage, Library, RejectedEvent, SourceType, TrackerEvent,
 };
 
 // Identity helpers
 
 /// Create an email identity.
+///
+/// The address is trimmed and lowercased but not validated; use
+/// [`Email::parse`] or [`strict_identity`](OutlitBuilder::strict_identity)
+/// to reject malformed addresses.
 pub fn email(e: impl Into<String>) -> Email {
-    Email(e.into())
+    Email(normalize_email(e.into()))
 }
 
 /// Create a user ID identity.
@@ -62,12 +133,69 @@ pub fn fingerprint(fp: impl Into<String>) -> Fingerprint {
 pub struct Email(pub(crate) String);
 
 impl Email {
+    /// Create a normalized email identity, returning
+    /// [`Error::InvalidIdentity`] if it isn't shaped like an address.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use outlit::Email;
+    ///
+    /// assert_eq!(Email::parse(" User@Example.com ").unwrap().as_str(), "user@example.com");
+    /// assert!(Email::parse("N/A").is_err());
+    /// ```
+    pub fn parse(e: impl Into<String>) -> Result<Email, Error> {
+        let email = email(e);
+        if !email.is_valid() {
+            return Err(Error::InvalidIdentity(format!(
+                "{:?} is not a valid email address",
+                email.0
+            )));
+        }
+        Ok(email)
+    }
+
+    /// Whether the email has the basic shape of an address: a local part,
+    /// one `@`, and a dotted domain, with no whitespace.
+    pub fn is_valid(&self) -> bool {
+        is_valid_email(&self.0)
+    }
+
     /// Get the email as a string slice.
     pub fn as_str(&self) -> &str {
         &self.0
     }
 }
 
+/// Trim surrounding whitespace and lowercase an email address.
+pub(crate) fn normalize_email(email: String) -> String {
+    let trimmed = email.trim();
+    if trimmed.len() == email.len() && !trimmed.chars().any(char::is_uppercase) {
+        return email;
+    }
+    trimmed.to_lowercase()
+}
+
+/// Check the basic shape of an email address.
+pub(crate) fn is_valid_email(email: &str) -> bool {
+    let Some((local, domain)) = email.split_once('@') else {
+        return false;
+    };
+    let labels_ok = domain.split('.').all(|label| {
+        !label.is_empty()
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label.chars().all(|c| c.is_alphanumeric() || c == '-')
+    });
+    !local.is_empty()
+        && local.len() <= 64
+        && !local
+            .chars()
+            .any(|c| c.is_whitespace() || c.is_control() || c == '@')
+        && domain.contains('.')
+        && labels_ok
+}
+
 impl From<Email> for String {
     fn from(e: Email) -> String {
         e.0
@@ -0,0 +1,161 @@
+//! Size limits on properties and events.
+
+use crate::types::{TrackerEvent, TRUNCATED_PROPERTY};
+use crate::Error;
+use serde_json::Value;
+use tracing::warn;
+
+/// Property and event size caps from the client configuration.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct SizeLimits {
+    pub(crate) max_property_bytes: Option<usize>,
+    pub(crate) max_event_bytes: Option<usize>,
+}
+
+impl SizeLimits {
+    /// Truncate oversized string properties, then reject the event if it is
+    /// still over the event cap.
+    pub(crate) fn apply(&self, event: &mut TrackerEvent) -> Result<(), Error> {
+        if let Some(max_bytes) = self.max_property_bytes {
+            truncate_properties(event, max_bytes);
+        }
+        if let Some(max_bytes) = self.max_event_bytes {
+            let size = serialized_size(event);
+            if size > max_bytes {
+                return Err(Error::Validation(format!(
+                    "event is {size} bytes, over max_event_bytes ({max_bytes})"
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Truncate string values longer than `max_bytes`, including ones nested in
+/// objects and arrays, listing the affected top-level keys in
+/// [`TRUNCATED_PROPERTY`].
+fn truncate_properties(event: &mut TrackerEvent, max_bytes: usize) {
+    let Some(properties) = event.properties_mut() else {
+        return;
+    };
+
+    let mut truncated: Vec<String> = properties
+        .iter_mut()
+        .filter_map(|(key, value)| truncate_strings(value, max_bytes).then(|| key.clone()))
+        .collect();
+    if truncated.is_empty() {
+        return;
+    }
+
+    truncated.sort();
+    warn!(keys = ?truncated, max_bytes, "truncated oversized properties");
+    properties.insert(TRUNCATED_PROPERTY.into(), Value::from(truncated));
+}
+
+/// Truncate every string in `value` to at most `max_bytes`, on a character
+/// boundary. Returns whether anything was truncated.
+fn truncate_strings(value: &mut Value, max_bytes: usize) -> bool {
+    match value {
+        Value::String(s) if s.len() > max_bytes => {
+            let mut end = max_bytes;
+            while !s.is_char_boundary(end) {
+                end -= 1;
+            }
+            s.truncate(end);
+            true
+        }
+        Value::Array(values) => values
+            .iter_mut()
+            .fold(false, |any, value| truncate_strings(value, max_bytes) | any),
+        Value::Object(map) => map
+            .values_mut()
+            .fold(false, |any, value| truncate_strings(value, max_bytes) | any),
+        _ => false,
+    }
+}
+
+/// Length of `value` serialized as JSON, or zero if it fails to serialize.
+pub(crate) fn serialized_size(value: &impl serde::Serialize) -> usize {
+    struct Counter(usize);
+
+    impl std::io::Write for Counter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0 += buf.len();
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let mut counter = Counter(0);
+    match serde_json::to_writer(&mut counter, value) {
+        Ok(()) => counter.0,
+        Err(_) => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::CustomEventData;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    fn event(properties: HashMap<String, Value>) -> TrackerEvent {
+        TrackerEvent::Custom(CustomEventData {
+            timestamp: 1706400000000,
+            url: "server://user@example.com".into(),
+            path: "/".into(),
+            event_name: "test".into(),
+            properties: Some(properties),
+            message_id: None,
+            event_id: None,
+        })
+    }
+
+    fn properties(event: &mut TrackerEvent) -> &HashMap<String, Value> {
+        event.properties_mut().unwrap()
+    }
+
+    #[test]
+    fn test_truncates_long_strings_on_char_boundary() {
+        let mut event = event(HashMap::from([
+            ("short".into(), json!("ok")),
+            ("long".into(), json!("ééééé")),
+            ("nested".into(), json!({ "list": ["abcdefgh"] })),
+        ]));
+        let limits = SizeLimits {
+            max_property_bytes: Some(5),
+            max_event_bytes: None,
+        };
+
+        limits.apply(&mut event).unwrap();
+
+        let properties = properties(&mut event);
+        assert_eq!(properties["short"], "ok");
+        assert_eq!(properties["long"], "éé");
+        assert_eq!(properties["nested"], json!({ "list": ["abcde"] }));
+        assert_eq!(properties[TRUNCATED_PROPERTY], json!(["long", "nested"]));
+    }
+
+    #[test]
+    fn test_rejects_events_over_max_event_bytes() {
+        let mut event = event(HashMap::from([("blob".into(), json!("x".repeat(100)))]));
+        let limits = SizeLimits {
+            max_property_bytes: None,
+            max_event_bytes: Some(50),
+        };
+
+        let err = limits.apply(&mut event).unwrap_err();
+        assert!(matches!(err, Error::Validation(ref m) if m.contains("max_event_bytes")));
+
+        // Truncating first can bring the event under the cap
+        let limits = SizeLimits {
+            max_property_bytes: Some(10),
+            max_event_bytes: Some(200),
+        };
+        assert!(limits.apply(&mut event).is_ok());
+    }
+}
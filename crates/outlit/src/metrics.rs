@@ -0,0 +1,63 @@
+//! Metrics emitted through the [`metrics`](https://docs.rs/metrics) facade.
+//!
+//! Enabled with the `metrics` feature.
+//! Install any `metrics` recorder, such as `metrics-exporter-prometheus`, to
+//! collect them:
+//!
+//! | Metric | Type | Description |
+//! |--------|------|-------------|
+//! | `outlit_queue_depth` | gauge | Events waiting to be sent |
+//! | `outlit_flush_duration_seconds` | histogram | Time taken by each flush that had events to send |
+//! | `outlit_flush_batch_size` | histogram | Events sent per successful flush |
+//! | `outlit_events_sent_total` | counter | Events sent in successful requests |
+//! | `outlit_events_rejected_total` | counter | Events rejected by the API or that failed to serialize |
+//! | `outlit_flush_failures_total` | counter | Flushes that failed, leaving their events queued |
+//! | `outlit_http_errors_total` | counter | Failed HTTP attempts, including retried ones, labelled by `status` |
+
+#[cfg(feature = "metrics")]
+use crate::types::FlushReport;
+use crate::Error;
+#[cfg(feature = "metrics")]
+use std::time::Duration;
+
+/// Record the number of events waiting to be sent.
+#[cfg(feature = "metrics")]
+pub(crate) fn queue_depth(depth: usize) {
+    ::metrics::gauge!("outlit_queue_depth").set(depth as f64);
+}
+
+/// Record the outcome of a flush. Flushes of an empty queue are skipped.
+#[cfg(feature = "metrics")]
+pub(crate) fn flush(duration: Duration, result: &Result<FlushReport, Error>) {
+    match result {
+        Ok(report) if report.sent == 0 && report.quarantined.is_empty() => {}
+        Ok(report) => {
+            let rejected = report.rejected.len() + report.quarantined.len();
+            ::metrics::histogram!("outlit_flush_duration_seconds").record(duration.as_secs_f64());
+            ::metrics::histogram!("outlit_flush_batch_size").record(report.sent as f64);
+            ::metrics::counter!("outlit_events_sent_total").increment(report.sent as u64);
+            ::metrics::counter!("outlit_events_rejected_total").increment(rejected as u64);
+        }
+        Err(_) => {
+            ::metrics::histogram!("outlit_flush_duration_seconds").record(duration.as_secs_f64());
+            ::metrics::counter!("outlit_flush_failures_total").increment(1);
+        }
+    }
+}
+
+/// Record a failed HTTP attempt.
+pub(crate) fn http_error(error: &Error) {
+    #[cfg(feature = "metrics")]
+    {
+        let status = match error {
+            Error::Api { status, .. } => status.to_string(),
+            Error::RateLimited { .. } => "429".into(),
+            Error::Http(e) if e.is_timeout() => "timeout".into(),
+            Error::Http(_) => "network".into(),
+            _ => "other".into(),
+        };
+        ::metrics::counter!("outlit_http_errors_total", "status" => status).increment(1);
+    }
+    #[cfg(not(feature = "metrics"))]
+    let _ = error;
+}
@@ -0,0 +1,242 @@
+//! Translate Paddle Billing webhooks into billing events.
+//!
+//! Enabled with the `paddle` feature. Works like [`stripe`](crate::stripe):
+//! [`translate`] reads a webhook body and returns the matching
+//! [`BillingUpdate`], which is sent for a customer's domain. The Paddle
+//! customer ID is sent as the `paddle_customer_id` property.
+//!
+//! | Paddle event | Billing event |
+//! |--------------|---------------|
+//! | `subscription.created`, `.updated`, `.activated`, `.trialing`, `.past_due`, `.paused`, `.resumed` | Subscription status, with plan, seats, and MRR |
+//! | `subscription.canceled` | [`Churned`](BillingStatus::Churned) |
+//! | `transaction.completed` | [`InvoicePaid`](BillingActivity::InvoicePaid), without a status |
+//! | `transaction.payment_failed` | [`InvoiceFailed`](BillingActivity::InvoiceFailed), without a status |
+//! | `adjustment.created` for a refund | [`Refund`](BillingActivity::Refund), without a status |
+//!
+//! Other events return `None`. Paddle doesn't send a subscription's
+//! previous state, so plan and seat changes arrive as status updates.
+//! Verify the `Paddle-Signature` header before translating; this module
+//! only reads the payload.
+
+use crate::billing::{int_at, interval, monthly, str_at, BillingUpdate, Provider};
+use crate::types::{BillingActivity, BillingStatus};
+use crate::Error;
+use serde_json::Value;
+
+/// Translate a Paddle webhook body into a billing update.
+///
+/// Returns `Ok(None)` for event types that don't map to billing events,
+/// [`Error::Serialization`] if the body isn't JSON, and
+/// [`Error::Validation`] if it isn't a Paddle event.
+pub fn translate(payload: &[u8]) -> Result<Option<BillingUpdate>, Error> {
+    translate_value(&serde_json::from_slice(payload)?)
+}
+
+/// Same as [`translate`], for a webhook body that is already parsed.
+pub fn translate_value(event: &Value) -> Result<Option<BillingUpdate>, Error> {
+    let (Some(event_id), Some(event_type)) =
+        (str_at(event, "/event_id"), str_at(event, "/event_type"))
+    else {
+        return Err(Error::Validation(
+            "not a Paddle event: missing event_id or event_type".into(),
+        ));
+    };
+    let data = event.get("data").unwrap_or(&Value::Null);
+    let new = |status| BillingUpdate::new(Provider::Paddle, status, event_id.to_string());
+
+    let mut update = match event_type {
+        "subscription.canceled" => {
+            let mut update = subscription(new(Some(BillingStatus::Churned)), data);
+            update.mrr = Some(0);
+            update
+        }
+        "subscription.created"
+        | "subscription.updated"
+        | "subscription.activated"
+        | "subscription.trialing"
+        | "subscription.past_due"
+        | "subscription.paused"
+        | "subscription.resumed" => {
+            let Some(status) = str_at(data, "/status").and_then(subscription_status) else {
+                return Ok(None);
+            };
+            subscription(new(Some(status)), data)
+        }
+        "transaction.completed" => {
+            let mut update = transaction(new(None), data);
+            update.activity = Some(BillingActivity::InvoicePaid);
+            update
+        }
+        "transaction.payment_failed" => {
+            let mut update = transaction(new(None), data);
+            update.activity = Some(BillingActivity::InvoiceFailed);
+            update
+        }
+        "adjustment.created" if str_at(data, "/action") == Some("refund") => {
+            let mut update = new(None);
+            update.activity = Some(BillingActivity::Refund);
+            update.invoice_id = str_at(data, "/transaction_id").map(String::from);
+            update.amount = int_at(data, "/totals/total");
+            update
+        }
+        _ => return Ok(None),
+    };
+
+    update.provider_customer_id = str_at(data, "/customer_id").map(String::from);
+    update.currency = str_at(data, "/currency_code").map(str::to_ascii_lowercase);
+    Ok(Some(update))
+}
+
+/// Map a Paddle subscription status.
+fn subscription_status(status: &str) -> Option<BillingStatus> {
+    Some(match status {
+        "trialing" => BillingStatus::Trialing,
+        "active" => BillingStatus::Paid,
+        "past_due" => BillingStatus::PastDue,
+        "paused" => BillingStatus::Paused,
+        "canceled" => BillingStatus::Canceled,
+        _ => return None,
+    })
+}
+
+/// Fill in plan, seats, interval, and MRR from a subscription.
+fn subscription(mut update: BillingUpdate, subscription: &Value) -> BillingUpdate {
+    let items = subscription
+        .get("items")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+
+    if let Some(item) = items.first() {
+        update.plan = str_at(item, "/price/name")
+            .or_else(|| str_at(item, "/product/name"))
+            .or_else(|| str_at(item, "/price/id"))
+            .map(String::from);
+        update.seats = int_at(item, "/quantity").and_then(|q| u32::try_from(q).ok());
+    }
+    update.interval = str_at(subscription, "/billing_cycle/interval").and_then(interval);
+    update.mrr = Some(
+        items
+            .iter()
+            .filter_map(|item| {
+                let amount = int_at(item, "/price/unit_price/amount")?;
+                let interval = str_at(item, "/price/billing_cycle/interval").and_then(interval)?;
+                let count = int_at(item, "/price/billing_cycle/frequency").unwrap_or(1);
+                let quantity = int_at(item, "/quantity").unwrap_or(1);
+                Some(monthly(amount * quantity, interval, count))
+            })
+            .sum(),
+    );
+    update
+}
+
+/// Fill in the invoice number and total from a transaction.
+fn transaction(mut update: BillingUpdate, transaction: &Value) -> BillingUpdate {
+    update.invoice_id = str_at(transaction, "/invoice_number")
+        .or_else(|| str_at(transaction, "/id"))
+        .map(String::from);
+    update.amount = int_at(transaction, "/details/totals/grand_total");
+    update
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::BillingInterval;
+    use serde_json::json;
+
+    #[test]
+    fn test_subscription_events() {
+        let event = json!({
+            "event_id": "evt_01",
+            "event_type": "subscription.updated",
+            "data": {
+                "id": "sub_01",
+                "status": "past_due",
+                "customer_id": "ctm_01",
+                "currency_code": "USD",
+                "billing_cycle": { "interval": "month", "frequency": 1 },
+                "items": [{
+                    "quantity": 3,
+                    "price": {
+                        "id": "pri_01",
+                        "name": "Pro",
+                        "unit_price": { "amount": "1500", "currency_code": "USD" },
+                        "billing_cycle": { "interval": "month", "frequency": 1 }
+                    }
+                }]
+            }
+        });
+        let update = translate_value(&event).unwrap().unwrap();
+        assert_eq!(update.status, Some(BillingStatus::PastDue));
+        assert_eq!(update.event_id, "evt_01");
+        assert_eq!(update.provider_customer_id.as_deref(), Some("ctm_01"));
+        assert_eq!(update.plan.as_deref(), Some("Pro"));
+        assert_eq!(update.seats, Some(3));
+        assert_eq!(update.interval, Some(BillingInterval::Month));
+        assert_eq!(update.mrr, Some(4500));
+        assert_eq!(update.currency.as_deref(), Some("usd"));
+
+        let mut event = event;
+        event["event_type"] = json!("subscription.canceled");
+        event["data"]["status"] = json!("canceled");
+        let update = translate_value(&event).unwrap().unwrap();
+        assert_eq!(update.status, Some(BillingStatus::Churned));
+        assert_eq!(update.mrr, Some(0));
+    }
+
+    #[test]
+    fn test_transaction_and_refund_events() {
+        let event = json!({
+            "event_id": "evt_02",
+            "event_type": "transaction.completed",
+            "data": {
+                "id": "txn_01",
+                "customer_id": "ctm_01",
+                "invoice_number": "325-10566",
+                "currency_code": "EUR",
+                "details": { "totals": { "grand_total": "4900" } }
+            }
+        });
+        let update = translate_value(&event).unwrap().unwrap();
+        assert_eq!(update.status, None);
+        assert_eq!(update.activity, Some(BillingActivity::InvoicePaid));
+        assert_eq!(update.invoice_id.as_deref(), Some("325-10566"));
+        assert_eq!(update.amount, Some(4900));
+        assert_eq!(update.currency.as_deref(), Some("eur"));
+
+        let event = json!({
+            "event_id": "evt_03",
+            "event_type": "adjustment.created",
+            "data": {
+                "id": "adj_01",
+                "action": "refund",
+                "transaction_id": "txn_01",
+                "customer_id": "ctm_01",
+                "currency_code": "EUR",
+                "totals": { "total": "1000" }
+            }
+        });
+        let update = translate_value(&event).unwrap().unwrap();
+        assert_eq!(update.status, None);
+        assert_eq!(update.activity, Some(BillingActivity::Refund));
+        assert_eq!(update.invoice_id.as_deref(), Some("txn_01"));
+        assert_eq!(update.amount, Some(1000));
+    }
+
+    #[test]
+    fn test_unhandled_and_invalid_events() {
+        let event = json!({
+            "event_id": "evt_04",
+            "event_type": "adjustment.created",
+            "data": { "action": "credit" }
+        });
+        assert_eq!(translate_value(&event).unwrap(), None);
+
+        assert!(matches!(
+            translate_value(&json!({ "event_type": "transaction.completed" })),
+            Err(Error::Validation(_))
+        ));
+        assert!(matches!(translate(b"{"), Err(Error::Serialization(_))));
+    }
+}
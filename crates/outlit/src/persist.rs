@@ -0,0 +1,477 @@
+//! Disk-backed storage for queued events.
+//!
+//! Events are stored as JSON lines. New events are appended, and sent events
+//! are marked removed by appending a line with their IDs. The file is only
+//! rewritten once it is empty or mostly removed events, so a process killed
+//! at any point loses at most a partially written line.
+//!
+//! Stores are written by a [`StoreWriter`] on a background thread, so
+//! enqueueing never waits on disk I/O, and writes queued together share a
+//! single sync.
+
+use crate::types::TrackerEvent;
+use crate::Error;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread::JoinHandle;
+use tokio::sync::oneshot;
+use tracing::warn;
+
+/// Number of removed events a file may hold before it is compacted, as long
+/// as they outnumber the events still queued.
+const COMPACT_AFTER: usize = 1_000;
+
+/// Append-only event log backing an [`EventQueue`](crate::queue::EventQueue).
+#[derive(Debug)]
+pub struct DiskStore {
+    path: PathBuf,
+    /// Events in the file that haven't been removed.
+    live: usize,
+    /// Events in the file that have been removed.
+    removed: usize,
+}
+
+/// A line marking events as removed.
+#[derive(Serialize, Deserialize)]
+struct Removed<'a> {
+    #[serde(borrow)]
+    removed: Vec<&'a str>,
+}
+
+impl DiskStore {
+    /// Open the store at `path`, returning any events left from a previous run.
+    pub fn open(path: impl Into<PathBuf>) -> Result<(Self, Vec<TrackerEvent>), Error> {
+        let path = path.into();
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut events = match File::open(&path) {
+            Ok(file) => read_events(BufReader::new(file))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+        // Removals are recorded by ID, so events from older files need one
+        events.iter_mut().for_each(TrackerEvent::ensure_event_id);
+
+        let mut store = Self {
+            path,
+            live: 0,
+            removed: 0,
+        };
+        // Rewrite to drop removed events and any partially written trailing
+        // line
+        store.rewrite(&events)?;
+
+        Ok((store, events))
+    }
+
+    /// Get the path of the backing file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append several events to the log with a single sync.
+    pub fn append_many(&mut self, events: &[TrackerEvent]) -> Result<(), Error> {
+        let mut lines = Vec::new();
+        for event in events {
+            serde_json::to_writer(&mut lines, event)?;
+            lines.push(b'\n');
+        }
+        self.write(&lines)?;
+        self.live += events.len();
+        Ok(())
+    }
+
+    /// Mark the events with `ids` as removed.
+    ///
+    /// The file is truncated once no events are left, and compacted once it
+    /// holds more than [`COMPACT_AFTER`] removed events and they outnumber
+    /// the rest.
+    pub fn remove(&mut self, ids: &[String]) -> Result<(), Error> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+        self.live = self.live.saturating_sub(ids.len());
+        self.removed += ids.len();
+        if self.live == 0 {
+            return self.rewrite(&[]);
+        }
+
+        let removed = Removed {
+            removed: ids.iter().map(String::as_str).collect(),
+        };
+        let mut line = serde_json::to_vec(&removed)?;
+        line.push(b'\n');
+        self.write(&line)?;
+
+        if self.removed > COMPACT_AFTER && self.removed > self.live {
+            let events = read_events(BufReader::new(File::open(&self.path)?))?;
+            self.rewrite(&events)?;
+        }
+        Ok(())
+    }
+
+    /// Replace the log contents with `events`.
+    pub fn rewrite(&mut self, events: &[TrackerEvent]) -> Result<(), Error> {
+        let tmp = tmp_path(&self.path);
+        {
+            let mut writer = BufWriter::new(File::create(&tmp)?);
+            for event in events {
+                serde_json::to_writer(&mut writer, event)?;
+                writer.write_all(b"\n")?;
+            }
+            writer.flush()?;
+            writer.get_ref().sync_data()?;
+        }
+        fs::rename(&tmp, &self.path)?;
+        self.live = events.len();
+        self.removed = 0;
+        Ok(())
+    }
+
+    /// Append `lines` to the log and sync them.
+    fn write(&self, lines: &[u8]) -> Result<(), Error> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        file.write_all(lines)?;
+        file.sync_data()?;
+        Ok(())
+    }
+}
+
+/// Get the temp file a rewrite of `path` goes through.
+///
+/// `.tmp` is appended rather than swapped for the extension, so per-project
+/// files like `queue.jsonl.growth` don't share a temp file.
+fn tmp_path(path: &Path) -> PathBuf {
+    let mut tmp = path.as_os_str().to_os_string();
+    tmp.push(".tmp");
+    PathBuf::from(tmp)
+}
+
+/// Read the events in a log, leaving out removed ones.
+fn read_events(reader: impl BufRead) -> Result<Vec<TrackerEvent>, Error> {
+    let mut events = Vec::new();
+    let mut removed = HashSet::new();
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if line.starts_with(r#"{"removed":"#) {
+            match serde_json::from_str::<Removed>(&line) {
+                Ok(line) => removed.extend(line.removed.into_iter().map(String::from)),
+                Err(e) => warn!(error = %e, "skipping unreadable removal"),
+            }
+            continue;
+        }
+        match serde_json::from_str(&line) {
+            Ok(event) => events.push(event),
+            Err(e) => warn!(error = %e, "skipping unreadable persisted event"),
+        }
+    }
+    if !removed.is_empty() {
+        events.retain(|event: &TrackerEvent| {
+            event.event_id().map_or(true, |id| !removed.contains(id))
+        });
+    }
+    Ok(events)
+}
+
+/// Storage backing a persistent [`EventQueue`](crate::queue::EventQueue).
+#[derive(Debug)]
+pub enum Store {
+    /// JSON lines in a file.
+    File(DiskStore),
+    /// Rows in an SQLite database.
+    #[cfg(feature = "sqlite")]
+    Sqlite(crate::sqlite::SqliteStore),
+}
+
+impl Store {
+    /// Get the path of the backing file.
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::File(store) => store.path(),
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(store) => store.path(),
+        }
+    }
+
+    /// Append several events.
+    pub fn append_many(&mut self, events: &[TrackerEvent]) -> Result<(), Error> {
+        match self {
+            Self::File(store) => store.append_many(events),
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(store) => store.append_many(events),
+        }
+    }
+
+    /// Remove the events with `ids`.
+    pub fn remove(&mut self, ids: &[String]) -> Result<(), Error> {
+        match self {
+            Self::File(store) => store.remove(ids),
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(store) => store.remove(ids),
+        }
+    }
+}
+
+/// A write waiting for the [`StoreWriter`] thread.
+#[derive(Debug)]
+enum StoreWrite {
+    Append(Vec<TrackerEvent>),
+    Remove(Vec<String>),
+    /// Signalled once the writes queued before it are applied.
+    Sync(oneshot::Sender<()>),
+}
+
+/// Writes to a [`Store`] on a background thread.
+///
+/// Writes are applied in the order they are queued. Dropping the writer
+/// waits for the thread to apply the writes already queued.
+#[derive(Debug)]
+pub struct StoreWriter {
+    sender: Option<mpsc::Sender<StoreWrite>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl StoreWriter {
+    /// Start a thread writing to `store`.
+    pub fn spawn(store: Store) -> Result<Self, Error> {
+        let (sender, receiver) = mpsc::channel();
+        let thread = std::thread::Builder::new()
+            .name("outlit-persist".into())
+            .spawn(move || write_loop(store, receiver))?;
+        Ok(Self {
+            sender: Some(sender),
+            thread: Some(thread),
+        })
+    }
+
+    /// Queue `events` to be appended.
+    pub fn append(&self, events: Vec<TrackerEvent>) {
+        if !events.is_empty() {
+            self.send(StoreWrite::Append(events));
+        }
+    }
+
+    /// Queue the events with `ids` to be removed.
+    pub fn remove(&self, ids: Vec<String>) {
+        if !ids.is_empty() {
+            self.send(StoreWrite::Remove(ids));
+        }
+    }
+
+    /// Wait until the writes queued so far have been applied.
+    pub async fn sync(&self) {
+        let (done, applied) = oneshot::channel();
+        self.send(StoreWrite::Sync(done));
+        let _ = applied.await;
+    }
+
+    fn send(&self, write: StoreWrite) {
+        if let Some(sender) = &self.sender {
+            // Only fails if the thread panicked, which was already reported
+            let _ = sender.send(write);
+        }
+    }
+}
+
+impl Drop for StoreWriter {
+    fn drop(&mut self) {
+        // Closing the channel stops the thread once it is drained
+        self.sender.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Apply writes from `receiver` until the [`StoreWriter`] is dropped.
+///
+/// Writes that queued up while the previous ones were applied are handled
+/// together: all appends in one write, then all removals in another. That
+/// is safe because an event is always appended before it is removed.
+fn write_loop(mut store: Store, receiver: mpsc::Receiver<StoreWrite>) {
+    while let Ok(first) = receiver.recv() {
+        let mut appended = Vec::new();
+        let mut removed = Vec::new();
+        let mut synced = Vec::new();
+        for write in std::iter::once(first).chain(receiver.try_iter()) {
+            match write {
+                StoreWrite::Append(events) => appended.extend(events),
+                StoreWrite::Remove(ids) => removed.extend(ids),
+                StoreWrite::Sync(done) => synced.push(done),
+            }
+        }
+
+        if !appended.is_empty() {
+            if let Err(e) = store.append_many(&appended) {
+                tracing::error!(error = %e, path = %store.path().display(), "failed to persist events");
+            }
+        }
+        if let Err(e) = store.remove(&removed) {
+            tracing::error!(error = %e, path = %store.path().display(), "failed to persist queue");
+        }
+        for done in synced {
+            let _ = done.send(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::CustomEventData;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("outlit-persist-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    fn make_test_event(id: i32) -> TrackerEvent {
+        TrackerEvent::Custom(CustomEventData {
+            timestamp: 1706400000000,
+            url: format!("server://test{}", id),
+            path: "/".into(),
+            event_name: format!("event_{}", id),
+            properties: None,
+            message_id: None,
+            event_id: Some(format!("evt_{id}")),
+        })
+    }
+
+    #[test]
+    fn test_open_missing_file_is_empty() {
+        let path = temp_path("missing.jsonl");
+        let (_, events) = DiskStore::open(&path).unwrap();
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn test_append_and_reload() {
+        let path = temp_path("reload.jsonl");
+        let (mut store, _) = DiskStore::open(&path).unwrap();
+
+        store.append_many(&[make_test_event(1)]).unwrap();
+        store.append_many(&[make_test_event(2)]).unwrap();
+
+        let (_, events) = DiskStore::open(&path).unwrap();
+        assert_eq!(events.len(), 2);
+        if let TrackerEvent::Custom(e) = &events[1] {
+            assert_eq!(e.event_name, "event_2");
+        } else {
+            panic!("Expected custom event");
+        }
+    }
+
+    #[test]
+    fn test_rewrite_replaces_contents() {
+        let path = temp_path("rewrite.jsonl");
+        let (mut store, _) = DiskStore::open(&path).unwrap();
+
+        store.append_many(&[make_test_event(1)]).unwrap();
+        store.append_many(&[make_test_event(2)]).unwrap();
+        store.rewrite(&[make_test_event(2)]).unwrap();
+
+        let (_, events) = DiskStore::open(&path).unwrap();
+        assert_eq!(events.len(), 1);
+
+        store.rewrite(&[]).unwrap();
+        assert_eq!(fs::read(&path).unwrap().len(), 0);
+    }
+
+    #[test]
+    fn test_removed_events_skipped_on_reload() {
+        let path = temp_path("remove.jsonl");
+        let (mut store, _) = DiskStore::open(&path).unwrap();
+        let events: Vec<_> = (1..=3).map(make_test_event).collect();
+        store.append_many(&events).unwrap();
+
+        store.remove(&["evt_1".into(), "evt_3".into()]).unwrap();
+        let (mut store, reloaded) = DiskStore::open(&path).unwrap();
+        assert_eq!(reloaded.len(), 1);
+        assert_eq!(reloaded[0].event_name(), Some("event_2"));
+
+        // Removing the last event empties the file
+        store.remove(&["evt_2".into()]).unwrap();
+        assert_eq!(fs::read(&path).unwrap().len(), 0);
+    }
+
+    #[test]
+    fn test_file_compacted_once_mostly_removed() {
+        let path = temp_path("compact.jsonl");
+        let (mut store, _) = DiskStore::open(&path).unwrap();
+        let events: Vec<_> = (0..=COMPACT_AFTER as i32 * 2)
+            .map(make_test_event)
+            .collect();
+        store.append_many(&events).unwrap();
+
+        let ids: Vec<String> = (0..=COMPACT_AFTER).map(|id| format!("evt_{id}")).collect();
+        store.remove(&ids).unwrap();
+
+        let contents = fs::read_to_string(&path).unwrap();
+        assert_eq!(contents.lines().count(), COMPACT_AFTER);
+        assert!(!contents.contains("removed"));
+    }
+
+    #[tokio::test]
+    async fn test_writer_applies_writes_in_order() {
+        let path = temp_path("writer.jsonl");
+        let (store, _) = DiskStore::open(&path).unwrap();
+        let writer = StoreWriter::spawn(Store::File(store)).unwrap();
+
+        writer.append(vec![make_test_event(1), make_test_event(2)]);
+        writer.remove(vec!["evt_1".into()]);
+        writer.append(vec![make_test_event(3)]);
+        writer.sync().await;
+
+        let (_, events) = DiskStore::open(&path).unwrap();
+        let names: Vec<_> = events.iter().filter_map(TrackerEvent::event_name).collect();
+        assert_eq!(names, ["event_2", "event_3"]);
+
+        // Dropping the writer finishes queued writes
+        writer.remove(vec!["evt_2".into()]);
+        drop(writer);
+        let (_, events) = DiskStore::open(&path).unwrap();
+        assert_eq!(events.len(), 1);
+    }
+
+    #[test]
+    fn test_tmp_path_keeps_full_file_name() {
+        assert_eq!(
+            tmp_path(Path::new("data/queue.jsonl.growth")),
+            Path::new("data/queue.jsonl.growth.tmp")
+        );
+        assert_ne!(
+            tmp_path(Path::new("queue.jsonl.growth")),
+            tmp_path(Path::new("queue.jsonl.billing"))
+        );
+        assert_eq!(tmp_path(Path::new("queue.tmp")), Path::new("queue.tmp.tmp"));
+    }
+
+    #[test]
+    fn test_partial_trailing_line_is_skipped() {
+        let path = temp_path("partial.jsonl");
+        let (mut store, _) = DiskStore::open(&path).unwrap();
+        store.append_many(&[make_test_event(1)]).unwrap();
+
+        // Simulate a write interrupted by a crash
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"{\"type\":\"custom\",\"timest").unwrap();
+
+        let (_, events) = DiskStore::open(&path).unwrap();
+        assert_eq!(events.len(), 1);
+    }
+}
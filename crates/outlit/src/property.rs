@@ -0,0 +1,116 @@
+//! Typed property values.
+
+use crate::Error;
+use serde_json::Value;
+
+/// A typed property value that converts cleanly into JSON.
+///
+/// Any `impl Into<serde_json::Value>` is still accepted by `.property()`,
+/// but `PropertyValue` makes the intended JSON type explicit and rejects
+/// non-finite floats, which would otherwise serialize to `null`.
+///
+/// # Example
+///
+/// ```rust
+/// use outlit::PropertyValue;
+///
+/// let seats = PropertyValue::int(5);
+/// let price = PropertyValue::float(49.99)?;
+/// let tags = PropertyValue::list([PropertyValue::string("a"), PropertyValue::string("b")]);
+/// # let _ = (seats, price, tags);
+/// # Ok::<(), outlit::Error>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropertyValue(Value);
+
+impl PropertyValue {
+    /// Create a string value.
+    pub fn string(value: impl Into<String>) -> Self {
+        Self(Value::String(value.into()))
+    }
+
+    /// Create an integer value.
+    pub fn int(value: i64) -> Self {
+        Self(Value::from(value))
+    }
+
+    /// Create a float value.
+    ///
+    /// Returns an error if the value is NaN or infinite.
+    pub fn float(value: f64) -> Result<Self, Error> {
+        serde_json::Number::from_f64(value)
+            .map(|n| Self(Value::Number(n)))
+            .ok_or_else(|| Error::Validation(format!("property value {value} is not finite")))
+    }
+
+    /// Create a boolean value.
+    pub fn bool(value: bool) -> Self {
+        Self(Value::Bool(value))
+    }
+
+    /// Create a list value.
+    pub fn list(values: impl IntoIterator<Item = PropertyValue>) -> Self {
+        Self(Value::Array(values.into_iter().map(|v| v.0).collect()))
+    }
+
+    /// Get the underlying JSON value.
+    pub fn as_value(&self) -> &Value {
+        &self.0
+    }
+}
+
+impl From<PropertyValue> for Value {
+    fn from(value: PropertyValue) -> Value {
+        value.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_typed_constructors_round_trip() {
+        assert_eq!(Value::from(PropertyValue::string("pro")), json!("pro"));
+        assert_eq!(Value::from(PropertyValue::int(-42)), json!(-42));
+        assert_eq!(Value::from(PropertyValue::float(1.5).unwrap()), json!(1.5));
+        assert_eq!(Value::from(PropertyValue::bool(true)), json!(true));
+        assert_eq!(
+            Value::from(PropertyValue::list([
+                PropertyValue::int(1),
+                PropertyValue::string("two"),
+            ])),
+            json!([1, "two"])
+        );
+    }
+
+    #[test]
+    fn test_float_rejects_nan() {
+        let result = PropertyValue::float(f64::NAN);
+        assert!(matches!(result, Err(Error::Validation(_))));
+    }
+
+    #[test]
+    fn test_float_rejects_infinity() {
+        assert!(PropertyValue::float(f64::INFINITY).is_err());
+        assert!(PropertyValue::float(f64::NEG_INFINITY).is_err());
+    }
+
+    #[test]
+    fn test_property_value_accepted_by_builder() {
+        use crate::builders::{BuildEvent, TrackBuilder};
+        use crate::email;
+        use crate::types::TrackerEvent;
+
+        let event = TrackBuilder::new("signup", email("user@example.com"))
+            .property("seats", PropertyValue::int(5))
+            .build();
+
+        if let TrackerEvent::Custom(data) = event {
+            assert_eq!(data.properties.unwrap().get("seats").unwrap(), &json!(5));
+        } else {
+            panic!("Expected custom event");
+        }
+    }
+}
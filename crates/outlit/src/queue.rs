@@ -1,41 +1,204 @@
 //! Event queue with batching.
 
+#[cfg(feature = "persist")]
+use crate::persist::{DiskStore, Store, StoreWriter};
 use crate::types::TrackerEvent;
-use std::sync::Arc;
-use tokio::sync::Mutex;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, MutexGuard};
+use tokio::sync::Notify;
+use tokio::time::Instant;
 
 /// Event queue that batches events for sending.
+///
+/// Events are only removed once a send is confirmed: a flush first
+/// [`reserve`](EventQueue::reserve)s a prefix of the queue, then either
+/// [`commit`](EventQueue::commit)s or [`rollback`](EventQueue::rollback)s it.
+/// Events enqueued while a reservation is in flight are appended behind it,
+/// so ordering is preserved whether or not the send succeeds.
+///
+/// Each event's failed delivery attempts are counted, and with
+/// [`with_max_attempts`](EventQueue::with_max_attempts) events are removed
+/// once they reach the limit instead of being kept for another flush.
 #[derive(Debug)]
 pub struct EventQueue {
-    events: Arc<Mutex<Vec<TrackerEvent>>>,
+    state: Arc<Mutex<QueueState>>,
+    /// Notified whenever a reservation is released.
+    released: Arc<Notify>,
     max_size: usize,
+    max_attempts: Option<u32>,
+    #[cfg(feature = "persist")]
+    store: Option<StoreWriter>,
+}
+
+#[derive(Debug, Default)]
+struct QueueState {
+    events: Vec<TrackerEvent>,
+    /// When each event in `events` was enqueued.
+    enqueued_at: Vec<Instant>,
+    /// Failed delivery attempts for each event in `events`.
+    attempts: Vec<u32>,
+    /// Number of events at the front of `events` held by a reservation.
+    reserved: usize,
+}
+
+fn lock(state: &Mutex<QueueState>) -> MutexGuard<'_, QueueState> {
+    state.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Events reserved for an in-flight send.
+///
+/// Should be passed back to [`EventQueue::commit`] or [`EventQueue::rollback`].
+/// Dropping it without doing so (e.g. when a flush is cancelled by a timeout)
+/// rolls it back.
+#[derive(Debug)]
+pub struct Reservation {
+    /// Copies of the reserved events, in queue order.
+    pub events: Vec<TrackerEvent>,
+    count: usize,
+    state: Arc<Mutex<QueueState>>,
+    released: bool,
+    released_signal: Arc<Notify>,
+}
+
+impl Drop for Reservation {
+    fn drop(&mut self) {
+        if !self.released {
+            lock(&self.state).reserved = 0;
+        }
+        // Committed and rolled back reservations are dropped after the
+        // queue lock is released, so waiters can reserve straight away
+        self.released_signal.notify_waiters();
+    }
 }
 
 impl EventQueue {
     /// Create a new event queue.
     pub fn new(max_size: usize) -> Self {
         Self {
-            events: Arc::new(Mutex::new(Vec::new())),
+            state: Arc::new(Mutex::new(QueueState::default())),
+            released: Arc::new(Notify::new()),
             max_size,
+            max_attempts: None,
+            #[cfg(feature = "persist")]
+            store: None,
+        }
+    }
+
+    /// Create an event queue backed by a file on disk.
+    ///
+    /// Events left over from a previous run are loaded into the queue.
+    #[cfg(feature = "persist")]
+    pub fn persistent(
+        max_size: usize,
+        path: impl Into<std::path::PathBuf>,
+    ) -> Result<Self, crate::Error> {
+        let (store, events) = DiskStore::open(path)?;
+        Self::with_store(max_size, Store::File(store), events)
+    }
+
+    /// Create an event queue backed by an SQLite database.
+    ///
+    /// Events left over from a previous run are loaded into the queue.
+    #[cfg(feature = "sqlite")]
+    pub fn sqlite(
+        max_size: usize,
+        path: impl Into<std::path::PathBuf>,
+    ) -> Result<Self, crate::Error> {
+        let (store, events) = crate::sqlite::SqliteStore::open(path)?;
+        Self::with_store(max_size, Store::Sqlite(store), events)
+    }
+
+    #[cfg(feature = "persist")]
+    fn with_store(
+        max_size: usize,
+        store: Store,
+        mut events: Vec<TrackerEvent>,
+    ) -> Result<Self, crate::Error> {
+        events.iter_mut().for_each(TrackerEvent::ensure_event_id);
+        if !events.is_empty() {
+            tracing::info!(event_count = events.len(), "recovered persisted events");
         }
+        let store = StoreWriter::spawn(store)?;
+        Ok(Self {
+            state: Arc::new(Mutex::new(QueueState {
+                enqueued_at: vec![Instant::now(); events.len()],
+                attempts: vec![0; events.len()],
+                events,
+                reserved: 0,
+            })),
+            released: Arc::new(Notify::new()),
+            max_size,
+            max_attempts: None,
+            store: Some(store),
+        })
+    }
+
+    /// Give up on events after `max_attempts` failed delivery attempts.
+    ///
+    /// `None` keeps events queued until they are delivered.
+    pub fn with_max_attempts(mut self, max_attempts: Option<u32>) -> Self {
+        self.max_attempts = max_attempts;
+        self
     }
 
     /// Add an event to the queue.
     pub async fn enqueue(&self, event: TrackerEvent) {
-        let mut events = self.events.lock().await;
-        events.push(event);
+        self.enqueue_nowait(event);
+    }
+
+    /// Add an event to the queue from synchronous code, returning the new
+    /// queue length.
+    pub fn enqueue_nowait(&self, mut event: TrackerEvent) -> usize {
+        // Attempts are matched back to requeued events by ID
+        event.ensure_event_id();
+        let mut state = lock(&self.state);
+        self.persist_appended(std::slice::from_ref(&event));
+        state.events.push(event);
+        state.enqueued_at.push(Instant::now());
+        state.attempts.push(0);
+        state.events.len()
+    }
+
+    /// Add several events to the queue under a single lock acquisition.
+    pub async fn enqueue_many(&self, mut events: Vec<TrackerEvent>) {
+        if events.is_empty() {
+            return;
+        }
+        events.iter_mut().for_each(TrackerEvent::ensure_event_id);
+        let mut state = lock(&self.state);
+        self.persist_appended(&events);
+        let now = Instant::now();
+        state
+            .enqueued_at
+            .extend(std::iter::repeat(now).take(events.len()));
+        state
+            .attempts
+            .extend(std::iter::repeat(0).take(events.len()));
+        state.events.extend(events);
     }
 
     /// Check if the queue should be flushed.
     pub async fn should_flush(&self) -> bool {
-        let events = self.events.lock().await;
-        events.len() >= self.max_size
+        let state = lock(&self.state);
+        state.events.len() >= self.max_size
+    }
+
+    /// Check if the queue has reached its high-water mark (80% of the max
+    /// size), at which point a background flush should be started.
+    pub async fn above_high_water(&self) -> bool {
+        let state = lock(&self.state);
+        state.events.len() >= self.high_water_mark()
     }
 
-    /// Get the number of events in the queue.
+    /// Queue length at which a background flush should be started.
+    pub fn high_water_mark(&self) -> usize {
+        (self.max_size * 4 / 5).max(1)
+    }
+
+    /// Get the number of events in the queue, including reserved ones.
     pub async fn len(&self) -> usize {
-        let events = self.events.lock().await;
-        events.len()
+        let state = lock(&self.state);
+        state.events.len()
     }
 
     /// Check if the queue is empty.
@@ -43,23 +206,252 @@ impl EventQueue {
         self.len().await == 0
     }
 
-    /// Drain all events from the queue.
-    pub async fn drain(&self) -> Vec<TrackerEvent> {
-        let mut events = self.events.lock().await;
-        std::mem::take(&mut *events)
+    /// Get when the oldest event in the queue was enqueued.
+    pub async fn oldest_enqueued_at(&self) -> Option<Instant> {
+        let state = lock(&self.state);
+        state.enqueued_at.first().copied()
     }
 
-    /// Prepend events to the front of the queue.
-    /// Used to requeue events after a failed send.
-    pub async fn requeue(&self, events_to_add: Vec<TrackerEvent>) {
-        if events_to_add.is_empty() {
-            return;
+    /// Reserve up to `n` events from the front of the queue.
+    ///
+    /// The events stay in the queue until the reservation is committed.
+    /// Returns `None` if the queue is empty or another reservation is
+    /// already in flight.
+    pub async fn reserve(&self, n: usize) -> Option<Reservation> {
+        let mut state = lock(&self.state);
+        if state.reserved > 0 {
+            return None;
+        }
+        self.reserve_locked(&mut state, n)
+    }
+
+    /// Reserve up to `n` events from the front of the queue, first waiting
+    /// for any reservation in flight to be released.
+    ///
+    /// Returns `None` if the queue is empty once nothing is in flight.
+    pub async fn reserve_when_released(&self, n: usize) -> Option<Reservation> {
+        loop {
+            // Created before checking, so a release in between isn't missed
+            let released = self.released.notified();
+            {
+                let mut state = lock(&self.state);
+                if state.reserved == 0 {
+                    return self.reserve_locked(&mut state, n);
+                }
+            }
+            tracing::debug!("waiting for in-flight flush");
+            released.await;
+        }
+    }
+
+    fn reserve_locked(&self, state: &mut QueueState, n: usize) -> Option<Reservation> {
+        if state.events.is_empty() || n == 0 {
+            return None;
+        }
+        let count = n.min(state.events.len());
+        state.reserved = count;
+        Some(Reservation {
+            events: state.events[..count].to_vec(),
+            count,
+            state: self.state.clone(),
+            released: false,
+            released_signal: self.released.clone(),
+        })
+    }
+
+    /// Remove reserved events from the queue after a successful send.
+    pub async fn commit(&self, mut reservation: Reservation) {
+        reservation.released = true;
+        let mut state = lock(&self.state);
+        debug_assert_eq!(state.reserved, reservation.count);
+        let sent: Vec<_> = state.events.drain(..reservation.count).collect();
+        state.enqueued_at.drain(..reservation.count);
+        state.attempts.drain(..reservation.count);
+        state.reserved = 0;
+        self.persist_removed(&sent);
+    }
+
+    /// Remove reserved events after a partly successful send, putting
+    /// `unsent` back at the front of the queue as a failed attempt.
+    ///
+    /// Returns the unsent events that reached the maximum number of
+    /// attempts, which are removed instead.
+    pub async fn commit_partial(
+        &self,
+        mut reservation: Reservation,
+        unsent: Vec<TrackerEvent>,
+    ) -> Vec<TrackerEvent> {
+        reservation.released = true;
+        let mut state = lock(&self.state);
+        debug_assert_eq!(state.reserved, reservation.count);
+        let previous: HashMap<&str, u32> = state.events[..reservation.count]
+            .iter()
+            .zip(&state.attempts)
+            .filter_map(|(event, &attempts)| Some((event.event_id()?, attempts)))
+            .collect();
+        let attempts: Vec<u32> = unsent
+            .iter()
+            .map(|event| {
+                let previous = event.event_id().and_then(|id| previous.get(id));
+                previous.copied().unwrap_or(0) + 1
+            })
+            .collect();
+        let (unsent, attempts, exhausted) = self.split_exhausted(unsent, attempts);
+        let kept: HashSet<String> = unsent
+            .iter()
+            .filter_map(TrackerEvent::event_id)
+            .map(String::from)
+            .collect();
+
+        // Unsent events keep the age of the oldest reserved event
+        let oldest = state.enqueued_at[0];
+        state.enqueued_at.splice(
+            ..reservation.count,
+            std::iter::repeat(oldest).take(unsent.len()),
+        );
+        state.attempts.splice(..reservation.count, attempts);
+        let mut sent: Vec<_> = state.events.splice(..reservation.count, unsent).collect();
+        state.reserved = 0;
+        sent.retain(|event| event.event_id().map_or(true, |id| !kept.contains(id)));
+        self.persist_removed(&sent);
+        exhausted
+    }
+
+    /// Remove all events that are not reserved by an in-flight send.
+    ///
+    /// Returns the number of events removed.
+    pub async fn clear(&self) -> usize {
+        self.take_unreserved().await.len()
+    }
+
+    /// Remove and return all events that are not reserved by an in-flight
+    /// send, in order.
+    pub async fn take_unreserved(&self) -> Vec<TrackerEvent> {
+        let mut state = lock(&self.state);
+        let reserved = state.reserved;
+        let removed = state.events.split_off(reserved);
+        state.enqueued_at.truncate(reserved);
+        state.attempts.truncate(reserved);
+        self.persist_removed(&removed);
+        removed
+    }
+
+    /// Remove the events not reserved by an in-flight send for which
+    /// `remove` returns `true`.
+    ///
+    /// Returns the number of events removed.
+    pub async fn remove_unreserved(&self, mut remove: impl FnMut(&TrackerEvent) -> bool) -> usize {
+        let mut state = lock(&self.state);
+        let state = &mut *state;
+        let mut removed = Vec::new();
+        let mut i = state.reserved;
+        while i < state.events.len() {
+            if remove(&state.events[i]) {
+                removed.push(state.events.remove(i));
+                state.enqueued_at.remove(i);
+                state.attempts.remove(i);
+            } else {
+                i += 1;
+            }
+        }
+        self.persist_removed(&removed);
+        removed.len()
+    }
+
+    /// Release reserved events after a failed send, leaving them in place.
+    ///
+    /// Returns the events that reached the maximum number of attempts, which
+    /// are removed instead.
+    pub async fn rollback(&self, mut reservation: Reservation) -> Vec<TrackerEvent> {
+        reservation.released = true;
+        let mut state = lock(&self.state);
+        debug_assert_eq!(state.reserved, reservation.count);
+        state.reserved = 0;
+        for attempts in &mut state.attempts[..reservation.count] {
+            *attempts += 1;
+        }
+        let Some(max_attempts) = self.max_attempts else {
+            return Vec::new();
+        };
+
+        let exhausted_at: Vec<usize> = (0..reservation.count)
+            .filter(|&i| state.attempts[i] >= max_attempts)
+            .collect();
+        if exhausted_at.is_empty() {
+            return Vec::new();
+        }
+        let mut exhausted = Vec::with_capacity(exhausted_at.len());
+        for &i in exhausted_at.iter().rev() {
+            state.enqueued_at.remove(i);
+            state.attempts.remove(i);
+            exhausted.push(state.events.remove(i));
+        }
+        exhausted.reverse();
+        self.persist_removed(&exhausted);
+        exhausted
+    }
+
+    /// Split off events that have reached the maximum number of attempts.
+    ///
+    /// Returns the remaining events and their attempt counts, in order, and
+    /// the exhausted events.
+    fn split_exhausted(
+        &self,
+        events: Vec<TrackerEvent>,
+        attempts: Vec<u32>,
+    ) -> (Vec<TrackerEvent>, Vec<u32>, Vec<TrackerEvent>) {
+        let Some(max_attempts) = self.max_attempts else {
+            return (events, attempts, Vec::new());
+        };
+        let mut kept = (Vec::new(), Vec::new());
+        let mut exhausted = Vec::new();
+        for (event, attempts) in events.into_iter().zip(attempts) {
+            if attempts >= max_attempts {
+                exhausted.push(event);
+            } else {
+                kept.0.push(event);
+                kept.1.push(attempts);
+            }
         }
-        let mut events = self.events.lock().await;
-        let mut combined = events_to_add;
-        combined.append(&mut *events);
-        *events = combined;
+        (kept.0, kept.1, exhausted)
     }
+
+    /// Wait until the backing store has caught up with the queue.
+    ///
+    /// Writes to the store happen in the background; call this before
+    /// exiting so the events queued last aren't lost.
+    pub async fn sync_store(&self) {
+        #[cfg(feature = "persist")]
+        if let Some(store) = &self.store {
+            store.sync().await;
+        }
+    }
+
+    /// Queue `events` to be appended to the backing store.
+    ///
+    /// Called with the state locked, so the store sees events in queue
+    /// order.
+    #[cfg(feature = "persist")]
+    fn persist_appended(&self, events: &[TrackerEvent]) {
+        if let Some(store) = &self.store {
+            store.append(events.to_vec());
+        }
+    }
+
+    #[cfg(not(feature = "persist"))]
+    fn persist_appended(&self, _events: &[TrackerEvent]) {}
+
+    /// Queue `events` to be removed from the backing store.
+    #[cfg(feature = "persist")]
+    fn persist_removed(&self, events: &[TrackerEvent]) {
+        if let Some(store) = &self.store {
+            let ids = events.iter().filter_map(TrackerEvent::event_id);
+            store.remove(ids.map(String::from).collect());
+        }
+    }
+
+    #[cfg(not(feature = "persist"))]
+    fn persist_removed(&self, _events: &[TrackerEvent]) {}
 }
 
 #[cfg(test)]
@@ -76,6 +468,8 @@ mod tests {
             path: "/".into(),
             event_name: format!("event_{}", id),
             properties: Some(HashMap::from([("id".into(), json!(id))])),
+            message_id: None,
+            event_id: None,
         })
     }
 
@@ -94,6 +488,92 @@ mod tests {
         assert_eq!(queue.len().await, 2);
     }
 
+    #[tokio::test]
+    async fn test_enqueue_many_preserves_order() {
+        let queue = EventQueue::new(10);
+
+        queue.enqueue(make_test_event(1)).await;
+        queue
+            .enqueue_many(vec![make_test_event(2), make_test_event(3)])
+            .await;
+        queue.enqueue(make_test_event(4)).await;
+
+        let events = std::mem::take(&mut queue.reserve(usize::MAX).await.unwrap().events);
+        assert_eq!(
+            event_urls(&events),
+            vec![
+                "server://test1",
+                "server://test2",
+                "server://test3",
+                "server://test4"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_enqueue_many_large_burst() {
+        let queue = EventQueue::new(100);
+        let events: Vec<_> = (0..10_000).map(make_test_event).collect();
+
+        queue.enqueue_many(events).await;
+
+        assert_eq!(queue.len().await, 10_000);
+        assert!(queue.should_flush().await);
+    }
+
+    #[tokio::test]
+    async fn test_commit_partial_keeps_unsent_in_front() {
+        let queue = EventQueue::new(10);
+        for i in 1..=3 {
+            queue.enqueue(make_test_event(i)).await;
+        }
+
+        let reservation = queue.reserve(usize::MAX).await.unwrap();
+        queue.enqueue(make_test_event(4)).await;
+        queue
+            .commit_partial(reservation, vec![make_test_event(3)])
+            .await;
+
+        let events = std::mem::take(&mut queue.reserve(usize::MAX).await.unwrap().events);
+        assert_eq!(
+            event_urls(&events),
+            vec!["server://test3", "server://test4"]
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_oldest_enqueued_at_follows_commits() {
+        let queue = EventQueue::new(10);
+        assert!(queue.oldest_enqueued_at().await.is_none());
+
+        let first = Instant::now();
+        queue.enqueue(make_test_event(1)).await;
+        tokio::time::advance(std::time::Duration::from_secs(1)).await;
+        let second = Instant::now();
+        queue.enqueue(make_test_event(2)).await;
+        assert_eq!(queue.oldest_enqueued_at().await, Some(first));
+
+        let reservation = queue.reserve(1).await.unwrap();
+        queue.commit(reservation).await;
+        assert_eq!(queue.oldest_enqueued_at().await, Some(second));
+
+        queue.clear().await;
+        assert!(queue.oldest_enqueued_at().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_high_water_mark_below_max_size() {
+        let queue = EventQueue::new(5);
+        for i in 0..3 {
+            queue.enqueue(make_test_event(i)).await;
+        }
+        assert!(!queue.above_high_water().await);
+
+        queue.enqueue(make_test_event(3)).await;
+        assert!(queue.above_high_water().await);
+        assert!(!queue.should_flush().await);
+    }
+
     #[tokio::test]
     async fn test_should_flush_at_max_size() {
         let queue = EventQueue::new(3);
@@ -106,19 +586,123 @@ mod tests {
         assert!(queue.should_flush().await);
     }
 
+    fn event_urls(events: &[TrackerEvent]) -> Vec<String> {
+        events
+            .iter()
+            .map(|e| match e {
+                TrackerEvent::Custom(e) => e.url.clone(),
+                _ => panic!("Expected custom event"),
+            })
+            .collect()
+    }
+
     #[tokio::test]
-    async fn test_drain() {
+    async fn test_reserve_and_commit() {
         let queue = EventQueue::new(10);
 
         queue.enqueue(make_test_event(1)).await;
         queue.enqueue(make_test_event(2)).await;
         queue.enqueue(make_test_event(3)).await;
 
-        let events = queue.drain().await;
-        assert_eq!(events.len(), 3);
+        let reservation = queue.reserve(2).await.unwrap();
+        assert_eq!(reservation.events.len(), 2);
+        // Reserved events stay in the queue until committed
+        assert_eq!(queue.len().await, 3);
+
+        queue.commit(reservation).await;
+        assert_eq!(queue.len().await, 1);
+
+        let remaining = queue.reserve(usize::MAX).await.unwrap();
+        assert_eq!(event_urls(&remaining.events), vec!["server://test3"]);
+    }
+
+    #[tokio::test]
+    async fn test_reserve_empty_returns_none() {
+        let queue = EventQueue::new(10);
+        assert!(queue.reserve(10).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_only_one_reservation_at_a_time() {
+        let queue = EventQueue::new(10);
+        queue.enqueue(make_test_event(1)).await;
+
+        let reservation = queue.reserve(10).await.unwrap();
+        assert!(queue.reserve(10).await.is_none());
+
+        queue.rollback(reservation).await;
+        assert!(queue.reserve(10).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_reserve_when_released_waits_for_in_flight_reservation() {
+        let queue = Arc::new(EventQueue::new(10));
+        queue.enqueue(make_test_event(1)).await;
+        let reservation = queue.reserve(10).await.unwrap();
+        queue.enqueue(make_test_event(2)).await;
+
+        let waiter = tokio::spawn({
+            let queue = queue.clone();
+            async move { queue.reserve_when_released(10).await }
+        });
+        tokio::task::yield_now().await;
+        assert!(!waiter.is_finished());
+
+        queue.commit(reservation).await;
+        let reservation = waiter.await.unwrap().unwrap();
+        assert_eq!(event_urls(&reservation.events), vec!["server://test2"]);
+    }
+
+    #[tokio::test]
+    async fn test_clear() {
+        let queue = EventQueue::new(10);
+
+        queue.enqueue(make_test_event(1)).await;
+        queue.enqueue(make_test_event(2)).await;
+
+        assert_eq!(queue.clear().await, 2);
+        assert!(queue.is_empty().await);
+        assert_eq!(queue.clear().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_clear_keeps_reserved_events() {
+        let queue = EventQueue::new(10);
+
+        queue.enqueue(make_test_event(1)).await;
+        let reservation = queue.reserve(10).await.unwrap();
+        queue.enqueue(make_test_event(2)).await;
+
+        assert_eq!(queue.clear().await, 1);
+        assert_eq!(queue.len().await, 1);
+
+        queue.commit(reservation).await;
         assert!(queue.is_empty().await);
     }
 
+    #[tokio::test]
+    async fn test_remove_unreserved() {
+        let queue = EventQueue::new(10);
+
+        queue.enqueue(make_test_event(1)).await;
+        let reservation = queue.reserve(10).await.unwrap();
+        for id in 2..=4 {
+            queue.enqueue(make_test_event(id)).await;
+        }
+
+        let odd =
+            |event: &TrackerEvent| event.event_name().is_some_and(|n| n.ends_with(['1', '3']));
+        assert_eq!(queue.remove_unreserved(odd).await, 1);
+        queue.commit(reservation).await;
+
+        let remaining = queue.take_unreserved().await;
+        let names: Vec<_> = remaining
+            .iter()
+            .filter_map(TrackerEvent::event_name)
+            .collect();
+        assert_eq!(names, ["event_2", "event_4"]);
+    }
+
     #[tokio::test]
     async fn test_concurrent_enqueue() {
         let queue = Arc::new(EventQueue::new(1000));
@@ -139,42 +723,92 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_requeue_prepends_events() {
+    async fn test_rollback_preserves_order_with_concurrent_enqueue() {
+        let queue = Arc::new(EventQueue::new(10));
+
+        queue.enqueue(make_test_event(1)).await;
+        queue.enqueue(make_test_event(2)).await;
+
+        let reservation = queue.reserve(usize::MAX).await.unwrap();
+
+        // Events enqueued while the send is in flight
+        let handles: Vec<_> = (3..=4)
+            .map(|i| {
+                let q = queue.clone();
+                tokio::spawn(async move { q.enqueue(make_test_event(i)).await })
+            })
+            .collect();
+        for handle in handles {
+            handle.await.unwrap();
+        }
+
+        // Send failed
+        queue.rollback(reservation).await;
+
+        let events = std::mem::take(&mut queue.reserve(usize::MAX).await.unwrap().events);
+        let urls = event_urls(&events);
+        assert_eq!(&urls[..2], ["server://test1", "server://test2"]);
+        assert_eq!(urls.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_dropped_reservation_is_rolled_back() {
         let queue = EventQueue::new(10);
+        queue.enqueue(make_test_event(1)).await;
 
-        // Add some events
-        queue.enqueue(make_test_event(3)).await;
-        queue.enqueue(make_test_event(4)).await;
+        drop(queue.reserve(10).await.unwrap());
 
-        // Requeue earlier events
-        let to_requeue = vec![make_test_event(1), make_test_event(2)];
-        queue.requeue(to_requeue).await;
+        let reservation = queue.reserve(10).await.unwrap();
+        assert_eq!(reservation.events.len(), 1);
+    }
 
-        // Events should be in order: 1, 2, 3, 4
-        let events = queue.drain().await;
-        assert_eq!(events.len(), 4);
+    #[tokio::test]
+    async fn test_rollback_drops_events_at_max_attempts() {
+        let queue = EventQueue::new(10).with_max_attempts(Some(2));
+        queue.enqueue(make_test_event(1)).await;
 
-        // Verify order by checking the url field
-        if let TrackerEvent::Custom(e) = &events[0] {
-            assert_eq!(e.url, "server://test1");
-        }
-        if let TrackerEvent::Custom(e) = &events[1] {
-            assert_eq!(e.url, "server://test2");
-        }
-        if let TrackerEvent::Custom(e) = &events[2] {
-            assert_eq!(e.url, "server://test3");
-        }
-        if let TrackerEvent::Custom(e) = &events[3] {
-            assert_eq!(e.url, "server://test4");
+        let reservation = queue.reserve(10).await.unwrap();
+        assert!(queue.rollback(reservation).await.is_empty());
+        queue.enqueue(make_test_event(2)).await;
+
+        let reservation = queue.reserve(1).await.unwrap();
+        let exhausted = queue.rollback(reservation).await;
+        assert_eq!(event_urls(&exhausted), vec!["server://test1"]);
+
+        let events = std::mem::take(&mut queue.reserve(usize::MAX).await.unwrap().events);
+        assert_eq!(event_urls(&events), vec!["server://test2"]);
+    }
+
+    #[tokio::test]
+    async fn test_commit_partial_counts_attempts_per_event() {
+        let queue = EventQueue::new(10).with_max_attempts(Some(2));
+        for i in 1..=3 {
+            queue.enqueue(make_test_event(i)).await;
         }
+
+        // Only the third event fails
+        let reservation = queue.reserve(usize::MAX).await.unwrap();
+        let unsent = vec![reservation.events[2].clone()];
+        assert!(queue.commit_partial(reservation, unsent).await.is_empty());
+        queue.enqueue(make_test_event(4)).await;
+
+        // Both fail; only the third has now failed twice
+        let reservation = queue.reserve(usize::MAX).await.unwrap();
+        let unsent = reservation.events.clone();
+        let exhausted = queue.commit_partial(reservation, unsent).await;
+        assert_eq!(event_urls(&exhausted), vec!["server://test3"]);
+
+        let events = std::mem::take(&mut queue.reserve(usize::MAX).await.unwrap().events);
+        assert_eq!(event_urls(&events), vec!["server://test4"]);
     }
 
     #[tokio::test]
-    async fn test_requeue_empty_is_noop() {
+    async fn test_rollback_keeps_all_events() {
         let queue = EventQueue::new(10);
         queue.enqueue(make_test_event(1)).await;
 
-        queue.requeue(vec![]).await;
+        let reservation = queue.reserve(10).await.unwrap();
+        queue.rollback(reservation).await;
 
         assert_eq!(queue.len().await, 1);
     }
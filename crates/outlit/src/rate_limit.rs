@@ -0,0 +1,134 @@
+//! Client-side token bucket limiting how fast events are queued.
+
+use crate::config::RateLimit;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Token bucket refilled at `events_per_second`, holding up to `burst`
+/// tokens. Each queued event takes one token.
+#[derive(Debug)]
+pub(crate) struct TokenBucket {
+    settings: RateLimit,
+    state: Mutex<State>,
+}
+
+#[derive(Debug)]
+struct State {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    /// Create a full bucket.
+    pub(crate) fn new(settings: RateLimit) -> Self {
+        Self {
+            settings,
+            state: Mutex::new(State {
+                tokens: f64::from(settings.burst),
+                updated: Instant::now(),
+            }),
+        }
+    }
+
+    pub(crate) fn settings(&self) -> RateLimit {
+        self.settings
+    }
+
+    /// Take up to `count` whole tokens without waiting, returning how many
+    /// were taken.
+    pub(crate) fn try_acquire(&self, count: usize) -> usize {
+        let mut state = self.refill();
+        let taken = (state.tokens.max(0.0).floor() as usize).min(count);
+        state.tokens -= taken as f64;
+        taken
+    }
+
+    /// Take `count` tokens, waiting for the bucket to refill if needed.
+    pub(crate) async fn acquire(&self, count: usize) {
+        let wait = self.reserve(count, true);
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Refill the bucket, then take `count` tokens if available. With
+    /// `borrow`, tokens are taken regardless and the bucket goes into debt,
+    /// returning how long until it is back at zero.
+    fn reserve(&self, count: usize, borrow: bool) -> Duration {
+        let mut state = self.refill();
+        let count = count as f64;
+        if state.tokens >= count {
+            state.tokens -= count;
+            return Duration::ZERO;
+        }
+        let deficit = count - state.tokens;
+        if borrow {
+            state.tokens -= count;
+        }
+        Duration::from_secs_f64(deficit / self.settings.events_per_second)
+    }
+
+    /// Add the tokens earned since the last update, returning the locked
+    /// state.
+    fn refill(&self) -> MutexGuard<'_, State> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        let elapsed = now.duration_since(state.updated).as_secs_f64();
+        state.tokens = (state.tokens + elapsed * self.settings.events_per_second)
+            .min(f64::from(self.settings.burst));
+        state.updated = now;
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RateLimitPolicy;
+
+    fn bucket(events_per_second: f64, burst: u32) -> TokenBucket {
+        TokenBucket::new(RateLimit {
+            events_per_second,
+            burst,
+            policy: RateLimitPolicy::Drop,
+        })
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_try_acquire_allows_burst_then_refills() {
+        let bucket = bucket(10.0, 5);
+
+        assert_eq!((0..8).filter(|_| bucket.try_acquire(1) == 1).count(), 5);
+
+        tokio::time::advance(Duration::from_millis(200)).await;
+        assert_eq!((0..8).filter(|_| bucket.try_acquire(1) == 1).count(), 2);
+
+        // Refill is capped at the burst size
+        tokio::time::advance(Duration::from_secs(60)).await;
+        assert_eq!((0..8).filter(|_| bucket.try_acquire(1) == 1).count(), 5);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_try_acquire_takes_what_is_available() {
+        let bucket = bucket(10.0, 5);
+
+        assert_eq!(bucket.try_acquire(8), 5);
+        assert_eq!(bucket.try_acquire(8), 0);
+
+        tokio::time::advance(Duration::from_millis(250)).await;
+        assert_eq!(bucket.try_acquire(8), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_acquire_waits_for_tokens() {
+        let bucket = bucket(10.0, 1);
+        let start = Instant::now();
+
+        bucket.acquire(1).await;
+        bucket.acquire(1).await;
+        bucket.acquire(2).await;
+
+        assert_eq!(start.elapsed(), Duration::from_millis(300));
+    }
+}
@@ -0,0 +1,335 @@
+//! PII redaction for properties, traits, and identity emails.
+
+use crate::types::{Identifiers, TrackerEvent, EMAIL_PROPERTY, RESERVED_PREFIX};
+use regex::Regex;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+/// Default text substituted for redacted values.
+const DEFAULT_REPLACEMENT: &str = "[REDACTED]";
+
+/// Scrubs PII from events before they are queued.
+///
+/// Every string in an event's properties or traits, including nested ones,
+/// is checked against the configured rules, and matches are replaced. Keys
+/// listed with [`key`](Self::key) are replaced wholesale. SDK-reserved `__`
+/// properties, which carry the identity, are left to
+/// [`hash_identity_emails`](Self::hash_identity_emails).
+///
+/// Redaction runs after `before_send` hooks, so it also covers values they
+/// add.
+///
+/// # Example
+///
+/// ```rust
+/// use outlit::{Outlit, Redactor};
+///
+/// let redactor = Redactor::new()
+///     .emails()
+///     .credit_cards()
+///     .pattern(r"\bSSN-\d{9}\b")?
+///     .key("notes")
+///     .hash_identity_emails();
+///
+/// let builder = Outlit::builder("pk_xxx").redactor(redactor);
+/// # let _ = builder;
+/// # Ok::<(), outlit::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Redactor {
+    patterns: Vec<Pattern>,
+    keys: Vec<String>,
+    replacement: String,
+    hash_identity_emails: bool,
+}
+
+#[derive(Debug, Clone)]
+struct Pattern {
+    regex: Regex,
+    /// Only replace matches whose digits pass the Luhn check.
+    luhn: bool,
+}
+
+impl Redactor {
+    /// Create a redactor with no rules.
+    pub fn new() -> Self {
+        Self {
+            patterns: Vec::new(),
+            keys: Vec::new(),
+            replacement: DEFAULT_REPLACEMENT.into(),
+            hash_identity_emails: false,
+        }
+    }
+
+    /// Redact email addresses.
+    pub fn emails(self) -> Self {
+        self.push(
+            r"[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}",
+            false,
+        )
+    }
+
+    /// Redact payment card numbers: 13 to 19 digits, optionally separated
+    /// by spaces or dashes, that pass the Luhn check.
+    pub fn credit_cards(self) -> Self {
+        self.push(r"\b\d(?:[ -]?\d){12,18}\b", true)
+    }
+
+    /// Redact matches of a custom regular expression.
+    ///
+    /// Returns [`Error::Config`](crate::Error::Config) if the pattern is
+    /// invalid.
+    pub fn pattern(mut self, pattern: &str) -> Result<Self, crate::Error> {
+        let regex = Regex::new(pattern).map_err(|e| {
+            crate::Error::Config(format!("invalid redaction pattern {pattern:?} ({e})"))
+        })?;
+        self.patterns.push(Pattern { regex, luhn: false });
+        Ok(self)
+    }
+
+    /// Always replace the whole value of properties or traits named `key`,
+    /// wherever they appear.
+    pub fn key(mut self, key: impl Into<String>) -> Self {
+        self.keys.push(key.into());
+        self
+    }
+
+    /// Set the text substituted for redacted values (default:
+    /// `"[REDACTED]"`).
+    pub fn replacement(mut self, replacement: impl Into<String>) -> Self {
+        self.replacement = replacement.into();
+        self
+    }
+
+    /// Replace identity emails with their hex SHA-256 hash, so users can
+    /// still be resolved without the address leaving the process.
+    pub fn hash_identity_emails(mut self) -> Self {
+        self.hash_identity_emails = true;
+        self
+    }
+
+    fn push(mut self, pattern: &str, luhn: bool) -> Self {
+        let regex = Regex::new(pattern).expect("built-in redaction patterns are valid");
+        self.patterns.push(Pattern { regex, luhn });
+        self
+    }
+
+    /// Redact the event in place.
+    pub(crate) fn apply(&self, event: &mut TrackerEvent) {
+        if let Some(properties) = event.properties_mut() {
+            for (key, value) in properties.iter_mut() {
+                if key.starts_with(RESERVED_PREFIX) {
+                    continue;
+                }
+                self.redact_entry(key, value);
+            }
+        }
+        if self.hash_identity_emails {
+            hash_identity_emails(event);
+        }
+    }
+
+    fn redact_entry(&self, key: &str, value: &mut Value) {
+        if self.keys.iter().any(|k| k == key) {
+            *value = Value::String(self.replacement.clone());
+            return;
+        }
+        self.redact_value(value);
+    }
+
+    fn redact_value(&self, value: &mut Value) {
+        match value {
+            Value::String(s) => {
+                if let Some(redacted) = self.redact_str(s) {
+                    *s = redacted;
+                }
+            }
+            Value::Array(values) => values.iter_mut().for_each(|v| self.redact_value(v)),
+            Value::Object(map) => map
+                .iter_mut()
+                .for_each(|(key, value)| self.redact_entry(key, value)),
+            _ => {}
+        }
+    }
+
+    /// Replace every match in `s`, returning `None` if nothing matched.
+    fn redact_str(&self, s: &str) -> Option<String> {
+        let mut result: Option<String> = None;
+        for pattern in &self.patterns {
+            let current = result.as_deref().unwrap_or(s);
+            let replaced = pattern
+                .regex
+                .replace_all(current, |caps: &regex::Captures<'_>| {
+                    let matched = &caps[0];
+                    if pattern.luhn && !passes_luhn(matched) {
+                        matched.to_string()
+                    } else {
+                        self.replacement.clone()
+                    }
+                });
+            if replaced != current {
+                result = Some(replaced.into_owned());
+            }
+        }
+        result
+    }
+}
+
+impl Default for Redactor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Whether the digits in `s` pass the Luhn checksum.
+fn passes_luhn(s: &str) -> bool {
+    let sum: u32 = s
+        .chars()
+        .rev()
+        .filter_map(|c| c.to_digit(10))
+        .enumerate()
+        .map(|(i, d)| match (i % 2 == 1, d * 2) {
+            (true, doubled) if doubled > 9 => doubled - 9,
+            (true, doubled) => doubled,
+            (false, _) => d,
+        })
+        .sum();
+    sum % 10 == 0
+}
+
+/// Hex SHA-256 of an email address.
+fn hash_email(email: &str) -> String {
+    Sha256::digest(email.as_bytes())
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// Replace every identity email on the event, and its occurrence in the
+/// event URL, with its hash.
+fn hash_identity_emails(event: &mut TrackerEvent) {
+    let mut hashed = Vec::new();
+    let mut hash = |email: &mut String| {
+        let hash = hash_email(email);
+        hashed.push((std::mem::replace(email, hash.clone()), hash));
+    };
+
+    match event {
+        TrackerEvent::Identify(e) => e.email.iter_mut().for_each(&mut hash),
+        TrackerEvent::Alias(e) => {
+            for ids in [&mut e.previous, &mut e.current] {
+                ids.email.iter_mut().for_each(&mut hash);
+            }
+        }
+        TrackerEvent::Group(e) => e
+            .user
+            .iter_mut()
+            .for_each(|ids: &mut Identifiers| ids.email.iter_mut().for_each(&mut hash)),
+        TrackerEvent::Billing(_) => {}
+        TrackerEvent::Custom(_) | TrackerEvent::Pageview(_) | TrackerEvent::Stage(_) => {
+            if let Some(Value::String(email)) = event
+                .properties_mut()
+                .and_then(|props| props.get_mut(EMAIL_PROPERTY))
+            {
+                hash(email);
+            }
+        }
+    }
+
+    let url = match event {
+        TrackerEvent::Custom(e) => &mut e.url,
+        TrackerEvent::Pageview(e) => &mut e.url,
+        TrackerEvent::Identify(e) => &mut e.url,
+        TrackerEvent::Stage(e) => &mut e.url,
+        TrackerEvent::Billing(e) => &mut e.url,
+        TrackerEvent::Alias(e) => &mut e.url,
+        TrackerEvent::Group(e) => &mut e.url,
+    };
+    for (email, hash) in hashed {
+        *url = url.replace(&email, &hash);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::CustomEventData;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    fn event(properties: Value) -> TrackerEvent {
+        let Value::Object(map) = properties else {
+            panic!("expected an object");
+        };
+        TrackerEvent::Custom(CustomEventData {
+            timestamp: 1706400000000,
+            url: "server://user@example.com".into(),
+            path: "/".into(),
+            event_name: "test".into(),
+            properties: Some(map.into_iter().collect::<HashMap<_, _>>()),
+            message_id: None,
+            event_id: None,
+        })
+    }
+
+    fn properties(event: &TrackerEvent) -> Value {
+        match event {
+            TrackerEvent::Custom(e) => json!(e.properties),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_redacts_matching_values() {
+        let redactor = Redactor::new()
+            .emails()
+            .credit_cards()
+            .pattern(r"SSN-\d+")
+            .unwrap()
+            .key("notes");
+        let mut event = event(json!({
+            "__email": "user@example.com",
+            "message": "contact jane.doe@mail.example.org or SSN-123456789",
+            "card": "4242 4242 4242 4242",
+            "order_id": "1234567890123",
+            "nested": { "notes": "anything", "list": ["bob@example.com"] },
+            "notes": 42,
+        }));
+
+        redactor.apply(&mut event);
+
+        assert_eq!(
+            properties(&event),
+            json!({
+                "__email": "user@example.com",
+                "message": "contact [REDACTED] or [REDACTED]",
+                "card": "[REDACTED]",
+                "order_id": "1234567890123",
+                "nested": { "notes": "[REDACTED]", "list": ["[REDACTED]"] },
+                "notes": "[REDACTED]",
+            })
+        );
+    }
+
+    #[test]
+    fn test_hashes_identity_emails() {
+        let mut event = event(json!({ "__email": "user@example.com" }));
+
+        Redactor::new().hash_identity_emails().apply(&mut event);
+
+        let hashed = "b4c9a289323b21a01c3e940f150eb9b8c542587f1abfd8f0e1cc1ffc5e475514";
+        assert_eq!(properties(&event)["__email"], hashed);
+        match event {
+            TrackerEvent::Custom(e) => assert_eq!(e.url, format!("server://{hashed}")),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_invalid_pattern_is_a_config_error() {
+        assert!(matches!(
+            Redactor::new().pattern("("),
+            Err(crate::Error::Config(_))
+        ));
+    }
+}
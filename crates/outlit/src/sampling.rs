@@ -0,0 +1,215 @@
+//! Probabilistic event sampling and the small generator behind it.
+
+use crate::types::TrackerEvent;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Small thread-safe SplitMix64 generator.
+///
+/// Sampling and retry jitter do not need cryptographic randomness.
+#[derive(Debug)]
+pub struct Rng {
+    state: AtomicU64,
+}
+
+impl Rng {
+    /// Create a generator seeded from the system clock.
+    pub fn new() -> Self {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos() as u64;
+        Self::with_seed(seed)
+    }
+
+    /// Create a generator with a fixed seed.
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            state: AtomicU64::new(seed),
+        }
+    }
+
+    /// Get the next value in `[0, 1)`.
+    pub fn next_f64(&self) -> f64 {
+        // Top 53 bits as a float in [0, 1)
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Get the next 64 random bits.
+    pub fn next_u64(&self) -> u64 {
+        let mut z = self
+            .state
+            .fetch_add(0x9E37_79B9_7F4A_7C15, Ordering::Relaxed)
+            .wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+/// Decides whether to keep events with a fixed probability, optionally
+/// overridden per event name.
+#[derive(Debug)]
+pub struct Sampler {
+    rate: f64,
+    event_rates: HashMap<String, f64>,
+    deterministic: bool,
+    rng: Rng,
+}
+
+impl Sampler {
+    /// Create a sampler seeded from the system clock.
+    pub fn new(rate: f64) -> Self {
+        Self {
+            rate,
+            event_rates: HashMap::new(),
+            deterministic: false,
+            rng: Rng::new(),
+        }
+    }
+
+    /// Create a sampler with a fixed seed.
+    #[cfg(test)]
+    pub fn with_seed(rate: f64, seed: u64) -> Self {
+        Self {
+            rng: Rng::with_seed(seed),
+            ..Self::new(rate)
+        }
+    }
+
+    /// Use these rates instead of the default for events with these names.
+    pub fn event_rates(mut self, rates: HashMap<String, f64>) -> Self {
+        self.event_rates = rates;
+        self
+    }
+
+    /// Decide by hashing the event name and identity, so a user's events
+    /// with the same name are either all kept or all dropped.
+    pub fn deterministic(mut self, deterministic: bool) -> Self {
+        self.deterministic = deterministic;
+        self
+    }
+
+    /// Returns `true` if the next event should be kept at the default rate.
+    #[cfg(test)]
+    pub fn sample(&self) -> bool {
+        self.keep(self.rate, None)
+    }
+
+    /// Returns `true` if `event` should be kept. Only custom events are
+    /// sampled; identity and lifecycle events are always kept.
+    pub fn sample_event(&self, event: &TrackerEvent) -> bool {
+        let TrackerEvent::Custom(data) = event else {
+            return true;
+        };
+        let rate = self
+            .event_rates
+            .get(&data.event_name)
+            .copied()
+            .unwrap_or(self.rate);
+        let key = self
+            .deterministic
+            .then(|| event.identity())
+            .flatten()
+            .and_then(|ids| ids.user_id.or(ids.email).or(ids.fingerprint))
+            .map(|id| (data.event_name.as_str(), id));
+        self.keep(rate, key.as_ref().map(|(name, id)| (*name, id.as_str())))
+    }
+
+    fn keep(&self, rate: f64, key: Option<(&str, &str)>) -> bool {
+        if rate >= 1.0 {
+            return true;
+        }
+        if rate <= 0.0 {
+            return false;
+        }
+        match key {
+            Some((event_name, identity)) => hash_unit(event_name, identity) < rate,
+            None => self.rng.next_f64() < rate,
+        }
+    }
+}
+
+/// Map an event name and identity to a stable value in `[0, 1)`.
+fn hash_unit(event_name: &str, identity: &str) -> f64 {
+    let digest = Sha256::new()
+        .chain_update(event_name.as_bytes())
+        .chain_update([0])
+        .chain_update(identity.as_bytes())
+        .finalize();
+    let bits = u64::from_be_bytes(digest[..8].try_into().expect("digest is 32 bytes"));
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builders::BuildEvent;
+
+    #[test]
+    fn test_rate_zero_drops_everything() {
+        let sampler = Sampler::with_seed(0.0, 42);
+        assert!((0..1000).all(|_| !sampler.sample()));
+    }
+
+    #[test]
+    fn test_rate_one_keeps_everything() {
+        let sampler = Sampler::with_seed(1.0, 42);
+        assert!((0..1000).all(|_| sampler.sample()));
+    }
+
+    #[test]
+    fn test_seeded_sampler_is_deterministic() {
+        let a = Sampler::with_seed(0.5, 7);
+        let b = Sampler::with_seed(0.5, 7);
+
+        let first: Vec<bool> = (0..100).map(|_| a.sample()).collect();
+        let second: Vec<bool> = (0..100).map(|_| b.sample()).collect();
+
+        assert_eq!(first, second);
+    }
+
+    fn custom(name: &str, user: &str) -> TrackerEvent {
+        crate::builders::TrackBuilder::new(name, crate::user_id(user)).build()
+    }
+
+    #[test]
+    fn test_event_rates_override_default() {
+        let sampler = Sampler::with_seed(1.0, 42)
+            .event_rates(HashMap::from([("heartbeat".to_string(), 0.0)]));
+
+        assert!(!sampler.sample_event(&custom("heartbeat", "usr_1")));
+        assert!(sampler.sample_event(&custom("purchase", "usr_1")));
+        let identify = crate::builders::IdentifyBuilder::new(crate::user_id("usr_1")).build();
+        assert!(Sampler::with_seed(0.0, 42).sample_event(&identify));
+    }
+
+    #[test]
+    fn test_deterministic_sampling_is_consistent_per_user() {
+        let sampler = Sampler::with_seed(0.5, 42).deterministic(true);
+
+        let users: Vec<String> = (0..200).map(|i| format!("usr_{i}")).collect();
+        let first: Vec<bool> = users
+            .iter()
+            .map(|u| sampler.sample_event(&custom("heartbeat", u)))
+            .collect();
+        let second: Vec<bool> = users
+            .iter()
+            .map(|u| sampler.sample_event(&custom("heartbeat", u)))
+            .collect();
+
+        assert_eq!(first, second);
+        let kept = first.iter().filter(|&&k| k).count();
+        assert!((60..140).contains(&kept), "kept {kept} of 200");
+    }
+
+    #[test]
+    fn test_seeded_sampler_approximates_rate() {
+        let sampler = Sampler::with_seed(0.25, 1234);
+        let kept = (0..10_000).filter(|_| sampler.sample()).count();
+
+        assert!((2_200..2_800).contains(&kept), "kept {kept} of 10000");
+    }
+}
@@ -0,0 +1,199 @@
+//! SQLite-backed storage for queued events.
+//!
+//! Each event is a row keyed by an increasing sequence number, so the
+//! queue's order survives restarts. Appends and removals each run in one
+//! transaction, so a crash or power loss leaves the database either before
+//! or after the change, and a confirmed send only deletes the rows it sent.
+
+use crate::types::TrackerEvent;
+use crate::Error;
+use rusqlite::{params, Connection};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use tracing::warn;
+
+/// SQLite database backing an [`EventQueue`](crate::queue::EventQueue).
+#[derive(Debug)]
+pub struct SqliteStore {
+    path: PathBuf,
+    connection: Mutex<Connection>,
+}
+
+impl SqliteStore {
+    /// Open or create the database at `path`, returning any events left
+    /// from a previous run.
+    pub fn open(path: impl Into<PathBuf>) -> Result<(Self, Vec<TrackerEvent>), Error> {
+        let path = path.into();
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let connection = Connection::open(&path).map_err(sqlite_error)?;
+        connection
+            .execute_batch(
+                "PRAGMA journal_mode = WAL;
+                 PRAGMA synchronous = FULL;
+                 CREATE TABLE IF NOT EXISTS events (
+                     seq INTEGER PRIMARY KEY AUTOINCREMENT,
+                     event_id TEXT NOT NULL,
+                     event TEXT NOT NULL
+                 );
+                 CREATE INDEX IF NOT EXISTS events_event_id ON events (event_id);",
+            )
+            .map_err(sqlite_error)?;
+
+        let store = Self {
+            path,
+            connection: Mutex::new(connection),
+        };
+        let events = store.load()?;
+        Ok((store, events))
+    }
+
+    /// Get the path of the database file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append several events in one transaction.
+    pub fn append_many(&self, events: &[TrackerEvent]) -> Result<(), Error> {
+        let mut connection = self.lock();
+        let transaction = connection.transaction().map_err(sqlite_error)?;
+        insert(&transaction, events)?;
+        transaction.commit().map_err(sqlite_error)
+    }
+
+    /// Delete the events with `ids`, in one transaction.
+    pub fn remove(&self, ids: &[String]) -> Result<(), Error> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+        let mut connection = self.lock();
+        let transaction = connection.transaction().map_err(sqlite_error)?;
+        {
+            let mut delete = transaction
+                .prepare_cached("DELETE FROM events WHERE event_id = ?1")
+                .map_err(sqlite_error)?;
+            for id in ids {
+                delete.execute([id]).map_err(sqlite_error)?;
+            }
+        }
+        transaction.commit().map_err(sqlite_error)
+    }
+
+    /// Read the stored events in queue order, skipping unreadable rows.
+    fn load(&self) -> Result<Vec<TrackerEvent>, Error> {
+        let connection = self.lock();
+        let mut select = connection
+            .prepare("SELECT event FROM events ORDER BY seq")
+            .map_err(sqlite_error)?;
+        let rows = select
+            .query_map([], |row| row.get::<_, String>(0))
+            .map_err(sqlite_error)?;
+
+        let mut events = Vec::new();
+        for row in rows {
+            match serde_json::from_str(&row.map_err(sqlite_error)?) {
+                Ok(event) => events.push(event),
+                Err(e) => warn!(error = %e, "skipping unreadable persisted event"),
+            }
+        }
+        Ok(events)
+    }
+
+    /// Number of stored events.
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.lock()
+            .query_row("SELECT COUNT(*) FROM events", [], |row| {
+                row.get::<_, i64>(0)
+            })
+            .unwrap() as usize
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Connection> {
+        self.connection.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn insert(connection: &Connection, events: &[TrackerEvent]) -> Result<(), Error> {
+    let mut insert = connection
+        .prepare_cached("INSERT INTO events (event_id, event) VALUES (?1, ?2)")
+        .map_err(sqlite_error)?;
+    for event in events {
+        let json = serde_json::to_string(event)?;
+        insert
+            .execute(params![event.event_id().unwrap_or_default(), json])
+            .map_err(sqlite_error)?;
+    }
+    Ok(())
+}
+
+fn sqlite_error(e: rusqlite::Error) -> Error {
+    Error::Io(std::io::Error::other(e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::CustomEventData;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("outlit-sqlite-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{suffix}", path.display()));
+        }
+        path
+    }
+
+    fn make_test_event(id: i32) -> TrackerEvent {
+        TrackerEvent::Custom(CustomEventData {
+            timestamp: 1706400000000,
+            url: format!("server://test{}", id),
+            path: "/".into(),
+            event_name: format!("event_{}", id),
+            properties: None,
+            message_id: None,
+            event_id: Some(format!("evt_{id}")),
+        })
+    }
+
+    fn names(events: &[TrackerEvent]) -> Vec<&str> {
+        events.iter().filter_map(TrackerEvent::event_name).collect()
+    }
+
+    #[test]
+    fn test_append_and_reload_in_order() {
+        let path = temp_path("reload.db");
+        let (store, events) = SqliteStore::open(&path).unwrap();
+        assert!(events.is_empty());
+
+        store.append_many(&[make_test_event(1)]).unwrap();
+        store
+            .append_many(&[make_test_event(2), make_test_event(3)])
+            .unwrap();
+        drop(store);
+
+        let (_, events) = SqliteStore::open(&path).unwrap();
+        assert_eq!(names(&events), ["event_1", "event_2", "event_3"]);
+    }
+
+    #[test]
+    fn test_remove_deletes_sent_events() {
+        let path = temp_path("remove.db");
+        let (store, _) = SqliteStore::open(&path).unwrap();
+        let events: Vec<_> = (1..=4).map(make_test_event).collect();
+        store.append_many(&events).unwrap();
+
+        // A flush confirmed the first two
+        store.remove(&["evt_1".into(), "evt_2".into()]).unwrap();
+        assert_eq!(store.len(), 2);
+        let (_, reloaded) = SqliteStore::open(&path).unwrap();
+        assert_eq!(names(&reloaded), ["event_3", "event_4"]);
+
+        store.remove(&["evt_3".into(), "evt_4".into()]).unwrap();
+        assert_eq!(store.len(), 0);
+    }
+}
@@ -0,0 +1,164 @@
+//! Delivery statistics.
+
+use crate::types::FlushReport;
+use crate::Error;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// Snapshot of a client's delivery counters, from
+/// [`Outlit::stats`](crate::Outlit::stats).
+///
+/// Counters start at zero when the client is built.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ClientStats {
+    /// Events added to the queue.
+    pub enqueued: u64,
+    /// Events sent in successful requests.
+    pub sent: u64,
+    /// Events the API rejected or that failed to serialize.
+    pub rejected: u64,
+    /// Rejected events queued again because of
+    /// [`requeue_rejected`](crate::OutlitBuilder::requeue_rejected).
+    pub requeued: u64,
+    /// Flushes that failed, leaving their events queued.
+    pub failed_flushes: u64,
+    /// Events discarded without being sent.
+    pub dropped: u64,
+    /// Outcome of the most recent flush that had events to send.
+    pub last_flush: Option<LastFlush>,
+}
+
+/// Outcome of a flush, as reported in [`ClientStats::last_flush`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct LastFlush {
+    /// When the flush finished.
+    pub at: SystemTime,
+    /// The error message, if the flush failed.
+    pub error: Option<String>,
+}
+
+/// Counters shared by the client and its background flush task.
+#[derive(Debug, Default)]
+pub(crate) struct Stats {
+    enqueued: AtomicU64,
+    sent: AtomicU64,
+    rejected: AtomicU64,
+    requeued: AtomicU64,
+    failed_flushes: AtomicU64,
+    dropped: AtomicU64,
+    last_flush: Mutex<Option<LastFlush>>,
+}
+
+impl Stats {
+    pub(crate) fn record_enqueued(&self, count: usize) {
+        self.enqueued.fetch_add(count as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_dropped(&self, count: usize) {
+        self.dropped.fetch_add(count as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Record a flush result. Flushes of an empty queue are not recorded.
+    pub(crate) fn record_flush(&self, result: &Result<FlushReport, Error>, requeue_rejected: bool) {
+        let error = match result {
+            Ok(report) if report.sent == 0 && report.quarantined.is_empty() => return,
+            Ok(report) => {
+                let rejected = report.rejected.len() + report.quarantined.len();
+                self.sent.fetch_add(report.sent as u64, Ordering::Relaxed);
+                self.rejected.fetch_add(rejected as u64, Ordering::Relaxed);
+                if requeue_rejected {
+                    let requeued = report.rejected.iter().filter(|r| r.event.is_some()).count();
+                    self.requeued.fetch_add(requeued as u64, Ordering::Relaxed);
+                }
+                None
+            }
+            Err(e) => {
+                self.failed_flushes.fetch_add(1, Ordering::Relaxed);
+                Some(e.to_string())
+            }
+        };
+
+        let mut last_flush = self.last_flush.lock().unwrap_or_else(|e| e.into_inner());
+        *last_flush = Some(LastFlush {
+            at: SystemTime::now(),
+            error,
+        });
+    }
+
+    pub(crate) fn snapshot(&self) -> ClientStats {
+        ClientStats {
+            enqueued: self.enqueued.load(Ordering::Relaxed),
+            sent: self.sent.load(Ordering::Relaxed),
+            rejected: self.rejected.load(Ordering::Relaxed),
+            requeued: self.requeued.load(Ordering::Relaxed),
+            failed_flushes: self.failed_flushes.load(Ordering::Relaxed),
+            dropped: self.dropped(),
+            last_flush: self
+                .last_flush
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::RejectedEvent;
+
+    fn rejected(index: usize) -> RejectedEvent {
+        RejectedEvent {
+            index,
+            message: "invalid".into(),
+            event: None,
+        }
+    }
+
+    #[test]
+    fn test_record_flush_counts_sent_and_rejected() {
+        let stats = Stats::default();
+        let report = FlushReport {
+            sent: 3,
+            processed: 2,
+            rejected: vec![rejected(1)],
+            quarantined: vec![rejected(3)],
+        };
+
+        stats.record_flush(&Ok(report), false);
+
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.sent, 3);
+        assert_eq!(snapshot.rejected, 2);
+        assert_eq!(snapshot.requeued, 0);
+        assert_eq!(snapshot.last_flush.unwrap().error, None);
+    }
+
+    #[test]
+    fn test_record_flush_failure_keeps_error() {
+        let stats = Stats::default();
+
+        stats.record_flush(&Err(Error::Timeout), false);
+
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.failed_flushes, 1);
+        assert_eq!(
+            snapshot.last_flush.unwrap().error.as_deref(),
+            Some("Operation timed out")
+        );
+    }
+
+    #[test]
+    fn test_record_flush_skips_empty_flushes() {
+        let stats = Stats::default();
+
+        stats.record_flush(&Ok(FlushReport::default()), false);
+
+        assert_eq!(stats.snapshot(), ClientStats::default());
+    }
+}
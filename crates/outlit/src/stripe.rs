@@ -0,0 +1,329 @@
+//! Translate Stripe webhooks into billing events.
+//!
+//! Enabled with the `stripe` feature. [`translate`] reads a webhook body
+//! and returns the matching [`BillingUpdate`], which is sent for a
+//! customer's domain:
+//!
+//! ```rust,no_run
+//! # use outlit::{BillingStatus, Outlit};
+//! # async fn example(client: &Outlit, body: &[u8]) -> Result<(), outlit::Error> {
+//! if let Some(mut update) = outlit::stripe::translate(body)? {
+//!     let domain = "acme.com"; // look up from update.provider_customer_id
+//!     if update.status.is_none() {
+//!         // Invoices and refunds: keep the status you have on record.
+//!         update.status = Some(BillingStatus::Paid);
+//!     }
+//!     update.to_billing(client, domain)?.send().await?;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! | Stripe event | Billing event |
+//! |--------------|---------------|
+//! | `customer.subscription.created`, `.updated`, `.paused`, `.resumed`, `.trial_will_end` | Subscription status, with plan, seats, and MRR; plan or seat changes in `.updated` are sent as [`PlanChanged`](BillingActivity::PlanChanged) or [`SeatsChanged`](BillingActivity::SeatsChanged) |
+//! | `customer.subscription.deleted` | [`Churned`](BillingStatus::Churned) |
+//! | `invoice.paid` | [`InvoicePaid`](BillingActivity::InvoicePaid), without a status |
+//! | `invoice.payment_failed` | [`InvoiceFailed`](BillingActivity::InvoiceFailed), without a status |
+//! | `charge.refunded` | [`Refund`](BillingActivity::Refund), without a status |
+//!
+//! Other events return `None`. Verify the `Stripe-Signature` header before
+//! translating; this module only reads the payload.
+
+use crate::billing::{int_at, interval, monthly, str_at, BillingUpdate, Provider};
+use crate::types::{BillingActivity, BillingStatus};
+use crate::Error;
+use serde_json::Value;
+
+/// Translate a Stripe webhook body into a billing update.
+///
+/// Returns `Ok(None)` for event types that don't map to billing events,
+/// [`Error::Serialization`] if the body isn't JSON, and
+/// [`Error::Validation`] if it isn't a Stripe event.
+pub fn translate(payload: &[u8]) -> Result<Option<BillingUpdate>, Error> {
+    translate_value(&serde_json::from_slice(payload)?)
+}
+
+/// Same as [`translate`], for a webhook body that is already parsed.
+pub fn translate_value(event: &Value) -> Result<Option<BillingUpdate>, Error> {
+    let (Some(event_id), Some(event_type)) = (str_at(event, "/id"), str_at(event, "/type")) else {
+        return Err(Error::Validation(
+            "not a Stripe event: missing id or type".into(),
+        ));
+    };
+    let object = event.pointer("/data/object").unwrap_or(&Value::Null);
+    let new = |status| BillingUpdate::new(Provider::Stripe, status, event_id.to_string());
+
+    let mut update = match event_type {
+        "customer.subscription.deleted" => {
+            let mut update = subscription(new(Some(BillingStatus::Churned)), object);
+            update.mrr = Some(0);
+            update
+        }
+        "customer.subscription.created"
+        | "customer.subscription.updated"
+        | "customer.subscription.paused"
+        | "customer.subscription.resumed"
+        | "customer.subscription.trial_will_end" => {
+            let Some(status) = str_at(object, "/status").and_then(subscription_status) else {
+                return Ok(None);
+            };
+            let mut update = subscription(new(Some(status)), object);
+            if let Some(previous) = event.pointer("/data/previous_attributes") {
+                changes(&mut update, previous);
+            }
+            update
+        }
+        "invoice.paid" => {
+            let mut update = invoice(new(None), object);
+            update.activity = Some(BillingActivity::InvoicePaid);
+            update.amount = int_at(object, "/amount_paid");
+            update
+        }
+        "invoice.payment_failed" => {
+            let mut update = invoice(new(None), object);
+            update.activity = Some(BillingActivity::InvoiceFailed);
+            update.amount = int_at(object, "/amount_due");
+            update
+        }
+        "charge.refunded" => {
+            let mut update = new(None);
+            update.activity = Some(BillingActivity::Refund);
+            update.invoice_id = str_at(object, "/invoice").map(String::from);
+            update.amount = int_at(object, "/amount_refunded");
+            update.customer_email = str_at(object, "/billing_details/email")
+                .or_else(|| str_at(object, "/receipt_email"))
+                .map(String::from);
+            update
+        }
+        _ => return Ok(None),
+    };
+
+    update.provider_customer_id = customer_id(object);
+    if update.currency.is_none() {
+        update.currency = str_at(object, "/currency").map(str::to_ascii_lowercase);
+    }
+    Ok(Some(update))
+}
+
+/// Map a Stripe subscription status.
+fn subscription_status(status: &str) -> Option<BillingStatus> {
+    Some(match status {
+        "trialing" => BillingStatus::Trialing,
+        "active" => BillingStatus::Paid,
+        "past_due" => BillingStatus::PastDue,
+        "canceled" => BillingStatus::Canceled,
+        "unpaid" => BillingStatus::Unpaid,
+        "paused" => BillingStatus::Paused,
+        "incomplete" => BillingStatus::Incomplete,
+        "incomplete_expired" => BillingStatus::Churned,
+        _ => return None,
+    })
+}
+
+/// Fill in plan, seats, interval, and MRR from a subscription.
+fn subscription(mut update: BillingUpdate, subscription: &Value) -> BillingUpdate {
+    let items = subscription
+        .pointer("/items/data")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+
+    if let Some(item) = items.first() {
+        update.plan = item.get("price").and_then(plan);
+        update.seats = item_quantity(item);
+        update.interval = str_at(item, "/price/recurring/interval").and_then(interval);
+    }
+    update.mrr = Some(
+        items
+            .iter()
+            .filter_map(|item| {
+                let amount = int_at(item, "/price/unit_amount")?;
+                let interval = str_at(item, "/price/recurring/interval").and_then(interval)?;
+                let count = int_at(item, "/price/recurring/interval_count").unwrap_or(1);
+                let quantity = i64::from(item_quantity(item).unwrap_or(1));
+                Some(monthly(amount * quantity, interval, count))
+            })
+            .sum(),
+    );
+    update
+}
+
+/// Detect plan and seat changes from a subscription update's
+/// `previous_attributes`.
+fn changes(update: &mut BillingUpdate, previous: &Value) {
+    let item = previous.pointer("/items/data/0");
+    let previous_plan = item.and_then(|item| item.get("price")).and_then(plan);
+    let previous_seats = item
+        .and_then(item_quantity)
+        .or_else(|| int_at(previous, "/quantity").and_then(|q| u32::try_from(q).ok()));
+
+    if previous_plan.is_some() && previous_plan != update.plan {
+        update.activity = Some(BillingActivity::PlanChanged);
+        update.previous_plan = previous_plan;
+    } else if previous_seats.is_some() && previous_seats != update.seats {
+        update.activity = Some(BillingActivity::SeatsChanged);
+        update.previous_seats = previous_seats;
+    }
+}
+
+/// Fill in the invoice ID and customer email from an invoice.
+fn invoice(mut update: BillingUpdate, invoice: &Value) -> BillingUpdate {
+    update.invoice_id = str_at(invoice, "/id").map(String::from);
+    update.customer_email = str_at(invoice, "/customer_email").map(String::from);
+    update
+}
+
+/// Plan name of a price: its lookup key, nickname, or ID.
+fn plan(price: &Value) -> Option<String> {
+    str_at(price, "/lookup_key")
+        .or_else(|| str_at(price, "/nickname"))
+        .or_else(|| str_at(price, "/id"))
+        .map(String::from)
+}
+
+fn item_quantity(item: &Value) -> Option<u32> {
+    int_at(item, "/quantity").and_then(|q| u32::try_from(q).ok())
+}
+
+/// The object's customer ID, whether `customer` is an ID or expanded.
+fn customer_id(object: &Value) -> Option<String> {
+    str_at(object, "/customer")
+        .or_else(|| str_at(object, "/customer/id"))
+        .map(String::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::BillingInterval;
+    use serde_json::json;
+
+    fn subscription_event(event_type: &str, status: &str, previous: Value) -> Value {
+        json!({
+            "id": "evt_1",
+            "type": event_type,
+            "data": {
+                "object": {
+                    "id": "sub_1",
+                    "customer": "cus_1",
+                    "status": status,
+                    "currency": "usd",
+                    "items": { "data": [{
+                        "quantity": 5,
+                        "price": {
+                            "id": "price_1",
+                            "lookup_key": "pro_yearly",
+                            "unit_amount": 12000,
+                            "recurring": { "interval": "year", "interval_count": 1 }
+                        }
+                    }] }
+                },
+                "previous_attributes": previous
+            }
+        })
+    }
+
+    #[test]
+    fn test_subscription_updated() {
+        let event = subscription_event("customer.subscription.updated", "past_due", json!({}));
+        let update = translate_value(&event).unwrap().unwrap();
+
+        assert_eq!(update.status, Some(BillingStatus::PastDue));
+        assert_eq!(update.activity, None);
+        assert_eq!(update.event_id, "evt_1");
+        assert_eq!(update.provider_customer_id.as_deref(), Some("cus_1"));
+        assert_eq!(update.plan.as_deref(), Some("pro_yearly"));
+        assert_eq!(update.seats, Some(5));
+        assert_eq!(update.interval, Some(BillingInterval::Year));
+        assert_eq!(update.mrr, Some(5000));
+        assert_eq!(update.currency.as_deref(), Some("usd"));
+    }
+
+    #[test]
+    fn test_subscription_plan_and_seat_changes() {
+        let previous = json!({ "items": { "data": [{
+            "quantity": 5,
+            "price": { "id": "price_0", "lookup_key": "starter_yearly" }
+        }] } });
+        let event = subscription_event("customer.subscription.updated", "active", previous);
+        let update = translate_value(&event).unwrap().unwrap();
+        assert_eq!(update.status, Some(BillingStatus::Paid));
+        assert_eq!(update.activity, Some(BillingActivity::PlanChanged));
+        assert_eq!(update.previous_plan.as_deref(), Some("starter_yearly"));
+
+        let previous = json!({ "items": { "data": [{
+            "quantity": 3,
+            "price": { "id": "price_1", "lookup_key": "pro_yearly" }
+        }] } });
+        let event = subscription_event("customer.subscription.updated", "active", previous);
+        let update = translate_value(&event).unwrap().unwrap();
+        assert_eq!(update.activity, Some(BillingActivity::SeatsChanged));
+        assert_eq!(update.previous_seats, Some(3));
+        assert_eq!(update.seats, Some(5));
+    }
+
+    #[test]
+    fn test_subscription_deleted_is_churn() {
+        let event = subscription_event("customer.subscription.deleted", "canceled", json!({}));
+        let update = translate_value(&event).unwrap().unwrap();
+        assert_eq!(update.status, Some(BillingStatus::Churned));
+        assert_eq!(update.mrr, Some(0));
+    }
+
+    #[test]
+    fn test_invoice_and_refund_events() {
+        let event = json!({
+            "id": "evt_2",
+            "type": "invoice.payment_failed",
+            "data": { "object": {
+                "id": "in_1",
+                "customer": { "id": "cus_1", "object": "customer" },
+                "customer_email": "billing@acme.com",
+                "amount_due": 4900,
+                "amount_paid": 0,
+                "currency": "EUR"
+            } }
+        });
+        let update = translate_value(&event).unwrap().unwrap();
+        assert_eq!(update.status, None);
+        assert_eq!(update.activity, Some(BillingActivity::InvoiceFailed));
+        assert_eq!(update.invoice_id.as_deref(), Some("in_1"));
+        assert_eq!(update.amount, Some(4900));
+        assert_eq!(update.currency.as_deref(), Some("eur"));
+        assert_eq!(update.provider_customer_id.as_deref(), Some("cus_1"));
+        assert_eq!(update.customer_email.as_deref(), Some("billing@acme.com"));
+
+        let event = json!({
+            "id": "evt_3",
+            "type": "charge.refunded",
+            "data": { "object": {
+                "id": "ch_1",
+                "customer": "cus_1",
+                "invoice": "in_1",
+                "amount_refunded": 1000,
+                "currency": "usd"
+            } }
+        });
+        let update = translate_value(&event).unwrap().unwrap();
+        assert_eq!(update.status, None);
+        assert_eq!(update.activity, Some(BillingActivity::Refund));
+        assert_eq!(update.amount, Some(1000));
+        assert_eq!(update.invoice_id.as_deref(), Some("in_1"));
+    }
+
+    #[test]
+    fn test_unhandled_and_invalid_events() {
+        let event = json!({ "id": "evt_4", "type": "customer.created", "data": {} });
+        assert_eq!(translate_value(&event).unwrap(), None);
+
+        assert!(matches!(
+            translate_value(&json!({ "type": "invoice.paid" })),
+            Err(Error::Validation(_))
+        ));
+        assert!(matches!(
+            translate(b"not json"),
+            Err(Error::Serialization(_))
+        ));
+    }
+}
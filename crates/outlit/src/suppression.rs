@@ -0,0 +1,86 @@
+//! Identities that must no longer be tracked, after a privacy request.
+
+use crate::types::{Identifiers, TrackerEvent};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::RwLock;
+
+/// Suppressed emails, user IDs, and fingerprints.
+///
+/// An event is suppressed if any of its identifiers is.
+#[derive(Debug, Default)]
+pub struct Suppressions {
+    /// Set once anything is suppressed, so the common case skips the lock.
+    active: AtomicBool,
+    suppressed: RwLock<Suppressed>,
+}
+
+#[derive(Debug, Default)]
+struct Suppressed {
+    emails: HashSet<String>,
+    user_ids: HashSet<String>,
+    fingerprints: HashSet<String>,
+}
+
+impl Suppressions {
+    /// Suppress each of `identifiers`.
+    pub fn insert(&self, identifiers: &Identifiers) {
+        let mut suppressed = self.suppressed.write().unwrap_or_else(|e| e.into_inner());
+        suppressed.emails.extend(identifiers.email.clone());
+        suppressed.user_ids.extend(identifiers.user_id.clone());
+        suppressed
+            .fingerprints
+            .extend(identifiers.fingerprint.clone());
+        self.active.store(true, Ordering::Release);
+    }
+
+    /// Whether any of `identifiers` is suppressed.
+    pub fn contains(&self, identifiers: &Identifiers) -> bool {
+        if !self.active.load(Ordering::Acquire) {
+            return false;
+        }
+        let suppressed = self.suppressed.read().unwrap_or_else(|e| e.into_inner());
+        let contains = |set: &HashSet<String>, id: &Option<String>| {
+            id.as_ref().is_some_and(|id| set.contains(id))
+        };
+        contains(&suppressed.emails, &identifiers.email)
+            || contains(&suppressed.user_ids, &identifiers.user_id)
+            || contains(&suppressed.fingerprints, &identifiers.fingerprint)
+    }
+
+    /// Whether `event` is about a suppressed user.
+    ///
+    /// Billing events are keyed by domain and never suppressed.
+    pub fn suppresses(&self, event: &TrackerEvent) -> bool {
+        self.active.load(Ordering::Acquire)
+            && event
+                .identity()
+                .is_some_and(|identifiers| self.contains(&identifiers))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builders::{BuildEvent, TrackBuilder};
+    use crate::{email, user_id};
+
+    #[test]
+    fn test_suppresses_events_matching_any_identifier() {
+        let suppressions = Suppressions::default();
+        let event = TrackBuilder::new("signup", email("user@example.com"))
+            .user_id("usr_123")
+            .build();
+        assert!(!suppressions.suppresses(&event));
+
+        suppressions.insert(&Identifiers {
+            email: None,
+            user_id: Some("usr_123".into()),
+            fingerprint: None,
+        });
+        assert!(suppressions.suppresses(&event));
+
+        let other = TrackBuilder::new("signup", user_id("usr_456")).build();
+        assert!(!suppressions.suppresses(&other));
+    }
+}
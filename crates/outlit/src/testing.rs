@@ -0,0 +1,178 @@
+//! In-memory capture of sent events for tests.
+//!
+//! Enabled with the `test-util` feature. Lets application tests check what
+//! was tracked without running a mock HTTP server.
+//!
+//! # Example
+//!
+//! ```rust
+//! use outlit::{Outlit, email};
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! let (client, capture) = Outlit::for_testing();
+//!
+//! client.track("signup", email("user@example.com"))
+//!     .property("plan", "pro")
+//!     .send()
+//!     .await
+//!     .unwrap();
+//!
+//! capture.assert_tracked("signup");
+//! capture.assert_not_tracked("churned");
+//! # }
+//! ```
+
+use crate::transport::Transport;
+use crate::types::{IngestPayload, IngestResponse, TrackerEvent};
+use crate::{Error, Outlit, OutlitBuilder};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Records every batch a client flushes instead of sending it.
+///
+/// Cheap to clone; clones share the same recorded events.
+#[derive(Debug, Clone, Default)]
+pub struct CaptureTransport {
+    events: Arc<Mutex<Vec<TrackerEvent>>>,
+}
+
+impl CaptureTransport {
+    /// Get all events sent so far, in order.
+    pub fn events(&self) -> Vec<TrackerEvent> {
+        self.lock().clone()
+    }
+
+    /// Get the names of all custom events sent so far, in order.
+    pub fn tracked_names(&self) -> Vec<String> {
+        self.lock()
+            .iter()
+            .filter_map(TrackerEvent::event_name)
+            .map(String::from)
+            .collect()
+    }
+
+    /// Panic unless a custom event named `name` was sent.
+    #[track_caller]
+    pub fn assert_tracked(&self, name: &str) {
+        let names = self.tracked_names();
+        assert!(
+            names.iter().any(|n| n == name),
+            "expected event {name:?} to be tracked, got {names:?}"
+        );
+    }
+
+    /// Panic if a custom event named `name` was sent.
+    #[track_caller]
+    pub fn assert_not_tracked(&self, name: &str) {
+        let names = self.tracked_names();
+        assert!(
+            !names.iter().any(|n| n == name),
+            "expected event {name:?} not to be tracked, got {names:?}"
+        );
+    }
+
+    /// Forget all recorded events.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    /// Record a flushed batch, reporting every event as processed.
+    pub(crate) fn record(&self, payload: &IngestPayload) -> IngestResponse {
+        self.lock().extend(payload.events.iter().cloned());
+        IngestResponse {
+            success: true,
+            processed: payload.events.len() as u32,
+            errors: None,
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<TrackerEvent>> {
+        self.events.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl OutlitBuilder {
+    /// Build a client that records flushed batches in memory instead of
+    /// sending them.
+    ///
+    /// All other configuration, such as batching, sampling, and `before_send`
+    /// hooks, applies as usual.
+    pub fn build_with_capture(self) -> Result<(Outlit, CaptureTransport), Error> {
+        let config = self.build_config()?;
+        let capture = CaptureTransport::default();
+        let client = Outlit::with_transport(config, |_| Ok(Transport::Capture(capture.clone())))?;
+        Ok((client, capture))
+    }
+}
+
+impl Outlit {
+    /// Create a client for tests that records each event as soon as it is
+    /// sent.
+    ///
+    /// Events are flushed one at a time, so they can be inspected right after
+    /// `send().await`. Use [`OutlitBuilder::build_with_capture`] to test with
+    /// other settings.
+    pub fn for_testing() -> (Outlit, CaptureTransport) {
+        Outlit::builder("pk_test")
+            .max_batch_size(1)
+            .disable_auto_flush()
+            .build_with_capture()
+            .expect("test client configuration is valid")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{email, user_id};
+
+    #[tokio::test]
+    async fn test_for_testing_captures_each_send() {
+        let (client, capture) = Outlit::for_testing();
+
+        client
+            .track("signup", email("user@example.com"))
+            .send()
+            .await
+            .unwrap();
+        client
+            .identify_by_user_id(user_id("usr_123"))
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(capture.events().len(), 2);
+        assert_eq!(capture.tracked_names(), vec!["signup"]);
+        capture.assert_tracked("signup");
+        capture.assert_not_tracked("login");
+        assert_eq!(client.pending_event_count().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_build_with_capture_applies_batching() {
+        let (client, capture) = Outlit::builder("pk_test")
+            .disable_auto_flush()
+            .build_with_capture()
+            .unwrap();
+
+        client
+            .track("signup", email("user@example.com"))
+            .send()
+            .await
+            .unwrap();
+        assert!(capture.events().is_empty());
+
+        client.flush().await.unwrap();
+        capture.assert_tracked("signup");
+
+        capture.clear();
+        assert!(capture.events().is_empty());
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "expected event \"login\" to be tracked")]
+    async fn test_assert_tracked_panics_when_missing() {
+        let (_client, capture) = Outlit::for_testing();
+        capture.assert_tracked("login");
+    }
+}
@@ -0,0 +1,91 @@
+//! Conversions from common time types to event timestamps.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A value that can be used as an event timestamp.
+///
+/// Event timestamps are milliseconds since the Unix epoch. Passing a
+/// `SystemTime` or datetime instead of a raw integer avoids unit mistakes,
+/// such as sending seconds.
+///
+/// Implemented for `i64` (milliseconds), [`SystemTime`],
+/// `chrono::DateTime<Utc>` with the `chrono` feature, and
+/// `time::OffsetDateTime` with the `time` feature.
+///
+/// # Example
+///
+/// ```rust
+/// use outlit::{email, Outlit};
+/// use std::time::{Duration, SystemTime};
+///
+/// # async fn example(client: &Outlit) -> Result<(), outlit::Error> {
+/// client.track("signup", email("user@example.com"))
+///     .timestamp(SystemTime::now() - Duration::from_secs(60))
+///     .send()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+pub trait IntoTimestamp {
+    /// Convert to milliseconds since the Unix epoch.
+    fn into_timestamp_millis(self) -> i64;
+}
+
+impl IntoTimestamp for i64 {
+    fn into_timestamp_millis(self) -> i64 {
+        self
+    }
+}
+
+impl IntoTimestamp for SystemTime {
+    fn into_timestamp_millis(self) -> i64 {
+        match self.duration_since(UNIX_EPOCH) {
+            Ok(d) => d.as_millis() as i64,
+            Err(e) => -(e.duration().as_millis() as i64),
+        }
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl IntoTimestamp for chrono::DateTime<chrono::Utc> {
+    fn into_timestamp_millis(self) -> i64 {
+        self.timestamp_millis()
+    }
+}
+
+#[cfg(feature = "time")]
+impl IntoTimestamp for time::OffsetDateTime {
+    fn into_timestamp_millis(self) -> i64 {
+        (self.unix_timestamp_nanos() / 1_000_000) as i64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_system_time_millis() {
+        let time = UNIX_EPOCH + Duration::from_millis(1706400000123);
+        assert_eq!(time.into_timestamp_millis(), 1706400000123);
+
+        let before_epoch = UNIX_EPOCH - Duration::from_millis(5);
+        assert_eq!(before_epoch.into_timestamp_millis(), -5);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_chrono_millis() {
+        let time = chrono::DateTime::from_timestamp_millis(1706400000123).unwrap();
+        assert_eq!(time.into_timestamp_millis(), 1706400000123);
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn test_time_millis() {
+        let time =
+            time::OffsetDateTime::from_unix_timestamp_nanos(1_706_400_000_123_456_789).unwrap();
+        assert_eq!(time.into_timestamp_millis(), 1706400000123);
+    }
+}
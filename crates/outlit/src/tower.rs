@@ -0,0 +1,180 @@
+//! Tower middleware that tracks HTTP requests as events.
+//!
+//! Enabled with the `tower` feature. [`OutlitLayer`] works with any
+//! `tower`-based server, such as axum, tonic, or hyper:
+//!
+//! ```rust,ignore
+//! use outlit::tower::OutlitLayer;
+//! use std::sync::Arc;
+//!
+//! let client = Arc::new(Outlit::builder("pk_xxx").build()?);
+//!
+//! let app = axum::Router::new()
+//!     .route("/", axum::routing::get(handler))
+//!     .layer(OutlitLayer::new(client).user_id_header("x-user-id"));
+//! ```
+//!
+//! Each request with an identity is tracked once its response is ready, as an
+//! `http_request` event with `method`, `path`, `status`, and `duration_ms`
+//! properties. Events are queued on a spawned task, so responses never wait
+//! on the client; they are sent in batches like any other event. Requests
+//! without an identity are not tracked.
+
+use crate::{email, user_id, Identity, Outlit};
+use http::{Extensions, HeaderMap, Request, Response};
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Instant;
+use tower_layer::Layer;
+use tower_service::Service;
+use tracing::warn;
+
+/// Default name of tracked request events.
+pub const DEFAULT_EVENT_NAME: &str = "http_request";
+
+type IdentifyFn = dyn Fn(&HeaderMap, &Extensions) -> Option<Identity> + Send + Sync;
+
+/// Layer that tracks requests through an [`Outlit`] client.
+///
+/// By default the identity is read from an [`Identity`] request extension,
+/// which authentication middleware can insert. Use
+/// [`user_id_header`](Self::user_id_header),
+/// [`email_header`](Self::email_header), or
+/// [`identify_with`](Self::identify_with) to read it from elsewhere.
+#[derive(Clone)]
+pub struct OutlitLayer {
+    client: Arc<Outlit>,
+    event_name: Arc<str>,
+    identify: Arc<IdentifyFn>,
+}
+
+impl OutlitLayer {
+    /// Create a layer that tracks requests through `client`.
+    pub fn new(client: Arc<Outlit>) -> Self {
+        Self {
+            client,
+            event_name: DEFAULT_EVENT_NAME.into(),
+            identify: Arc::new(|_, extensions| extensions.get::<Identity>().cloned()),
+        }
+    }
+
+    /// Set the name requests are tracked under. Defaults to `http_request`.
+    pub fn event_name(mut self, name: impl Into<String>) -> Self {
+        self.event_name = name.into().into();
+        self
+    }
+
+    /// Identify requests by the user ID in the `name` header.
+    pub fn user_id_header(self, name: &'static str) -> Self {
+        self.identify_with(move |headers, _| header_value(headers, name).map(|v| user_id(v).into()))
+    }
+
+    /// Identify requests by the email address in the `name` header.
+    pub fn email_header(self, name: &'static str) -> Self {
+        self.identify_with(move |headers, _| header_value(headers, name).map(|v| email(v).into()))
+    }
+
+    /// Identify requests with a custom function of the request headers and
+    /// extensions. Return `None` to leave a request untracked.
+    pub fn identify_with<F>(mut self, identify: F) -> Self
+    where
+        F: Fn(&HeaderMap, &Extensions) -> Option<Identity> + Send + Sync + 'static,
+    {
+        self.identify = Arc::new(identify);
+        self
+    }
+}
+
+impl fmt::Debug for OutlitLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OutlitLayer")
+            .field("event_name", &self.event_name)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S> Layer<S> for OutlitLayer {
+    type Service = OutlitService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        OutlitService {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+/// Service created by [`OutlitLayer`].
+#[derive(Clone, Debug)]
+pub struct OutlitService<S> {
+    inner: S,
+    layer: OutlitLayer,
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for OutlitService<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    ReqBody: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<ReqBody>) -> Self::Future {
+        let identity = (self.layer.identify)(request.headers(), request.extensions());
+        let method = request.method().to_string();
+        let path = request.uri().path().to_string();
+        let started = Instant::now();
+
+        // Use the instance that was polled ready and leave a fresh clone behind
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let layer = self.layer.clone();
+
+        Box::pin(async move {
+            let response = inner.call(request).await?;
+
+            if let Some(identity) = identity {
+                let status = response.status().as_u16();
+                let duration_ms = started.elapsed().as_millis() as u64;
+                tokio::spawn(async move {
+                    let client = &layer.client;
+                    let name = &*layer.event_name;
+                    let track = match identity {
+                        Identity::Email(email) => client.track(name, email),
+                        Identity::UserId(id) => client.track_by_user_id(name, id),
+                        Identity::Fingerprint(fp) => client.track_by_fingerprint(name, fp),
+                    };
+                    let result = track
+                        .property("method", method)
+                        .property("path", path)
+                        .property("status", status)
+                        .property("duration_ms", duration_ms)
+                        .send()
+                        .await;
+                    if let Err(e) = result {
+                        warn!(error = %e, "failed to track request");
+                    }
+                });
+            }
+
+            Ok(response)
+        })
+    }
+}
+
+fn header_value(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| !value.is_empty())
+        .map(String::from)
+}
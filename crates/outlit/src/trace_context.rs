@@ -0,0 +1,63 @@
+//! OpenTelemetry trace context for events and ingest requests.
+
+use opentelemetry::trace::{SpanContext, TraceContextExt};
+use opentelemetry::Context;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+/// Get the span context of the current `tracing` span, falling back to the
+/// current OpenTelemetry context. Returns `None` outside a sampled or
+/// remote span.
+pub(crate) fn current() -> Option<SpanContext> {
+    let from_tracing = tracing::Span::current().context();
+    let span_context = from_tracing.span().span_context().clone();
+    if span_context.is_valid() {
+        return Some(span_context);
+    }
+
+    let span_context = Context::current().span().span_context().clone();
+    span_context.is_valid().then_some(span_context)
+}
+
+/// Format a W3C `traceparent` header value.
+pub(crate) fn traceparent(span_context: &SpanContext) -> String {
+    format!(
+        "00-{}-{}-{:02x}",
+        span_context.trace_id(),
+        span_context.span_id(),
+        span_context.trace_flags().to_u8()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::trace::{SpanId, TraceFlags, TraceId, TraceState};
+
+    fn span_context() -> SpanContext {
+        SpanContext::new(
+            TraceId::from_hex("4bf92f3577b34da6a3ce929d0e0e4736").unwrap(),
+            SpanId::from_hex("00f067aa0ba902b7").unwrap(),
+            TraceFlags::SAMPLED,
+            true,
+            TraceState::default(),
+        )
+    }
+
+    #[test]
+    fn test_traceparent_format() {
+        assert_eq!(
+            traceparent(&span_context()),
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01"
+        );
+    }
+
+    #[test]
+    fn test_current_uses_attached_context() {
+        assert!(current().is_none());
+
+        let _guard = Context::new()
+            .with_remote_span_context(span_context())
+            .attach();
+        assert_eq!(current(), Some(span_context()));
+    }
+}
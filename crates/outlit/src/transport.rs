@@ -1,48 +1,286 @@
 //! HTTP transport for sending events.
 
-use crate::config::Config;
+use crate::config::{Config, FieldCase, RetryPolicy, SecretKey};
+use crate::sampling::Rng;
 use crate::types::{IngestPayload, IngestResponse};
 use crate::Error;
-use tracing::{debug, warn};
+use hmac::{Hmac, Mac};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use sha2::Sha256;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{debug, trace, warn};
+
+/// Destination for flushed batches.
+#[derive(Debug)]
+pub enum Transport {
+    /// Send to the Outlit API.
+    Http(Box<HttpTransport>),
+    /// Record in memory, for tests.
+    #[cfg(feature = "test-util")]
+    Capture(crate::testing::CaptureTransport),
+}
+
+impl Transport {
+    /// Send a payload to the configured destination.
+    pub async fn send(&self, payload: &IngestPayload) -> Result<IngestResponse, Error> {
+        match self {
+            Transport::Http(transport) => transport.send(payload).await,
+            #[cfg(feature = "test-util")]
+            Transport::Capture(capture) => Ok(capture.record(payload)),
+        }
+    }
+
+    /// Fetch a resource from the read API; `None` if it doesn't exist.
+    pub(crate) async fn get<T: DeserializeOwned>(
+        &self,
+        segments: &[&str],
+    ) -> Result<Option<T>, Error> {
+        match self {
+            Transport::Http(transport) => transport.get(segments).await,
+            #[cfg(feature = "test-util")]
+            Transport::Capture(_) => Err(Error::Config(
+                "the read API is not available on a test client".into(),
+            )),
+        }
+    }
+
+    /// Post a request to the API.
+    pub(crate) async fn post(&self, segments: &[&str], body: &Value) -> Result<(), Error> {
+        match self {
+            Transport::Http(transport) => transport.post(segments, body).await,
+            // Nothing to report back, so test clients accept and drop it
+            #[cfg(feature = "test-util")]
+            Transport::Capture(_) => Ok(()),
+        }
+    }
+}
 
 /// HTTP transport for sending events to the Outlit API.
 #[derive(Debug)]
 pub struct HttpTransport {
     client: reqwest::Client,
+    timeout: Duration,
+    user_agent: String,
+    headers: reqwest::header::HeaderMap,
+    secret_key: Option<SecretKey>,
+    api_host: String,
     endpoint: String,
+    fallback_endpoint: Option<String>,
+    field_case: FieldCase,
+    log_bodies: bool,
+    #[cfg(feature = "opentelemetry")]
+    trace_context: bool,
+    retry: RetryPolicy,
+    rng: Rng,
 }
 
 impl HttpTransport {
     /// Create a new HTTP transport.
     pub fn new(config: &Config) -> Result<Self, Error> {
-        let client = reqwest::Client::builder()
-            .timeout(config.timeout())
-            .build()?;
-
-        let endpoint = format!(
-            "{}/api/i/v1/{}/events",
-            config.api_host(),
-            config.public_key()
-        );
+        let client = match config.http_client() {
+            Some(client) => client.clone(),
+            None => {
+                let mut builder = reqwest::Client::builder()
+                    .timeout(config.timeout())
+                    .user_agent(config.user_agent());
+                if let Some(connect_timeout) = config.connect_timeout() {
+                    builder = builder.connect_timeout(connect_timeout);
+                }
+                if let Some(proxy) = config.proxy() {
+                    builder = builder.proxy(reqwest::Proxy::all(proxy)?);
+                }
+                builder.build()?
+            }
+        };
+
+        let path = config
+            .endpoint_path()
+            .replace("{public_key}", config.public_key());
+        let endpoint = format!("{}{}", config.api_host(), path);
+        let fallback_endpoint = config
+            .fallback_api_host()
+            .map(|host| format!("{host}{path}"));
 
-        Ok(Self { client, endpoint })
+        Ok(Self {
+            client,
+            timeout: config.timeout(),
+            user_agent: config.user_agent().to_string(),
+            headers: config.headers().clone(),
+            secret_key: config.secret_key.clone(),
+            api_host: config.api_host().to_string(),
+            endpoint,
+            fallback_endpoint,
+            field_case: config.field_case(),
+            retry: config.retry_policy(),
+            rng: Rng::new(),
+            log_bodies: config.log_bodies(),
+            #[cfg(feature = "opentelemetry")]
+            trace_context: config.trace_context(),
+        })
     }
 
     /// Send a payload to the ingest API.
+    ///
+    /// If the primary host is unreachable or returns a 5xx error and a
+    /// fallback host is configured, the payload is sent there instead.
     pub async fn send(&self, payload: &IngestPayload) -> Result<IngestResponse, Error> {
-        debug!(
-            endpoint = %self.endpoint,
-            event_count = payload.events.len(),
-            "sending events"
-        );
+        let body = encode_payload(payload, self.field_case)?;
+        if self.log_bodies {
+            trace!(body = %String::from_utf8_lossy(&body), "request body");
+        }
 
+        let event_count = payload.events.len();
+        match (
+            self.send_with_retries(&self.endpoint, &body, event_count)
+                .await,
+            &self.fallback_endpoint,
+        ) {
+            (Err(e), Some(fallback)) if is_transient(&e) => {
+                warn!(error = %e, fallback = %fallback, "primary host failed, trying fallback");
+                self.send_with_retries(fallback, &body, event_count).await
+            }
+            (result, _) => result,
+        }
+    }
+
+    /// GET `/api/v1/{segments}`. Returns `None` on 404.
+    async fn get<T: DeserializeOwned>(&self, segments: &[&str]) -> Result<Option<T>, Error> {
         let response = self
-            .client
-            .post(&self.endpoint)
-            .header("Content-Type", "application/json")
-            .json(payload)
+            .api_request(reqwest::Method::GET, segments)?
             .send()
             .await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let response = check_api_response(response).await?;
+        Ok(Some(serde_json::from_slice(&response.bytes().await?)?))
+    }
+
+    /// POST `body` as JSON to `/api/v1/{segments}`.
+    async fn post(&self, segments: &[&str], body: &Value) -> Result<(), Error> {
+        let response = self
+            .api_request(reqwest::Method::POST, segments)?
+            .json(body)
+            .send()
+            .await?;
+        check_api_response(response).await.map(drop)
+    }
+
+    /// Build a request to `/api/v1/{segments}` on the API host,
+    /// authenticated with the secret key.
+    fn api_request(
+        &self,
+        method: reqwest::Method,
+        segments: &[&str],
+    ) -> Result<reqwest::RequestBuilder, Error> {
+        let Some(secret_key) = &self.secret_key else {
+            return Err(Error::Config(
+                "this API requires a secret key; set .secret_key()".into(),
+            ));
+        };
+        let mut url = reqwest::Url::parse(&self.api_host)
+            .map_err(|e| Error::Config(format!("invalid API host: {e}")))?;
+        url.path_segments_mut()
+            .map_err(|_| Error::Config(format!("invalid API host: {}", self.api_host)))?
+            .pop_if_empty()
+            .extend(["api", "v1"])
+            .extend(segments);
+        debug!(method = %method, url = %url, "API request");
+
+        Ok(self
+            .client
+            .request(method, url)
+            .timeout(self.timeout)
+            .header("User-Agent", &self.user_agent)
+            .headers(self.headers.clone())
+            .bearer_auth(&secret_key.0))
+    }
+
+    /// Send to `endpoint`, retrying transient failures per the retry policy.
+    async fn send_with_retries(
+        &self,
+        endpoint: &str,
+        body: &[u8],
+        event_count: usize,
+    ) -> Result<IngestResponse, Error> {
+        let mut attempt = 1;
+        loop {
+            let result = self.send_to(endpoint, body, event_count).await;
+            if let Err(e) = &result {
+                crate::metrics::http_error(e);
+            }
+            match result {
+                // Wait as asked, unless that's longer than the policy allows
+                Err(Error::RateLimited {
+                    retry_after: Some(retry_after),
+                }) if attempt < self.retry.max_attempts && retry_after <= self.retry.max_delay => {
+                    warn!(
+                        attempt,
+                        delay_ms = retry_after.as_millis() as u64,
+                        "rate limited, retrying"
+                    );
+                    tokio::time::sleep(retry_after).await;
+                    attempt += 1;
+                }
+                Err(e) if is_retryable(&e) && attempt < self.retry.max_attempts => {
+                    let delay = self.retry.delay(attempt, &self.rng);
+                    warn!(error = %e, attempt, delay_ms = delay.as_millis() as u64, "send failed, retrying");
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    async fn send_to(
+        &self,
+        endpoint: &str,
+        body: &[u8],
+        event_count: usize,
+    ) -> Result<IngestResponse, Error> {
+        debug!(endpoint = %endpoint, event_count, "sending events");
+
+        let request = self
+            .client
+            .post(endpoint)
+            // Set per request so they also apply to an injected client
+            .timeout(self.timeout)
+            .header("User-Agent", &self.user_agent)
+            .header("Content-Type", "application/json")
+            .headers(self.headers.clone());
+        let request = match &self.secret_key {
+            Some(secret_key) => {
+                let timestamp = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs();
+                request
+                    .header("X-Outlit-Timestamp", timestamp)
+                    .header("X-Outlit-Signature", sign(secret_key, timestamp, body))
+            }
+            None => request,
+        };
+        #[cfg(feature = "opentelemetry")]
+        let request = match self.trace_context.then(crate::trace_context::current) {
+            Some(Some(span_context)) => request.header(
+                "traceparent",
+                crate::trace_context::traceparent(&span_context),
+            ),
+            _ => request,
+        };
+        let response = request.body(body.to_vec()).send().await?;
+
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            let retry_after = response
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(parse_retry_after);
+            warn!(retry_after = ?retry_after, "rate limited by API");
+            return Err(Error::RateLimited { retry_after });
+        }
 
         if !response.status().is_success() {
             let status = response.status();
@@ -57,7 +295,13 @@ impl HttpTransport {
             });
         }
 
-        let result = response.json::<IngestResponse>().await?;
+        let result = if self.log_bodies {
+            let body = response.text().await?;
+            trace!(body = %body, "response body");
+            serde_json::from_str::<IngestResponse>(&body)?
+        } else {
+            response.json::<IngestResponse>().await?
+        };
 
         if let Some(errors) = &result.errors {
             for error in errors {
@@ -75,11 +319,144 @@ impl HttpTransport {
     }
 }
 
+/// Turn rate limiting and other error statuses of an API response into
+/// errors.
+async fn check_api_response(response: reqwest::Response) -> Result<reqwest::Response, Error> {
+    match response.status() {
+        reqwest::StatusCode::TOO_MANY_REQUESTS => Err(Error::RateLimited {
+            retry_after: response
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(parse_retry_after),
+        }),
+        status if !status.is_success() => Err(Error::Api {
+            status: status.as_u16(),
+            message: response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".into()),
+        }),
+        _ => Ok(response),
+    }
+}
+
+/// Serialize a payload as JSON with the configured field casing.
+fn encode_payload(payload: &IngestPayload, field_case: FieldCase) -> Result<Vec<u8>, Error> {
+    match field_case {
+        FieldCase::Camel => Ok(serde_json::to_vec(payload)?),
+        FieldCase::Snake => {
+            let mut value = serde_json::to_value(payload)?;
+            if let Some(library) = value.get_mut("library") {
+                snake_case_event(library);
+            }
+            if let Some(events) = value.get_mut("events").and_then(Value::as_array_mut) {
+                for event in events {
+                    snake_case_event(event);
+                }
+            }
+            Ok(serde_json::to_vec(&value)?)
+        }
+    }
+}
+
+/// Rename an event's field names to snake_case.
+///
+/// User-supplied `properties` and `traits` keep their keys as given.
+fn snake_case_event(event: &mut Value) {
+    let Some(fields) = event.as_object_mut() else {
+        return;
+    };
+    *fields = std::mem::take(fields)
+        .into_iter()
+        .map(|(key, mut value)| {
+            if matches!(key.as_str(), "previous" | "current" | "user") {
+                snake_case_event(&mut value);
+            }
+            (to_snake_case(&key), value)
+        })
+        .collect();
+}
+
+fn to_snake_case(name: &str) -> String {
+    let mut snake = String::with_capacity(name.len() + 4);
+    for c in name.chars() {
+        if c.is_ascii_uppercase() {
+            snake.push('_');
+            snake.push(c.to_ascii_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
+
+/// Compute the `X-Outlit-Signature` header value: `v1=` and the hex
+/// HMAC-SHA256 of `"{timestamp}.{body}"` keyed with the secret.
+pub(crate) fn sign(secret_key: &SecretKey, timestamp: u64, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret_key.0.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+
+    let digest = mac.finalize().into_bytes();
+    let mut signature = String::with_capacity(3 + digest.len() * 2);
+    signature.push_str("v1=");
+    for byte in digest {
+        signature.push_str(&format!("{byte:02x}"));
+    }
+    signature
+}
+
+/// Parse a `Retry-After` value given in seconds. HTTP dates are not
+/// supported.
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    value.trim().parse().ok().map(Duration::from_secs)
+}
+
+/// Whether a failed send is worth retrying with backoff: transient failures
+/// and rate limiting without a usable `Retry-After`.
+fn is_retryable(error: &Error) -> bool {
+    match error {
+        Error::RateLimited { retry_after } => retry_after.is_none(),
+        _ => is_transient(error),
+    }
+}
+
+/// Whether a failed send is worth retrying, or sending to the fallback host.
+///
+/// Only connection failures, timeouts, and server errors qualify; a 4xx
+/// would fail the same way again.
+fn is_transient(error: &Error) -> bool {
+    match error {
+        Error::Http(e) => e.is_connect() || e.is_timeout(),
+        Error::Api { status, .. } => *status >= 500,
+        _ => false,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::builders::BuildEvent;
     use crate::config::OutlitBuilder;
 
+    #[test]
+    fn test_parse_retry_after_seconds() {
+        assert_eq!(parse_retry_after(" 30 "), Some(Duration::from_secs(30)));
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2026 07:28:00 GMT"), None);
+    }
+
+    #[test]
+    fn test_sign_matches_reference_hmac() {
+        let secret_key = SecretKey("sk_test_secret".into());
+        assert_eq!(
+            sign(&secret_key, 1706400000, br#"{"events":[]}"#),
+            "v1=03a8c4129f2a950b4e9b7aab8227f58e16f4ae1083a9793dd6f94aebcfa795f3"
+        );
+    }
+
     #[test]
     fn test_endpoint_construction() {
         let config = OutlitBuilder::new("pk_test_123")
@@ -94,4 +471,175 @@ mod tests {
             "https://example.com/api/i/v1/pk_test_123/events"
         );
     }
+
+    #[test]
+    fn test_endpoint_with_custom_path_template() {
+        let config = OutlitBuilder::new("pk_test_123")
+            .api_host("https://proxy.example.com")
+            .endpoint_path("/outlit/ingest/{public_key}")
+            .build_config()
+            .unwrap();
+
+        let transport = HttpTransport::new(&config).unwrap();
+
+        assert_eq!(
+            transport.endpoint,
+            "https://proxy.example.com/outlit/ingest/pk_test_123"
+        );
+    }
+
+    #[test]
+    fn test_endpoint_defaults_to_hosted_api() {
+        let config = OutlitBuilder::new("pk_test").build_config().unwrap();
+
+        let transport = HttpTransport::new(&config).unwrap();
+
+        assert_eq!(
+            transport.endpoint,
+            "https://app.outlit.ai/api/i/v1/pk_test/events"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_connect_timeout_fails_before_request_timeout() {
+        // Non-routable address: connecting hangs until a timeout fires
+        let config = OutlitBuilder::new("pk_test")
+            .api_host("http://10.255.255.1")
+            .timeout(std::time::Duration::from_secs(30))
+            .connect_timeout(std::time::Duration::from_millis(200))
+            .build_config()
+            .unwrap();
+        let transport = HttpTransport::new(&config).unwrap();
+        let payload = IngestPayload::new(crate::types::SourceType::Server, vec![]);
+
+        let start = std::time::Instant::now();
+        let result = transport.send(&payload).await;
+
+        assert!(matches!(result, Err(Error::Http(_))));
+        assert!(start.elapsed() < std::time::Duration::from_secs(5));
+    }
+
+    /// Records the `body` field of every event.
+    #[derive(Clone, Default)]
+    struct BodyCapture(std::sync::Arc<std::sync::Mutex<Vec<String>>>);
+
+    struct BodyVisitor<'a>(&'a mut Vec<String>);
+
+    impl tracing::field::Visit for BodyVisitor<'_> {
+        fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+            if field.name() == "body" {
+                self.0.push(format!("{value:?}"));
+            }
+        }
+    }
+
+    impl tracing::Subscriber for BodyCapture {
+        fn enabled(&self, _: &tracing::Metadata<'_>) -> bool {
+            true
+        }
+        fn new_span(&self, _: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+            tracing::span::Id::from_u64(1)
+        }
+        fn record(&self, _: &tracing::span::Id, _: &tracing::span::Record<'_>) {}
+        fn record_follows_from(&self, _: &tracing::span::Id, _: &tracing::span::Id) {}
+        fn event(&self, event: &tracing::Event<'_>) {
+            event.record(&mut BodyVisitor(&mut self.0.lock().unwrap()));
+        }
+        fn enter(&self, _: &tracing::span::Id) {}
+        fn exit(&self, _: &tracing::span::Id) {}
+    }
+
+    async fn captured_bodies(log_bodies: bool) -> Vec<String> {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "success": true, "processed": 0 })),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let config = OutlitBuilder::new("pk_test")
+            .api_host(mock_server.uri())
+            .log_bodies(log_bodies)
+            .build_config()
+            .unwrap();
+        let transport = HttpTransport::new(&config).unwrap();
+        let payload = IngestPayload::new(crate::types::SourceType::Server, vec![]);
+
+        let capture = BodyCapture::default();
+        let _guard = tracing::subscriber::set_default(capture.clone());
+        transport.send(&payload).await.unwrap();
+
+        let bodies = capture.0.lock().unwrap().clone();
+        bodies
+    }
+
+    #[tokio::test]
+    async fn test_bodies_logged_when_enabled() {
+        let bodies = captured_bodies(true).await;
+
+        assert_eq!(bodies.len(), 2);
+        assert!(bodies[0].contains(r#""source":"server""#));
+        assert!(bodies[1].contains(r#""processed":0"#));
+    }
+
+    #[tokio::test]
+    async fn test_bodies_not_logged_by_default() {
+        assert!(captured_bodies(false).await.is_empty());
+    }
+
+    fn encoded_event(field_case: FieldCase) -> Value {
+        let event = crate::builders::TrackBuilder::new("signup", crate::email("user@example.com"))
+            .property("planName", "pro")
+            .idempotency_key("msg_1")
+            .build();
+        let payload = IngestPayload::new(crate::types::SourceType::Server, vec![event]);
+        let body = encode_payload(&payload, field_case).unwrap();
+        let json: Value = serde_json::from_slice(&body).unwrap();
+        json["events"][0].clone()
+    }
+
+    #[test]
+    fn test_camel_case_is_default_encoding() {
+        let config = OutlitBuilder::new("pk_test").build_config().unwrap();
+        assert_eq!(config.field_case(), FieldCase::Camel);
+
+        let event = encoded_event(FieldCase::Camel);
+        assert_eq!(event["eventName"], "signup");
+        assert_eq!(event["messageId"], "msg_1");
+        assert!(event.get("event_name").is_none());
+    }
+
+    #[test]
+    fn test_snake_case_encoding() {
+        let event = encoded_event(FieldCase::Snake);
+
+        assert_eq!(event["type"], "custom");
+        assert_eq!(event["event_name"], "signup");
+        assert_eq!(event["message_id"], "msg_1");
+        assert!(event.get("eventName").is_none());
+        // Property keys are left as given
+        assert_eq!(event["properties"]["planName"], "pro");
+        assert_eq!(event["properties"]["__userId"], Value::Null);
+    }
+
+    #[test]
+    fn test_snake_case_renames_nested_identifiers() {
+        let event = crate::builders::AliasBuilder::new(
+            crate::fingerprint("device_1"),
+            crate::user_id("usr_123"),
+        )
+        .build();
+        let payload = IngestPayload::new(crate::types::SourceType::Server, vec![event]);
+
+        let body = encode_payload(&payload, FieldCase::Snake).unwrap();
+        let json: Value = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(json["events"][0]["current"]["user_id"], "usr_123");
+        assert!(json["library"]["rust_version"].is_string());
+    }
 }
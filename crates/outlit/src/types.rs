@@ -3,33 +3,149 @@
 use serde::Serialize;
 use std::collections::HashMap;
 
+/// Prefix of property keys reserved for the SDK.
+///
+/// User properties with this prefix are moved under
+/// [`RESERVED_NAMESPACE`] instead of overwriting SDK-set values.
+pub const RESERVED_PREFIX: &str = "__";
+
+/// Namespace prepended to user property keys that start with
+/// [`RESERVED_PREFIX`], so `__email` is sent as `user.__email`.
+pub const RESERVED_NAMESPACE: &str = "user.";
+
+/// Reserved property carrying the email identity.
+pub const EMAIL_PROPERTY: &str = "__email";
+
+/// Reserved property carrying the user ID identity.
+pub const USER_ID_PROPERTY: &str = "__userId";
+
+/// Reserved property carrying the fingerprint identity.
+pub const FINGERPRINT_PROPERTY: &str = "__fingerprint";
+
+/// Reserved property carrying the client's
+/// [`environment`](crate::OutlitBuilder::environment).
+pub const ENVIRONMENT_PROPERTY: &str = "__environment";
+
+/// Reserved property carrying the [`Consent`] an event requires, set with
+/// `.consent()`.
+pub const CONSENT_PROPERTY: &str = "__consent";
+
+/// Reserved property listing keys truncated by
+/// [`max_property_bytes`](crate::OutlitBuilder::max_property_bytes).
+pub const TRUNCATED_PROPERTY: &str = "__truncated";
+
 /// Source type for events.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "serde-deserialize", derive(serde::Deserialize))]
 #[serde(rename_all = "lowercase")]
 pub enum SourceType {
     Server,
+    /// Data imported from another system, such as a historical backfill.
+    Integration,
 }
 
 /// Journey stage values.
-#[derive(Debug, Clone, Serialize)]
-#[serde(rename_all = "lowercase")]
+///
+/// Serialized as a lowercase string. Use [`JourneyStage::Custom`] for
+/// product-specific stages beyond the built-in ones.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum JourneyStage {
     Activated,
     Engaged,
     Inactive,
+    /// A product-specific stage, serialized as the given name.
+    Custom(String),
 }
 
-/// Billing status values.
-#[derive(Debug, Clone, Serialize)]
+impl JourneyStage {
+    /// Get the serialized name of the stage.
+    pub fn as_str(&self) -> &str {
+        match self {
+            JourneyStage::Activated => "activated",
+            JourneyStage::Engaged => "engaged",
+            JourneyStage::Inactive => "inactive",
+            JourneyStage::Custom(name) => name,
+        }
+    }
+}
+
+impl From<&str> for JourneyStage {
+    fn from(name: &str) -> Self {
+        match name {
+            "activated" => JourneyStage::Activated,
+            "engaged" => JourneyStage::Engaged,
+            "inactive" => JourneyStage::Inactive,
+            other => JourneyStage::Custom(other.to_string()),
+        }
+    }
+}
+
+impl Serialize for JourneyStage {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "serde-deserialize")]
+impl<'de> serde::Deserialize<'de> for JourneyStage {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Ok(JourneyStage::from(name.as_str()))
+    }
+}
+
+/// Consent category an event falls under, as decided in a consent
+/// management platform.
+///
+/// Serialized in lowercase.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, serde::Deserialize)]
 #[serde(rename_all = "lowercase")]
+pub enum Consent {
+    /// Needed to provide the service; always allowed.
+    Necessary,
+    /// Preferences and features beyond the essentials.
+    Functional,
+    /// Product analytics and usage measurement.
+    Analytics,
+    /// Advertising and marketing.
+    Marketing,
+}
+
+impl Consent {
+    /// Every category, for users who accept all.
+    pub const ALL: [Consent; 4] = [
+        Consent::Necessary,
+        Consent::Functional,
+        Consent::Analytics,
+        Consent::Marketing,
+    ];
+}
+
+/// Billing status values.
+///
+/// Serialized in snake_case, matching Stripe's subscription statuses
+/// (`"past_due"`, `"canceled"`, ...).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum BillingStatus {
     Trialing,
     Paid,
     Churned,
+    /// A payment failed and is being retried.
+    PastDue,
+    /// The subscription was canceled.
+    Canceled,
+    /// Payment retries were exhausted without cancelling.
+    Unpaid,
+    /// Billing is paused.
+    Paused,
+    /// The first payment has not completed yet.
+    Incomplete,
 }
 
 /// Custom event data.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "serde-deserialize", derive(serde::Deserialize))]
 #[serde(rename_all = "camelCase")]
 pub struct CustomEventData {
     pub timestamp: i64,
@@ -38,10 +154,41 @@ pub struct CustomEventData {
     pub event_name: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub properties: Option<HashMap<String, serde_json::Value>>,
+    /// Idempotency key used to deduplicate retried or repeated sends.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message_id: Option<String>,
+    /// Unique ID assigned when the event is built, for server-side
+    /// deduplication and for referring to the event in callbacks.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_id: Option<String>,
+}
+
+/// Page view event data, for web pages and app screens.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "serde-deserialize", derive(serde::Deserialize))]
+#[serde(rename_all = "camelCase")]
+pub struct PageviewEventData {
+    pub timestamp: i64,
+    pub url: String,
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub referrer: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub properties: Option<HashMap<String, serde_json::Value>>,
+    /// Idempotency key used to deduplicate retried or repeated sends.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message_id: Option<String>,
+    /// Unique ID assigned when the event is built, for server-side
+    /// deduplication and for referring to the event in callbacks.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_id: Option<String>,
 }
 
 /// Identify event data.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "serde-deserialize", derive(serde::Deserialize))]
 #[serde(rename_all = "camelCase")]
 pub struct IdentifyEventData {
     pub timestamp: i64,
@@ -55,22 +202,48 @@ pub struct IdentifyEventData {
     pub fingerprint: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub traits: Option<HashMap<String, serde_json::Value>>,
+    /// Idempotency key used to deduplicate retried or repeated sends.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message_id: Option<String>,
+    /// Unique ID assigned when the event is built, for server-side
+    /// deduplication and for referring to the event in callbacks.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_id: Option<String>,
 }
 
 /// Stage event data.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "serde-deserialize", derive(serde::Deserialize))]
 #[serde(rename_all = "camelCase")]
 pub struct StageEventData {
     pub timestamp: i64,
     pub url: String,
     pub path: String,
     pub stage: JourneyStage,
+    /// Why the user moved to this stage, such as `"completed_onboarding"`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// Stage the user moved from.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_stage: Option<JourneyStage>,
+    /// When the transition took effect, in milliseconds since the Unix
+    /// epoch, if different from `timestamp`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub effective_at: Option<i64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub properties: Option<HashMap<String, serde_json::Value>>,
+    /// Idempotency key used to deduplicate retried or repeated sends.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message_id: Option<String>,
+    /// Unique ID assigned when the event is built, for server-side
+    /// deduplication and for referring to the event in callbacks.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_id: Option<String>,
 }
 
 /// Billing event data.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "serde-deserialize", derive(serde::Deserialize))]
 #[serde(rename_all = "camelCase")]
 pub struct BillingEventData {
     pub timestamp: i64,
@@ -83,50 +256,500 @@ pub struct BillingEventData {
     pub stripe_customer_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub domain: Option<String>,
+    /// Amount charged, in the currency's minor unit (e.g. cents).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount: Option<i64>,
+    /// Lowercase ISO 4217 currency code, such as `"usd"`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
+    /// Monthly recurring revenue, in the currency's minor unit.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mrr: Option<i64>,
+    /// How often the customer is billed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interval: Option<BillingInterval>,
+    /// What happened, for events about a single invoice or change rather
+    /// than a status update.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub activity: Option<BillingActivity>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub invoice_id: Option<String>,
+    /// Current plan.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plan: Option<String>,
+    /// Plan before a [`BillingActivity::PlanChanged`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_plan: Option<String>,
+    /// Number of seats, or the subscription quantity.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seats: Option<u32>,
+    /// Seats before a [`BillingActivity::SeatsChanged`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_seats: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub properties: Option<HashMap<String, serde_json::Value>>,
+    /// Idempotency key used to deduplicate retried or repeated sends.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message_id: Option<String>,
+    /// Unique ID assigned when the event is built, for server-side
+    /// deduplication and for referring to the event in callbacks.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_id: Option<String>,
+}
+
+/// Subscription lifecycle events sent alongside a [`BillingStatus`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "serde-deserialize", derive(serde::Deserialize))]
+#[serde(rename_all = "snake_case")]
+pub enum BillingActivity {
+    InvoicePaid,
+    InvoiceFailed,
+    Refund,
+    PlanChanged,
+    SeatsChanged,
+}
+
+/// Billing period of a subscription.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BillingInterval {
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+/// Set of identifiers for a user.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "serde-deserialize", derive(serde::Deserialize))]
+#[serde(rename_all = "camelCase")]
+pub struct Identifiers {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<String>,
+}
+
+impl Identifiers {
+    /// Read identifiers from the reserved identity properties.
+    fn from_properties(properties: Option<&HashMap<String, serde_json::Value>>) -> Self {
+        let get = |key: &str| {
+            properties
+                .and_then(|props| props.get(key))
+                .and_then(serde_json::Value::as_str)
+                .map(String::from)
+        };
+        Self {
+            email: get(EMAIL_PROPERTY),
+            user_id: get(USER_ID_PROPERTY),
+            fingerprint: get(FINGERPRINT_PROPERTY),
+        }
+    }
+}
+
+/// Alias event data, linking a previous identity to the current one.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "serde-deserialize", derive(serde::Deserialize))]
+#[serde(rename_all = "camelCase")]
+pub struct AliasEventData {
+    pub timestamp: i64,
+    pub url: String,
+    pub path: String,
+    pub previous: Identifiers,
+    pub current: Identifiers,
+    /// Idempotency key used to deduplicate retried or repeated sends.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message_id: Option<String>,
+    /// Unique ID assigned when the event is built, for server-side
+    /// deduplication and for referring to the event in callbacks.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_id: Option<String>,
+}
+
+/// Group (account) event data.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "serde-deserialize", derive(serde::Deserialize))]
+#[serde(rename_all = "camelCase")]
+pub struct GroupEventData {
+    pub timestamp: i64,
+    pub url: String,
+    pub path: String,
+    pub group_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<Identifiers>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub traits: Option<HashMap<String, serde_json::Value>>,
+    /// Idempotency key used to deduplicate retried or repeated sends.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message_id: Option<String>,
+    /// Unique ID assigned when the event is built, for server-side
+    /// deduplication and for referring to the event in callbacks.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_id: Option<String>,
 }
 
 /// All event types.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "serde-deserialize", derive(serde::Deserialize))]
 #[serde(tag = "type", rename_all = "camelCase")]
 pub enum TrackerEvent {
     #[serde(rename = "custom")]
     Custom(CustomEventData),
+    #[serde(rename = "pageview")]
+    Pageview(PageviewEventData),
     #[serde(rename = "identify")]
     Identify(IdentifyEventData),
     #[serde(rename = "stage")]
     Stage(StageEventData),
     #[serde(rename = "billing")]
     Billing(BillingEventData),
+    #[serde(rename = "alias")]
+    Alias(AliasEventData),
+    #[serde(rename = "group")]
+    Group(GroupEventData),
+}
+
+impl TrackerEvent {
+    /// Get the event's idempotency key, if set.
+    pub fn message_id(&self) -> Option<&str> {
+        match self {
+            TrackerEvent::Custom(e) => e.message_id.as_deref(),
+            TrackerEvent::Pageview(e) => e.message_id.as_deref(),
+            TrackerEvent::Identify(e) => e.message_id.as_deref(),
+            TrackerEvent::Stage(e) => e.message_id.as_deref(),
+            TrackerEvent::Billing(e) => e.message_id.as_deref(),
+            TrackerEvent::Alias(e) => e.message_id.as_deref(),
+            TrackerEvent::Group(e) => e.message_id.as_deref(),
+        }
+    }
+
+    /// Get the event's client-side ID, assigned when it was built.
+    pub fn event_id(&self) -> Option<&str> {
+        match self {
+            TrackerEvent::Custom(e) => e.event_id.as_deref(),
+            TrackerEvent::Pageview(e) => e.event_id.as_deref(),
+            TrackerEvent::Identify(e) => e.event_id.as_deref(),
+            TrackerEvent::Stage(e) => e.event_id.as_deref(),
+            TrackerEvent::Billing(e) => e.event_id.as_deref(),
+            TrackerEvent::Alias(e) => e.event_id.as_deref(),
+            TrackerEvent::Group(e) => e.event_id.as_deref(),
+        }
+    }
+
+    /// Assign a new event ID if the event has none, e.g. because it was
+    /// constructed directly rather than with a builder.
+    pub(crate) fn ensure_event_id(&mut self) {
+        let event_id = match self {
+            TrackerEvent::Custom(e) => &mut e.event_id,
+            TrackerEvent::Pageview(e) => &mut e.event_id,
+            TrackerEvent::Identify(e) => &mut e.event_id,
+            TrackerEvent::Stage(e) => &mut e.event_id,
+            TrackerEvent::Billing(e) => &mut e.event_id,
+            TrackerEvent::Alias(e) => &mut e.event_id,
+            TrackerEvent::Group(e) => &mut e.event_id,
+        };
+        event_id.get_or_insert_with(crate::builders::new_event_id);
+    }
+
+    /// Get the event's properties, or traits for identify and group events.
+    ///
+    /// Includes the reserved `__` properties the SDK adds, such as the
+    /// identity. Returns `None` for aliases and events without any.
+    pub fn properties(&self) -> Option<&HashMap<String, serde_json::Value>> {
+        match self {
+            TrackerEvent::Custom(e) => e.properties.as_ref(),
+            TrackerEvent::Pageview(e) => e.properties.as_ref(),
+            TrackerEvent::Identify(e) => e.traits.as_ref(),
+            TrackerEvent::Stage(e) => e.properties.as_ref(),
+            TrackerEvent::Billing(e) => e.properties.as_ref(),
+            TrackerEvent::Alias(_) => None,
+            TrackerEvent::Group(e) => e.traits.as_ref(),
+        }
+    }
+
+    /// Mutable version of [`properties`](Self::properties).
+    pub(crate) fn properties_mut(&mut self) -> Option<&mut HashMap<String, serde_json::Value>> {
+        match self {
+            TrackerEvent::Custom(e) => e.properties.as_mut(),
+            TrackerEvent::Pageview(e) => e.properties.as_mut(),
+            TrackerEvent::Identify(e) => e.traits.as_mut(),
+            TrackerEvent::Stage(e) => e.properties.as_mut(),
+            TrackerEvent::Billing(e) => e.properties.as_mut(),
+            TrackerEvent::Alias(_) => None,
+            TrackerEvent::Group(e) => e.traits.as_mut(),
+        }
+    }
+
+    /// Like [`properties_mut`](Self::properties_mut), but creating an empty
+    /// map if the event has none. Returns `None` for aliases.
+    pub(crate) fn properties_or_default(
+        &mut self,
+    ) -> Option<&mut HashMap<String, serde_json::Value>> {
+        let properties = match self {
+            TrackerEvent::Custom(e) => &mut e.properties,
+            TrackerEvent::Pageview(e) => &mut e.properties,
+            TrackerEvent::Identify(e) => &mut e.traits,
+            TrackerEvent::Stage(e) => &mut e.properties,
+            TrackerEvent::Billing(e) => &mut e.properties,
+            TrackerEvent::Alias(_) => return None,
+            TrackerEvent::Group(e) => &mut e.traits,
+        };
+        Some(properties.get_or_insert_with(HashMap::new))
+    }
+
+    /// Get the event timestamp (milliseconds since epoch).
+    pub fn timestamp(&self) -> i64 {
+        match self {
+            TrackerEvent::Custom(e) => e.timestamp,
+            TrackerEvent::Pageview(e) => e.timestamp,
+            TrackerEvent::Identify(e) => e.timestamp,
+            TrackerEvent::Stage(e) => e.timestamp,
+            TrackerEvent::Billing(e) => e.timestamp,
+            TrackerEvent::Alias(e) => e.timestamp,
+            TrackerEvent::Group(e) => e.timestamp,
+        }
+    }
+
+    pub(crate) fn set_timestamp(&mut self, timestamp: i64) {
+        match self {
+            TrackerEvent::Custom(e) => e.timestamp = timestamp,
+            TrackerEvent::Pageview(e) => e.timestamp = timestamp,
+            TrackerEvent::Identify(e) => e.timestamp = timestamp,
+            TrackerEvent::Stage(e) => e.timestamp = timestamp,
+            TrackerEvent::Billing(e) => e.timestamp = timestamp,
+            TrackerEvent::Alias(e) => e.timestamp = timestamp,
+            TrackerEvent::Group(e) => e.timestamp = timestamp,
+        }
+    }
+
+    /// Get the event type, as serialized in the `type` field.
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            TrackerEvent::Custom(_) => "custom",
+            TrackerEvent::Pageview(_) => "pageview",
+            TrackerEvent::Identify(_) => "identify",
+            TrackerEvent::Stage(_) => "stage",
+            TrackerEvent::Billing(_) => "billing",
+            TrackerEvent::Alias(_) => "alias",
+            TrackerEvent::Group(_) => "group",
+        }
+    }
+
+    /// Get the consent the event was marked with, from the reserved
+    /// [`CONSENT_PROPERTY`].
+    pub(crate) fn consent(&self) -> Option<Consent> {
+        let properties = match self {
+            TrackerEvent::Custom(e) => e.properties.as_ref(),
+            TrackerEvent::Pageview(e) => e.properties.as_ref(),
+            TrackerEvent::Identify(e) => e.traits.as_ref(),
+            TrackerEvent::Stage(e) => e.properties.as_ref(),
+            _ => None,
+        };
+        serde_json::from_value(properties?.get(CONSENT_PROPERTY)?.clone()).ok()
+    }
+
+    /// Get the name of a custom event.
+    pub fn event_name(&self) -> Option<&str> {
+        match self {
+            TrackerEvent::Custom(e) => Some(&e.event_name),
+            _ => None,
+        }
+    }
+
+    /// Get the identifiers of the user the event is about.
+    ///
+    /// For track, page view, and stage events these are read from the reserved
+    /// `__email`/`__userId`/`__fingerprint` properties; for aliases this is
+    /// the current identity. Returns `None` for billing events, which are
+    /// keyed by domain, and when no identifier is set.
+    pub fn identity(&self) -> Option<Identifiers> {
+        let identifiers = match self {
+            TrackerEvent::Custom(e) => Identifiers::from_properties(e.properties.as_ref()),
+            TrackerEvent::Pageview(e) => Identifiers::from_properties(e.properties.as_ref()),
+            TrackerEvent::Stage(e) => Identifiers::from_properties(e.properties.as_ref()),
+            TrackerEvent::Identify(e) => Identifiers {
+                email: e.email.clone(),
+                user_id: e.user_id.clone(),
+                fingerprint: e.fingerprint.clone(),
+            },
+            TrackerEvent::Alias(e) => e.current.clone(),
+            TrackerEvent::Group(e) => e.user.clone()?,
+            TrackerEvent::Billing(_) => return None,
+        };
+
+        let empty = identifiers.email.is_none()
+            && identifiers.user_id.is_none()
+            && identifiers.fingerprint.is_none();
+        (!empty).then_some(identifiers)
+    }
 }
 
 /// Payload sent to the ingest API.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "serde-deserialize", derive(serde::Deserialize))]
 #[serde(rename_all = "camelCase")]
 pub struct IngestPayload {
     pub source: SourceType,
     pub events: Vec<TrackerEvent>,
+    /// The SDK that sent the payload.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub library: Option<Library>,
+}
+
+impl IngestPayload {
+    /// Create a payload tagged with this SDK's [`Library`] details.
+    pub fn new(source: SourceType, events: Vec<TrackerEvent>) -> Self {
+        Self {
+            source,
+            events,
+            library: Some(Library::current()),
+        }
+    }
+}
+
+/// Name this SDK reports in [`Library::name`].
+pub const SDK_NAME: &str = "outlit-rust";
+
+/// SDK and runtime details sent with each payload, so traffic can be
+/// attributed to an SDK version.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "serde-deserialize", derive(serde::Deserialize))]
+#[serde(rename_all = "camelCase")]
+pub struct Library {
+    /// SDK name, [`SDK_NAME`] for this crate.
+    pub name: String,
+    /// SDK version.
+    pub version: String,
+    /// Version of the Rust compiler the SDK was built with.
+    pub rust_version: String,
+    /// Operating system, as in [`std::env::consts::OS`].
+    pub os: String,
+}
+
+impl Library {
+    /// Details of this SDK build.
+    pub fn current() -> Self {
+        Self {
+            name: SDK_NAME.into(),
+            version: env!("CARGO_PKG_VERSION").into(),
+            rust_version: env!("OUTLIT_RUSTC_VERSION").into(),
+            os: std::env::consts::OS.into(),
+        }
+    }
 }
 
 /// Response from the ingest API.
-#[derive(Debug, Clone, serde::Deserialize)]
+///
+/// Also serializable, so a relay that receives payloads can answer in the
+/// API's format.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct IngestResponse {
     pub success: bool,
     pub processed: u32,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub errors: Option<Vec<IngestError>>,
 }
 
 /// Error from the ingest API.
-#[derive(Debug, Clone, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct IngestError {
     pub index: usize,
     pub message: String,
 }
 
+/// A customer's billing state as Outlit reports it, returned by
+/// [`CustomerMethods::status`](crate::CustomerMethods::status).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomerStatus {
+    pub domain: String,
+    pub status: BillingStatus,
+    /// Monthly recurring revenue, in the currency's minor unit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mrr: Option<i64>,
+    /// Lowercase ISO 4217 currency code.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interval: Option<BillingInterval>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plan: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seats: Option<u32>,
+    /// When the status last changed, in milliseconds since the Unix epoch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<i64>,
+}
+
+/// Outcome of a flush.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FlushReport {
+    /// Number of events sent to the API.
+    pub sent: usize,
+    /// Number of events the API reported as processed.
+    pub processed: u32,
+    /// Events the API rejected, with the reason for each.
+    pub rejected: Vec<RejectedEvent>,
+    /// Events dropped before sending because they failed to serialize or
+    /// exceeded [`max_payload_bytes`](crate::OutlitBuilder::max_payload_bytes).
+    ///
+    /// Their `index` is the position within the flushed batch.
+    pub quarantined: Vec<RejectedEvent>,
+}
+
+impl FlushReport {
+    /// Map an API response back onto the events that were sent.
+    pub(crate) fn from_response(events: Vec<TrackerEvent>, response: IngestResponse) -> Self {
+        let sent = events.len();
+        let mut events: Vec<Option<TrackerEvent>> = events.into_iter().map(Some).collect();
+        let rejected = response
+            .errors
+            .unwrap_or_default()
+            .into_iter()
+            .map(|error| RejectedEvent {
+                event: events.get_mut(error.index).and_then(Option::take),
+                index: error.index,
+                message: error.message,
+            })
+            .collect();
+
+        Self {
+            sent,
+            processed: response.processed,
+            rejected,
+            quarantined: Vec::new(),
+        }
+    }
+
+    /// Fold in the report for a sub-batch starting at `offset` within this
+    /// flush, shifting its rejected indices accordingly.
+    pub(crate) fn merge(&mut self, other: FlushReport, offset: usize) {
+        self.sent += other.sent;
+        self.processed += other.processed;
+        self.rejected
+            .extend(other.rejected.into_iter().map(|mut rejected| {
+                rejected.index += offset;
+                rejected
+            }));
+        self.quarantined.extend(other.quarantined);
+    }
+}
+
+/// An event rejected by the API.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RejectedEvent {
+    /// Index of the event within the sent batch.
+    pub index: usize,
+    /// Error message from the API.
+    pub message: String,
+    /// The rejected event, if the index matched one that was sent.
+    pub event: Option<TrackerEvent>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -140,6 +763,8 @@ mod tests {
             path: "/".into(),
             event_name: "signup".into(),
             properties: Some(HashMap::from([("plan".into(), json!("pro"))])),
+            message_id: None,
+            event_id: None,
         });
 
         let json = serde_json::to_value(&event).unwrap();
@@ -159,6 +784,8 @@ mod tests {
             user_id: Some("usr_123".into()),
             fingerprint: None,
             traits: None,
+            message_id: None,
+            event_id: None,
         });
 
         let json = serde_json::to_value(&event).unwrap();
@@ -177,6 +804,8 @@ mod tests {
             user_id: Some("usr_123".into()),
             fingerprint: Some("device_abc123".into()),
             traits: None,
+            message_id: None,
+            event_id: None,
         });
 
         let json = serde_json::to_value(&event).unwrap();
@@ -197,6 +826,8 @@ mod tests {
             user_id: None,
             fingerprint: None,
             traits: None,
+            message_id: None,
+            event_id: None,
         });
 
         let json_str = serde_json::to_string(&event).unwrap();
@@ -211,7 +842,12 @@ mod tests {
             url: "server://user@example.com".into(),
             path: "/".into(),
             stage: JourneyStage::Activated,
+            reason: None,
+            previous_stage: None,
+            effective_at: None,
             properties: None,
+            message_id: None,
+            event_id: None,
         });
 
         let json = serde_json::to_value(&event).unwrap();
@@ -221,6 +857,42 @@ mod tests {
         assert_eq!(json["stage"], "activated");
     }
 
+    #[test]
+    fn test_custom_stage_serialization() {
+        let event = TrackerEvent::Stage(StageEventData {
+            timestamp: 1706400000000,
+            url: "server://user@example.com".into(),
+            path: "/".into(),
+            stage: JourneyStage::Custom("power_user".into()),
+            reason: None,
+            previous_stage: None,
+            effective_at: None,
+            properties: None,
+            message_id: None,
+            event_id: None,
+        });
+
+        let json = serde_json::to_value(&event).unwrap();
+
+        assert_eq!(json["stage"], "power_user");
+    }
+
+    #[test]
+    fn test_builtin_stages_serialize_lowercase() {
+        assert_eq!(json!(JourneyStage::Activated), json!("activated"));
+        assert_eq!(json!(JourneyStage::Engaged), json!("engaged"));
+        assert_eq!(json!(JourneyStage::Inactive), json!("inactive"));
+    }
+
+    #[test]
+    fn test_stage_from_name() {
+        assert_eq!(JourneyStage::from("activated"), JourneyStage::Activated);
+        assert_eq!(
+            JourneyStage::from("onboarded"),
+            JourneyStage::Custom("onboarded".into())
+        );
+    }
+
     #[test]
     fn test_billing_event_camel_case() {
         let event = TrackerEvent::Billing(BillingEventData {
@@ -231,7 +903,19 @@ mod tests {
             customer_id: Some("cust_123".into()),
             stripe_customer_id: Some("cus_xxx".into()),
             domain: Some("acme.com".into()),
+            amount: None,
+            currency: None,
+            mrr: None,
+            interval: None,
+            activity: None,
+            invoice_id: None,
+            plan: None,
+            previous_plan: None,
+            seats: None,
+            previous_seats: None,
             properties: None,
+            message_id: None,
+            event_id: None,
         });
 
         let json = serde_json::to_value(&event).unwrap();
@@ -242,6 +926,98 @@ mod tests {
         assert_eq!(json["stripeCustomerId"], "cus_xxx"); // camelCase
     }
 
+    #[test]
+    fn test_alias_event_serialization() {
+        let event = TrackerEvent::Alias(AliasEventData {
+            timestamp: 1706400000000,
+            url: "server://new@example.com".into(),
+            path: "/".into(),
+            previous: Identifiers {
+                email: Some("old@example.com".into()),
+                user_id: None,
+                fingerprint: None,
+            },
+            current: Identifiers {
+                email: Some("new@example.com".into()),
+                user_id: Some("usr_123".into()),
+                fingerprint: None,
+            },
+            message_id: None,
+            event_id: None,
+        });
+
+        let json = serde_json::to_value(&event).unwrap();
+
+        assert_eq!(json["type"], "alias");
+        assert_eq!(json["previous"], json!({ "email": "old@example.com" }));
+        assert_eq!(
+            json["current"],
+            json!({ "email": "new@example.com", "userId": "usr_123" })
+        );
+    }
+
+    #[test]
+    fn test_group_event_camel_case() {
+        let event = TrackerEvent::Group(GroupEventData {
+            timestamp: 1706400000000,
+            url: "server://org_123".into(),
+            path: "/".into(),
+            group_id: "org_123".into(),
+            user: Some(Identifiers {
+                email: Some("user@example.com".into()),
+                user_id: None,
+                fingerprint: None,
+            }),
+            traits: Some(HashMap::from([("plan".into(), json!("enterprise"))])),
+            message_id: None,
+            event_id: None,
+        });
+
+        let json = serde_json::to_value(&event).unwrap();
+
+        assert_eq!(json["type"], "group");
+        assert_eq!(json["groupId"], "org_123"); // camelCase
+        assert_eq!(json["user"]["email"], "user@example.com");
+        assert_eq!(json["traits"]["plan"], "enterprise");
+    }
+
+    #[test]
+    fn test_group_event_omits_empty_fields() {
+        let event = TrackerEvent::Group(GroupEventData {
+            timestamp: 1706400000000,
+            url: "server://org_123".into(),
+            path: "/".into(),
+            group_id: "org_123".into(),
+            user: None,
+            traits: None,
+            message_id: None,
+            event_id: None,
+        });
+
+        let json_str = serde_json::to_string(&event).unwrap();
+
+        assert!(!json_str.contains("traits"));
+        assert!(!json_str.contains("user"));
+    }
+
+    #[test]
+    fn test_message_id_serialization() {
+        let event = TrackerEvent::Custom(CustomEventData {
+            timestamp: 1706400000000,
+            url: "server://user@example.com".into(),
+            path: "/".into(),
+            event_name: "test".into(),
+            properties: None,
+            message_id: Some("msg_123".into()),
+            event_id: None,
+        });
+
+        let json = serde_json::to_value(&event).unwrap();
+
+        assert_eq!(json["messageId"], "msg_123");
+        assert_eq!(event.message_id(), Some("msg_123"));
+    }
+
     #[test]
     fn test_optional_fields_omitted() {
         let event = TrackerEvent::Custom(CustomEventData {
@@ -250,19 +1026,19 @@ mod tests {
             path: "/".into(),
             event_name: "test".into(),
             properties: None,
+            message_id: None,
+            event_id: None,
         });
 
         let json_str = serde_json::to_string(&event).unwrap();
 
         assert!(!json_str.contains("properties"));
+        assert!(!json_str.contains("messageId"));
     }
 
     #[test]
     fn test_ingest_payload_structure() {
-        let payload = IngestPayload {
-            source: SourceType::Server,
-            events: vec![],
-        };
+        let payload = IngestPayload::new(SourceType::Server, vec![]);
 
         let json = serde_json::to_value(&payload).unwrap();
 
@@ -270,4 +1046,174 @@ mod tests {
         assert!(json["events"].is_array());
         assert!(json.get("visitorId").is_none()); // server events don't have visitorId
     }
+
+    #[test]
+    fn test_accessors_for_each_variant() {
+        let identity_props = Some(HashMap::from([
+            ("__email".to_string(), json!("user@example.com")),
+            ("__userId".to_string(), json!(null)),
+            ("__fingerprint".to_string(), json!("device_1")),
+        ]));
+        let user = Identifiers {
+            email: None,
+            user_id: Some("usr_123".into()),
+            fingerprint: None,
+        };
+
+        let events = [
+            TrackerEvent::Custom(CustomEventData {
+                timestamp: 1,
+                url: "server://user@example.com".into(),
+                path: "/".into(),
+                event_name: "signup".into(),
+                properties: identity_props.clone(),
+                message_id: None,
+                event_id: None,
+            }),
+            TrackerEvent::Identify(IdentifyEventData {
+                timestamp: 2,
+                url: "server://usr_123".into(),
+                path: "/".into(),
+                email: None,
+                user_id: Some("usr_123".into()),
+                fingerprint: None,
+                traits: None,
+                message_id: None,
+                event_id: None,
+            }),
+            TrackerEvent::Stage(StageEventData {
+                timestamp: 3,
+                url: "server://user@example.com".into(),
+                path: "/".into(),
+                stage: JourneyStage::Activated,
+                reason: None,
+                previous_stage: None,
+                effective_at: None,
+                properties: identity_props.clone(),
+                message_id: None,
+                event_id: None,
+            }),
+            TrackerEvent::Billing(BillingEventData {
+                timestamp: 4,
+                url: "server://acme.com".into(),
+                path: "/".into(),
+                status: BillingStatus::Paid,
+                customer_id: None,
+                stripe_customer_id: None,
+                domain: Some("acme.com".into()),
+                amount: None,
+                currency: None,
+                mrr: None,
+                interval: None,
+                activity: None,
+                invoice_id: None,
+                plan: None,
+                previous_plan: None,
+                seats: None,
+                previous_seats: None,
+                properties: None,
+                message_id: None,
+                event_id: None,
+            }),
+            TrackerEvent::Alias(AliasEventData {
+                timestamp: 5,
+                url: "server://usr_123".into(),
+                path: "/".into(),
+                previous: Identifiers {
+                    email: None,
+                    user_id: None,
+                    fingerprint: Some("device_1".into()),
+                },
+                current: user.clone(),
+                message_id: None,
+                event_id: None,
+            }),
+            TrackerEvent::Group(GroupEventData {
+                timestamp: 6,
+                url: "server://org_1".into(),
+                path: "/".into(),
+                group_id: "org_1".into(),
+                user: Some(user.clone()),
+                traits: None,
+                message_id: None,
+                event_id: None,
+            }),
+        ];
+
+        let types: Vec<_> = events.iter().map(TrackerEvent::event_type).collect();
+        assert_eq!(
+            types,
+            ["custom", "identify", "stage", "billing", "alias", "group"]
+        );
+        for (event, ty) in events.iter().zip(types) {
+            assert_eq!(serde_json::to_value(event).unwrap()["type"], ty);
+        }
+
+        let timestamps: Vec<_> = events.iter().map(TrackerEvent::timestamp).collect();
+        assert_eq!(timestamps, [1, 2, 3, 4, 5, 6]);
+
+        assert_eq!(events[0].event_name(), Some("signup"));
+        assert!(events[1..].iter().all(|e| e.event_name().is_none()));
+
+        for event in [&events[0], &events[2]] {
+            let identity = event.identity().unwrap();
+            assert_eq!(identity.email.as_deref(), Some("user@example.com"));
+            assert_eq!(identity.user_id, None);
+            assert_eq!(identity.fingerprint.as_deref(), Some("device_1"));
+        }
+        for event in [&events[1], &events[4], &events[5]] {
+            assert_eq!(event.identity().as_ref(), Some(&user));
+        }
+        assert!(events[3].identity().is_none());
+
+        assert_eq!(events[0].properties(), identity_props.as_ref());
+        assert!(events[3].properties().is_none());
+        assert!(events[4].properties().is_none());
+
+        assert_eq!(events, events.clone());
+        assert_ne!(events[0], events[2]);
+    }
+
+    #[test]
+    fn test_flush_report_maps_errors_to_events() {
+        let events: Vec<TrackerEvent> = (0..3)
+            .map(|i| {
+                TrackerEvent::Custom(CustomEventData {
+                    timestamp: 1706400000000,
+                    url: "server://user@example.com".into(),
+                    path: "/".into(),
+                    event_name: format!("event_{i}"),
+                    properties: None,
+                    message_id: None,
+                    event_id: None,
+                })
+            })
+            .collect();
+        let response = IngestResponse {
+            success: true,
+            processed: 2,
+            errors: Some(vec![
+                IngestError {
+                    index: 1,
+                    message: "invalid event".into(),
+                },
+                IngestError {
+                    index: 7,
+                    message: "out of range".into(),
+                },
+            ]),
+        };
+
+        let report = FlushReport::from_response(events, response);
+
+        assert_eq!(report.sent, 3);
+        assert_eq!(report.processed, 2);
+        assert_eq!(report.rejected.len(), 2);
+        assert_eq!(report.rejected[0].message, "invalid event");
+        match &report.rejected[0].event {
+            Some(TrackerEvent::Custom(e)) => assert_eq!(e.event_name, "event_1"),
+            other => panic!("Expected custom event, got {other:?}"),
+        }
+        assert!(report.rejected[1].event.is_none());
+    }
 }
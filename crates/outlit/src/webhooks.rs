@@ -0,0 +1,367 @@
+//! Receive Outlit's outgoing webhooks.
+//!
+//! Enabled with the `webhooks` feature. Outlit signs each delivery the
+//! same way the SDK signs ingest requests: the `X-Outlit-Signature` header
+//! holds `v1=` and the hex HMAC-SHA256 of `"{timestamp}.{body}"`, keyed
+//! with the webhook secret, and `X-Outlit-Timestamp` holds the Unix timestamp in
+//! seconds. [`Verifier::parse`] checks both and decodes the body:
+//!
+//! ```rust,no_run
+//! use outlit::webhooks::{Verifier, WebhookEvent};
+//!
+//! # fn handle(timestamp: &str, signature: &str, body: &[u8]) -> Result<(), outlit::Error> {
+//! let verifier = Verifier::new("whsec_xxx");
+//! let webhook = verifier.parse(timestamp, signature, body)?;
+//! match webhook.event {
+//!     WebhookEvent::CustomerChurned(churn) => println!("{} churned", churn.domain),
+//!     WebhookEvent::StageChanged(change) => println!("now {:?}", change.stage),
+//!     _ => {}
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::types::{BillingStatus, JourneyStage};
+use crate::Error;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Header holding the delivery's signature.
+pub const SIGNATURE_HEADER: &str = "X-Outlit-Signature";
+
+/// Header holding the delivery's Unix timestamp, in seconds.
+pub const TIMESTAMP_HEADER: &str = "X-Outlit-Timestamp";
+
+/// How far a delivery's timestamp may be from now by default.
+pub const DEFAULT_TOLERANCE: Duration = Duration::from_secs(5 * 60);
+
+/// A webhook delivery from Outlit.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "RawWebhook", into = "RawWebhook")]
+pub struct Webhook {
+    /// Unique ID of the delivery; redeliveries keep the same ID.
+    pub id: String,
+    /// When the change happened, in milliseconds since the Unix epoch.
+    pub created_at: i64,
+    pub event: WebhookEvent,
+}
+
+/// What a [`Webhook`] is about, from its `type` and `data` fields.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WebhookEvent {
+    /// `stage.changed`: a user moved to another journey stage.
+    StageChanged(StageChanged),
+    /// `customer.billing_changed`: a customer's billing status changed.
+    BillingChanged(BillingChanged),
+    /// `customer.churned`: a customer churned.
+    CustomerChurned(CustomerChurned),
+    /// An event type this version of the SDK doesn't know.
+    Unknown { event_type: String, data: Value },
+}
+
+/// A webhook body as sent, before `data` is decoded for its `type`.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawWebhook {
+    id: String,
+    created_at: i64,
+    #[serde(rename = "type")]
+    event_type: String,
+    #[serde(default)]
+    data: Value,
+}
+
+impl TryFrom<RawWebhook> for Webhook {
+    type Error = serde_json::Error;
+
+    fn try_from(raw: RawWebhook) -> Result<Self, Self::Error> {
+        let event = match raw.event_type.as_str() {
+            "stage.changed" => WebhookEvent::StageChanged(serde_json::from_value(raw.data)?),
+            "customer.billing_changed" => {
+                WebhookEvent::BillingChanged(serde_json::from_value(raw.data)?)
+            }
+            "customer.churned" => WebhookEvent::CustomerChurned(serde_json::from_value(raw.data)?),
+            _ => WebhookEvent::Unknown {
+                event_type: raw.event_type,
+                data: raw.data,
+            },
+        };
+        Ok(Self {
+            id: raw.id,
+            created_at: raw.created_at,
+            event,
+        })
+    }
+}
+
+impl From<Webhook> for RawWebhook {
+    fn from(webhook: Webhook) -> Self {
+        let (event_type, data) = match webhook.event {
+            WebhookEvent::StageChanged(data) => ("stage.changed".into(), json!(data)),
+            WebhookEvent::BillingChanged(data) => ("customer.billing_changed".into(), json!(data)),
+            WebhookEvent::CustomerChurned(data) => ("customer.churned".into(), json!(data)),
+            WebhookEvent::Unknown { event_type, data } => (event_type, data),
+        };
+        Self {
+            id: webhook.id,
+            created_at: webhook.created_at,
+            event_type,
+            data,
+        }
+    }
+}
+
+/// Data of a [`WebhookEvent::StageChanged`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StageChanged {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<String>,
+    /// Domain of the customer the user belongs to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub domain: Option<String>,
+    pub stage: JourneyStage,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_stage: Option<JourneyStage>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// Data of a [`WebhookEvent::BillingChanged`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BillingChanged {
+    pub domain: String,
+    pub status: BillingStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_status: Option<BillingStatus>,
+    /// Monthly recurring revenue, in the currency's minor unit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mrr: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plan: Option<String>,
+}
+
+/// Data of a [`WebhookEvent::CustomerChurned`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomerChurned {
+    pub domain: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_status: Option<BillingStatus>,
+    /// MRR lost, in the currency's minor unit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mrr: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// Verifies webhook signatures with the webhook secret.
+#[derive(Clone)]
+pub struct Verifier {
+    secret: String,
+    tolerance: Duration,
+}
+
+impl std::fmt::Debug for Verifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Verifier")
+            .field("secret", &"[redacted]")
+            .field("tolerance", &self.tolerance)
+            .finish()
+    }
+}
+
+impl Verifier {
+    /// Create a verifier for the webhook secret shown in Outlit's settings.
+    pub fn new(secret: impl Into<String>) -> Self {
+        Self {
+            secret: secret.into(),
+            tolerance: DEFAULT_TOLERANCE,
+        }
+    }
+
+    /// Set how far a delivery's timestamp may be from now, to reject
+    /// replayed deliveries. Default: [`DEFAULT_TOLERANCE`].
+    pub fn tolerance(mut self, tolerance: Duration) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Check a delivery's signature and timestamp headers against its raw
+    /// body.
+    ///
+    /// The signature header may hold several comma-separated signatures
+    /// while a secret is being rotated; any match is accepted, with or
+    /// without its `v1=` prefix. Returns
+    /// [`Error::Validation`] if none match or the timestamp is too old.
+    pub fn verify(&self, timestamp: &str, signature: &str, body: &[u8]) -> Result<(), Error> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        self.verify_at(timestamp, signature, body, now)
+    }
+
+    /// Verify a delivery, then decode its body.
+    pub fn parse(&self, timestamp: &str, signature: &str, body: &[u8]) -> Result<Webhook, Error> {
+        self.verify(timestamp, signature, body)?;
+        Ok(serde_json::from_slice(body)?)
+    }
+
+    fn verify_at(
+        &self,
+        timestamp: &str,
+        signature: &str,
+        body: &[u8],
+        now: u64,
+    ) -> Result<(), Error> {
+        let sent: u64 = timestamp
+            .trim()
+            .parse()
+            .map_err(|_| Error::Validation(format!("invalid webhook timestamp {timestamp:?}")))?;
+        if now.abs_diff(sent) > self.tolerance.as_secs() {
+            return Err(Error::Validation(
+                "webhook timestamp is outside the tolerance".into(),
+            ));
+        }
+
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.secret.as_bytes())
+            .expect("HMAC accepts keys of any length");
+        mac.update(timestamp.trim().as_bytes());
+        mac.update(b".");
+        mac.update(body);
+        let matches = signature
+            .split(',')
+            .map(|candidate| candidate.trim())
+            .filter_map(|candidate| decode_hex(candidate.strip_prefix("v1=").unwrap_or(candidate)))
+            .any(|candidate| mac.clone().verify_slice(&candidate).is_ok());
+        if matches {
+            Ok(())
+        } else {
+            Err(Error::Validation("webhook signature does not match".into()))
+        }
+    }
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SecretKey;
+
+    const BODY: &[u8] = br#"{"id":"wh_1","createdAt":1706400000000,"type":"customer.churned","data":{"domain":"acme.com","previousStatus":"paid","mrr":4900}}"#;
+
+    fn sign(secret: &str, timestamp: u64, body: &[u8]) -> String {
+        crate::transport::sign(&SecretKey(secret.into()), timestamp, body)
+    }
+
+    #[test]
+    fn test_verify_accepts_valid_signature() {
+        let verifier = Verifier::new("whsec_test");
+        let signature = sign("whsec_test", 1706400000, BODY);
+        assert!(verifier
+            .verify_at("1706400000", &signature, BODY, 1706400060)
+            .is_ok());
+
+        // Any signature matches during secret rotation
+        let rotated = format!("{}, {signature}", sign("whsec_old", 1706400000, BODY));
+        assert!(verifier
+            .verify_at("1706400000", &rotated, BODY, 1706400000)
+            .is_ok());
+
+        // Bare hex, without the version prefix
+        let bare = signature.strip_prefix("v1=").unwrap();
+        assert!(verifier
+            .verify_at("1706400000", bare, BODY, 1706400000)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_verify_rejects_tampering_and_replays() {
+        let verifier = Verifier::new("whsec_test");
+        let signature = sign("whsec_test", 1706400000, BODY);
+
+        let tampered = [BODY, b" "].concat();
+        assert!(matches!(
+            verifier.verify_at("1706400000", &signature, &tampered, 1706400000),
+            Err(Error::Validation(_))
+        ));
+        assert!(verifier
+            .verify_at(
+                "1706400000",
+                &sign("whsec_other", 1706400000, BODY),
+                BODY,
+                1706400000
+            )
+            .is_err());
+        assert!(verifier
+            .verify_at("1706400000", "not hex", BODY, 1706400000)
+            .is_err());
+        assert!(verifier
+            .verify_at("1706400000", &signature, BODY, 1706400000 + 301)
+            .is_err());
+        assert!(verifier
+            .tolerance(Duration::from_secs(600))
+            .verify_at("1706400000", &signature, BODY, 1706400000 + 301)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_webhook_deserialization() {
+        let webhook: Webhook = serde_json::from_slice(BODY).unwrap();
+        assert_eq!(webhook.id, "wh_1");
+        assert_eq!(webhook.created_at, 1706400000000);
+        assert_eq!(
+            webhook.event,
+            WebhookEvent::CustomerChurned(CustomerChurned {
+                domain: "acme.com".into(),
+                previous_status: Some(BillingStatus::Paid),
+                mrr: Some(4900),
+                reason: None,
+            })
+        );
+
+        let webhook: Webhook = serde_json::from_str(
+            r#"{"id":"wh_2","createdAt":1,"type":"stage.changed",
+                "data":{"email":"user@acme.com","stage":"power_user","previousStage":"activated"}}"#,
+        )
+        .unwrap();
+        let WebhookEvent::StageChanged(change) = webhook.event else {
+            panic!("expected stage change");
+        };
+        assert_eq!(change.stage, JourneyStage::Custom("power_user".into()));
+        assert_eq!(change.previous_stage, Some(JourneyStage::Activated));
+
+        let webhook: Webhook = serde_json::from_str(
+            r#"{"id":"wh_3","createdAt":1,"type":"account.merged","data":{"into":"x"}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            webhook.event,
+            WebhookEvent::Unknown {
+                event_type: "account.merged".into(),
+                data: json!({ "into": "x" }),
+            }
+        );
+        let json = serde_json::to_value(&webhook).unwrap();
+        assert_eq!(json["type"], "account.merged");
+        assert_eq!(json["createdAt"], 1);
+    }
+}
@@ -1,11 +1,13 @@
 //! Integration tests for the Outlit SDK.
 
-use outlit::{email, fingerprint, user_id, Outlit};
+use outlit::{
+    email, fingerprint, user_id, Consent, ConsentPolicy, EventNamePolicy, Outlit, RetryPolicy,
+};
 use serde_json::json;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
-use wiremock::matchers::{method, path};
+use wiremock::matchers::{body_json, header, method, path};
 use wiremock::{Mock, MockServer, ResponseTemplate};
 
 #[tokio::test]
@@ -454,34 +456,3477 @@ async fn test_multiple_batches_flush_correctly() {
     assert_eq!(client.pending_event_count().await, 0);
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_high_water_mark_drains_in_background() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!({ "success": true, "processed": 8 }))
+                .set_delay(Duration::from_millis(300)),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .max_batch_size(10)
+        .flush_interval(Duration::from_secs(100))
+        .build()
+        .unwrap();
+
+    // Reach the high-water mark (8 of 10) without filling the queue
+    let start = std::time::Instant::now();
+    for i in 0..8 {
+        client
+            .track(format!("event_{i}"), email("user@test.com"))
+            .send()
+            .await
+            .unwrap();
+    }
+    // send() must not wait on the slow server
+    assert!(start.elapsed() < Duration::from_millis(300));
+
+    // The background task drains the queue without an explicit flush
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while client.pending_event_count().await > 0 {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .expect("events were not drained in the background");
+
+    let sent: usize = mock_server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .map(|r| event_names(r).len())
+        .sum();
+    assert_eq!(sent, 8);
+}
+
+/// A client whose background flush of 8 events is in flight against a slow
+/// server, with one more event queued behind it.
+async fn client_with_flush_in_flight(mock_server: &MockServer) -> Outlit {
+    Mock::given(method("POST"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!({ "success": true, "processed": 8 }))
+                .set_delay(Duration::from_millis(300)),
+        )
+        .mount(mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .max_batch_size(10)
+        .flush_interval(Duration::from_secs(100))
+        .build()
+        .unwrap();
+    for i in 0..8 {
+        client
+            .track(format!("event_{i}"), email("user@test.com"))
+            .send()
+            .await
+            .unwrap();
+    }
+    // Let the background task reserve the events and start sending
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    client
+        .track("late", email("user@test.com"))
+        .send()
+        .await
+        .unwrap();
+    client
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_flush_waits_for_background_flush_in_flight() {
+    let mock_server = MockServer::start().await;
+    let client = client_with_flush_in_flight(&mock_server).await;
+
+    client.flush().await.unwrap();
+    assert_eq!(client.pending_event_count().await, 0);
+
+    let sent: Vec<String> = mock_server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .flat_map(event_names)
+        .collect();
+    assert_eq!(sent.len(), 9);
+    assert_eq!(sent.last().map(String::as_str), Some("late"));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_shutdown_sends_events_reserved_by_background_flush() {
+    let mock_server = MockServer::start().await;
+    let client = client_with_flush_in_flight(&mock_server).await;
+
+    client.shutdown().await.unwrap();
+    assert_eq!(client.pending_event_count().await, 0);
+
+    let requests = mock_server.received_requests().await.unwrap();
+    let last = requests.last().unwrap();
+    assert!(event_names(last).contains(&"late".to_string()));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_send_nowait_never_flushes_inline() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!({ "success": true, "processed": 4 }))
+                .set_delay(Duration::from_millis(300)),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .max_batch_size(4)
+        .flush_interval(Duration::from_secs(100))
+        .build()
+        .unwrap();
+
+    // Past max_batch_size, where send() would flush inline
+    let start = std::time::Instant::now();
+    for i in 0..6 {
+        client
+            .track(format!("event_{i}"), email("user@test.com"))
+            .send_nowait()
+            .unwrap();
+    }
+    assert!(start.elapsed() < Duration::from_millis(300));
+
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while client.pending_event_count().await > 0 {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .expect("events were not drained in the background");
+
+    client.shutdown().await.unwrap();
+    assert!(matches!(
+        client.track("late", email("user@test.com")).send_nowait(),
+        Err(outlit::Error::Shutdown)
+    ));
+}
+
+#[tokio::test]
+async fn test_high_water_mark_ignored_without_auto_flush() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .max_batch_size(10)
+        .disable_auto_flush()
+        .build()
+        .unwrap();
+
+    for i in 0..9 {
+        client
+            .track(format!("event_{i}"), email("user@test.com"))
+            .send()
+            .await
+            .unwrap();
+    }
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    assert_eq!(client.pending_event_count().await, 9);
+}
+
+#[tokio::test]
+async fn test_ping_ok() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/i/v1/pk_test/events"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "success": true,
+            "processed": 0
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .disable_auto_flush()
+        .build()
+        .unwrap();
+
+    client.ping().await.unwrap();
+
+    let requests = mock_server.received_requests().await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+    assert_eq!(body["events"], json!([]));
+}
+
+#[tokio::test]
+async fn test_ping_unauthorized_returns_auth_error() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(401).set_body_string("invalid public key"))
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_wrong")
+        .api_host(mock_server.uri())
+        .disable_auto_flush()
+        .build()
+        .unwrap();
+
+    match client.ping().await {
+        Err(outlit::Error::Api { status, message }) => {
+            assert_eq!(status, 401);
+            assert!(message.contains("pk_wrong"));
+            assert!(message.contains("invalid public key"));
+        }
+        other => panic!("expected auth error, got {other:?}"),
+    }
+}
+
+/// Rejects any batch containing an event with the given name.
+struct PoisonResponder {
+    poison: &'static str,
+}
+
+impl wiremock::Respond for PoisonResponder {
+    fn respond(&self, request: &wiremock::Request) -> ResponseTemplate {
+        let names = event_names(request);
+        if names.iter().any(|name| name == self.poison) {
+            ResponseTemplate::new(400).set_body_string("invalid event")
+        } else {
+            ResponseTemplate::new(200).set_body_json(json!({
+                "success": true,
+                "processed": names.len()
+            }))
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_bisect_drops_poison_event_and_delivers_rest() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(PoisonResponder { poison: "event_5" })
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .max_bisect_depth(4)
+        .disable_auto_flush()
+        .build()
+        .unwrap();
+
+    for i in 0..8 {
+        client
+            .track(format!("event_{i}"), email("user@test.com"))
+            .send()
+            .await
+            .unwrap();
+    }
+
+    let report = client.flush_detailed().await.unwrap();
+
+    assert_eq!(client.pending_event_count().await, 0);
+    assert_eq!(report.sent, 7);
+    assert_eq!(report.processed, 7);
+    assert_eq!(report.rejected.len(), 1);
+    assert_eq!(report.rejected[0].index, 5);
+    assert_eq!(report.rejected[0].message, "invalid event");
+
+    let delivered: Vec<String> = mock_server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .filter(|r| !event_names(r).contains(&"event_5".to_string()))
+        .flat_map(event_names)
+        .collect();
+    assert_eq!(
+        delivered,
+        ["event_0", "event_1", "event_2", "event_3", "event_4", "event_6", "event_7"]
+    );
+}
+
+#[tokio::test]
+async fn test_bad_request_without_bisect_keeps_batch() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(PoisonResponder { poison: "event_1" })
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .disable_auto_flush()
+        .build()
+        .unwrap();
+
+    for i in 0..3 {
+        client
+            .track(format!("event_{i}"), email("user@test.com"))
+            .send()
+            .await
+            .unwrap();
+    }
+
+    assert!(client.flush().await.is_err());
+    assert_eq!(client.pending_event_count().await, 3);
+}
+
+fn fast_retries(max_attempts: u32) -> RetryPolicy {
+    RetryPolicy {
+        max_attempts,
+        base_delay: Duration::from_millis(1),
+        max_delay: Duration::from_millis(10),
+        jitter: true,
+    }
+}
+
+#[tokio::test]
+async fn test_transient_errors_retried_before_requeue() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(2)
+        .expect(2)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "success": true,
+            "processed": 1
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .retry_policy(fast_retries(3))
+        .disable_auto_flush()
+        .build()
+        .unwrap();
+
+    client
+        .track("event", email("user@test.com"))
+        .send()
+        .await
+        .unwrap();
+    client.flush().await.unwrap();
+
+    assert_eq!(client.pending_event_count().await, 0);
+}
+
+#[tokio::test]
+async fn test_retries_exhausted_keeps_events_queued() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(500))
+        .expect(3)
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .retry_policy(fast_retries(3))
+        .disable_auto_flush()
+        .build()
+        .unwrap();
+
+    client
+        .track("event", email("user@test.com"))
+        .send()
+        .await
+        .unwrap();
+
+    assert!(client.flush().await.is_err());
+    assert_eq!(client.pending_event_count().await, 1);
+}
+
+#[tokio::test]
+async fn test_rate_limit_retried_after_short_retry_after() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "0"))
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "success": true,
+            "processed": 1
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .retry_policy(fast_retries(3))
+        .disable_auto_flush()
+        .build()
+        .unwrap();
+
+    client
+        .track("event", email("user@test.com"))
+        .send()
+        .await
+        .unwrap();
+    client.flush().await.unwrap();
+
+    assert_eq!(client.pending_event_count().await, 0);
+}
+
+#[tokio::test]
+async fn test_rate_limit_with_long_retry_after_returns_error() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "120"))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .retry_policy(fast_retries(3))
+        .disable_auto_flush()
+        .build()
+        .unwrap();
+
+    client
+        .track("event", email("user@test.com"))
+        .send()
+        .await
+        .unwrap();
+
+    let err = client.flush().await.unwrap_err();
+    assert!(matches!(
+        err,
+        outlit::Error::RateLimited {
+            retry_after: Some(d)
+        } if d == Duration::from_secs(120)
+    ));
+    assert_eq!(client.pending_event_count().await, 1);
+}
+
+#[tokio::test]
+async fn test_circuit_breaker_pauses_flushes_until_shutdown() {
+    let mock_server = MockServer::start().await;
+
+    // Two failed flushes open the circuit; only shutdown gets through after
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(503))
+        .expect(3)
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .retry_policy(RetryPolicy::none())
+        .circuit_breaker(outlit::CircuitBreaker {
+            failure_threshold: 2,
+            cool_down: Duration::from_secs(3600),
+        })
+        .disable_auto_flush()
+        .build()
+        .unwrap();
+
+    client
+        .track("event", email("user@test.com"))
+        .send()
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        client.flush().await,
+        Err(outlit::Error::Api { .. })
+    ));
+    assert!(matches!(
+        client.flush().await,
+        Err(outlit::Error::Api { .. })
+    ));
+    assert!(matches!(
+        client.flush().await,
+        Err(outlit::Error::CircuitOpen)
+    ));
+    assert_eq!(client.pending_event_count().await, 1);
+
+    assert!(client.shutdown().await.is_err());
+}
+
+#[tokio::test]
+async fn test_client_errors_not_retried() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(400))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .retry_policy(fast_retries(3))
+        .disable_auto_flush()
+        .build()
+        .unwrap();
+
+    client
+        .track("event", email("user@test.com"))
+        .send()
+        .await
+        .unwrap();
+
+    assert!(client.flush().await.is_err());
+}
+
+#[tokio::test]
+async fn test_fallback_host_receives_batch_when_primary_fails() {
+    let primary = MockServer::start().await;
+    let fallback = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(503))
+        .expect(1)
+        .mount(&primary)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/api/i/v1/pk_test/events"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "success": true,
+            "processed": 2
+        })))
+        .expect(1)
+        .mount(&fallback)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(primary.uri())
+        .fallback_api_host(fallback.uri())
+        .retry_policy(RetryPolicy::none())
+        .disable_auto_flush()
+        .build()
+        .unwrap();
+
+    for name in ["first", "second"] {
+        client
+            .track(name, email("user@test.com"))
+            .send()
+            .await
+            .unwrap();
+    }
+    client.flush().await.unwrap();
+
+    assert_eq!(client.pending_event_count().await, 0);
+    let requests = fallback.received_requests().await.unwrap();
+    assert_eq!(event_names(&requests[0]), ["first", "second"]);
+}
+
+#[tokio::test]
+async fn test_fallback_host_not_used_for_client_errors() {
+    let primary = MockServer::start().await;
+    let fallback = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(401))
+        .expect(1)
+        .mount(&primary)
+        .await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&fallback)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(primary.uri())
+        .fallback_api_host(fallback.uri())
+        .disable_auto_flush()
+        .build()
+        .unwrap();
+
+    client
+        .track("event", email("user@test.com"))
+        .send()
+        .await
+        .unwrap();
+
+    assert!(client.flush().await.is_err());
+    assert_eq!(client.pending_event_count().await, 1);
+}
+
+// ============================================
+// FINGERPRINT TESTS
+// ============================================
+
+#[tokio::test]
+async fn test_track_with_fingerprint_only() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/i/v1/pk_test/events"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "success": true,
+            "processed": 1
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .flush_interval(Duration::from_secs(100))
+        .build()
+        .unwrap();
+
+    // Track with fingerprint only (anonymous user)
+    client
+        .track_by_fingerprint("page_view", fingerprint("device_abc123"))
+        .property("page", "/pricing")
+        .send()
+        .await
+        .unwrap();
+
+    client.flush().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_track_with_fingerprint_and_email() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/i/v1/pk_test/events"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "success": true,
+            "processed": 1
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .flush_interval(Duration::from_secs(100))
+        .build()
+        .unwrap();
+
+    // Track with email + fingerprint (links device to user)
+    client
+        .track("signup", email("user@test.com"))
+        .fingerprint("device_abc123")
+        .property("plan", "pro")
+        .send()
+        .await
+        .unwrap();
+
+    client.flush().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_track_with_fingerprint_and_user_id() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "success": true,
+            "processed": 1
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .flush_interval(Duration::from_secs(100))
+        .build()
+        .unwrap();
+
+    // Track with fingerprint + user_id
+    client
+        .track_by_fingerprint("feature_used", fingerprint("device_abc123"))
+        .user_id("usr_123")
+        .send()
+        .await
+        .unwrap();
+
+    client.flush().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_identify_with_fingerprint_links_device() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "success": true,
+            "processed": 1
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .flush_interval(Duration::from_secs(100))
+        .build()
+        .unwrap();
+
+    // Identify with email + fingerprint to link device
+    client
+        .identify(email("user@test.com"))
+        .fingerprint("device_abc123")
+        .user_id("usr_123")
+        .trait_("name", "John")
+        .send()
+        .await
+        .unwrap();
+
+    client.flush().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_stage_with_fingerprint() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "success": true,
+            "processed": 1
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .flush_interval(Duration::from_secs(100))
+        .build()
+        .unwrap();
+
+    // Stage event with fingerprint identity
+    client
+        .user()
+        .activate_by_fingerprint(fingerprint("device_abc123"))
+        .send()
+        .await
+        .unwrap();
+
+    client.flush().await.unwrap();
+}
+
+// ============================================
+// ORDERING TESTS
+// ============================================
+
+fn event_names(request: &wiremock::Request) -> Vec<String> {
+    let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+    body["events"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["eventName"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_failed_flush_preserves_order_with_concurrent_enqueue() {
+    let mock_server = MockServer::start().await;
+
+    // First attempt fails slowly, leaving time to enqueue during the send
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(500).set_delay(Duration::from_millis(100)))
+        .up_to_n_times(1)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "success": true,
+            "processed": 3
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .retry_policy(RetryPolicy::none())
+        .flush_interval(Duration::from_secs(100))
+        .build()
+        .unwrap();
+
+    for name in ["first", "second"] {
+        client
+            .track(name, email("user@test.com"))
+            .send()
+            .await
+            .unwrap();
+    }
+
+    let (flush_result, _) = tokio::join!(client.flush(), async {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        // In-flight events are still counted as pending
+        assert_eq!(client.pending_event_count().await, 2);
+        client
+            .track("third", email("user@test.com"))
+            .send()
+            .await
+            .unwrap();
+    });
+    assert!(flush_result.is_err());
+    assert_eq!(client.pending_event_count().await, 3);
+
+    client.flush().await.unwrap();
+    assert_eq!(client.pending_event_count().await, 0);
+
+    let requests = mock_server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(event_names(&requests[0]), vec!["first", "second"]);
+    assert_eq!(event_names(&requests[1]), vec!["first", "second", "third"]);
+}
+
+// ============================================
+// SAMPLING TESTS
+// ============================================
+
+#[tokio::test]
+async fn test_sample_rate_zero_drops_custom_events_only() {
+    let client = Outlit::builder("pk_test")
+        .api_host("http://127.0.0.1:1")
+        .sample_rate(0.0)
+        .flush_interval(Duration::from_secs(100))
+        .build()
+        .unwrap();
+
+    for i in 0..10 {
+        client
+            .track(format!("event_{i}"), email("user@test.com"))
+            .send()
+            .await
+            .unwrap();
+    }
+    assert_eq!(client.pending_event_count().await, 0);
+
+    client
+        .identify(email("user@test.com"))
+        .send()
+        .await
+        .unwrap();
+    client
+        .user()
+        .activate(email("user@test.com"))
+        .send()
+        .await
+        .unwrap();
+    client.customer().paid("acme.com").send().await.unwrap();
+    assert_eq!(client.pending_event_count().await, 3);
+}
+
+#[tokio::test]
+async fn test_sample_rate_one_keeps_all_events() {
+    let client = Outlit::builder("pk_test")
+        .api_host("http://127.0.0.1:1")
+        .sample_rate(1.0)
+        .flush_interval(Duration::from_secs(100))
+        .build()
+        .unwrap();
+
+    for i in 0..10 {
+        client
+            .track(format!("event_{i}"), email("user@test.com"))
+            .send()
+            .await
+            .unwrap();
+    }
+    assert_eq!(client.pending_event_count().await, 10);
+}
+
+#[test]
+fn test_sample_rate_out_of_range_rejected() {
+    let result = Outlit::builder("pk_test").sample_rate(1.5).build();
+    assert!(matches!(result, Err(outlit::Error::Config(_))));
+}
+
+#[tokio::test]
+async fn test_discard_pending_drops_queued_events() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .flush_interval(Duration::from_secs(100))
+        .build()
+        .unwrap();
+
+    for i in 0..4 {
+        client
+            .track(format!("event_{i}"), email("user@test.com"))
+            .send()
+            .await
+            .unwrap();
+    }
+
+    assert_eq!(client.discard_pending().await, 4);
+    assert_eq!(client.pending_event_count().await, 0);
+    assert_eq!(client.dropped_event_count(), 4);
+
+    // Nothing left to send
+    client.flush().await.unwrap();
+    assert_eq!(client.discard_pending().await, 0);
+    assert_eq!(client.dropped_event_count(), 4);
+}
+
+#[tokio::test]
+async fn test_alias_sends_both_identities() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/i/v1/pk_test/events"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "success": true,
+            "processed": 1
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .flush_interval(Duration::from_secs(100))
+        .build()
+        .unwrap();
+
+    client
+        .alias(email("old@test.com"), email("new@test.com"))
+        .user_id("usr_123")
+        .send()
+        .await
+        .unwrap();
+
+    client.flush().await.unwrap();
+
+    let requests = mock_server.received_requests().await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+    let event = &body["events"][0];
+    assert_eq!(event["type"], "alias");
+    assert_eq!(event["previous"]["email"], "old@test.com");
+    assert_eq!(event["current"]["email"], "new@test.com");
+    assert_eq!(event["current"]["userId"], "usr_123");
+}
+
+#[tokio::test]
+async fn test_disable_auto_flush_skips_background_timer() {
+    let mock_server = MockServer::start().await;
+    let received = Arc::new(AtomicUsize::new(0));
+
+    Mock::given(method("POST"))
+        .respond_with(CountingResponder {
+            counter: received.clone(),
+        })
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .flush_interval(Duration::from_millis(20))
+        .disable_auto_flush()
+        .build()
+        .unwrap();
+
+    client
+        .track("event", email("user@test.com"))
+        .send()
+        .await
+        .unwrap();
+
+    // Several intervals pass without a background flush
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(received.load(Ordering::SeqCst), 0);
+    assert_eq!(client.pending_event_count().await, 1);
+
+    // Manual flush still works
+    client.flush().await.unwrap();
+    assert_eq!(received.load(Ordering::SeqCst), 1);
+    assert_eq!(client.pending_event_count().await, 0);
+}
+
+// ============================================
+// PERSISTENCE TESTS
+// ============================================
+
+#[cfg(feature = "persist")]
+#[tokio::test]
+async fn test_persisted_events_recovered_after_restart() {
+    let dir = std::env::temp_dir().join(format!("outlit-it-{}", std::process::id()));
+    let queue_path = dir.join("restart.jsonl");
+    let _ = std::fs::remove_file(&queue_path);
+
+    // First process: events are queued but never delivered
+    {
+        let client = Outlit::builder("pk_test")
+            .api_host("http://127.0.0.1:1")
+            .persist_path(&queue_path)
+            .disable_auto_flush()
+            .build()
+            .unwrap();
+
+        for name in ["first", "second"] {
+            client
+                .track(name, email("user@test.com"))
+                .send()
+                .await
+                .unwrap();
+        }
+        // Dropped without shutdown, as if killed
+    }
+
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "success": true,
+            "processed": 2
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    // Second process over the same file
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .persist_path(&queue_path)
+        .disable_auto_flush()
+        .build()
+        .unwrap();
+
+    assert_eq!(client.pending_event_count().await, 2);
+    client.shutdown().await.unwrap();
+
+    let requests = mock_server.received_requests().await.unwrap();
+    assert_eq!(event_names(&requests[0]), vec!["first", "second"]);
+
+    // Clean shutdown leaves the file empty
+    assert_eq!(std::fs::read(&queue_path).unwrap().len(), 0);
+}
+
+#[cfg(feature = "persist")]
+#[tokio::test]
+async fn test_recovered_events_flushed_on_startup() {
+    let dir = std::env::temp_dir().join(format!("outlit-it-{}", std::process::id()));
+    let queue_path = dir.join("resume.jsonl");
+    let _ = std::fs::remove_file(&queue_path);
+
+    {
+        let client = Outlit::builder("pk_test")
+            .api_host("http://127.0.0.1:1")
+            .persist_path(&queue_path)
+            .disable_auto_flush()
+            .build()
+            .unwrap();
+        client
+            .track("spooled", email("user@test.com"))
+            .send()
+            .await
+            .unwrap();
+    }
+
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "success": true,
+            "processed": 1
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    // Long interval: delivery must not wait for the first tick
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .persist_path(&queue_path)
+        .flush_interval(Duration::from_secs(100))
+        .build()
+        .unwrap();
+
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while client.pending_event_count().await > 0 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("recovered events were not flushed");
+
+    let requests = mock_server.received_requests().await.unwrap();
+    assert_eq!(event_names(&requests[0]), vec!["spooled"]);
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn test_sqlite_queue_resumes_interrupted_flush() {
+    let dir = std::env::temp_dir().join(format!("outlit-it-{}", std::process::id()));
+    let queue_path = dir.join("resume.db");
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{}{suffix}", queue_path.display()));
+    }
+
+    // First process: the connection drops after the first batch
+    {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "success": true,
+                "processed": 2
+            })))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&mock_server)
+            .await;
+
+        let client = Outlit::builder("pk_test")
+            .api_host(mock_server.uri())
+            .persist_sqlite(&queue_path)
+            .max_batch_size(2)
+            .retry_policy(RetryPolicy::none())
+            .disable_auto_flush()
+            .build()
+            .unwrap();
+        for name in ["first", "second", "third"] {
+            client
+                .track(name, email("user@test.com"))
+                .send()
+                .await
+                .unwrap();
+        }
+        assert!(client.flush().await.is_err());
+    }
+
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "success": true,
+            "processed": 1
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    // Second process picks up after the delivered batch
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .persist_sqlite(&queue_path)
+        .disable_auto_flush()
+        .build()
+        .unwrap();
+
+    assert_eq!(client.pending_event_count().await, 1);
+    client.shutdown().await.unwrap();
+
+    let requests = mock_server.received_requests().await.unwrap();
+    assert_eq!(event_names(&requests[0]), vec!["third"]);
+}
+
+// ============================================
+// DELIVERY REPORT TESTS
+// ============================================
+
+#[tokio::test]
+async fn test_flush_detailed_reports_rejected_events() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "success": true,
+            "processed": 2,
+            "errors": [
+                { "index": 1, "message": "eventName is required" }
+            ]
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .flush_interval(Duration::from_secs(100))
+        .build()
+        .unwrap();
+
+    for name in ["first", "second", "third"] {
+        client
+            .track(name, email("user@test.com"))
+            .send()
+            .await
+            .unwrap();
+    }
+
+    let report = client.flush_detailed().await.unwrap();
+
+    assert_eq!(report.sent, 3);
+    assert_eq!(report.processed, 2);
+    assert_eq!(report.rejected.len(), 1);
+    assert_eq!(report.rejected[0].index, 1);
+    assert_eq!(report.rejected[0].message, "eventName is required");
+    match &report.rejected[0].event {
+        Some(outlit::TrackerEvent::Custom(e)) => assert_eq!(e.event_name, "second"),
+        other => panic!("Expected custom event, got {other:?}"),
+    }
+
+    // Partial failures are not requeued
+    assert_eq!(client.pending_event_count().await, 0);
+}
+
+#[tokio::test]
+async fn test_flush_detailed_empty_queue() {
+    let client = Outlit::builder("pk_test")
+        .api_host("http://127.0.0.1:1")
+        .flush_interval(Duration::from_secs(100))
+        .build()
+        .unwrap();
+
+    let report = client.flush_detailed().await.unwrap();
+
+    assert_eq!(report.sent, 0);
+    assert!(report.rejected.is_empty());
+}
+
+#[tokio::test]
+async fn test_requeue_rejected_retries_only_failed_events() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "success": true,
+            "processed": 2,
+            "errors": [
+                { "index": 1, "message": "temporarily unavailable" }
+            ]
+        })))
+        .up_to_n_times(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "success": true,
+            "processed": 2
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .requeue_rejected(true)
+        .disable_auto_flush()
+        .build()
+        .unwrap();
+
+    for name in ["first", "second", "third"] {
+        client
+            .track(name, email("user@test.com"))
+            .send()
+            .await
+            .unwrap();
+    }
+
+    let report = client.flush_detailed().await.unwrap();
+    assert_eq!(report.rejected.len(), 1);
+    assert_eq!(client.pending_event_count().await, 1);
+
+    // The failed event goes out ahead of newer events
+    client
+        .track("fourth", email("user@test.com"))
+        .send()
+        .await
+        .unwrap();
+    client.flush().await.unwrap();
+
+    let requests = mock_server.received_requests().await.unwrap();
+    assert_eq!(event_names(&requests[1]), ["second", "fourth"]);
+    assert_eq!(client.pending_event_count().await, 0);
+}
+
+#[tokio::test]
+async fn test_dead_letter_receives_rejected_events() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "success": true,
+            "processed": 1,
+            "errors": [
+                { "index": 0, "message": "eventName is required" }
+            ]
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .dead_letter(tx)
+        .disable_auto_flush()
+        .build()
+        .unwrap();
+
+    for name in ["first", "second"] {
+        client
+            .track(name, email("user@test.com"))
+            .send()
+            .await
+            .unwrap();
+    }
+    client.flush().await.unwrap();
+
+    let letter = rx.try_recv().unwrap();
+    assert_eq!(letter.reason, outlit::DeadLetterReason::Rejected);
+    assert_eq!(letter.message, "eventName is required");
+    match letter.event {
+        outlit::TrackerEvent::Custom(e) => assert_eq!(e.event_name, "first"),
+        other => panic!("Expected custom event, got {other:?}"),
+    }
+    assert!(rx.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn test_max_delivery_attempts_drops_permanently_rejected_events() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "success": true,
+            "processed": 0,
+            "errors": [{ "index": 0, "message": "malformed event" }]
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .requeue_rejected(true)
+        .max_delivery_attempts(2)
+        .dead_letter(tx)
+        .disable_auto_flush()
+        .build()
+        .unwrap();
+
+    client
+        .track("malformed", email("user@test.com"))
+        .send()
+        .await
+        .unwrap();
+
+    client.flush().await.unwrap();
+    assert_eq!(client.pending_event_count().await, 1);
+    assert!(rx.try_recv().is_err());
+
+    client.flush().await.unwrap();
+    assert_eq!(client.pending_event_count().await, 0);
+    assert_eq!(client.stats().dropped, 1);
+    let letter = rx.try_recv().unwrap();
+    assert_eq!(letter.reason, outlit::DeadLetterReason::RetriesExhausted);
+
+    // Nothing left to retry
+    client.flush().await.unwrap();
+    assert_eq!(mock_server.received_requests().await.unwrap().len(), 2);
+}
+
+// ============================================
+// TIMEOUT TESTS
+// ============================================
+
+#[tokio::test]
+async fn test_flush_with_timeout_returns_remaining_events() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!({ "success": true, "processed": 1 }))
+                .set_delay(Duration::from_secs(1)),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .disable_auto_flush()
+        .build()
+        .unwrap();
+
+    client
+        .track("slow", email("user@test.com"))
+        .send()
+        .await
+        .unwrap();
+
+    let started = std::time::Instant::now();
+    let remaining = client.flush_with_timeout(Duration::from_millis(100)).await;
+    assert!(started.elapsed() < Duration::from_secs(1));
+    assert_eq!(remaining, 1);
+
+    // The timed-out flush released its events
+    assert!(client.flush_detailed().await.is_ok());
+    assert_eq!(client.pending_event_count().await, 0);
+}
+
+#[tokio::test]
+async fn test_shutdown_with_timeout_spills_unsent_events() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!({ "success": true, "processed": 1 }))
+                .set_delay(Duration::from_secs(1)),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .dead_letter(tx)
+        .build()
+        .unwrap();
+
+    client
+        .track("slow", email("user@test.com"))
+        .send()
+        .await
+        .unwrap();
+
+    let started = std::time::Instant::now();
+    let result = client
+        .shutdown_with_timeout(Duration::from_millis(100))
+        .await;
+    assert!(started.elapsed() < Duration::from_secs(1));
+    assert!(matches!(result, Err(outlit::Error::Timeout)));
+
+    let letter = rx.try_recv().unwrap();
+    assert_eq!(letter.reason, outlit::DeadLetterReason::Shutdown);
+    assert_eq!(client.pending_event_count().await, 0);
+    assert_eq!(client.stats().dropped, 1);
+}
+
+// ============================================
+// REQUEST SIZE AND CONCURRENCY TESTS
+// ============================================
+
+#[tokio::test]
+async fn test_max_payload_bytes_splits_large_batches() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "success": true,
+            "processed": 1
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .max_payload_bytes(1_000)
+        .disable_auto_flush()
+        .build()
+        .unwrap();
+
+    for (name, size) in [("first", 500), ("huge", 2_000), ("second", 500)] {
+        client
+            .track(name, email("user@test.com"))
+            .property("blob", "x".repeat(size))
+            .send()
+            .await
+            .unwrap();
+    }
+
+    let report = client.flush_detailed().await.unwrap();
+
+    let requests = mock_server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 2);
+    assert!(requests.iter().all(|r| r.body.len() <= 1_000));
+    assert_eq!(event_names(&requests[0]), ["first"]);
+    assert_eq!(event_names(&requests[1]), ["second"]);
+    assert_eq!(report.sent, 2);
+    assert_eq!(report.quarantined.len(), 1);
+    assert!(report.quarantined[0].message.contains("max_payload_bytes"));
+    assert_eq!(client.pending_event_count().await, 0);
+}
+
+#[tokio::test]
+async fn test_flush_concurrency_sends_batches_in_parallel() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!({ "success": true, "processed": 1 }))
+                .set_delay(Duration::from_millis(300)),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .max_payload_bytes(1_000)
+        .flush_concurrency(3)
+        .disable_auto_flush()
+        .build()
+        .unwrap();
+
+    for name in ["first", "second", "third"] {
+        client
+            .track(name, email("user@test.com"))
+            .property("blob", "x".repeat(500))
+            .send()
+            .await
+            .unwrap();
+    }
+
+    let start = std::time::Instant::now();
+    let report = client.flush_detailed().await.unwrap();
+    assert!(start.elapsed() < Duration::from_millis(900));
+
+    assert_eq!(report.sent, 3);
+    assert_eq!(mock_server.received_requests().await.unwrap().len(), 3);
+    assert_eq!(client.pending_event_count().await, 0);
+}
+
+// ============================================
+// VALIDATION TESTS
+// ============================================
+
+#[tokio::test]
+async fn test_property_and_event_size_limits() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "success": true,
+            "processed": 1
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .max_property_bytes(100)
+        .max_event_bytes(1_000)
+        .disable_auto_flush()
+        .build()
+        .unwrap();
+
+    client
+        .track("upload", email("user@test.com"))
+        .property("log", "x".repeat(5_000))
+        .send()
+        .await
+        .unwrap();
+
+    let tags: Vec<_> = (0..100).map(|i| format!("tag_{i}")).collect();
+    let result = client
+        .track("tagged", email("user@test.com"))
+        .property("tags", tags)
+        .send()
+        .await;
+    assert!(matches!(result, Err(outlit::Error::Validation(_))));
+
+    client.flush().await.unwrap();
+
+    let requests = mock_server.received_requests().await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+    let properties = &body["events"][0]["properties"];
+    assert_eq!(properties["log"].as_str().unwrap().len(), 100);
+    assert_eq!(properties["__truncated"], json!(["log"]));
+    assert_eq!(body["events"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_strict_identity_rejects_malformed_emails() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "success": true,
+            "processed": 1
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .strict_identity(true)
+        .disable_auto_flush()
+        .build()
+        .unwrap();
+
+    let result = client.track("signup", email("N/A")).send().await;
+    assert!(matches!(result, Err(outlit::Error::InvalidIdentity(_))));
+
+    client
+        .track("signup", email(" User@Test.com "))
+        .send()
+        .await
+        .unwrap();
+    client.flush().await.unwrap();
+
+    let requests = mock_server.received_requests().await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+    assert_eq!(body["events"].as_array().unwrap().len(), 1);
+    assert_eq!(body["events"][0]["properties"]["__email"], "user@test.com");
+}
+
+#[tokio::test]
+async fn test_event_name_policy_rejects_invalid_names() {
+    let client = Outlit::builder("pk_test")
+        .api_host("http://127.0.0.1:1")
+        .event_name_policy(EventNamePolicy {
+            required_prefix: Some("app.".into()),
+            ..EventNamePolicy::default()
+        })
+        .disable_auto_flush()
+        .build()
+        .unwrap();
+
+    let result = client
+        .track("app.sign-up!", email("user@test.com"))
+        .send()
+        .await;
+    assert!(matches!(result, Err(outlit::Error::Validation(ref m)) if m.contains("'!'")));
+
+    let result = client.track("signup", email("user@test.com")).send().await;
+    assert!(matches!(result, Err(outlit::Error::Validation(ref m)) if m.contains("app.")));
+
+    client
+        .track("app.signup", email("user@test.com"))
+        .send()
+        .await
+        .unwrap();
+    client
+        .identify(email("user@test.com"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(client.pending_event_count().await, 2);
+}
+
+// ============================================
+// PRIVACY TESTS
+// ============================================
+
+#[tokio::test]
+async fn test_property_allow_and_deny_lists() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "success": true,
+            "processed": 2
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .allow_properties(["plan", "seats", "name"])
+        .deny_properties(["seats"])
+        .disable_auto_flush()
+        .build()
+        .unwrap();
+
+    client
+        .track("upgrade", email("user@test.com"))
+        .property("plan", "pro")
+        .property("seats", 5)
+        .property("ssn", "123-45-6789")
+        .send()
+        .await
+        .unwrap();
+    client
+        .identify(email("user@test.com"))
+        .trait_("name", "Jane")
+        .trait_("phone", "555-0100")
+        .send()
+        .await
+        .unwrap();
+    client.flush().await.unwrap();
+
+    let requests = mock_server.received_requests().await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+    let properties = &body["events"][0]["properties"];
+    assert_eq!(properties["plan"], "pro");
+    assert!(properties.get("seats").is_none());
+    assert!(properties.get("ssn").is_none());
+    assert_eq!(properties["__email"], "user@test.com");
+    assert_eq!(body["events"][1]["traits"], json!({ "name": "Jane" }));
+}
+
+#[cfg(feature = "redact")]
+#[tokio::test]
+async fn test_redactor_scrubs_pii_before_sending() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "success": true,
+            "processed": 1
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .redactor(
+            outlit::Redactor::new()
+                .emails()
+                .credit_cards()
+                .key("password")
+                .hash_identity_emails(),
+        )
+        .disable_auto_flush()
+        .build()
+        .unwrap();
+
+    client
+        .track("support_ticket", email("user@test.com"))
+        .property(
+            "body",
+            "reach me at other@test.com, card 4111-1111-1111-1111",
+        )
+        .property("password", "hunter2")
+        .send()
+        .await
+        .unwrap();
+    client.flush().await.unwrap();
+
+    let requests = mock_server.received_requests().await.unwrap();
+    let body = String::from_utf8(requests[0].body.clone()).unwrap();
+    assert!(!body.contains("@test.com"));
+    assert!(!body.contains("4111"));
+    assert!(!body.contains("hunter2"));
+
+    let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+    let properties = &body["events"][0]["properties"];
+    assert_eq!(
+        properties["body"],
+        "reach me at [REDACTED], card [REDACTED]"
+    );
+    assert_eq!(properties["__email"].as_str().unwrap().len(), 64);
+}
+
+#[tokio::test]
+async fn test_privacy_suppression_and_deletion() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v1/privacy/suppressions"))
+        .and(header("authorization", "Bearer sk_test_secret"))
+        .and(body_json(json!({ "email": "gone@example.com" })))
+        .respond_with(ResponseTemplate::new(202))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/api/v1/privacy/deletions"))
+        .and(body_json(json!({ "userId": "usr_erased" })))
+        .respond_with(ResponseTemplate::new(202))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .secret_key("sk_test_secret")
+        .flush_interval(Duration::from_secs(100))
+        .build()
+        .unwrap();
+
+    client
+        .track("queued", email("gone@example.com"))
+        .send()
+        .await
+        .unwrap();
+    client
+        .track("kept", email("stay@example.com"))
+        .send()
+        .await
+        .unwrap();
+
+    // Queued events for the user are removed, and later ones dropped
+    client
+        .privacy()
+        .suppress_user(email("gone@example.com"))
+        .await
+        .unwrap();
+    assert_eq!(client.pending_event_count().await, 1);
+    client
+        .track("later", email("gone@example.com"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(client.pending_event_count().await, 1);
+    assert!(client.privacy().is_suppressed(email("Gone@Example.com")));
+
+    client
+        .privacy()
+        .delete_user(user_id("usr_erased"))
+        .await
+        .unwrap();
+    client
+        .identify(email("new@example.com"))
+        .user_id("usr_erased")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(client.pending_event_count().await, 1);
+    assert!(!client.privacy().is_suppressed(email("stay@example.com")));
+}
+
+#[tokio::test]
+async fn test_consent_policy_drops_events_without_consent() {
+    let client = Outlit::builder("pk_test")
+        .consent_policy(ConsentPolicy::opt_in())
+        .flush_interval(Duration::from_secs(100))
+        .build()
+        .unwrap();
+
+    // No decision yet: only necessary events are queued
+    client
+        .track("viewed", email("user@example.com"))
+        .send()
+        .await
+        .unwrap();
+    client
+        .track("logged_in", email("user@example.com"))
+        .consent(Consent::Necessary)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(client.pending_event_count().await, 1);
+
+    client.set_consent(email("user@example.com"), [Consent::Analytics]);
+    client
+        .track("viewed", email("user@example.com"))
+        .send()
+        .await
+        .unwrap();
+    client
+        .track("ad_clicked", email("user@example.com"))
+        .consent(Consent::Marketing)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(client.pending_event_count().await, 2);
+
+    // Billing events aren't about a user and are always sent
+    client.customer().paid("example.com").send().await.unwrap();
+    assert_eq!(client.pending_event_count().await, 3);
+}
+
+// ============================================
+// RATE LIMIT TESTS
+// ============================================
+
+#[tokio::test]
+async fn test_rate_limit_drops_excess_events() {
+    let client = Outlit::builder("pk_test")
+        .api_host("http://127.0.0.1:1")
+        .rate_limit(outlit::RateLimit::per_second(1).burst(3))
+        .disable_auto_flush()
+        .build()
+        .unwrap();
+
+    for _ in 0..10 {
+        client
+            .track("heartbeat", email("user@test.com"))
+            .send()
+            .await
+            .unwrap();
+    }
+
+    assert_eq!(client.pending_event_count().await, 3);
+    assert_eq!(client.stats().dropped, 7);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_rate_limit_keeps_part_of_batch_larger_than_burst() {
+    let client = Outlit::builder("pk_test")
+        .api_host("http://127.0.0.1:1")
+        .rate_limit(outlit::RateLimit::per_second(1).burst(3))
+        .disable_auto_flush()
+        .build()
+        .unwrap();
+
+    client
+        .track_batch((0..5).map(|i| custom_event(&format!("event_{i}"))))
+        .await
+        .unwrap();
+
+    assert_eq!(client.pending_event_count().await, 3);
+    assert_eq!(client.stats().dropped, 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_rate_limit_throttles_send() {
+    let client = Outlit::builder("pk_test")
+        .api_host("http://127.0.0.1:1")
+        .rate_limit(
+            outlit::RateLimit::per_second(10)
+                .burst(1)
+                .policy(outlit::RateLimitPolicy::Throttle),
+        )
+        .disable_auto_flush()
+        .build()
+        .unwrap();
+
+    let start = tokio::time::Instant::now();
+    for _ in 0..5 {
+        client
+            .track("heartbeat", email("user@test.com"))
+            .send()
+            .await
+            .unwrap();
+    }
+
+    assert_eq!(client.pending_event_count().await, 5);
+    assert_eq!(start.elapsed(), Duration::from_millis(400));
+}
+
+// ============================================
+// ENVIRONMENT TESTS
+// ============================================
+
+#[tokio::test]
+async fn test_disabled_client_is_a_no_op() {
+    let client = Outlit::builder("pk_test")
+        .api_host("http://127.0.0.1:1")
+        .disabled()
+        .disable_auto_flush()
+        .build()
+        .unwrap();
+    assert!(!client.is_enabled());
+
+    client
+        .track("signup", email("user@test.com"))
+        .send()
+        .await
+        .unwrap();
+    client
+        .track("signup", email("user@test.com"))
+        .send_nowait()
+        .unwrap();
+    assert_eq!(client.pending_event_count().await, 0);
+
+    client.set_enabled(true);
+    client
+        .track("signup", email("user@test.com"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(client.pending_event_count().await, 1);
+
+    client.set_enabled(false);
+    client
+        .identify(email("user@test.com"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(client.pending_event_count().await, 1);
+    assert_eq!(client.stats().enqueued, 1);
+}
+
+#[tokio::test]
+async fn test_environment_is_attached_to_every_event() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "success": true,
+            "processed": 3
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .environment("staging")
+        .disable_auto_flush()
+        .build()
+        .unwrap();
+
+    client
+        .track("signup", email("user@test.com"))
+        .send()
+        .await
+        .unwrap();
+    client
+        .identify(email("user@test.com"))
+        .send()
+        .await
+        .unwrap();
+    client.customer().paid("acme.com").send().await.unwrap();
+    client.flush().await.unwrap();
+
+    let requests = mock_server.received_requests().await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+    assert_eq!(body["events"][0]["properties"]["__environment"], "staging");
+    assert_eq!(body["events"][1]["traits"]["__environment"], "staging");
+    assert_eq!(body["events"][2]["properties"]["__environment"], "staging");
+}
+
+#[tokio::test]
+async fn test_production_only_drops_other_environments() {
+    let build = |environment: &str| {
+        Outlit::builder("pk_test")
+            .api_host("http://127.0.0.1:1")
+            .environment(environment)
+            .production_only()
+            .disable_auto_flush()
+            .build()
+            .unwrap()
+    };
+
+    let staging = build("staging");
+    staging
+        .track("signup", email("user@test.com"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(staging.pending_event_count().await, 0);
+
+    let production = build("production");
+    production
+        .track("signup", email("user@test.com"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(production.pending_event_count().await, 1);
+
+    let result = Outlit::builder("pk_test").production_only().build();
+    assert!(matches!(result, Err(outlit::Error::Config(_))));
+}
+
+// ============================================
+// PROJECT ROUTING TESTS
+// ============================================
+
+#[tokio::test]
+async fn test_events_are_routed_to_projects() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "success": true,
+            "processed": 1
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_product")
+        .api_host(mock_server.uri())
+        .project("growth", "pk_growth")
+        .route_event("campaign_clicked", "growth")
+        .disable_auto_flush()
+        .build()
+        .unwrap();
+
+    client
+        .track("signup", email("user@test.com"))
+        .send()
+        .await
+        .unwrap();
+    client
+        .track("campaign_clicked", email("user@test.com"))
+        .send()
+        .await
+        .unwrap();
+    client
+        .identify(email("user@test.com"))
+        .project("growth")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(client.pending_event_count().await, 3);
+    client.flush().await.unwrap();
+
+    let mut bodies: Vec<(String, serde_json::Value)> = mock_server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .map(|r| (r.url.path().to_string(), r.body_json().unwrap()))
+        .collect();
+    bodies.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(bodies.len(), 2);
+    assert_eq!(bodies[0].0, "/api/i/v1/pk_growth/events");
+    assert_eq!(bodies[0].1["events"][0]["eventName"], "campaign_clicked");
+    assert_eq!(bodies[0].1["events"][1]["type"], "identify");
+    assert_eq!(bodies[1].0, "/api/i/v1/pk_product/events");
+    assert_eq!(bodies[1].1["events"][0]["eventName"], "signup");
+    assert_eq!(client.stats().sent, 3);
+
+    let result = client
+        .track("signup", email("user@test.com"))
+        .project("unknown")
+        .send()
+        .await;
+    assert!(matches!(result, Err(outlit::Error::Config(_))));
+
+    let result = Outlit::builder("pk_test")
+        .route_event("signup", "unknown")
+        .build();
+    assert!(matches!(result, Err(outlit::Error::Config(_))));
+}
+
+// ============================================
+// TIMESTAMP TESTS
+// ============================================
+
+#[tokio::test]
+async fn test_fixed_clock_timestamps_events() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "success": true,
+            "processed": 3
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let clock = outlit::FixedClock::new(1706400000000);
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .clock(clock.clone())
+        .disable_auto_flush()
+        .build()
+        .unwrap();
+
+    client
+        .track("signup", email("user@test.com"))
+        .send()
+        .await
+        .unwrap();
+    clock.advance(Duration::from_secs(1));
+    client
+        .identify(email("user@test.com"))
+        .send()
+        .await
+        .unwrap();
+    client
+        .track("upgrade", email("user@test.com"))
+        .timestamp(5)
+        .send()
+        .await
+        .unwrap();
+    client.flush().await.unwrap();
+
+    let requests = mock_server.received_requests().await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+    assert_eq!(body["events"][0]["timestamp"], 1706400000000i64);
+    assert_eq!(body["events"][1]["timestamp"], 1706400001000i64);
+    assert_eq!(body["events"][2]["timestamp"], 5);
+}
+
+#[tokio::test]
+async fn test_timestamp_policy_rejects_wrong_units() {
+    let client = Outlit::builder("pk_test")
+        .api_host("http://127.0.0.1:1")
+        .clock(outlit::FixedClock::new(1706400000000))
+        .timestamp_policy(outlit::TimestampPolicy::default())
+        .disable_auto_flush()
+        .build()
+        .unwrap();
+
+    // Nanoseconds
+    let result = client
+        .track("signup", email("user@test.com"))
+        .timestamp(1706400000000000000)
+        .send()
+        .await;
+    assert!(matches!(result, Err(outlit::Error::Validation(ref m)) if m.contains("future")));
+
+    client
+        .track("signup", email("user@test.com"))
+        .timestamp(1706399000000)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(client.pending_event_count().await, 1);
+}
+
+// ============================================
+// BILLING TESTS
+// ============================================
+
+#[tokio::test]
+async fn test_subscription_lifecycle_events() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "success": true,
+            "processed": 4
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .disable_auto_flush()
+        .build()
+        .unwrap();
+
+    let customer = client.customer();
+    customer
+        .invoice_paid("acme.com", "in_1", 4900)
+        .currency("usd")
+        .send()
+        .await
+        .unwrap();
+    customer
+        .invoice_failed("acme.com", "in_2", 4900)
+        .send()
+        .await
+        .unwrap();
+    customer
+        .refund("acme.com", 1000)
+        .invoice_id("in_1")
+        .send()
+        .await
+        .unwrap();
+    customer
+        .plan_changed("acme.com", "starter", "pro")
+        .send()
+        .await
+        .unwrap();
+    customer
+        .seats_changed("acme.com", 10, 15)
+        .send()
+        .await
+        .unwrap();
+    customer.paid("acme.com").seats(15).send().await.unwrap();
+    client.flush().await.unwrap();
+
+    let requests = mock_server.received_requests().await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+    let events = &body["events"];
+    assert_eq!(events[0]["activity"], "invoice_paid");
+    assert_eq!(events[0]["status"], "paid");
+    assert_eq!(events[0]["invoiceId"], "in_1");
+    assert_eq!(events[0]["amount"], 4900);
+    assert_eq!(events[0]["currency"], "usd");
+    assert_eq!(events[1]["activity"], "invoice_failed");
+    assert_eq!(events[1]["status"], "past_due");
+    assert_eq!(events[2]["activity"], "refund");
+    assert_eq!(events[2]["amount"], 1000);
+    assert_eq!(events[2]["invoiceId"], "in_1");
+    assert_eq!(events[3]["activity"], "plan_changed");
+    assert_eq!(events[3]["previousPlan"], "starter");
+    assert_eq!(events[3]["plan"], "pro");
+    assert_eq!(events[4]["activity"], "seats_changed");
+    assert_eq!(events[4]["previousSeats"], 10);
+    assert_eq!(events[4]["seats"], 15);
+    assert_eq!(events[5]["seats"], 15);
+    assert!(events[5].get("activity").is_none());
+}
+
+#[cfg(feature = "stripe")]
+#[tokio::test]
+async fn test_stripe_refund_keeps_churned_status() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "success": true,
+            "processed": 1
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .disable_auto_flush()
+        .build()
+        .unwrap();
+
+    let webhook = json!({
+        "id": "evt_1",
+        "type": "charge.refunded",
+        "data": { "object": {
+            "id": "ch_1",
+            "customer": "cus_1",
+            "amount_refunded": 1000,
+            "currency": "usd"
+        } }
+    });
+    let mut update = outlit::stripe::translate_value(&webhook).unwrap().unwrap();
+    assert!(matches!(
+        update.to_billing(&client, "acme.com"),
+        Err(outlit::Error::Validation(_))
+    ));
+
+    update.status = Some(outlit::BillingStatus::Churned);
+    update
+        .to_billing(&client, "acme.com")
+        .unwrap()
+        .send()
+        .await
+        .unwrap();
+    client.flush().await.unwrap();
+
+    let requests = mock_server.received_requests().await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+    let event = &body["events"][0];
+    assert_eq!(event["activity"], "refund");
+    assert_eq!(event["status"], "churned");
+    assert_eq!(event["amount"], 1000);
+}
+
+#[tokio::test]
+async fn test_customer_status_lookup() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v1/customers/acme.com/billing"))
+        .and(header("authorization", "Bearer sk_test_secret"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "domain": "acme.com",
+            "status": "past_due",
+            "mrr": 4900,
+            "currency": "usd",
+            "interval": "month",
+            "plan": "pro"
+        })))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/customers/unknown.com/billing"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .secret_key("sk_test_secret")
+        .build()
+        .unwrap();
+
+    let status = client.customer().status("acme.com").await.unwrap().unwrap();
+    assert_eq!(status.status, outlit::BillingStatus::PastDue);
+    assert_eq!(status.mrr, Some(4900));
+    assert_eq!(status.interval, Some(outlit::BillingInterval::Month));
+    assert_eq!(status.plan.as_deref(), Some("pro"));
+    assert_eq!(status.seats, None);
+
+    assert_eq!(client.customer().status("unknown.com").await.unwrap(), None);
+
+    // Reads need the secret key
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .build()
+        .unwrap();
+    assert!(matches!(
+        client.customer().status("acme.com").await,
+        Err(outlit::Error::Config(_))
+    ));
+}
+
+// ============================================
+// CONTEXT TESTS
+// ============================================
+
+#[tokio::test]
+async fn test_reset_context_clears_prior_context() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "success": true,
+            "processed": 2
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .flush_interval(Duration::from_secs(100))
+        .build()
+        .unwrap();
+
+    client.set_context("tenant", "acme");
+    client.set_context("plan", "pro");
+    client
+        .track("first", email("alice@acme.com"))
+        .property("plan", "enterprise")
+        .send()
+        .await
+        .unwrap();
+
+    client.reset_context();
+    client
+        .track("second", email("bob@globex.com"))
+        .send()
+        .await
+        .unwrap();
+
+    client.flush().await.unwrap();
+
+    let requests = mock_server.received_requests().await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+    let events = body["events"].as_array().unwrap();
+    assert_eq!(events[0]["properties"]["tenant"], "acme");
+    // Event properties take precedence over context
+    assert_eq!(events[0]["properties"]["plan"], "enterprise");
+    assert!(events[1]["properties"].get("tenant").is_none());
+    assert!(events[1]["properties"].get("plan").is_none());
+}
+
+#[tokio::test]
+async fn test_default_properties_merged_below_context() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "success": true,
+            "processed": 3
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .flush_interval(Duration::from_secs(100))
+        .default_property("app_version", "1.2.3")
+        .default_property("env", "production")
+        .build()
+        .unwrap();
+
+    client.set_context("env", "staging");
+    client
+        .track("first", email("alice@acme.com"))
+        .send()
+        .await
+        .unwrap();
+    client
+        .user()
+        .activate(email("alice@acme.com"))
+        .property("app_version", "2.0.0")
+        .send()
+        .await
+        .unwrap();
+    client.reset_context();
+    client
+        .identify(email("alice@acme.com"))
+        .send()
+        .await
+        .unwrap();
+
+    client.flush().await.unwrap();
+
+    let requests = mock_server.received_requests().await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+    let events = body["events"].as_array().unwrap();
+    assert_eq!(events[0]["properties"]["app_version"], "1.2.3");
+    // Context takes precedence over default properties
+    assert_eq!(events[0]["properties"]["env"], "staging");
+    assert_eq!(events[1]["properties"]["app_version"], "2.0.0");
+    // Identify events carry traits, not properties
+    assert!(events[2].get("properties").is_none());
+}
+
+#[cfg(feature = "opentelemetry")]
+#[tokio::test]
+async fn test_trace_context_attached_to_events_and_requests() {
+    use opentelemetry::trace::{
+        SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState,
+    };
+
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(header(
+            "traceparent",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "success": true,
+            "processed": 2
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .flush_interval(Duration::from_secs(100))
+        .trace_context(true)
+        .build()
+        .unwrap();
+
+    client
+        .track("outside_span", email("alice@acme.com"))
+        .send()
+        .await
+        .unwrap();
+
+    let span_context = SpanContext::new(
+        TraceId::from_hex("4bf92f3577b34da6a3ce929d0e0e4736").unwrap(),
+        SpanId::from_hex("00f067aa0ba902b7").unwrap(),
+        TraceFlags::SAMPLED,
+        true,
+        TraceState::default(),
+    );
+    let _guard = opentelemetry::Context::new()
+        .with_remote_span_context(span_context)
+        .attach();
+
+    client
+        .track("inside_span", email("alice@acme.com"))
+        .send()
+        .await
+        .unwrap();
+    client.flush().await.unwrap();
+
+    let requests = mock_server.received_requests().await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+    let events = body["events"].as_array().unwrap();
+    assert!(events[0]["properties"].get("trace_id").is_none());
+    assert_eq!(
+        events[1]["properties"]["trace_id"],
+        "4bf92f3577b34da6a3ce929d0e0e4736"
+    );
+    assert_eq!(events[1]["properties"]["span_id"], "00f067aa0ba902b7");
+}
+
+// ============================================
+// BEFORE SEND HOOK TESTS
+// ============================================
+
+#[tokio::test]
+async fn test_before_send_hooks_mutate_and_filter() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "success": true,
+            "processed": 2
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .flush_interval(Duration::from_secs(100))
+        .before_send(|event| {
+            if let outlit::TrackerEvent::Custom(data) = event {
+                if let Some(props) = data.properties.as_mut() {
+                    props.remove("password");
+                }
+            }
+            true
+        })
+        .before_send(|event| match event {
+            outlit::TrackerEvent::Custom(data) => data.event_name != "debug",
+            _ => true,
+        })
+        .build()
+        .unwrap();
+
+    client
+        .track("login", email("user@test.com"))
+        .property("password", "hunter2")
+        .property("method", "sso")
+        .send()
+        .await
+        .unwrap();
+    client
+        .track("debug", email("user@test.com"))
+        .send()
+        .await
+        .unwrap();
+    client
+        .identify(email("user@test.com"))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(client.pending_event_count().await, 2);
+
+    client.flush().await.unwrap();
+
+    let requests = mock_server.received_requests().await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+    let events = body["events"].as_array().unwrap();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0]["eventName"], "login");
+    assert!(events[0]["properties"].get("password").is_none());
+    assert_eq!(events[0]["properties"]["method"], "sso");
+    assert_eq!(events[1]["type"], "identify");
+}
+
+#[tokio::test]
+async fn test_custom_stage_event() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "success": true,
+            "processed": 1
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .flush_interval(Duration::from_secs(100))
+        .build()
+        .unwrap();
+
+    client
+        .user()
+        .stage("power_user", user_id("usr_123"))
+        .send()
+        .await
+        .unwrap();
+
+    client.flush().await.unwrap();
+
+    let requests = mock_server.received_requests().await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+    assert_eq!(body["events"][0]["type"], "stage");
+    assert_eq!(body["events"][0]["stage"], "power_user");
+}
+
+// ============================================
+// BATCH TESTS
+// ============================================
+
+fn custom_event(name: &str) -> outlit::TrackerEvent {
+    outlit::TrackerEvent::Custom(outlit::types::CustomEventData {
+        timestamp: 1706400000000,
+        url: "server://user@test.com".into(),
+        path: "/".into(),
+        event_name: name.into(),
+        properties: None,
+        message_id: None,
+        event_id: None,
+    })
+}
+
+#[tokio::test]
+async fn test_track_batch_flushes_once_in_order() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "success": true,
+            "processed": 250
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .max_batch_size(100)
+        .flush_interval(Duration::from_secs(100))
+        .build()
+        .unwrap();
+
+    let names: Vec<String> = (0..250).map(|i| format!("event_{i}")).collect();
+    client
+        .track_batch(names.iter().map(|n| custom_event(n)))
+        .await
+        .unwrap();
+
+    assert_eq!(client.pending_event_count().await, 0);
+
+    let requests = mock_server.received_requests().await.unwrap();
+    assert_eq!(event_names(&requests[0]), names);
+}
+
+#[tokio::test]
+async fn test_track_batch_below_batch_size_does_not_flush() {
+    let client = Outlit::builder("pk_test")
+        .api_host("http://127.0.0.1:1")
+        .flush_interval(Duration::from_secs(100))
+        .before_send(|event| match event {
+            outlit::TrackerEvent::Custom(data) => data.event_name != "debug",
+            _ => true,
+        })
+        .build()
+        .unwrap();
+
+    client
+        .track_batch(vec![
+            custom_event("a"),
+            custom_event("debug"),
+            custom_event("b"),
+        ])
+        .await
+        .unwrap();
+
+    assert_eq!(client.pending_event_count().await, 2);
+}
+
+#[tokio::test]
+async fn test_every_event_gets_an_event_id() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "success": true,
+            "processed": 2
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .flush_interval(Duration::from_secs(100))
+        .build()
+        .unwrap();
+
+    client
+        .track("built", email("user@test.com"))
+        .send()
+        .await
+        .unwrap();
+    // Constructed directly, without an ID
+    client
+        .track_batch([custom_event("constructed")])
+        .await
+        .unwrap();
+    client.flush().await.unwrap();
+
+    let requests = mock_server.received_requests().await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+    let events = body["events"].as_array().unwrap();
+    let first = events[0]["eventId"].as_str().unwrap();
+    let second = events[1]["eventId"].as_str().unwrap();
+    assert_eq!(first.len(), 36);
+    assert_ne!(first, second);
+}
+
+// ============================================
+// DEDUPLICATION TESTS
+// ============================================
+
+#[tokio::test]
+async fn test_duplicate_idempotency_key_enqueued_once() {
+    let client = Outlit::builder("pk_test")
+        .api_host("http://127.0.0.1:1")
+        .flush_interval(Duration::from_secs(100))
+        .build()
+        .unwrap();
+
+    for _ in 0..2 {
+        client
+            .track("checkout", email("user@test.com"))
+            .idempotency_key("order_42")
+            .send()
+            .await
+            .unwrap();
+    }
+
+    assert_eq!(client.pending_event_count().await, 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_rejected_event_can_be_retried_with_same_idempotency_key() {
+    let client = Outlit::builder("pk_test")
+        .api_host("http://127.0.0.1:1")
+        .max_event_bytes(1_000)
+        .rate_limit(outlit::RateLimit::per_second(1).burst(1))
+        .disable_auto_flush()
+        .build()
+        .unwrap();
+
+    let result = client
+        .track("checkout", email("user@test.com"))
+        .property("cart", "x".repeat(5_000))
+        .idempotency_key("order_42")
+        .send()
+        .await;
+    assert!(matches!(result, Err(outlit::Error::Validation(_))));
+
+    // Fixed and retried with the same key
+    client
+        .track("checkout", email("user@test.com"))
+        .idempotency_key("order_42")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(client.pending_event_count().await, 1);
+
+    // Dropped by the rate limit, so the key isn't used up either
+    client
+        .track("refund", email("user@test.com"))
+        .idempotency_key("order_43")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(client.pending_event_count().await, 1);
+    tokio::time::sleep(Duration::from_millis(1_100)).await;
+    client
+        .track("refund", email("user@test.com"))
+        .idempotency_key("order_43")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(client.pending_event_count().await, 2);
+}
+
+#[tokio::test]
+async fn test_track_batch_validation_failure_records_no_keys() {
+    let client = Outlit::builder("pk_test")
+        .api_host("http://127.0.0.1:1")
+        .max_event_bytes(1_000)
+        .disable_auto_flush()
+        .build()
+        .unwrap();
+
+    let event = |key: &str, size: usize| {
+        let mut event = custom_event("checkout");
+        if let outlit::TrackerEvent::Custom(data) = &mut event {
+            data.properties = Some([("cart".to_string(), json!("x".repeat(size)))].into());
+            data.message_id = Some(key.into());
+        }
+        event
+    };
+    let result = client
+        .track_batch([event("order_1", 10), event("order_2", 5_000)])
+        .await;
+    assert!(matches!(result, Err(outlit::Error::Validation(_))));
+
+    client
+        .track_batch([event("order_1", 10), event("order_2", 10)])
+        .await
+        .unwrap();
+    assert_eq!(client.pending_event_count().await, 2);
+}
+
+#[tokio::test]
+async fn test_distinct_idempotency_keys_both_enqueued() {
+    let client = Outlit::builder("pk_test")
+        .api_host("http://127.0.0.1:1")
+        .flush_interval(Duration::from_secs(100))
+        .build()
+        .unwrap();
+
+    client
+        .track("checkout", email("user@test.com"))
+        .idempotency_key("order_42")
+        .send()
+        .await
+        .unwrap();
+    client
+        .customer()
+        .paid("acme.com")
+        .idempotency_key("order_43")
+        .send()
+        .await
+        .unwrap();
+    // Events without a key are never deduplicated
+    client
+        .track("checkout", email("user@test.com"))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(client.pending_event_count().await, 3);
+}
+
+#[tokio::test]
+async fn test_dedup_window_zero_disables_deduplication() {
+    let client = Outlit::builder("pk_test")
+        .api_host("http://127.0.0.1:1")
+        .dedup_window(0)
+        .flush_interval(Duration::from_secs(100))
+        .build()
+        .unwrap();
+
+    for _ in 0..2 {
+        client
+            .track("checkout", email("user@test.com"))
+            .idempotency_key("order_42")
+            .send()
+            .await
+            .unwrap();
+    }
+
+    assert_eq!(client.pending_event_count().await, 2);
+}
+
+#[tokio::test]
+async fn test_flush_task_alive_until_shutdown() {
+    let client = Outlit::builder("pk_test")
+        .api_host("http://127.0.0.1:1")
+        .flush_interval(Duration::from_secs(100))
+        .build()
+        .unwrap();
+
+    assert!(client.flush_task_alive().await);
+
+    client.shutdown().await.unwrap();
+
+    assert!(!client.flush_task_alive().await);
+}
+
+#[tokio::test]
+async fn test_flush_task_not_started_when_auto_flush_disabled() {
+    let client = Outlit::builder("pk_test")
+        .api_host("http://127.0.0.1:1")
+        .disable_auto_flush()
+        .build()
+        .unwrap();
+
+    assert!(!client.flush_task_alive().await);
+}
+
+#[tokio::test]
+async fn test_dedup_identical_skips_repeated_events() {
+    let client = Outlit::builder("pk_test")
+        .api_host("http://127.0.0.1:1")
+        .dedup_identical(Duration::from_secs(60))
+        .disable_auto_flush()
+        .build()
+        .unwrap();
+
+    for plan in ["pro", "pro", "team", "pro"] {
+        client
+            .track("upgrade", email("user@test.com"))
+            .property("plan", plan)
+            .send()
+            .await
+            .unwrap();
+    }
+    client
+        .track("upgrade", email("other@test.com"))
+        .property("plan", "pro")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(client.pending_event_count().await, 3);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_dedup_identical_ignores_events_that_were_not_queued() {
+    let client = Outlit::builder("pk_test")
+        .api_host("http://127.0.0.1:1")
+        .dedup_identical(Duration::from_secs(60))
+        .rate_limit(outlit::RateLimit::per_second(1).burst(1))
+        .disable_auto_flush()
+        .build()
+        .unwrap();
+
+    client
+        .track("signup", email("user@test.com"))
+        .send()
+        .await
+        .unwrap();
+    // Dropped by the rate limit, then resent once it refills
+    client
+        .track("upgrade", email("user@test.com"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(client.pending_event_count().await, 1);
+    tokio::time::sleep(Duration::from_millis(1_100)).await;
+    client
+        .track("upgrade", email("user@test.com"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(client.pending_event_count().await, 2);
+}
+
 // ============================================
-// FINGERPRINT TESTS
+// BLOCKING FLUSH TESTS
+// ============================================
+
+#[test]
+fn test_flush_blocking_outside_runtime() {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let mock_server = rt.block_on(MockServer::start());
+    rt.block_on(
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "success": true,
+                "processed": 1
+            })))
+            .expect(1)
+            .mount(&mock_server),
+    );
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .disable_auto_flush()
+        .build()
+        .unwrap();
+
+    rt.block_on(client.track("event", email("user@test.com")).send())
+        .unwrap();
+
+    // Called from plain synchronous code with no runtime context
+    client.flush_blocking(Duration::from_secs(5)).unwrap();
+
+    let requests = rt.block_on(mock_server.received_requests()).unwrap();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(rt.block_on(client.pending_event_count()), 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_flush_blocking_inside_multi_thread_runtime() {
+    let mock_server = MockServer::start().await;
+    let received = Arc::new(AtomicUsize::new(0));
+
+    Mock::given(method("POST"))
+        .respond_with(CountingResponder {
+            counter: received.clone(),
+        })
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .flush_interval(Duration::from_secs(100))
+        .build()
+        .unwrap();
+
+    client
+        .track("event", email("user@test.com"))
+        .send()
+        .await
+        .unwrap();
+
+    client.flush_blocking(Duration::from_secs(5)).unwrap();
+
+    assert_eq!(received.load(Ordering::SeqCst), 1);
+}
+
+// The only test that touches the global client, as it can be set once per
+// process.
+#[tokio::test]
+async fn test_global_client() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "success": true,
+            "processed": 2
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    assert!(matches!(
+        outlit::track("early", email("user@test.com")),
+        Err(outlit::Error::NotInitialized)
+    ));
+
+    let builder = || {
+        Outlit::builder("pk_test")
+            .api_host(mock_server.uri())
+            .disable_auto_flush()
+    };
+    outlit::init(builder()).unwrap();
+    assert!(matches!(
+        outlit::init(builder()),
+        Err(outlit::Error::Config(_))
+    ));
+
+    outlit::track("signup", email("user@test.com"))
+        .unwrap()
+        .send()
+        .await
+        .unwrap();
+    outlit::identify(email("user@test.com"))
+        .unwrap()
+        .trait_("plan", "pro")
+        .send()
+        .await
+        .unwrap();
+    outlit::shutdown().await.unwrap();
+
+    let requests = mock_server.received_requests().await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+    assert_eq!(body["events"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_guard_flushes_on_drop() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "success": true,
+            "processed": 1
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .flush_interval(Duration::from_secs(100))
+        .build()
+        .unwrap()
+        .into_guard();
+
+    client
+        .track("event", email("user@test.com"))
+        .send()
+        .await
+        .unwrap();
+    drop(client);
+
+    let requests = mock_server.received_requests().await.unwrap();
+    assert_eq!(event_names(&requests[0]), ["event"]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_guard_drop_spills_events_when_flush_times_out() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!({ "success": true, "processed": 1 }))
+                .set_delay(Duration::from_secs(5)),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .dead_letter(tx)
+        .disable_auto_flush()
+        .build()
+        .unwrap()
+        .into_guard()
+        .drop_timeout(Duration::from_millis(100));
+
+    client
+        .track("slow", email("user@test.com"))
+        .send()
+        .await
+        .unwrap();
+    drop(client);
+
+    let letter = rx.try_recv().unwrap();
+    assert_eq!(letter.reason, outlit::DeadLetterReason::Shutdown);
+}
+
+#[test]
+fn test_guard_into_inner_does_not_shut_down() {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let client = Outlit::builder("pk_test")
+        .api_host("http://127.0.0.1:1")
+        .disable_auto_flush()
+        .build()
+        .unwrap()
+        .into_guard();
+
+    let client = client.into_inner();
+
+    rt.block_on(client.track("event", email("user@test.com")).send())
+        .unwrap();
+    assert_eq!(rt.block_on(client.pending_event_count()), 1);
+}
+
+#[test]
+fn test_flush_blocking_times_out() {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let mock_server = rt.block_on(MockServer::start());
+    rt.block_on(
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(5)))
+            .mount(&mock_server),
+    );
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .disable_auto_flush()
+        .build()
+        .unwrap();
+
+    rt.block_on(client.track("event", email("user@test.com")).send())
+        .unwrap();
+
+    let result = client.flush_blocking(Duration::from_millis(50));
+
+    assert!(matches!(result, Err(outlit::Error::Timeout)));
+    // Events stay queued for a later attempt
+    assert_eq!(rt.block_on(client.pending_event_count()), 1);
+
+    rt.block_on(async {
+        mock_server.reset().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "success": true,
+                "processed": 1
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+    });
+
+    // The cancelled flush released its reservation
+    client.flush_blocking(Duration::from_secs(5)).unwrap();
+    assert_eq!(rt.block_on(client.pending_event_count()), 0);
+}
+
 // ============================================
+// HEADER TESTS
+// ============================================
+
+#[tokio::test]
+async fn test_default_user_agent_header() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(header(
+            "user-agent",
+            concat!("outlit-rust/", env!("CARGO_PKG_VERSION")),
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "success": true,
+            "processed": 1
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .flush_interval(Duration::from_secs(100))
+        .build()
+        .unwrap();
+
+    client
+        .track("event", email("user@test.com"))
+        .send()
+        .await
+        .unwrap();
+
+    client.flush().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_custom_user_agent_replaces_default() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(header("user-agent", "my-service/2.0"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "success": true,
+            "processed": 1
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .user_agent("my-service/2.0")
+        .flush_interval(Duration::from_secs(100))
+        .build()
+        .unwrap();
+
+    client
+        .track("event", email("user@test.com"))
+        .send()
+        .await
+        .unwrap();
+
+    client.flush().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_custom_headers_sent() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(header("x-tenant-id", "acme"))
+        .and(header("x-service", "billing"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "success": true,
+            "processed": 1
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .header("X-Tenant-Id", "acme")
+        .header("X-Service", "billing")
+        .flush_interval(Duration::from_secs(100))
+        .build()
+        .unwrap();
+
+    client
+        .track("event", email("user@test.com"))
+        .send()
+        .await
+        .unwrap();
+
+    client.flush().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_secret_key_signs_requests() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(wiremock::matchers::header_exists("x-outlit-timestamp"))
+        .and(wiremock::matchers::header_exists("x-outlit-signature"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "success": true,
+            "processed": 1
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .secret_key("sk_test_secret")
+        .flush_interval(Duration::from_secs(100))
+        .build()
+        .unwrap();
+
+    client
+        .track("event", email("user@test.com"))
+        .send()
+        .await
+        .unwrap();
+    client.flush().await.unwrap();
+
+    let requests = mock_server.received_requests().await.unwrap();
+    let timestamp = requests[0].headers["x-outlit-timestamp"].to_str().unwrap();
+    assert!(timestamp.parse::<u64>().is_ok());
+    let signature = requests[0].headers["x-outlit-signature"].to_str().unwrap();
+    assert!(signature.starts_with("v1="));
+    assert_eq!(signature.len(), 3 + 64);
+}
+
+#[tokio::test]
+async fn test_injected_http_client_is_used() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(header("x-shared-client", "yes"))
+        .and(header(
+            "user-agent",
+            concat!("outlit-rust/", env!("CARGO_PKG_VERSION")),
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "success": true,
+            "processed": 1
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let mut headers = reqwest::header::HeaderMap::new();
+    headers.insert("x-shared-client", "yes".parse().unwrap());
+    let http_client = reqwest::Client::builder()
+        .default_headers(headers)
+        .user_agent("shared/1.0")
+        .build()
+        .unwrap();
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .http_client(http_client)
+        .flush_interval(Duration::from_secs(100))
+        .build()
+        .unwrap();
+
+    client
+        .track("event", email("user@test.com"))
+        .send()
+        .await
+        .unwrap();
+
+    client.flush().await.unwrap();
+}
 
 #[tokio::test]
-async fn test_track_with_fingerprint_only() {
-    let mock_server = MockServer::start().await;
+async fn test_requests_sent_through_proxy() {
+    let proxy = MockServer::start().await;
 
+    // Plain HTTP requests reach the proxy with the target's path
     Mock::given(method("POST"))
         .and(path("/api/i/v1/pk_test/events"))
+        .and(header("host", "api.outlit.invalid"))
         .respond_with(ResponseTemplate::new(200).set_body_json(json!({
             "success": true,
             "processed": 1
         })))
         .expect(1)
-        .mount(&mock_server)
+        .mount(&proxy)
         .await;
 
     let client = Outlit::builder("pk_test")
-        .api_host(mock_server.uri())
+        .api_host("http://api.outlit.invalid")
+        .proxy(proxy.uri())
         .flush_interval(Duration::from_secs(100))
         .build()
         .unwrap();
 
-    // Track with fingerprint only (anonymous user)
     client
-        .track_by_fingerprint("page_view", fingerprint("device_abc123"))
-        .property("page", "/pricing")
+        .track("event", email("user@test.com"))
         .send()
         .await
         .unwrap();
@@ -489,15 +3934,87 @@ async fn test_track_with_fingerprint_only() {
     client.flush().await.unwrap();
 }
 
+#[cfg(feature = "blocking")]
+#[test]
+fn test_blocking_client_sends_on_shutdown() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mock_server = runtime.block_on(MockServer::start());
+    runtime.block_on(
+        Mock::given(method("POST"))
+            .and(path("/api/i/v1/pk_test/events"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "success": true,
+                "processed": 2
+            })))
+            .expect(1)
+            .mount(&mock_server),
+    );
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .flush_interval(Duration::from_secs(100))
+        .build_blocking()
+        .unwrap();
+
+    client
+        .track("signup", email("user@test.com"))
+        .property("plan", "pro")
+        .send()
+        .unwrap();
+    client
+        .user()
+        .activate(email("user@test.com"))
+        .send()
+        .unwrap();
+    assert_eq!(client.pending_event_count(), 2);
+
+    client.shutdown().unwrap();
+    assert_eq!(client.pending_event_count(), 0);
+
+    let requests = runtime.block_on(mock_server.received_requests()).unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+    assert_eq!(body["events"][0]["eventName"], "signup");
+    assert_eq!(body["events"][1]["stage"], "activated");
+}
+
+#[cfg(feature = "blocking")]
+#[test]
+fn test_blocking_client_flushes_in_background() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mock_server = runtime.block_on(MockServer::start());
+    let received = Arc::new(AtomicUsize::new(0));
+    runtime.block_on(
+        Mock::given(method("POST"))
+            .respond_with(CountingResponder {
+                counter: received.clone(),
+            })
+            .mount(&mock_server),
+    );
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .flush_interval(Duration::from_millis(50))
+        .build_blocking()
+        .unwrap();
+
+    client
+        .track("event", email("user@test.com"))
+        .send()
+        .unwrap();
+    std::thread::sleep(Duration::from_millis(200));
+
+    assert_eq!(received.load(Ordering::SeqCst), 1);
+    assert_eq!(client.pending_event_count(), 0);
+}
+
 #[tokio::test]
-async fn test_track_with_fingerprint_and_email() {
+async fn test_page_and_screen_events() {
     let mock_server = MockServer::start().await;
 
     Mock::given(method("POST"))
-        .and(path("/api/i/v1/pk_test/events"))
         .respond_with(ResponseTemplate::new(200).set_body_json(json!({
             "success": true,
-            "processed": 1
+            "processed": 2
         })))
         .expect(1)
         .mount(&mock_server)
@@ -509,82 +4026,440 @@ async fn test_track_with_fingerprint_and_email() {
         .build()
         .unwrap();
 
-    // Track with email + fingerprint (links device to user)
     client
-        .track("signup", email("user@test.com"))
-        .fingerprint("device_abc123")
-        .property("plan", "pro")
+        .page(email("user@test.com"), "Pricing")
+        .url("https://example.com/pricing?plan=pro")
+        .send()
+        .await
+        .unwrap();
+    client
+        .screen(user_id("usr_123"), "Settings")
         .send()
         .await
         .unwrap();
+    client.flush().await.unwrap();
+
+    let requests = mock_server.received_requests().await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+    let page = &body["events"][0];
+    assert_eq!(page["type"], "pageview");
+    assert_eq!(page["url"], "https://example.com/pricing?plan=pro");
+    assert_eq!(page["path"], "/pricing");
+    assert_eq!(page["title"], "Pricing");
+    assert_eq!(page["properties"]["__email"], "user@test.com");
+    let screen = &body["events"][1];
+    assert_eq!(screen["type"], "pageview");
+    assert_eq!(screen["url"], "screen://Settings");
+    assert_eq!(screen["properties"]["__userId"], "usr_123");
+}
+
+#[tokio::test]
+async fn test_import_sends_in_import_batches_bypassing_queue() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "success": true,
+            "processed": 2
+        })))
+        .expect(3)
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .flush_interval(Duration::from_secs(100))
+        .max_batch_size(1)
+        .import_batch_size(2)
+        .sample_rate(0.0) // imports are never sampled
+        .build()
+        .unwrap();
+
+    let report = client
+        .import((0..5).map(|i| custom_event(&format!("event_{i}"))))
+        .await
+        .unwrap();
+
+    assert_eq!(report.sent, 5);
+    assert_eq!(client.pending_event_count().await, 0);
+
+    let requests = mock_server.received_requests().await.unwrap();
+    let bodies: Vec<serde_json::Value> = requests
+        .iter()
+        .map(|r| serde_json::from_slice(&r.body).unwrap())
+        .collect();
+    assert!(bodies.iter().all(|b| b["source"] == "integration"));
+    assert_eq!(bodies[0]["events"].as_array().unwrap().len(), 2);
+    assert_eq!(bodies[2]["events"][0]["eventName"], "event_4");
+    assert_eq!(bodies[2]["events"][0]["timestamp"], 1706400000000i64);
+}
+
+#[tokio::test]
+async fn test_failed_import_can_be_retried_with_same_idempotency_keys() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(500))
+        .up_to_n_times(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "success": true,
+            "processed": 2
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .retry_policy(RetryPolicy::none())
+        .import_batch_size(2)
+        .disable_auto_flush()
+        .build()
+        .unwrap();
+    let rows = || {
+        (0..3).map(|i| {
+            let mut event = custom_event(&format!("event_{i}"));
+            if let outlit::TrackerEvent::Custom(data) = &mut event {
+                data.message_id = Some(format!("row_{i}"));
+            }
+            event
+        })
+    };
+
+    assert!(client.import(rows()).await.is_err());
+    let report = client.import(rows()).await.unwrap();
+
+    assert_eq!(report.sent, 3);
+    let requests = mock_server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 3);
+}
+
+#[cfg(feature = "derive")]
+#[tokio::test]
+async fn test_track_event_sends_derived_properties() {
+    use outlit::OutlitEvent;
+
+    #[derive(OutlitEvent)]
+    struct SignupEvent {
+        plan: String,
+        seats: u32,
+        #[outlit(rename = "referralCode")]
+        referral: Option<String>,
+        coupon: Option<String>,
+        #[outlit(skip)]
+        #[allow(dead_code)]
+        internal_note: String,
+    }
+
+    #[derive(OutlitEvent)]
+    #[outlit(name = "plan_changed")]
+    struct Upgrade {
+        to: String,
+    }
+
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "success": true,
+            "processed": 2
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .flush_interval(Duration::from_secs(100))
+        .build()
+        .unwrap();
 
+    let signup = SignupEvent {
+        plan: "pro".into(),
+        seats: 5,
+        referral: Some("friend".into()),
+        coupon: None,
+        internal_note: "vip".into(),
+    };
+    client
+        .track_event(&signup, email("user@test.com"))
+        .unwrap()
+        .send()
+        .await
+        .unwrap();
+    client
+        .track_event(Upgrade { to: "team".into() }, user_id("usr_123"))
+        .unwrap()
+        .send()
+        .await
+        .unwrap();
     client.flush().await.unwrap();
+
+    let requests = mock_server.received_requests().await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+    let signup = &body["events"][0];
+    assert_eq!(signup["eventName"], "signup");
+    assert_eq!(signup["properties"]["plan"], "pro");
+    assert_eq!(signup["properties"]["seats"], 5);
+    assert_eq!(signup["properties"]["referralCode"], "friend");
+    assert!(signup["properties"].get("coupon").is_none());
+    assert!(signup["properties"].get("internal_note").is_none());
+    assert_eq!(signup["properties"]["__email"], "user@test.com");
+    assert_eq!(body["events"][1]["eventName"], "plan_changed");
+    assert_eq!(body["events"][1]["properties"]["to"], "team");
 }
 
 #[tokio::test]
-async fn test_track_with_fingerprint_and_user_id() {
+async fn test_flush_callbacks_report_success_and_failure() {
     let mock_server = MockServer::start().await;
 
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(1)
+        .mount(&mock_server)
+        .await;
     Mock::given(method("POST"))
         .respond_with(ResponseTemplate::new(200).set_body_json(json!({
             "success": true,
             "processed": 1
         })))
-        .expect(1)
         .mount(&mock_server)
         .await;
 
+    let delivered = Arc::new(AtomicUsize::new(0));
+    let failures = Arc::new(AtomicUsize::new(0));
     let client = Outlit::builder("pk_test")
         .api_host(mock_server.uri())
+        .retry_policy(RetryPolicy::none())
         .flush_interval(Duration::from_secs(100))
+        .on_flush_success({
+            let delivered = delivered.clone();
+            move |report| {
+                delivered.fetch_add(report.sent, Ordering::SeqCst);
+            }
+        })
+        .on_flush_failure({
+            let failures = failures.clone();
+            move |e| {
+                assert!(matches!(e, outlit::Error::Api { status: 503, .. }));
+                failures.fetch_add(1, Ordering::SeqCst);
+            }
+        })
         .build()
         .unwrap();
 
-    // Track with fingerprint + user_id
+    // Empty flushes aren't reported
+    client.flush().await.unwrap();
+    assert_eq!(delivered.load(Ordering::SeqCst), 0);
+
     client
-        .track_by_fingerprint("feature_used", fingerprint("device_abc123"))
-        .user_id("usr_123")
+        .track("event", email("user@test.com"))
         .send()
         .await
         .unwrap();
+    assert!(client.flush().await.is_err());
+    assert_eq!(failures.load(Ordering::SeqCst), 1);
+    assert_eq!(delivered.load(Ordering::SeqCst), 0);
 
     client.flush().await.unwrap();
+    assert_eq!(failures.load(Ordering::SeqCst), 1);
+    assert_eq!(delivered.load(Ordering::SeqCst), 1);
 }
 
 #[tokio::test]
-async fn test_identify_with_fingerprint_links_device() {
+async fn test_stats_track_delivery() {
     let mock_server = MockServer::start().await;
 
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(500))
+        .up_to_n_times(1)
+        .mount(&mock_server)
+        .await;
     Mock::given(method("POST"))
         .respond_with(ResponseTemplate::new(200).set_body_json(json!({
             "success": true,
-            "processed": 1
+            "processed": 1,
+            "errors": [{ "index": 1, "message": "invalid event" }]
         })))
-        .expect(1)
         .mount(&mock_server)
         .await;
 
     let client = Outlit::builder("pk_test")
         .api_host(mock_server.uri())
+        .retry_policy(RetryPolicy::none())
         .flush_interval(Duration::from_secs(100))
         .build()
         .unwrap();
+    assert_eq!(client.stats(), outlit::ClientStats::default());
 
-    // Identify with email + fingerprint to link device
+    for name in ["first", "second"] {
+        client
+            .track(name, email("user@test.com"))
+            .send()
+            .await
+            .unwrap();
+    }
+    assert!(client.flush().await.is_err());
+
+    let stats = client.stats();
+    assert_eq!(stats.enqueued, 2);
+    assert_eq!(stats.failed_flushes, 1);
+    assert!(stats.last_flush.unwrap().error.is_some());
+
+    client.flush().await.unwrap();
     client
-        .identify(email("user@test.com"))
-        .fingerprint("device_abc123")
-        .user_id("usr_123")
-        .trait_("name", "John")
+        .track("third", email("user@test.com"))
         .send()
         .await
         .unwrap();
+    client.discard_pending().await;
+
+    let stats = client.stats();
+    assert_eq!(stats.enqueued, 3);
+    assert_eq!(stats.sent, 2);
+    assert_eq!(stats.rejected, 1);
+    assert_eq!(stats.dropped, 1);
+    assert_eq!(stats.last_flush.unwrap().error, None);
+}
+
+// ============================================
+// TOWER MIDDLEWARE TESTS
+// ============================================
+
+#[cfg(feature = "tower")]
+#[tokio::test]
+async fn test_tower_layer_tracks_identified_requests() {
+    use outlit::tower::OutlitLayer;
+    use tower::{service_fn, Layer, ServiceExt};
+
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "success": true,
+            "processed": 1
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = Arc::new(
+        Outlit::builder("pk_test")
+            .api_host(mock_server.uri())
+            .flush_interval(Duration::from_secs(100))
+            .build()
+            .unwrap(),
+    );
+    let service = OutlitLayer::new(client.clone())
+        .user_id_header("x-user-id")
+        .layer(service_fn(|_: http::Request<()>| async {
+            Ok::<_, std::convert::Infallible>(
+                http::Response::builder().status(201).body(()).unwrap(),
+            )
+        }));
+
+    let identified = http::Request::post("/projects?page=2")
+        .header("x-user-id", "usr_123")
+        .body(())
+        .unwrap();
+    let response = service.clone().oneshot(identified).await.unwrap();
+    assert_eq!(response.status(), 201);
+    let anonymous = http::Request::get("/health").body(()).unwrap();
+    service.oneshot(anonymous).await.unwrap();
+
+    // Tracking happens on a spawned task
+    while client.stats().enqueued == 0 {
+        tokio::task::yield_now().await;
+    }
+    client.flush().await.unwrap();
+
+    let requests = mock_server.received_requests().await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+    let events = body["events"].as_array().unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0]["eventName"], "http_request");
+    assert_eq!(events[0]["properties"]["__userId"], "usr_123");
+    assert_eq!(events[0]["properties"]["method"], "POST");
+    assert_eq!(events[0]["properties"]["path"], "/projects");
+    assert_eq!(events[0]["properties"]["status"], 201);
+}
+
+// ============================================
+// ACTIX-WEB TESTS
+// ============================================
+
+#[cfg(feature = "actix-web")]
+#[actix_web::test]
+async fn test_actix_middleware_tracks_requests_and_shares_client() {
+    use actix_web::{test, web, App, HttpResponse};
+    use outlit::actix::{configure, OutlitMiddleware};
+
+    let mock_server = MockServer::start().await;
 
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "success": true,
+            "processed": 2
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = web::Data::new(
+        Outlit::builder("pk_test")
+            .api_host(mock_server.uri())
+            .flush_interval(Duration::from_secs(100))
+            .build()
+            .unwrap(),
+    );
+    let app = test::init_service(
+        App::new()
+            .configure(configure(client.clone()))
+            .wrap(OutlitMiddleware::new(client.clone()).user_id_header("x-user-id"))
+            .route(
+                "/signup",
+                web::post().to(|client: web::Data<Outlit>| async move {
+                    client
+                        .track_by_user_id("signed_up", user_id("usr_123"))
+                        .send()
+                        .await
+                        .unwrap();
+                    HttpResponse::Created().finish()
+                }),
+            ),
+    )
+    .await;
+
+    let request = test::TestRequest::post()
+        .uri("/signup")
+        .insert_header(("x-user-id", "usr_123"))
+        .to_request();
+    let response = test::call_service(&app, request).await;
+    assert_eq!(response.status(), 201);
+
+    // Tracking happens on a spawned task
+    while client.stats().enqueued < 2 {
+        actix_web::rt::task::yield_now().await;
+    }
     client.flush().await.unwrap();
+
+    let requests = mock_server.received_requests().await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+    let events = body["events"].as_array().unwrap();
+    assert_eq!(events[0]["eventName"], "signed_up");
+    assert_eq!(events[1]["eventName"], "http_request");
+    assert_eq!(events[1]["properties"]["path"], "/signup");
+    assert_eq!(events[1]["properties"]["status"], 201);
 }
 
+// ============================================
+// SERVERLESS TESTS
+// ============================================
+
 #[tokio::test]
-async fn test_stage_with_fingerprint() {
+async fn test_serverless_flush_if_needed_waits_for_flush_interval() {
     let mock_server = MockServer::start().await;
 
     Mock::given(method("POST"))
@@ -598,17 +4473,58 @@ async fn test_stage_with_fingerprint() {
 
     let client = Outlit::builder("pk_test")
         .api_host(mock_server.uri())
-        .flush_interval(Duration::from_secs(100))
+        .flush_interval(Duration::from_millis(100))
+        .serverless()
         .build()
         .unwrap();
 
-    // Stage event with fingerprint identity
+    client.flush_if_needed().await.unwrap();
     client
-        .user()
-        .activate_by_fingerprint(fingerprint("device_abc123"))
+        .track("checkout", email("user@test.com"))
         .send()
         .await
         .unwrap();
+    client.flush_if_needed().await.unwrap();
+    assert_eq!(client.pending_event_count().await, 1);
 
-    client.flush().await.unwrap();
+    // No background timer flushes it meanwhile
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    assert_eq!(client.pending_event_count().await, 1);
+
+    client.flush_if_needed().await.unwrap();
+    assert_eq!(client.pending_event_count().await, 0);
+}
+
+#[tokio::test]
+async fn test_flush_after_sends_events_from_invocation() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "success": true,
+            "processed": 1
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = Outlit::builder("pk_test")
+        .api_host(mock_server.uri())
+        .serverless()
+        .build()
+        .unwrap();
+
+    let output = client
+        .flush_after(async {
+            client
+                .track("invoked", email("user@test.com"))
+                .send()
+                .await
+                .unwrap();
+            42
+        })
+        .await;
+
+    assert_eq!(output, 42);
+    assert_eq!(client.pending_event_count().await, 0);
 }
@@ -4,8 +4,8 @@
 //! what the server expects (based on TypeScript types).
 
 use outlit::types::{
-    BillingEventData, BillingStatus, CustomEventData, IdentifyEventData, JourneyStage,
-    StageEventData,
+    AliasEventData, BillingEventData, BillingStatus, CustomEventData, Identifiers,
+    IdentifyEventData, JourneyStage, PageviewEventData, StageEventData,
 };
 use outlit::{IngestPayload, SourceType, TrackerEvent};
 use serde_json::json;
@@ -18,6 +18,8 @@ fn test_custom_event_json_structure() {
         path: "/".into(),
         event_name: "signup".into(),
         properties: Some([("plan".to_string(), json!("pro"))].into_iter().collect()),
+        message_id: None,
+        event_id: None,
     });
 
     let json = serde_json::to_value(&event).unwrap();
@@ -34,6 +36,28 @@ fn test_custom_event_json_structure() {
     assert!(json.get("properties").is_some());
 }
 
+#[test]
+fn test_pageview_event_json_structure() {
+    let event = TrackerEvent::Pageview(PageviewEventData {
+        timestamp: 1706400000000,
+        url: "https://example.com/pricing".into(),
+        path: "/pricing".into(),
+        title: Some("Pricing".into()),
+        referrer: None,
+        properties: None,
+        message_id: None,
+        event_id: None,
+    });
+
+    let json = serde_json::to_value(&event).unwrap();
+
+    assert_eq!(json["type"], "pageview");
+    assert_eq!(json["url"], "https://example.com/pricing");
+    assert_eq!(json["path"], "/pricing");
+    assert_eq!(json["title"], "Pricing");
+    assert!(json.get("referrer").is_none());
+}
+
 #[test]
 fn test_identify_event_json_structure() {
     let event = TrackerEvent::Identify(IdentifyEventData {
@@ -44,6 +68,8 @@ fn test_identify_event_json_structure() {
         user_id: Some("usr_123".into()),
         fingerprint: None,
         traits: Some([("name".to_string(), json!("John"))].into_iter().collect()),
+        message_id: None,
+        event_id: None,
     });
 
     let json = serde_json::to_value(&event).unwrap();
@@ -65,6 +91,8 @@ fn test_identify_event_with_fingerprint_json_structure() {
         user_id: Some("usr_123".into()),
         fingerprint: Some("device_abc123".into()),
         traits: None,
+        message_id: None,
+        event_id: None,
     });
 
     let json = serde_json::to_value(&event).unwrap();
@@ -82,7 +110,12 @@ fn test_stage_event_json_structure() {
         url: "server://user@test.com".into(),
         path: "/".into(),
         stage: JourneyStage::Activated,
+        reason: None,
+        previous_stage: None,
+        effective_at: None,
         properties: None,
+        message_id: None,
+        event_id: None,
     });
 
     let json = serde_json::to_value(&event).unwrap();
@@ -92,6 +125,23 @@ fn test_stage_event_json_structure() {
     assert_eq!(json["stage"], "activated"); // lowercase enum value
 }
 
+#[test]
+fn test_billing_status_values() {
+    let statuses = [
+        (BillingStatus::Trialing, "trialing"),
+        (BillingStatus::Paid, "paid"),
+        (BillingStatus::Churned, "churned"),
+        (BillingStatus::PastDue, "past_due"),
+        (BillingStatus::Canceled, "canceled"),
+        (BillingStatus::Unpaid, "unpaid"),
+        (BillingStatus::Paused, "paused"),
+        (BillingStatus::Incomplete, "incomplete"),
+    ];
+    for (status, expected) in statuses {
+        assert_eq!(serde_json::to_value(status).unwrap(), expected);
+    }
+}
+
 #[test]
 fn test_billing_event_json_structure() {
     let event = TrackerEvent::Billing(BillingEventData {
@@ -102,7 +152,19 @@ fn test_billing_event_json_structure() {
         customer_id: Some("cust_123".into()),
         stripe_customer_id: Some("cus_xxx".into()),
         domain: Some("acme.com".into()),
+        amount: None,
+        currency: None,
+        mrr: None,
+        interval: None,
+        activity: None,
+        invoice_id: None,
+        plan: None,
+        previous_plan: None,
+        seats: None,
+        previous_seats: None,
         properties: None,
+        message_id: None,
+        event_id: None,
     });
 
     let json = serde_json::to_value(&event).unwrap();
@@ -114,18 +176,50 @@ fn test_billing_event_json_structure() {
     assert!(json.get("customer_id").is_none()); // snake_case should NOT exist
 }
 
+#[test]
+fn test_alias_event_json_structure() {
+    let event = TrackerEvent::Alias(AliasEventData {
+        timestamp: 1706400000000,
+        url: "server://usr_123".into(),
+        path: "/".into(),
+        previous: Identifiers {
+            email: None,
+            user_id: None,
+            fingerprint: Some("device_abc123".into()),
+        },
+        current: Identifiers {
+            email: None,
+            user_id: Some("usr_123".into()),
+            fingerprint: None,
+        },
+        message_id: None,
+        event_id: None,
+    });
+
+    let json = serde_json::to_value(&event).unwrap();
+
+    assert_eq!(json["type"], "alias");
+    assert_eq!(json["previous"]["fingerprint"], "device_abc123");
+    assert_eq!(json["current"]["userId"], "usr_123"); // camelCase
+    assert!(json["current"].get("user_id").is_none());
+    assert!(json["current"].get("email").is_none()); // None fields omitted
+}
+
 #[test]
 fn test_ingest_payload_json_structure() {
-    let payload = IngestPayload {
-        source: SourceType::Server,
-        events: vec![],
-    };
+    let payload = IngestPayload::new(SourceType::Server, vec![]);
 
     let json = serde_json::to_value(&payload).unwrap();
 
     assert_eq!(json["source"], "server"); // lowercase enum value
     assert!(json["events"].is_array());
 
+    // SDK metadata, like the TypeScript SDK's `library` block
+    assert_eq!(json["library"]["name"], "outlit-rust");
+    assert_eq!(json["library"]["version"], env!("CARGO_PKG_VERSION"));
+    assert!(json["library"]["rustVersion"].is_string()); // camelCase
+    assert_eq!(json["library"]["os"], std::env::consts::OS);
+
     // Server payloads should NOT have visitorId (that's for browser SDK)
     // This is intentional - the TypeScript types show visitorId as optional
 }
@@ -154,6 +248,8 @@ fn test_nested_customer_traits_structure() {
             .into_iter()
             .collect(),
         ),
+        message_id: None,
+        event_id: None,
     });
 
     let json = serde_json::to_value(&event).unwrap();
@@ -174,13 +270,12 @@ fn test_print_example_payloads() {
         path: "/".into(),
         event_name: "signup".into(),
         properties: Some([("plan".to_string(), json!("pro"))].into_iter().collect()),
+        message_id: None,
+        event_id: None,
     });
 
     // Full payload
-    let payload = IngestPayload {
-        source: SourceType::Server,
-        events: vec![custom],
-    };
+    let payload = IngestPayload::new(SourceType::Server, vec![custom]);
 
     let json = serde_json::to_string_pretty(&payload).unwrap();
     println!("Example IngestPayload:\n{}", json);
@@ -200,3 +295,155 @@ fn test_print_example_payloads() {
     //   ]
     // }
 }
+
+#[cfg(feature = "serde-deserialize")]
+#[test]
+fn test_ingest_payload_round_trip() {
+    use outlit::types::{GroupEventData, Identifiers};
+
+    let identifiers = Identifiers {
+        email: Some("user@test.com".into()),
+        user_id: Some("usr_123".into()),
+        fingerprint: None,
+    };
+    let payload = IngestPayload {
+        source: SourceType::Server,
+        events: vec![
+            TrackerEvent::Custom(CustomEventData {
+                timestamp: 1706400000000,
+                url: "server://user@test.com".into(),
+                path: "/".into(),
+                event_name: "signup".into(),
+                properties: Some([("plan".to_string(), json!("pro"))].into_iter().collect()),
+                message_id: Some("msg_1".into()),
+                event_id: None,
+            }),
+            TrackerEvent::Identify(IdentifyEventData {
+                timestamp: 1706400000000,
+                url: "server://user@test.com".into(),
+                path: "/".into(),
+                email: Some("user@test.com".into()),
+                user_id: None,
+                fingerprint: Some("device_abc123".into()),
+                traits: None,
+                message_id: None,
+                event_id: None,
+            }),
+            TrackerEvent::Stage(StageEventData {
+                timestamp: 1706400000000,
+                url: "server://user@test.com".into(),
+                path: "/".into(),
+                stage: JourneyStage::Custom("power_user".into()),
+                reason: None,
+                previous_stage: None,
+                effective_at: None,
+                properties: None,
+                message_id: None,
+                event_id: None,
+            }),
+            TrackerEvent::Billing(BillingEventData {
+                timestamp: 1706400000000,
+                url: "server://acme.com".into(),
+                path: "/".into(),
+                status: BillingStatus::Paid,
+                customer_id: Some("cust_123".into()),
+                stripe_customer_id: None,
+                domain: Some("acme.com".into()),
+                amount: None,
+                currency: None,
+                mrr: None,
+                interval: None,
+                activity: None,
+                invoice_id: None,
+                plan: None,
+                previous_plan: None,
+                seats: None,
+                previous_seats: None,
+                properties: None,
+                message_id: None,
+                event_id: None,
+            }),
+            TrackerEvent::Alias(AliasEventData {
+                timestamp: 1706400000000,
+                url: "server://usr_123".into(),
+                path: "/".into(),
+                previous: Identifiers {
+                    email: Some("old@test.com".into()),
+                    user_id: None,
+                    fingerprint: None,
+                },
+                current: identifiers.clone(),
+                message_id: None,
+                event_id: None,
+            }),
+            TrackerEvent::Group(GroupEventData {
+                timestamp: 1706400000000,
+                url: "server://org_123".into(),
+                path: "/".into(),
+                group_id: "org_123".into(),
+                user: Some(identifiers),
+                traits: None,
+                message_id: None,
+                event_id: None,
+            }),
+        ],
+        library: Some(outlit::Library::current()),
+    };
+
+    let json = serde_json::to_value(&payload).unwrap();
+    let decoded: IngestPayload = serde_json::from_value(json.clone()).unwrap();
+
+    assert_eq!(serde_json::to_value(&decoded).unwrap(), json);
+    assert!(matches!(decoded.events[0], TrackerEvent::Custom(_)));
+    assert!(matches!(decoded.events[1], TrackerEvent::Identify(_)));
+    assert!(matches!(
+        &decoded.events[2],
+        TrackerEvent::Stage(StageEventData { stage: JourneyStage::Custom(name), .. }) if name == "power_user"
+    ));
+    assert!(matches!(decoded.events[3], TrackerEvent::Billing(_)));
+    assert!(matches!(decoded.events[4], TrackerEvent::Alias(_)));
+    assert!(matches!(decoded.events[5], TrackerEvent::Group(_)));
+}
+
+#[cfg(feature = "serde-deserialize")]
+#[test]
+fn test_relay_decodes_typescript_payload() {
+    // A payload as sent by the TypeScript server SDK, without a `library`
+    // block
+    let json = json!({
+        "source": "server",
+        "events": [
+            {
+                "type": "pageview",
+                "timestamp": 1706400000000i64,
+                "url": "https://example.com/pricing",
+                "path": "/pricing",
+                "title": "Pricing"
+            },
+            {
+                "type": "custom",
+                "timestamp": 1706400000000i64,
+                "url": "https://example.com/pricing",
+                "path": "/pricing",
+                "eventName": "plan_selected",
+                "properties": { "plan": "pro" }
+            }
+        ]
+    });
+
+    let payload: IngestPayload = serde_json::from_value(json).unwrap();
+    assert!(matches!(payload.source, SourceType::Server));
+    assert!(payload.library.is_none());
+    assert_eq!(payload.events[1].event_name(), Some("plan_selected"));
+
+    // Responses can be re-encoded for the relay's own callers
+    let response = outlit::IngestResponse {
+        success: true,
+        processed: 2,
+        errors: None,
+    };
+    assert_eq!(
+        serde_json::to_value(&response).unwrap(),
+        json!({ "success": true, "processed": 2 })
+    );
+}